@@ -66,6 +66,12 @@ export interface TemplateVar {
 export interface StateConfig {
   initial_state?: string;
   transitions?: StateTransition[];
+  session_key?: SessionKey;
+}
+
+export interface SessionKey {
+  source: 'header' | 'cookie';
+  name: string;
 }
 
 export interface StateTransition {
@@ -419,6 +419,23 @@ pub struct StateTransition {
     pub response: Option<ResponseConfig>,
 }
 
+/// Where a stateful mock reads its session key from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SessionKeySource {
+    Header,
+    Cookie,
+}
+
+/// Session key scoping the state of a stateful mock
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionKey {
+    /// Key source
+    pub source: SessionKeySource,
+    /// Header or cookie name
+    pub name: String,
+}
+
 /// State machine configuration
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct StateConfig {
@@ -427,6 +444,9 @@ pub struct StateConfig {
     /// State transitions
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub transitions: Vec<StateTransition>,
+    /// Session key; without it all requests share one state
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_key: Option<SessionKey>,
 }
 
 // ============================================================================
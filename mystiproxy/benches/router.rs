@@ -0,0 +1,61 @@
+//! Router matching benchmarks.
+//!
+//! Builds tables of 100, 1k and 10k locations that mix `Full`, `Prefix`,
+//! `Regex` and `PrefixRegex` routes, and measures matching a route near the
+//! end of the table, a regex route, and a path that matches nothing.
+//!
+//! Run with `cargo bench -p mystiproxy --bench router`.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use mystiproxy::config::{LocationConfig, MatchMode};
+use mystiproxy::router::{Route, Router};
+
+fn location(pattern: &str, mode: MatchMode) -> LocationConfig {
+    serde_yaml::from_str(&format!(
+        "location: \"{pattern}\"\nmode: {mode:?}\nprovider: mock\n"
+    ))
+    .unwrap()
+}
+
+/// Four routes per `i`, one of each match mode.
+fn build_router(size: usize) -> Router {
+    let mut router = Router::new();
+    for i in 0..size / 4 {
+        for (pattern, mode) in [
+            (format!("/api/v1/resource-{i}"), MatchMode::Full),
+            (format!("/static/bundle-{i}/"), MatchMode::Prefix),
+            (format!("/users/{{id}}/orders-{i}"), MatchMode::Regex),
+            (
+                format!("/tenants/{{tenant}}/app-{i}/"),
+                MatchMode::PrefixRegex,
+            ),
+        ] {
+            let location = location(&pattern, mode.clone());
+            router.add_route(Route::new(pattern, mode, location).unwrap());
+        }
+    }
+    router
+}
+
+fn bench_match(c: &mut Criterion) {
+    let mut group = c.benchmark_group("router_match");
+    for size in [100, 1_000, 10_000] {
+        let router = build_router(size);
+        let last = size / 4 - 1;
+        let cases = [
+            ("full", format!("/api/v1/resource-{last}")),
+            ("prefix", format!("/static/bundle-{last}/js/app.js")),
+            ("regex", format!("/users/42/orders-{last}")),
+            ("miss", "/not/configured".to_string()),
+        ];
+        for (name, uri) in cases {
+            group.bench_with_input(BenchmarkId::new(name, size), &uri, |b, uri| {
+                b.iter(|| router.match_uri_candidates(black_box(uri)).len())
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_match);
+criterion_main!(benches);
@@ -0,0 +1,256 @@
+//! 上游主动健康检查
+//!
+//! 按引擎配置周期性探测每个 endpoint（TCP/UDS 建连或 HTTP GET），
+//! 连续失败/成功达到阈值后切换 endpoint 的健康状态。
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use bytes::Bytes;
+use http_body_util::Empty;
+use hyper::client::conn::http1::Builder;
+use hyper::{Method, Request};
+use hyper_util::rt::TokioIo;
+use tokio::task::JoinHandle;
+use tracing::debug;
+
+use super::{Endpoint, LoadBalancer};
+use crate::config::{ActiveHealthCheckConfig, HealthCheckType};
+use crate::error::{MystiProxyError, Result};
+use crate::io::SocketStream;
+
+/// 默认探测间隔
+const DEFAULT_INTERVAL: Duration = Duration::from_secs(10);
+
+/// 默认单次探测超时
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// 启动引擎的主动健康检查任务
+///
+/// # 参数
+/// - `engine`: 引擎名称（用于日志）
+/// - `balancer`: 引擎的负载均衡器
+/// - `config`: 主动探测配置
+pub fn spawn_active_health_checks(
+    engine: String,
+    balancer: Arc<LoadBalancer>,
+    config: ActiveHealthCheckConfig,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let interval = config.interval.unwrap_or(DEFAULT_INTERVAL);
+        let healthy_threshold = config.healthy_threshold.unwrap_or(1).max(1);
+        let unhealthy_threshold = config.unhealthy_threshold.unwrap_or(1).max(1);
+        // 每个 endpoint 的 (连续成功, 连续失败) 次数
+        let mut streaks = vec![(0u32, 0u32); balancer.endpoints().len()];
+
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            let probes = balancer
+                .endpoints()
+                .iter()
+                .map(|endpoint| probe(endpoint, &config));
+            let results = futures::future::join_all(probes).await;
+
+            for ((endpoint, result), streak) in balancer
+                .endpoints()
+                .iter()
+                .zip(results)
+                .zip(streaks.iter_mut())
+            {
+                match result {
+                    Ok(()) => {
+                        *streak = (streak.0.saturating_add(1), 0);
+                        if streak.0 >= healthy_threshold {
+                            endpoint.set_probe_healthy(true);
+                        }
+                    }
+                    Err(e) => {
+                        debug!(
+                            "[{}] Health check for {} failed: {}",
+                            engine,
+                            endpoint.address(),
+                            e
+                        );
+                        *streak = (0, streak.1.saturating_add(1));
+                        if streak.1 >= unhealthy_threshold {
+                            endpoint.set_probe_healthy(false);
+                        }
+                    }
+                }
+            }
+        }
+    })
+}
+
+/// 对单个 endpoint 执行一次探测
+async fn probe(endpoint: &Endpoint, config: &ActiveHealthCheckConfig) -> Result<()> {
+    let timeout = config.timeout.unwrap_or(DEFAULT_TIMEOUT);
+    tokio::time::timeout(timeout, probe_inner(endpoint.address(), config)).await?
+}
+
+async fn probe_inner(address: &str, config: &ActiveHealthCheckConfig) -> Result<()> {
+    match config.check_type {
+        HealthCheckType::Tcp | HealthCheckType::Uds => {
+            SocketStream::connect(tcp_probe_address(address)).await?;
+            Ok(())
+        }
+        HealthCheckType::Http => probe_http(address, config).await,
+    }
+}
+
+/// `https://host[:port]` 目标按 TCP 建连探测（默认端口 443）
+fn tcp_probe_address(address: &str) -> String {
+    match address.strip_prefix("https://") {
+        Some(authority) => {
+            let authority = authority.trim_end_matches('/');
+            if authority
+                .rsplit_once(':')
+                .is_some_and(|(_, port)| !port.contains(']'))
+            {
+                format!("tcp://{authority}")
+            } else {
+                format!("tcp://{authority}:443")
+            }
+        }
+        None => address.to_string(),
+    }
+}
+
+async fn probe_http(address: &str, config: &ActiveHealthCheckConfig) -> Result<()> {
+    let stream = SocketStream::connect(address.to_string()).await?;
+    let (mut sender, conn) = Builder::new()
+        .handshake(TokioIo::new(stream))
+        .await
+        .map_err(|e| MystiProxyError::Hyper(e.to_string()))?;
+    tokio::spawn(conn);
+
+    let host = address
+        .strip_prefix("tcp://")
+        .unwrap_or("localhost")
+        .to_string();
+    let request = Request::builder()
+        .method(Method::GET)
+        .uri(config.path.as_deref().unwrap_or("/"))
+        .header(hyper::header::HOST, host)
+        .header(hyper::header::USER_AGENT, "MystiProxy-HealthCheck")
+        .body(Empty::<Bytes>::new())?;
+    let response = sender
+        .send_request(request)
+        .await
+        .map_err(|e| MystiProxyError::Hyper(e.to_string()))?;
+
+    let expected = config.expected_status.unwrap_or(200);
+    if response.status().as_u16() == expected {
+        Ok(())
+    } else {
+        Err(MystiProxyError::Proxy(format!(
+            "unexpected health check status {} (expected {})",
+            response.status(),
+            expected
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{TargetConfig, WeightedTarget};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    async fn start_status_server(status: &'static str) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut buf = [0u8; 1024];
+                let _ = stream.read(&mut buf).await;
+                let resp = format!("HTTP/1.1 {status}\r\nContent-Length: 0\r\n\r\n");
+                let _ = stream.write_all(resp.as_bytes()).await;
+            }
+        });
+        format!("tcp://{addr}")
+    }
+
+    fn active(check_type: HealthCheckType) -> ActiveHealthCheckConfig {
+        ActiveHealthCheckConfig {
+            check_type,
+            interval: Some(Duration::from_millis(20)),
+            timeout: Some(Duration::from_millis(500)),
+            ..Default::default()
+        }
+    }
+
+    async fn closed_address() -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        format!("tcp://{}", listener.local_addr().unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_tcp_probe() {
+        let up = start_status_server("200 OK").await;
+        let down = closed_address().await;
+        let config = active(HealthCheckType::Tcp);
+        assert!(probe_inner(&up, &config).await.is_ok());
+        assert!(probe_inner(&down, &config).await.is_err());
+    }
+
+    #[test]
+    fn test_tcp_probe_address_for_https_target() {
+        assert_eq!(
+            tcp_probe_address("https://10.0.0.1:8443"),
+            "tcp://10.0.0.1:8443"
+        );
+        assert_eq!(
+            tcp_probe_address("https://api.internal"),
+            "tcp://api.internal:443"
+        );
+        assert_eq!(tcp_probe_address("https://[::1]"), "tcp://[::1]:443");
+        assert_eq!(tcp_probe_address("tcp://10.0.0.1:80"), "tcp://10.0.0.1:80");
+    }
+
+    #[tokio::test]
+    async fn test_http_probe_checks_status() {
+        let ok = start_status_server("200 OK").await;
+        let failing = start_status_server("503 Service Unavailable").await;
+        let config = active(HealthCheckType::Http);
+        assert!(probe_inner(&ok, &config).await.is_ok());
+        assert!(probe_inner(&failing, &config).await.is_err());
+
+        let config = ActiveHealthCheckConfig {
+            expected_status: Some(503),
+            ..config
+        };
+        assert!(probe_inner(&failing, &config).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_active_checks_mark_endpoints() {
+        let up = start_status_server("200 OK").await;
+        let down = closed_address().await;
+        let target = TargetConfig::Weighted(
+            [&up, &down]
+                .iter()
+                .map(|address| WeightedTarget {
+                    address: address.to_string(),
+                    weight: 1,
+                })
+                .collect(),
+        );
+        let balancer = Arc::new(LoadBalancer::new(&target, None).unwrap());
+
+        let handle = spawn_active_health_checks(
+            "test".to_string(),
+            balancer.clone(),
+            active(HealthCheckType::Tcp),
+        );
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        handle.abort();
+
+        assert!(balancer.endpoints()[0].is_probe_healthy());
+        assert!(!balancer.endpoints()[1].is_probe_healthy());
+        for _ in 0..4 {
+            assert_eq!(balancer.select(None, None).address(), up);
+        }
+    }
+}
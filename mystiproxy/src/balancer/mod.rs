@@ -0,0 +1,630 @@
+//! 上游负载均衡模块
+//!
+//! 为多目标引擎选择上游 endpoint，支持加权轮询、最少连接、加权随机与一致性哈希。
+//! TCP 转发与 HTTP 代理共用同一实现。
+//!
+//! 主动探测（[`health`]）与被动异常检测会把 endpoint 标记为不可用，
+//! 选择时跳过；全部不可用时退化为在全部 endpoint 中选择，避免整体中断。
+
+mod health;
+
+pub use health::spawn_active_health_checks;
+
+use std::net::IpAddr;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use hyper::header::HeaderMap;
+use rand::Rng;
+use tracing::{debug, info, warn};
+
+use crate::config::{
+    EngineConfig, LoadBalanceConfig, LoadBalanceStrategy, PassiveHealthCheckConfig, TargetConfig,
+};
+use crate::error::{MystiProxyError, Result};
+
+/// 一致性哈希环上每单位权重的虚拟节点数
+const VIRTUAL_NODES_PER_WEIGHT: u32 = 100;
+
+/// 被动检测默认连续失败阈值
+const DEFAULT_PASSIVE_FAILURES: u32 = 5;
+
+/// 被动检测默认摘除冷却时间
+const DEFAULT_PASSIVE_COOLDOWN: Duration = Duration::from_secs(30);
+
+/// 上游 endpoint
+#[derive(Debug)]
+pub struct Endpoint {
+    address: String,
+    weight: u32,
+    active: AtomicUsize,
+    /// 主动探测结果
+    probe_healthy: AtomicBool,
+    /// 被动检测的连续失败次数
+    consecutive_failures: AtomicU32,
+    /// 被动摘除的恢复时间
+    ejected_until: Mutex<Option<Instant>>,
+}
+
+impl Endpoint {
+    /// 目标地址
+    pub fn address(&self) -> &str {
+        &self.address
+    }
+
+    /// 权重
+    pub fn weight(&self) -> u32 {
+        self.weight
+    }
+
+    /// 当前活动连接数
+    pub fn active_connections(&self) -> usize {
+        self.active.load(Ordering::Relaxed)
+    }
+
+    /// 是否可用（主动探测健康且未被被动摘除）
+    pub fn is_healthy(&self) -> bool {
+        self.probe_healthy.load(Ordering::Relaxed) && !self.is_ejected()
+    }
+
+    /// 主动探测是否健康
+    pub fn is_probe_healthy(&self) -> bool {
+        self.probe_healthy.load(Ordering::Relaxed)
+    }
+
+    /// 是否处于被动摘除冷却期
+    pub fn is_ejected(&self) -> bool {
+        let mut ejected = self.ejected_until.lock().unwrap_or_else(|e| e.into_inner());
+        match *ejected {
+            Some(until) if Instant::now() < until => true,
+            Some(_) => {
+                // 冷却结束，重新放入候选并重新计数
+                *ejected = None;
+                self.consecutive_failures.store(0, Ordering::Relaxed);
+                info!("Upstream {} cooldown expired, restored", self.address);
+                false
+            }
+            None => false,
+        }
+    }
+
+    pub(crate) fn set_probe_healthy(&self, healthy: bool) {
+        let previous = self.probe_healthy.swap(healthy, Ordering::Relaxed);
+        if previous != healthy {
+            if healthy {
+                info!("Upstream {} is healthy", self.address);
+            } else {
+                warn!("Upstream {} failed health check", self.address);
+            }
+        }
+    }
+}
+
+/// 已选中的 endpoint，持有期间计入活动连接数（供最少连接策略使用）
+#[derive(Debug)]
+pub struct EndpointGuard {
+    endpoint: Arc<Endpoint>,
+}
+
+impl EndpointGuard {
+    fn new(endpoint: Arc<Endpoint>) -> Self {
+        endpoint.active.fetch_add(1, Ordering::Relaxed);
+        Self { endpoint }
+    }
+
+    /// 选中的目标地址
+    pub fn address(&self) -> &str {
+        &self.endpoint.address
+    }
+
+    /// 选中的 endpoint
+    pub fn endpoint(&self) -> &Arc<Endpoint> {
+        &self.endpoint
+    }
+}
+
+impl Drop for EndpointGuard {
+    fn drop(&mut self) {
+        self.endpoint.active.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// 负载均衡器
+#[derive(Debug)]
+pub struct LoadBalancer {
+    endpoints: Vec<Arc<Endpoint>>,
+    strategy: LoadBalanceStrategy,
+    hash_header: Option<String>,
+    /// 平滑加权轮询的当前权重
+    current_weights: Mutex<Vec<i64>>,
+    /// 一致性哈希环：(哈希值, endpoint 下标)，按哈希值升序
+    ring: Vec<(u64, usize)>,
+    /// 被动检测：(连续失败阈值, 冷却时间)
+    passive: Option<(u32, Duration)>,
+}
+
+impl LoadBalancer {
+    /// 根据目标与均衡配置创建负载均衡器
+    pub fn new(target: &TargetConfig, config: Option<&LoadBalanceConfig>) -> Result<Self> {
+        let endpoints: Vec<Arc<Endpoint>> = target
+            .endpoints()
+            .into_iter()
+            .map(|t| {
+                Arc::new(Endpoint {
+                    address: t.address,
+                    weight: t.weight,
+                    active: AtomicUsize::new(0),
+                    probe_healthy: AtomicBool::new(true),
+                    consecutive_failures: AtomicU32::new(0),
+                    ejected_until: Mutex::new(None),
+                })
+            })
+            .collect();
+
+        if endpoints.is_empty() {
+            return Err(MystiProxyError::Config(
+                "target list must not be empty".to_string(),
+            ));
+        }
+        if endpoints.iter().any(|e| e.weight == 0) {
+            return Err(MystiProxyError::Config(
+                "target weight must be greater than 0".to_string(),
+            ));
+        }
+
+        let config = config.cloned().unwrap_or_default();
+
+        let mut ring = Vec::new();
+        if config.strategy == LoadBalanceStrategy::ConsistentHash {
+            for (index, endpoint) in endpoints.iter().enumerate() {
+                for replica in 0..endpoint.weight * VIRTUAL_NODES_PER_WEIGHT {
+                    let key = format!("{}#{}", endpoint.address, replica);
+                    ring.push((hash_key(key.as_bytes()), index));
+                }
+            }
+            ring.sort_unstable();
+        }
+
+        Ok(Self {
+            current_weights: Mutex::new(vec![0; endpoints.len()]),
+            endpoints,
+            strategy: config.strategy,
+            hash_header: config.hash_header,
+            ring,
+            passive: None,
+        })
+    }
+
+    /// 根据引擎配置创建负载均衡器（含被动健康检查设置）
+    pub fn from_engine_config(config: &EngineConfig) -> Result<Self> {
+        let balancer = Self::new(&config.target, config.load_balance.as_ref())?;
+        let passive = config
+            .health_check
+            .as_ref()
+            .and_then(|h| h.passive.as_ref());
+        Ok(match passive {
+            Some(passive) => balancer.with_passive_health_check(passive),
+            None => balancer,
+        })
+    }
+
+    /// 启用被动健康检查
+    pub fn with_passive_health_check(mut self, config: &PassiveHealthCheckConfig) -> Self {
+        self.passive = Some((
+            config
+                .consecutive_failures
+                .unwrap_or(DEFAULT_PASSIVE_FAILURES)
+                .max(1),
+            config.cooldown.unwrap_or(DEFAULT_PASSIVE_COOLDOWN),
+        ));
+        self
+    }
+
+    /// 报告一次成功的上游交互（清零连续失败计数）
+    pub fn report_success(&self, endpoint: &Endpoint) {
+        if self.passive.is_some() {
+            endpoint.consecutive_failures.store(0, Ordering::Relaxed);
+        }
+    }
+
+    /// 报告一次失败的上游交互（连接错误或 5xx）
+    ///
+    /// 连续失败达到阈值时摘除该 endpoint，冷却期结束后自动恢复。
+    pub fn report_failure(&self, endpoint: &Endpoint) {
+        let Some((threshold, cooldown)) = self.passive else {
+            return;
+        };
+        let failures = endpoint
+            .consecutive_failures
+            .fetch_add(1, Ordering::Relaxed)
+            + 1;
+        if failures < threshold {
+            return;
+        }
+        let mut ejected = endpoint
+            .ejected_until
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        if ejected.is_none() {
+            warn!(
+                "Upstream {} ejected after {} consecutive failures for {:?}",
+                endpoint.address, failures, cooldown
+            );
+            *ejected = Some(Instant::now() + cooldown);
+        }
+    }
+
+    /// 全部 endpoint
+    pub fn endpoints(&self) -> &[Arc<Endpoint>] {
+        &self.endpoints
+    }
+
+    /// 均衡策略
+    pub fn strategy(&self) -> LoadBalanceStrategy {
+        self.strategy
+    }
+
+    /// 按策略选择 endpoint
+    ///
+    /// # 参数
+    /// - `client_ip`: 客户端 IP（一致性哈希的默认键）
+    /// - `headers`: 请求头（HTTP 引擎可用，用于按 `hash_header` 哈希）
+    pub fn select(&self, client_ip: Option<IpAddr>, headers: Option<&HeaderMap>) -> EndpointGuard {
+        self.select_excluding(client_ip, headers, &[])
+    }
+
+    /// 按策略选择 endpoint，尽量避开 `exclude` 中的 endpoint（用于重试换节点）
+    ///
+    /// 可用 endpoint 全部被排除时退化为在未排除的 endpoint 中选择，
+    /// 仍无候选时在全部 endpoint 中选择。
+    pub fn select_excluding(
+        &self,
+        client_ip: Option<IpAddr>,
+        headers: Option<&HeaderMap>,
+        exclude: &[Arc<Endpoint>],
+    ) -> EndpointGuard {
+        let excluded: Vec<bool> = self
+            .endpoints
+            .iter()
+            .map(|e| exclude.iter().any(|x| Arc::ptr_eq(x, e)))
+            .collect();
+        let mut available: Vec<bool> = self
+            .endpoints
+            .iter()
+            .zip(&excluded)
+            .map(|(e, x)| e.is_healthy() && !x)
+            .collect();
+        if !available.iter().any(|a| *a) {
+            // 全部不可用时不摘除任何 endpoint
+            available = excluded.iter().map(|x| !x).collect();
+        }
+        if !available.iter().any(|a| *a) {
+            available.fill(true);
+        }
+        let index = match self.strategy {
+            LoadBalanceStrategy::RoundRobin => self.select_round_robin(&available),
+            LoadBalanceStrategy::LeastConnections => self.select_least_connections(&available),
+            LoadBalanceStrategy::Random => self.select_random(&available),
+            LoadBalanceStrategy::ConsistentHash => {
+                self.select_consistent_hash(&available, client_ip, headers)
+            }
+        };
+        let endpoint = self.endpoints[index].clone();
+        debug!(
+            "Load balancer ({:?}) selected {}",
+            self.strategy, endpoint.address
+        );
+        EndpointGuard::new(endpoint)
+    }
+
+    /// 平滑加权轮询（与 nginx 算法一致）
+    fn select_round_robin(&self, available: &[bool]) -> usize {
+        let mut current = self
+            .current_weights
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        let mut total: i64 = 0;
+        let mut best: Option<usize> = None;
+        for (i, endpoint) in self.endpoints.iter().enumerate() {
+            if !available[i] {
+                continue;
+            }
+            total += endpoint.weight as i64;
+            current[i] += endpoint.weight as i64;
+            if best.is_none_or(|b| current[i] > current[b]) {
+                best = Some(i);
+            }
+        }
+        let best = best.unwrap_or(0);
+        current[best] -= total;
+        best
+    }
+
+    /// 活动连接数 / 权重 最小者；相同时取靠前者
+    fn select_least_connections(&self, available: &[bool]) -> usize {
+        let mut best = 0;
+        let mut best_score = f64::MAX;
+        for (i, endpoint) in self.endpoints.iter().enumerate() {
+            if !available[i] {
+                continue;
+            }
+            let score = endpoint.active_connections() as f64 / endpoint.weight as f64;
+            if score < best_score {
+                best = i;
+                best_score = score;
+            }
+        }
+        best
+    }
+
+    fn select_random(&self, available: &[bool]) -> usize {
+        let total: u64 = self
+            .endpoints
+            .iter()
+            .zip(available)
+            .filter(|(_, a)| **a)
+            .map(|(e, _)| e.weight as u64)
+            .sum();
+        let mut pick = rand::thread_rng().gen_range(0..total);
+        for (i, endpoint) in self.endpoints.iter().enumerate() {
+            if !available[i] {
+                continue;
+            }
+            if pick < endpoint.weight as u64 {
+                return i;
+            }
+            pick -= endpoint.weight as u64;
+        }
+        self.endpoints.len() - 1
+    }
+
+    fn select_consistent_hash(
+        &self,
+        available: &[bool],
+        client_ip: Option<IpAddr>,
+        headers: Option<&HeaderMap>,
+    ) -> usize {
+        let header_value = self
+            .hash_header
+            .as_deref()
+            .and_then(|name| headers?.get(name))
+            .map(|v| v.as_bytes().to_vec());
+        let key = match (header_value, client_ip) {
+            (Some(value), _) => value,
+            (None, Some(ip)) => ip.to_string().into_bytes(),
+            // 无可用哈希键时退化为轮询
+            (None, None) => return self.select_round_robin(available),
+        };
+
+        // 沿环顺时针找到第一个可用 endpoint，仅影响原本落在不可用节点上的键
+        let hash = hash_key(&key);
+        let pos = self.ring.partition_point(|(h, _)| *h < hash);
+        (0..self.ring.len())
+            .map(|offset| self.ring[(pos + offset) % self.ring.len()].1)
+            .find(|index| available[*index])
+            .unwrap_or(self.ring[pos % self.ring.len()].1)
+    }
+}
+
+/// FNV-1a 64 位哈希（跨进程稳定）
+fn hash_key(data: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in data {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    // 末端再混合一次，改善相邻键的分布
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(0xff51afd7ed558ccd);
+    hash ^= hash >> 33;
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::WeightedTarget;
+    use std::collections::HashMap;
+
+    fn targets(list: &[(&str, u32)]) -> TargetConfig {
+        TargetConfig::Weighted(
+            list.iter()
+                .map(|(address, weight)| WeightedTarget {
+                    address: address.to_string(),
+                    weight: *weight,
+                })
+                .collect(),
+        )
+    }
+
+    fn balancer(list: &[(&str, u32)], strategy: LoadBalanceStrategy) -> LoadBalancer {
+        let config = LoadBalanceConfig {
+            strategy,
+            hash_header: Some("X-User".to_string()),
+        };
+        LoadBalancer::new(&targets(list), Some(&config)).unwrap()
+    }
+
+    #[test]
+    fn test_single_target() {
+        let lb = LoadBalancer::new(&TargetConfig::from("tcp://127.0.0.1:80"), None).unwrap();
+        assert_eq!(lb.select(None, None).address(), "tcp://127.0.0.1:80");
+    }
+
+    #[test]
+    fn test_weighted_round_robin() {
+        let lb = balancer(
+            &[("tcp://a:1", 2), ("tcp://b:1", 1)],
+            LoadBalanceStrategy::RoundRobin,
+        );
+        let picks: Vec<String> = (0..6)
+            .map(|_| lb.select(None, None).address().to_string())
+            .collect();
+        // 平滑加权：a 不会连续占满整个周期
+        assert_eq!(
+            picks,
+            [
+                "tcp://a:1",
+                "tcp://b:1",
+                "tcp://a:1",
+                "tcp://a:1",
+                "tcp://b:1",
+                "tcp://a:1"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_least_connections() {
+        let lb = balancer(
+            &[("tcp://a:1", 1), ("tcp://b:1", 1)],
+            LoadBalanceStrategy::LeastConnections,
+        );
+        let first = lb.select(None, None);
+        let second = lb.select(None, None);
+        assert_ne!(first.address(), second.address());
+
+        let freed = first.address().to_string();
+        drop(first);
+        assert_eq!(lb.select(None, None).address(), freed);
+    }
+
+    #[test]
+    fn test_random_reaches_all_targets() {
+        let lb = balancer(
+            &[("tcp://a:1", 1), ("tcp://b:1", 1)],
+            LoadBalanceStrategy::Random,
+        );
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        for _ in 0..200 {
+            *counts
+                .entry(lb.select(None, None).address().to_string())
+                .or_default() += 1;
+        }
+        assert_eq!(counts.len(), 2);
+    }
+
+    #[test]
+    fn test_consistent_hash_is_sticky() {
+        let lb = balancer(
+            &[("tcp://a:1", 1), ("tcp://b:1", 1), ("tcp://c:1", 1)],
+            LoadBalanceStrategy::ConsistentHash,
+        );
+        let ip: IpAddr = "10.1.2.3".parse().unwrap();
+        let first = lb.select(Some(ip), None).address().to_string();
+        for _ in 0..10 {
+            assert_eq!(lb.select(Some(ip), None).address(), first);
+        }
+
+        let mut headers = HeaderMap::new();
+        headers.insert("X-User", "alice".parse().unwrap());
+        let by_header = lb.select(Some(ip), Some(&headers)).address().to_string();
+        let other_ip: IpAddr = "192.168.0.9".parse().unwrap();
+        assert_eq!(
+            lb.select(Some(other_ip), Some(&headers)).address(),
+            by_header
+        );
+    }
+
+    #[test]
+    fn test_consistent_hash_spreads_keys() {
+        let lb = balancer(
+            &[("tcp://a:1", 1), ("tcp://b:1", 1), ("tcp://c:1", 1)],
+            LoadBalanceStrategy::ConsistentHash,
+        );
+        let mut seen = std::collections::HashSet::new();
+        for i in 0..100u8 {
+            let ip: IpAddr = [10, 0, 0, i].into();
+            seen.insert(lb.select(Some(ip), None).address().to_string());
+        }
+        assert_eq!(seen.len(), 3);
+    }
+
+    #[test]
+    fn test_unhealthy_endpoint_skipped() {
+        let lb = balancer(
+            &[("tcp://a:1", 1), ("tcp://b:1", 1)],
+            LoadBalanceStrategy::RoundRobin,
+        );
+        lb.endpoints()[0].set_probe_healthy(false);
+        for _ in 0..4 {
+            assert_eq!(lb.select(None, None).address(), "tcp://b:1");
+        }
+
+        // 全部不可用时仍然返回 endpoint
+        lb.endpoints()[1].set_probe_healthy(false);
+        let picks: std::collections::HashSet<String> = (0..4)
+            .map(|_| lb.select(None, None).address().to_string())
+            .collect();
+        assert_eq!(picks.len(), 2);
+    }
+
+    #[test]
+    fn test_consistent_hash_skips_unhealthy() {
+        let lb = balancer(
+            &[("tcp://a:1", 1), ("tcp://b:1", 1), ("tcp://c:1", 1)],
+            LoadBalanceStrategy::ConsistentHash,
+        );
+        let ip: IpAddr = "10.1.2.3".parse().unwrap();
+        let first = lb.select(Some(ip), None).endpoint().clone();
+        first.set_probe_healthy(false);
+        let second = lb.select(Some(ip), None).address().to_string();
+        assert_ne!(second, first.address());
+        for _ in 0..5 {
+            assert_eq!(lb.select(Some(ip), None).address(), second);
+        }
+    }
+
+    #[test]
+    fn test_passive_ejection_and_cooldown() {
+        let lb = balancer(
+            &[("tcp://a:1", 1), ("tcp://b:1", 1)],
+            LoadBalanceStrategy::RoundRobin,
+        )
+        .with_passive_health_check(&PassiveHealthCheckConfig {
+            consecutive_failures: Some(2),
+            cooldown: Some(Duration::from_millis(50)),
+        });
+        let a = lb.endpoints()[0].clone();
+
+        lb.report_failure(&a);
+        lb.report_success(&a);
+        lb.report_failure(&a);
+        assert!(a.is_healthy(), "success resets the failure count");
+
+        lb.report_failure(&a);
+        assert!(a.is_ejected());
+        for _ in 0..4 {
+            assert_eq!(lb.select(None, None).address(), "tcp://b:1");
+        }
+
+        std::thread::sleep(Duration::from_millis(60));
+        assert!(a.is_healthy());
+    }
+
+    #[test]
+    fn test_select_excluding_prefers_untried_endpoints() {
+        let lb = balancer(
+            &[("tcp://a:1", 1), ("tcp://b:1", 1), ("tcp://c:1", 1)],
+            LoadBalanceStrategy::ConsistentHash,
+        );
+        let ip: IpAddr = "10.1.2.3".parse().unwrap();
+        let first = lb.select(Some(ip), None).endpoint().clone();
+        let second = lb
+            .select_excluding(Some(ip), None, std::slice::from_ref(&first))
+            .endpoint()
+            .clone();
+        assert!(!Arc::ptr_eq(&first, &second));
+
+        // 全部排除时仍然返回 endpoint
+        let all: Vec<Arc<Endpoint>> = lb.endpoints().to_vec();
+        lb.select_excluding(Some(ip), None, &all);
+    }
+
+    #[test]
+    fn test_zero_weight_rejected() {
+        let result = LoadBalancer::new(&targets(&[("tcp://a:1", 0)]), None);
+        assert!(result.is_err());
+    }
+}
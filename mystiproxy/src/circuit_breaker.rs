@@ -0,0 +1,421 @@
+//! 上游熔断模块
+//!
+//! 每个上游目标一个熔断器：连续失败次数或窗口内失败率超过阈值后打开，
+//! 打开期间直接拒绝请求；`open_duration` 到期后进入半开状态，放行少量探测请求，
+//! 探测全部成功则关闭，任一失败则重新打开。
+//!
+//! 状态变化以引擎名（[`crate::context::get_engine_name`]）记录日志并导出指标。
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use tracing::{info, warn};
+
+use crate::config::CircuitBreakerConfig;
+use crate::metrics::global_metrics;
+
+/// 默认连续失败阈值
+const DEFAULT_CONSECUTIVE_FAILURES: u32 = 5;
+
+/// 默认按失败率判定的最少请求数
+const DEFAULT_MIN_REQUESTS: u32 = 20;
+
+/// 默认失败率统计窗口
+const DEFAULT_WINDOW: Duration = Duration::from_secs(10);
+
+/// 默认打开时长
+const DEFAULT_OPEN_DURATION: Duration = Duration::from_secs(30);
+
+/// 默认半开探测请求数
+const DEFAULT_HALF_OPEN_REQUESTS: u32 = 1;
+
+/// 熔断器状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    /// 正常放行
+    Closed,
+    /// 拒绝请求
+    Open,
+    /// 放行探测请求
+    HalfOpen,
+}
+
+impl CircuitState {
+    /// 指标与日志中使用的名称
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CircuitState::Closed => "closed",
+            CircuitState::Open => "open",
+            CircuitState::HalfOpen => "half_open",
+        }
+    }
+}
+
+impl fmt::Display for CircuitState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// 解析后的熔断参数
+#[derive(Debug, Clone)]
+struct Settings {
+    consecutive_failures: u32,
+    failure_ratio: Option<f64>,
+    min_requests: u32,
+    window: Duration,
+    open_duration: Duration,
+    half_open_requests: u32,
+}
+
+impl Settings {
+    fn from_config(config: &CircuitBreakerConfig) -> Self {
+        Self {
+            consecutive_failures: config
+                .consecutive_failures
+                .unwrap_or(DEFAULT_CONSECUTIVE_FAILURES)
+                .max(1),
+            failure_ratio: config.failure_ratio,
+            min_requests: config.min_requests.unwrap_or(DEFAULT_MIN_REQUESTS).max(1),
+            window: config.window.unwrap_or(DEFAULT_WINDOW),
+            open_duration: config.open_duration.unwrap_or(DEFAULT_OPEN_DURATION),
+            half_open_requests: config
+                .half_open_requests
+                .unwrap_or(DEFAULT_HALF_OPEN_REQUESTS)
+                .max(1),
+        }
+    }
+}
+
+/// 熔断器可变状态
+#[derive(Debug)]
+struct Inner {
+    state: CircuitState,
+    consecutive_failures: u32,
+    window_start: Instant,
+    window_requests: u32,
+    window_failures: u32,
+    opened_at: Instant,
+    /// 半开状态下已放行、尚未返回结果的探测数
+    probes_in_flight: u32,
+    /// 半开状态下已成功的探测数
+    probe_successes: u32,
+}
+
+/// 单个上游目标的熔断器
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    engine: String,
+    target: String,
+    settings: Settings,
+    inner: Mutex<Inner>,
+}
+
+impl CircuitBreaker {
+    /// 创建关闭状态的熔断器
+    pub fn new(
+        engine: impl Into<String>,
+        target: impl Into<String>,
+        config: &CircuitBreakerConfig,
+    ) -> Self {
+        let now = Instant::now();
+        Self {
+            engine: engine.into(),
+            target: target.into(),
+            settings: Settings::from_config(config),
+            inner: Mutex::new(Inner {
+                state: CircuitState::Closed,
+                consecutive_failures: 0,
+                window_start: now,
+                window_requests: 0,
+                window_failures: 0,
+                opened_at: now,
+                probes_in_flight: 0,
+                probe_successes: 0,
+            }),
+        }
+    }
+
+    /// 上游目标地址
+    pub fn target(&self) -> &str {
+        &self.target
+    }
+
+    /// 当前状态（打开时长已到期的显示为半开）
+    pub fn state(&self) -> CircuitState {
+        let mut inner = self.inner.lock().unwrap();
+        self.refresh(&mut inner);
+        inner.state
+    }
+
+    /// 申请放行一个请求
+    ///
+    /// 打开状态或半开探测名额已满时返回 None；返回的许可需以
+    /// [`CircuitPermit::success`] 或 [`CircuitPermit::failure`] 上报结果。
+    pub fn try_acquire(self: &Arc<Self>) -> Option<CircuitPermit> {
+        let mut inner = self.inner.lock().unwrap();
+        self.refresh(&mut inner);
+        let probe = match inner.state {
+            CircuitState::Closed => false,
+            CircuitState::Open => return None,
+            CircuitState::HalfOpen => {
+                if inner.probes_in_flight + inner.probe_successes
+                    >= self.settings.half_open_requests
+                {
+                    return None;
+                }
+                inner.probes_in_flight += 1;
+                true
+            }
+        };
+        Some(CircuitPermit {
+            breaker: self.clone(),
+            probe,
+            reported: false,
+        })
+    }
+
+    /// 打开时长到期后转为半开
+    fn refresh(&self, inner: &mut Inner) {
+        if inner.state == CircuitState::Open
+            && inner.opened_at.elapsed() >= self.settings.open_duration
+        {
+            inner.probes_in_flight = 0;
+            inner.probe_successes = 0;
+            self.transition(inner, CircuitState::HalfOpen);
+        }
+    }
+
+    fn record(&self, probe: bool, success: bool) {
+        let mut inner = self.inner.lock().unwrap();
+        if probe {
+            inner.probes_in_flight = inner.probes_in_flight.saturating_sub(1);
+        }
+        match inner.state {
+            CircuitState::Closed => {
+                if inner.window_start.elapsed() >= self.settings.window {
+                    inner.window_start = Instant::now();
+                    inner.window_requests = 0;
+                    inner.window_failures = 0;
+                }
+                inner.window_requests += 1;
+                if success {
+                    inner.consecutive_failures = 0;
+                    return;
+                }
+                inner.window_failures += 1;
+                inner.consecutive_failures += 1;
+                let ratio_tripped = self.settings.failure_ratio.is_some_and(|ratio| {
+                    inner.window_requests >= self.settings.min_requests
+                        && f64::from(inner.window_failures) / f64::from(inner.window_requests)
+                            >= ratio
+                });
+                if inner.consecutive_failures >= self.settings.consecutive_failures || ratio_tripped
+                {
+                    self.open(&mut inner);
+                }
+            }
+            // 半开期间的探测结果决定去留；打开前放行的请求结果不再计入
+            CircuitState::HalfOpen if probe => {
+                if !success {
+                    self.open(&mut inner);
+                    return;
+                }
+                inner.probe_successes += 1;
+                if inner.probe_successes >= self.settings.half_open_requests {
+                    inner.consecutive_failures = 0;
+                    inner.window_start = Instant::now();
+                    inner.window_requests = 0;
+                    inner.window_failures = 0;
+                    self.transition(&mut inner, CircuitState::Closed);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// 未上报结果的许可被丢弃时归还半开探测名额
+    fn release(&self, probe: bool) {
+        if probe {
+            let mut inner = self.inner.lock().unwrap();
+            inner.probes_in_flight = inner.probes_in_flight.saturating_sub(1);
+        }
+    }
+
+    fn open(&self, inner: &mut Inner) {
+        inner.opened_at = Instant::now();
+        self.transition(inner, CircuitState::Open);
+    }
+
+    fn transition(&self, inner: &mut Inner, state: CircuitState) {
+        let previous = inner.state;
+        inner.state = state;
+        match state {
+            CircuitState::Open => warn!(
+                "[{}] Circuit breaker for {} opened (was {})",
+                self.engine, self.target, previous
+            ),
+            _ => info!(
+                "[{}] Circuit breaker for {} is now {} (was {})",
+                self.engine, self.target, state, previous
+            ),
+        }
+        global_metrics().record_circuit_breaker(&self.engine, &self.target, state);
+    }
+}
+
+/// 熔断器放行许可
+///
+/// 丢弃而未上报结果时（如请求被取消）不计成功或失败。
+#[derive(Debug)]
+pub struct CircuitPermit {
+    breaker: Arc<CircuitBreaker>,
+    probe: bool,
+    reported: bool,
+}
+
+impl CircuitPermit {
+    /// 上报请求成功
+    pub fn success(mut self) {
+        self.reported = true;
+        self.breaker.record(self.probe, true);
+    }
+
+    /// 上报请求失败
+    pub fn failure(mut self) {
+        self.reported = true;
+        self.breaker.record(self.probe, false);
+    }
+}
+
+impl Drop for CircuitPermit {
+    fn drop(&mut self) {
+        if !self.reported {
+            self.breaker.release(self.probe);
+        }
+    }
+}
+
+/// 一个引擎内按目标地址索引的熔断器集合
+#[derive(Debug)]
+pub struct CircuitBreakers {
+    engine: String,
+    config: CircuitBreakerConfig,
+    breakers: Mutex<HashMap<String, Arc<CircuitBreaker>>>,
+}
+
+impl CircuitBreakers {
+    /// 创建熔断器集合，引擎名取自当前线程上下文
+    pub fn new(config: CircuitBreakerConfig) -> Self {
+        Self {
+            engine: crate::context::get_engine_name().unwrap_or_else(|| "default".to_string()),
+            config,
+            breakers: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 获取目标的熔断器，首次访问时创建
+    pub fn get(&self, target: &str) -> Arc<CircuitBreaker> {
+        let mut breakers = self.breakers.lock().unwrap();
+        breakers
+            .entry(target.to_string())
+            .or_insert_with(|| Arc::new(CircuitBreaker::new(&self.engine, target, &self.config)))
+            .clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn breaker(config: CircuitBreakerConfig) -> Arc<CircuitBreaker> {
+        Arc::new(CircuitBreaker::new("test", "tcp://127.0.0.1:1", &config))
+    }
+
+    #[test]
+    fn test_opens_after_consecutive_failures() {
+        let cb = breaker(CircuitBreakerConfig {
+            consecutive_failures: Some(3),
+            ..Default::default()
+        });
+        cb.try_acquire().unwrap().failure();
+        cb.try_acquire().unwrap().failure();
+        cb.try_acquire().unwrap().success();
+        cb.try_acquire().unwrap().failure();
+        cb.try_acquire().unwrap().failure();
+        assert_eq!(cb.state(), CircuitState::Closed);
+        cb.try_acquire().unwrap().failure();
+        assert_eq!(cb.state(), CircuitState::Open);
+        assert!(cb.try_acquire().is_none());
+    }
+
+    #[test]
+    fn test_opens_on_failure_ratio() {
+        let cb = breaker(CircuitBreakerConfig {
+            consecutive_failures: Some(100),
+            failure_ratio: Some(0.5),
+            min_requests: Some(4),
+            ..Default::default()
+        });
+        cb.try_acquire().unwrap().failure();
+        cb.try_acquire().unwrap().failure();
+        cb.try_acquire().unwrap().success();
+        // 请求数不足 min_requests 时不按失败率判定
+        assert_eq!(cb.state(), CircuitState::Closed);
+        cb.try_acquire().unwrap().success();
+        cb.try_acquire().unwrap().failure();
+        // 3/5 的失败率超过阈值
+        assert_eq!(cb.state(), CircuitState::Open);
+    }
+
+    #[test]
+    fn test_half_open_probe_closes_or_reopens() {
+        let cb = breaker(CircuitBreakerConfig {
+            consecutive_failures: Some(1),
+            open_duration: Some(Duration::from_millis(20)),
+            ..Default::default()
+        });
+        cb.try_acquire().unwrap().failure();
+        assert!(cb.try_acquire().is_none());
+
+        std::thread::sleep(Duration::from_millis(30));
+        assert_eq!(cb.state(), CircuitState::HalfOpen);
+        let probe = cb.try_acquire().unwrap();
+        // 探测名额已占用
+        assert!(cb.try_acquire().is_none());
+        probe.failure();
+        assert_eq!(cb.state(), CircuitState::Open);
+
+        std::thread::sleep(Duration::from_millis(30));
+        cb.try_acquire().unwrap().success();
+        assert_eq!(cb.state(), CircuitState::Closed);
+        assert!(cb.try_acquire().is_some());
+    }
+
+    #[test]
+    fn test_dropped_probe_releases_slot() {
+        let cb = breaker(CircuitBreakerConfig {
+            consecutive_failures: Some(1),
+            open_duration: Some(Duration::from_millis(10)),
+            ..Default::default()
+        });
+        cb.try_acquire().unwrap().failure();
+        std::thread::sleep(Duration::from_millis(20));
+        drop(cb.try_acquire().unwrap());
+        assert_eq!(cb.state(), CircuitState::HalfOpen);
+        assert!(cb.try_acquire().is_some());
+    }
+
+    #[test]
+    fn test_breakers_are_per_target() {
+        let breakers = CircuitBreakers::new(CircuitBreakerConfig {
+            consecutive_failures: Some(1),
+            ..Default::default()
+        });
+        breakers.get("tcp://a:1").try_acquire().unwrap().failure();
+        assert_eq!(breakers.get("tcp://a:1").state(), CircuitState::Open);
+        assert_eq!(breakers.get("tcp://b:1").state(), CircuitState::Closed);
+    }
+}
@@ -338,7 +338,7 @@ mod tests {
     fn test_validate_engine_config_valid() {
         let engine = EngineConfig {
             listen: "tcp://0.0.0.0:8080".to_string(),
-            target: "tcp://127.0.0.1:80".to_string(),
+            target: "tcp://127.0.0.1:80".to_string().into(),
             proxy_type: ProxyType::Http,
             request_timeout: None,
             connection_timeout: None,
@@ -350,6 +350,20 @@ mod tests {
             allow: None,
             deny: None,
             management: None,
+            max_buffered_body_size: None,
+            load_balance: None,
+            health_check: None,
+            http2: None,
+            connection_pool: None,
+            upstream_tls: None,
+            retry: None,
+            circuit_breaker: None,
+            rate_limit: None,
+            connection_limit: None,
+            cache: None,
+            compression: None,
+            gateway: None,
+            mock_root: None,
         };
         assert!(validate_engine_config(&engine).is_ok());
     }
@@ -358,7 +372,7 @@ mod tests {
     fn test_validate_engine_config_invalid() {
         let engine = EngineConfig {
             listen: "tcp://0.0.0.0:8080".to_string(),
-            target: "tcp://127.0.0.1:80".to_string(),
+            target: "tcp://127.0.0.1:80".to_string().into(),
             proxy_type: ProxyType::Tcp,
             request_timeout: None,
             connection_timeout: None,
@@ -370,11 +384,25 @@ mod tests {
             allow: None,
             deny: None,
             management: None,
+            max_buffered_body_size: None,
+            load_balance: None,
+            health_check: None,
+            http2: None,
+            connection_pool: None,
+            upstream_tls: None,
+            retry: None,
+            circuit_breaker: None,
+            rate_limit: None,
+            connection_limit: None,
+            cache: None,
+            compression: None,
+            gateway: None,
+            mock_root: None,
         };
         assert!(validate_engine_config(&engine).is_ok());
 
         let mut engine2 = engine.clone();
-        engine2.target = "http://example.com".to_string();
+        engine2.target = "http://example.com".into();
         assert!(validate_engine_config(&engine2).is_err());
     }
 }
@@ -175,7 +175,7 @@ mod tests {
             "test".to_string(),
             EngineConfig {
                 listen: "tcp://0.0.0.0:8080".to_string(),
-                target: "tcp://127.0.0.1:80".to_string(),
+                target: "tcp://127.0.0.1:80".to_string().into(),
                 proxy_type: ProxyType::Http,
                 request_timeout: None,
                 connection_timeout: None,
@@ -187,6 +187,20 @@ mod tests {
                 allow: None,
                 deny: None,
                 management: None,
+                max_buffered_body_size: None,
+                load_balance: None,
+                health_check: None,
+                http2: None,
+                connection_pool: None,
+                upstream_tls: None,
+                retry: None,
+                circuit_breaker: None,
+                rate_limit: None,
+                connection_limit: None,
+                cache: None,
+                compression: None,
+                gateway: None,
+                mock_root: None,
             },
         );
         MystiConfig {
@@ -309,7 +323,7 @@ mod tests {
         let manager = ConfigurationManager::new(config).unwrap();
 
         let mut bad = create_test_config();
-        bad.mysti.engine.get_mut("test").unwrap().target = "http://example.com".to_string();
+        bad.mysti.engine.get_mut("test").unwrap().target = "http://example.com".into();
         // tcp 代理 target 必须是 tcp:// → 验证失败
         assert!(manager.update_config(bad).await.is_err());
 
@@ -42,8 +42,8 @@ pub struct Mysti {
 pub struct EngineConfig {
     /// 监听地址 (支持 tcp://, unix://)
     pub listen: String,
-    /// 目标地址 (支持 tcp://, unix://)
-    pub target: String,
+    /// 目标地址 (支持 tcp://, unix://)，可为单个地址或加权地址列表
+    pub target: TargetConfig,
     /// 代理类型
     pub proxy_type: ProxyType,
     /// 请求超时时间（完整代理操作）
@@ -85,6 +85,333 @@ pub struct EngineConfig {
     /// 本地管理模块（feature local-management）
     #[serde(default)]
     pub management: Option<ManagementConfig>,
+    /// 需要完整 body 时（如 JSON body 变换）允许缓冲的最大字节数，默认 10 MiB；
+    /// 其余请求/响应 body 均流式转发
+    #[serde(default)]
+    pub max_buffered_body_size: Option<usize>,
+    /// 多目标负载均衡策略（target 为列表时生效）
+    #[serde(default)]
+    pub load_balance: Option<LoadBalanceConfig>,
+    /// 上游健康检查（target 为列表时生效）
+    #[serde(default)]
+    pub health_check: Option<HealthCheckConfig>,
+    /// HTTP/2 配置（仅 http 引擎）
+    #[serde(default)]
+    pub http2: Option<Http2Config>,
+    /// 上游连接池配置（仅 http 引擎）
+    #[serde(default)]
+    pub connection_pool: Option<ConnectionPoolConfig>,
+    /// `https://` 上游的客户端 TLS 配置（仅 http 引擎）
+    #[serde(default)]
+    pub upstream_tls: Option<UpstreamTlsConfig>,
+    /// 代理请求的重试策略（仅 http 引擎，location 可覆盖）
+    #[serde(default)]
+    pub retry: Option<RetryConfig>,
+    /// 按上游目标熔断
+    #[serde(default)]
+    pub circuit_breaker: Option<CircuitBreakerConfig>,
+    /// 请求限流（仅 http 引擎）
+    #[serde(default)]
+    pub rate_limit: Option<RateLimitConfig>,
+    /// 连接数限制（仅 tcp 引擎）
+    #[serde(default)]
+    pub connection_limit: Option<ConnectionLimitConfig>,
+    /// 代理 GET 响应缓存（仅 http 引擎）
+    #[serde(default)]
+    pub cache: Option<CacheConfig>,
+    /// 代理与静态文件响应的压缩（仅 http 引擎，location 可覆盖）
+    #[serde(default)]
+    pub compression: Option<CompressionConfig>,
+    /// 网关路由：未命中 locations 的请求按 URI 映射转发到对应服务（仅 http 引擎）
+    #[serde(default)]
+    pub gateway: Option<GatewayConfig>,
+    /// mock `type: file` 响应体相对路径的根目录，默认为进程工作目录
+    #[serde(default)]
+    pub mock_root: Option<String>,
+}
+
+/// 网关配置
+///
+/// ```yaml
+/// gateway:
+///   services:
+///     users: tcp://10.0.0.1:8080
+///   routes:
+///     - method: GET,POST
+///       uri: /api/users/{id:[0-9]+}
+///       target_uri: /v1/users/{id}
+///       target_service: users
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GatewayConfig {
+    /// 服务名到上游地址的映射，供路由的 `target_service` 引用
+    #[serde(default)]
+    pub services: HashMap<String, String>,
+    /// URI 映射规则，按路径权重（更长、变量更少者优先）而非配置顺序匹配
+    pub routes: Vec<crate::gateway::UriMapping>,
+}
+
+/// 上游目标：单个地址或加权地址列表
+///
+/// ```yaml
+/// target: tcp://127.0.0.1:8080
+/// # 或
+/// target:
+///   - tcp://10.0.0.1:8080
+///   - address: tcp://10.0.0.2:8080
+///     weight: 3
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum TargetConfig {
+    /// 单个目标地址
+    Single(String),
+    /// 加权目标列表
+    Weighted(Vec<WeightedTarget>),
+}
+
+impl TargetConfig {
+    /// 第一个目标地址（用于日志展示与单目标转发）
+    pub fn primary(&self) -> &str {
+        match self {
+            TargetConfig::Single(addr) => addr,
+            TargetConfig::Weighted(targets) => {
+                targets.first().map(|t| t.address.as_str()).unwrap_or("")
+            }
+        }
+    }
+
+    /// 全部目标（单地址视为权重 1 的唯一目标）
+    pub fn endpoints(&self) -> Vec<WeightedTarget> {
+        match self {
+            TargetConfig::Single(addr) => vec![WeightedTarget {
+                address: addr.clone(),
+                weight: default_target_weight(),
+            }],
+            TargetConfig::Weighted(targets) => targets.clone(),
+        }
+    }
+
+    /// 是否配置了多个目标
+    pub fn is_multiple(&self) -> bool {
+        matches!(self, TargetConfig::Weighted(targets) if targets.len() > 1)
+    }
+}
+
+impl std::fmt::Display for TargetConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TargetConfig::Single(addr) => write!(f, "{addr}"),
+            TargetConfig::Weighted(targets) => {
+                let addrs: Vec<&str> = targets.iter().map(|t| t.address.as_str()).collect();
+                write!(f, "[{}]", addrs.join(", "))
+            }
+        }
+    }
+}
+
+impl From<String> for TargetConfig {
+    fn from(addr: String) -> Self {
+        TargetConfig::Single(addr)
+    }
+}
+
+impl From<&str> for TargetConfig {
+    fn from(addr: &str) -> Self {
+        TargetConfig::Single(addr.to_string())
+    }
+}
+
+impl PartialEq<&str> for TargetConfig {
+    fn eq(&self, other: &&str) -> bool {
+        matches!(self, TargetConfig::Single(addr) if addr == other)
+    }
+}
+
+/// 加权目标（列表项可直接写地址字符串，权重默认为 1）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "WeightedTargetRepr")]
+pub struct WeightedTarget {
+    /// 目标地址 (支持 tcp://, unix://)
+    pub address: String,
+    /// 权重
+    pub weight: u32,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum WeightedTargetRepr {
+    Address(String),
+    Full {
+        address: String,
+        #[serde(default = "default_target_weight")]
+        weight: u32,
+    },
+}
+
+impl From<WeightedTargetRepr> for WeightedTarget {
+    fn from(repr: WeightedTargetRepr) -> Self {
+        match repr {
+            WeightedTargetRepr::Address(address) => WeightedTarget {
+                address,
+                weight: default_target_weight(),
+            },
+            WeightedTargetRepr::Full { address, weight } => WeightedTarget { address, weight },
+        }
+    }
+}
+
+fn default_target_weight() -> u32 {
+    1
+}
+
+/// 负载均衡配置
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LoadBalanceConfig {
+    /// 均衡策略
+    #[serde(default)]
+    pub strategy: LoadBalanceStrategy,
+    /// 一致性哈希使用的请求头；未配置或请求缺失该头时按客户端 IP 哈希
+    #[serde(default)]
+    pub hash_header: Option<String>,
+}
+
+/// HTTP/2 配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Http2Config {
+    /// 入站接受 HTTP/2：TLS 经 ALPN 协商 h2，明文为 prior-knowledge h2c
+    ///
+    /// 配置了 `http2` 时默认 true；未配置 `http2` 时入站仅 HTTP/1.1
+    #[serde(default = "default_http2_server")]
+    pub server: bool,
+    /// 以 prior-knowledge HTTP/2 连接上游（默认 false）
+    #[serde(default)]
+    pub upstream: bool,
+}
+
+impl Default for Http2Config {
+    fn default() -> Self {
+        Self {
+            server: default_http2_server(),
+            upstream: false,
+        }
+    }
+}
+
+fn default_http2_server() -> bool {
+    true
+}
+
+/// 上游连接池配置
+///
+/// 每个上游目标维护一组空闲的 keep-alive 连接，请求优先复用。
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ConnectionPoolConfig {
+    /// 每个目标保留的最大空闲连接数（默认 32，0 表示不复用连接）
+    #[serde(default)]
+    pub max_idle: Option<usize>,
+    /// 空闲连接的最长保留时间（默认 90s）
+    #[serde(
+        default,
+        deserialize_with = "deserialize_option_duration",
+        serialize_with = "serialize_option_duration"
+    )]
+    pub idle_timeout: Option<Duration>,
+    /// 连接自建立起的最长使用时间（默认不限）
+    #[serde(
+        default,
+        deserialize_with = "deserialize_option_duration",
+        serialize_with = "serialize_option_duration"
+    )]
+    pub max_lifetime: Option<Duration>,
+}
+
+/// 上游健康检查配置
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HealthCheckConfig {
+    /// 主动探测
+    #[serde(default)]
+    pub active: Option<ActiveHealthCheckConfig>,
+    /// 被动异常检测（基于真实流量）
+    #[serde(default)]
+    pub passive: Option<PassiveHealthCheckConfig>,
+}
+
+/// 主动健康检查配置
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ActiveHealthCheckConfig {
+    /// 探测类型
+    #[serde(rename = "type", default)]
+    pub check_type: HealthCheckType,
+    /// 探测间隔（默认 10s）
+    #[serde(
+        default,
+        deserialize_with = "deserialize_option_duration",
+        serialize_with = "serialize_option_duration"
+    )]
+    pub interval: Option<Duration>,
+    /// 单次探测超时（默认 2s）
+    #[serde(
+        default,
+        deserialize_with = "deserialize_option_duration",
+        serialize_with = "serialize_option_duration"
+    )]
+    pub timeout: Option<Duration>,
+    /// HTTP 探测路径（默认 /）
+    #[serde(default)]
+    pub path: Option<String>,
+    /// HTTP 探测期望状态码（默认 200）
+    #[serde(default)]
+    pub expected_status: Option<u16>,
+    /// 连续成功多少次恢复为健康（默认 1）
+    #[serde(default)]
+    pub healthy_threshold: Option<u32>,
+    /// 连续失败多少次标记为不健康（默认 1）
+    #[serde(default)]
+    pub unhealthy_threshold: Option<u32>,
+}
+
+/// 主动探测类型
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum HealthCheckType {
+    /// TCP 建连
+    #[default]
+    Tcp,
+    /// HTTP GET 并校验状态码
+    Http,
+    /// Unix Domain Socket 建连
+    Uds,
+}
+
+/// 被动异常检测配置
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PassiveHealthCheckConfig {
+    /// 连续多少次连接错误或 5xx 后摘除（默认 5）
+    #[serde(default)]
+    pub consecutive_failures: Option<u32>,
+    /// 摘除后的冷却时间，到期自动恢复（默认 30s）
+    #[serde(
+        default,
+        deserialize_with = "deserialize_option_duration",
+        serialize_with = "serialize_option_duration"
+    )]
+    pub cooldown: Option<Duration>,
+}
+
+/// 负载均衡策略
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum LoadBalanceStrategy {
+    /// 加权轮询
+    #[default]
+    RoundRobin,
+    /// 最少连接（按权重归一）
+    LeastConnections,
+    /// 加权随机
+    Random,
+    /// 一致性哈希（客户端 IP 或指定请求头）
+    ConsistentHash,
 }
 
 /// TLS 配置
@@ -102,6 +429,222 @@ pub struct TlsConfig {
     pub mutual_auth: bool,
 }
 
+/// 代理请求重试策略
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RetryConfig {
+    /// 最大尝试次数，含首次请求（默认 3）
+    #[serde(default)]
+    pub attempts: Option<u32>,
+    /// 触发重试的上游响应状态码（默认 502、503、504）
+    #[serde(default)]
+    pub retry_on_status: Option<Vec<u16>>,
+    /// 触发重试的错误类型（默认 connect_error、timeout）
+    #[serde(default)]
+    pub retry_on: Option<Vec<RetryOn>>,
+    /// 允许重试的请求方法（默认仅幂等方法：GET、HEAD、OPTIONS、PUT、DELETE、TRACE）
+    #[serde(default)]
+    pub methods: Option<Vec<String>>,
+    /// 指数退避的初始间隔（默认 25ms）
+    #[serde(
+        default,
+        deserialize_with = "deserialize_option_duration",
+        serialize_with = "serialize_option_duration"
+    )]
+    pub backoff: Option<Duration>,
+    /// 退避间隔上限（默认 250ms）
+    #[serde(
+        default,
+        deserialize_with = "deserialize_option_duration",
+        serialize_with = "serialize_option_duration"
+    )]
+    pub max_backoff: Option<Duration>,
+    /// 单次尝试的超时时间，与 request_timeout 分别计算（默认不限）
+    #[serde(
+        default,
+        deserialize_with = "deserialize_option_duration",
+        serialize_with = "serialize_option_duration"
+    )]
+    pub per_try_timeout: Option<Duration>,
+}
+
+/// 可重试的错误类型
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RetryOn {
+    /// 建连失败或收到响应前连接中断
+    ConnectError,
+    /// 单次尝试超时
+    Timeout,
+}
+
+/// 上游熔断配置
+///
+/// 每个上游目标独立熔断：打开期间直接拒绝请求，冷却后进入半开状态放行探测请求。
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CircuitBreakerConfig {
+    /// 连续失败多少次后打开（默认 5）
+    #[serde(default)]
+    pub consecutive_failures: Option<u32>,
+    /// 统计窗口内失败率达到该值（0~1）后打开，默认不按失败率熔断
+    #[serde(default)]
+    pub failure_ratio: Option<f64>,
+    /// 按失败率判定所需的窗口内最少请求数（默认 20）
+    #[serde(default)]
+    pub min_requests: Option<u32>,
+    /// 失败率统计窗口（默认 10s）
+    #[serde(
+        default,
+        deserialize_with = "deserialize_option_duration",
+        serialize_with = "serialize_option_duration"
+    )]
+    pub window: Option<Duration>,
+    /// 打开后保持的时间，到期进入半开状态（默认 30s）
+    #[serde(
+        default,
+        deserialize_with = "deserialize_option_duration",
+        serialize_with = "serialize_option_duration"
+    )]
+    pub open_duration: Option<Duration>,
+    /// 半开状态放行的探测请求数，全部成功后关闭（默认 1）
+    #[serde(default)]
+    pub half_open_requests: Option<u32>,
+    /// 打开期间返回的响应（仅 http 引擎），默认 503
+    #[serde(default)]
+    pub fallback: Option<ResponseConfig>,
+}
+
+/// 令牌桶请求限流配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RateLimitConfig {
+    /// 每秒补充的令牌数（即平均每秒请求数）
+    pub requests_per_second: f64,
+    /// 桶容量，即允许的突发请求数（默认为 requests_per_second 向上取整）
+    #[serde(default)]
+    pub burst: Option<u32>,
+    /// 限流键（默认 client_ip）
+    #[serde(default)]
+    pub key: RateLimitKey,
+    /// key 为 header 时取值的请求头
+    #[serde(default)]
+    pub header_name: Option<String>,
+}
+
+/// 限流键
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RateLimitKey {
+    /// 客户端 IP
+    #[default]
+    ClientIp,
+    /// 指定请求头的值（缺失时按客户端 IP）
+    Header,
+    /// JWT 鉴权的 `sub`（缺失时按客户端 IP）
+    JwtSub,
+    /// 所有请求共享一个桶
+    Global,
+}
+
+/// TCP 引擎连接数限制
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ConnectionLimitConfig {
+    /// 最大并发连接数
+    #[serde(default)]
+    pub max_connections: Option<usize>,
+    /// 每秒新建连接数
+    #[serde(default)]
+    pub connections_per_second: Option<f64>,
+    /// 新建连接的突发容量（默认为 connections_per_second 向上取整）
+    #[serde(default)]
+    pub burst: Option<u32>,
+}
+
+/// 代理响应缓存配置
+///
+/// 按 `Cache-Control`/`Expires` 计算新鲜度，过期后携带 `ETag`/`Last-Modified` 向上游验证，
+/// 并按 `Vary` 区分变体。
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CacheConfig {
+    /// 内存中最多缓存的响应数，超出后按 LRU 淘汰（默认 1024）
+    #[serde(default)]
+    pub max_entries: Option<usize>,
+    /// 单个响应体的最大字节数，超出不缓存（默认 1 MiB）
+    #[serde(default)]
+    pub max_entry_size: Option<usize>,
+    /// 磁盘缓存目录；配置后响应同时写入磁盘，内存未命中时从磁盘加载
+    #[serde(default)]
+    pub disk_path: Option<String>,
+    /// 允许通过指标服务的 `POST /cache/purge` 清除该引擎的缓存（默认关闭）
+    #[serde(default)]
+    pub purge_endpoint: bool,
+}
+
+/// location 级缓存规则
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LocationCacheConfig {
+    /// 强制使用的新鲜期，忽略上游的 `Cache-Control`/`Expires`（`no-store`、`private` 仍不缓存）
+    #[serde(
+        default,
+        deserialize_with = "deserialize_option_duration",
+        serialize_with = "serialize_option_duration"
+    )]
+    pub ttl: Option<Duration>,
+    /// 跳过缓存
+    #[serde(default)]
+    pub bypass: bool,
+}
+
+/// 响应压缩配置
+///
+/// 按 `Accept-Encoding` 协商编码；引擎级与 location 级可同时配置，未设置的字段沿用引擎级。
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CompressionConfig {
+    /// 是否启用（默认 true，location 可设为 false 关闭）
+    #[serde(default)]
+    pub enabled: Option<bool>,
+    /// 可用编码，按服务端偏好排序（默认 br、zstd、gzip）
+    #[serde(default)]
+    pub algorithms: Option<Vec<CompressionAlgorithm>>,
+    /// 小于该字节数的响应不压缩（默认 1024，仅在 `Content-Length` 已知时判断）
+    #[serde(default)]
+    pub min_size: Option<usize>,
+    /// 允许压缩的 Content-Type，`text/*` 形式匹配整类（默认文本、JSON、JavaScript、XML 与 SVG）
+    #[serde(default)]
+    pub content_types: Option<Vec<String>>,
+    /// JSON body 变换需要读取带 `Content-Encoding` 的 body 时先解压（默认 false，不解压则跳过变换）
+    #[serde(default)]
+    pub decompress_for_transform: Option<bool>,
+}
+
+/// 压缩编码
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CompressionAlgorithm {
+    Gzip,
+    #[serde(alias = "brotli")]
+    Br,
+    Zstd,
+}
+
+/// 上游客户端 TLS 配置
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UpstreamTlsConfig {
+    /// 校验上游证书的 CA 证书路径（未配置时使用内置根证书）
+    #[serde(default)]
+    pub ca_path: Option<String>,
+    /// 客户端证书路径（双向认证）
+    #[serde(default)]
+    pub cert_path: Option<String>,
+    /// 客户端私钥路径（双向认证）
+    #[serde(default)]
+    pub key_path: Option<String>,
+    /// 覆盖 SNI 与证书校验使用的服务器名（默认取 target 主机名）
+    #[serde(default)]
+    pub sni: Option<String>,
+    /// 跳过上游证书校验（仅用于测试环境）
+    #[serde(default)]
+    pub insecure_skip_verify: bool,
+}
+
 /// HTTP 鉴权配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AuthConfig {
@@ -190,6 +733,39 @@ pub struct LocationConfig {
     pub index_files: Option<Vec<String>>,
     #[serde(default)]
     pub enable_directory_listing: Option<bool>,
+    /// gRPC 配置（provider 为 grpc 时生效）
+    #[serde(default)]
+    pub grpc: Option<GrpcConfig>,
+    /// 重定向配置（provider 为 redirect 时生效）
+    #[serde(default)]
+    pub redirect: Option<RedirectConfig>,
+    /// 重试策略（覆盖引擎级 retry）
+    #[serde(default)]
+    pub retry: Option<RetryConfig>,
+    /// 该路由的请求限流（与引擎级 rate_limit 同时生效）
+    #[serde(default)]
+    pub rate_limit: Option<RateLimitConfig>,
+    /// 该路由的缓存规则（需引擎配置 cache）
+    #[serde(default)]
+    pub cache: Option<LocationCacheConfig>,
+    /// 该路由的压缩配置，逐字段覆盖引擎级 compression
+    #[serde(default)]
+    pub compression: Option<CompressionConfig>,
+    /// 按 Host 匹配：精确主机名或 `*.example.com` 通配子域名，未配置时匹配任意 Host
+    #[serde(default)]
+    pub hosts: Option<Vec<String>>,
+    /// 按请求方法匹配（大小写不敏感），未配置时匹配任意方法
+    #[serde(default)]
+    pub methods: Option<Vec<String>>,
+    /// 按请求头匹配：值须相等，值为 `*` 时只要求请求头存在
+    #[serde(default)]
+    pub match_headers: Option<HashMap<String, String>>,
+    /// 按查询参数匹配：值须相等，值为 `*` 时只要求参数存在
+    #[serde(default)]
+    pub match_query: Option<HashMap<String, String>>,
+    /// 该路由的上游地址，覆盖引擎级 target（不参与引擎级负载均衡）
+    #[serde(default)]
+    pub target: Option<String>,
 }
 
 /// 匹配模式
@@ -217,6 +793,69 @@ pub enum ProviderType {
     Mock,
     /// 代理提供者
     Proxy,
+    /// gRPC 提供者：按 `/package.Service/Method` 路由，mock 一元响应或经 HTTP/2 代理
+    Grpc,
+    /// 重定向提供者：返回 301/302/307/308 与 `Location`
+    Redirect,
+}
+
+/// 重定向 provider 配置
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RedirectConfig {
+    /// 状态码：301/302/307/308，默认 302
+    #[serde(default)]
+    pub status: Option<u16>,
+    /// `Location` 模板：路径或绝对 URL，`{name}` 引用路由参数；未配置时沿用请求路径
+    #[serde(default)]
+    pub location: Option<String>,
+    /// 不追加原请求的查询串
+    #[serde(default)]
+    pub drop_query: bool,
+    /// 非 HTTPS 请求重定向到 `https://`
+    #[serde(default)]
+    pub force_https: bool,
+    /// 规范主机名：请求 Host 不同时重定向到该主机
+    #[serde(default)]
+    pub host: Option<String>,
+    /// 路径末尾斜杠处理
+    #[serde(default)]
+    pub trailing_slash: Option<TrailingSlash>,
+}
+
+/// 路径末尾斜杠规则
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TrailingSlash {
+    /// 补全末尾斜杠
+    Add,
+    /// 去掉末尾斜杠
+    Strip,
+}
+
+/// gRPC provider 配置
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GrpcConfig {
+    /// FileDescriptorSet 文件路径（`protoc --include_imports --descriptor_set_out=...`），
+    /// mock 响应消息编码时必需
+    #[serde(default)]
+    pub descriptor_set: Option<String>,
+    /// mock 一元响应；未配置时代理到上游
+    #[serde(default)]
+    pub mock: Option<GrpcMockConfig>,
+}
+
+/// gRPC mock 响应配置
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GrpcMockConfig {
+    /// 响应消息的 JSON 表示，按方法的输出类型编码为 protobuf
+    #[serde(default)]
+    pub body: Option<serde_json::Value>,
+    /// grpc-status（默认 0，即 OK）
+    #[serde(default)]
+    pub status: u32,
+    /// grpc-message
+    #[serde(default)]
+    pub message: Option<String>,
 }
 
 /// 头部动作配置
@@ -247,11 +886,17 @@ pub enum HeaderActionType {
 }
 
 /// 响应配置
+///
+/// mock location 据此生成响应；proxy location 据此改写上游响应
+/// （状态码、响应头，以及 JSON body 变换或按模版/静态内容替换 body）。
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ResponseConfig {
-    /// 状态码
+    /// 状态码（proxy location 上覆盖所有未被 status_map 命中的上游状态码）
     #[serde(default)]
     pub status: Option<u16>,
+    /// 上游状态码重映射（仅 proxy location），如 `502: 503`
+    #[serde(default)]
+    pub status_map: Option<HashMap<u16, u16>>,
     /// 响应头
     #[serde(default)]
     pub headers: Option<HashMap<String, HeaderAction>>,
@@ -261,6 +906,52 @@ pub struct ResponseConfig {
     /// 命中条件（多条件 AND；不命中则回退下一 location）
     #[serde(default)]
     pub conditions: Option<Vec<ConditionCfg>>,
+    /// 响应序列（仅 mock location）：按调用次数在多个响应间切换
+    #[serde(default)]
+    pub sequence: Option<ResponseSequence>,
+}
+
+/// 响应序列配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResponseSequence {
+    /// 选择模式
+    #[serde(default)]
+    pub mode: SequenceMode,
+    /// 有序响应列表；未设置的字段沿用外层 response
+    pub responses: Vec<ResponseStep>,
+}
+
+/// 响应序列的选择模式
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum SequenceMode {
+    /// 依次返回，结束后停留在最后一个
+    #[default]
+    Sequential,
+    /// 依次返回，结束后从头循环
+    Cycle,
+    /// 按权重随机选择
+    RandomWeighted,
+}
+
+/// 响应序列中的一项
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ResponseStep {
+    /// 状态码
+    #[serde(default)]
+    pub status: Option<u16>,
+    /// 响应头（与外层合并，同名覆盖）
+    #[serde(default)]
+    pub headers: Option<HashMap<String, HeaderAction>>,
+    /// 响应体
+    #[serde(default)]
+    pub body: Option<BodyConfig>,
+    /// 连续返回次数（sequential/cycle），默认 1
+    #[serde(default)]
+    pub repeat: Option<u32>,
+    /// 权重（random-weighted），默认 1
+    #[serde(default)]
+    pub weight: Option<u32>,
 }
 
 /// Mock 命中条件（配置面）
@@ -293,12 +984,24 @@ pub struct RequestConfig {
 /// URI 配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UriConfig {
-    /// 路径
+    /// 路径，支持 `{name}` 引用路由捕获的参数及 `{remaining}` 剩余路径
     #[serde(default)]
     pub path: Option<String>,
     /// 查询参数
     #[serde(default)]
     pub query: Option<String>,
+    /// 正则替换规则，作用于 `path` 展开后的路径
+    #[serde(default)]
+    pub rewrite: Option<PathRewriteConfig>,
+}
+
+/// 路径正则替换规则
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PathRewriteConfig {
+    /// 匹配路径的正则
+    pub pattern: String,
+    /// 替换内容，支持 `$1`、`${name}` 引用捕获组
+    pub replacement: String,
 }
 
 /// 请求体配置
@@ -316,6 +1019,12 @@ pub struct BodyConfig {
     /// 模版字符串（type=template 时生效）
     #[serde(default)]
     pub template: Option<String>,
+    /// 文件路径（type=file 时生效），相对路径基于引擎的 `mock_root`
+    #[serde(default)]
+    pub file: Option<String>,
+    /// 是否将文件内容作为模版渲染（type=file 时生效），默认 false 时原样流式返回
+    #[serde(default)]
+    pub templated: Option<bool>,
 }
 
 /// JSON 请求体配置
@@ -354,6 +1063,8 @@ pub enum BodyType {
     Json,
     /// 模版类型（占位符替换）
     Template,
+    /// 文件类型（每次请求时从磁盘读取）
+    File,
 }
 
 /// 自定义 Duration 反序列化函数（支持 Option<Duration>）
@@ -682,4 +1393,304 @@ cert:
         assert_eq!(config.cert.len(), 1);
         assert_eq!(config.cert[0].name, "client1");
     }
+
+    #[test]
+    fn test_weighted_target_list() {
+        let yaml = r#"
+listen: tcp://0.0.0.0:3128
+target:
+  - tcp://10.0.0.1:8080
+  - address: tcp://10.0.0.2:8080
+    weight: 3
+proxy_type: http
+load_balance:
+  strategy: consistent_hash
+  hash_header: X-User-Id
+"#;
+        let config: EngineConfig = serde_yaml::from_str(yaml).unwrap();
+        assert!(config.target.is_multiple());
+        assert_eq!(config.target.primary(), "tcp://10.0.0.1:8080");
+        assert_eq!(
+            config.target.endpoints(),
+            vec![
+                WeightedTarget {
+                    address: "tcp://10.0.0.1:8080".to_string(),
+                    weight: 1,
+                },
+                WeightedTarget {
+                    address: "tcp://10.0.0.2:8080".to_string(),
+                    weight: 3,
+                },
+            ]
+        );
+        let lb = config.load_balance.unwrap();
+        assert_eq!(lb.strategy, LoadBalanceStrategy::ConsistentHash);
+        assert_eq!(lb.hash_header.as_deref(), Some("X-User-Id"));
+
+        // 单地址保持原格式往返
+        let single: TargetConfig = serde_yaml::from_str("tcp://127.0.0.1:80").unwrap();
+        assert_eq!(single, "tcp://127.0.0.1:80");
+        assert!(!single.is_multiple());
+        assert_eq!(
+            serde_yaml::to_string(&single).unwrap().trim(),
+            "tcp://127.0.0.1:80"
+        );
+    }
+
+    #[test]
+    fn test_health_check_config() {
+        let yaml = r#"
+listen: tcp://0.0.0.0:3128
+target:
+  - tcp://10.0.0.1:8080
+  - tcp://10.0.0.2:8080
+proxy_type: http
+health_check:
+  active:
+    type: http
+    interval: 5s
+    timeout: 1s
+    path: /healthz
+    expected_status: 204
+  passive:
+    consecutive_failures: 3
+    cooldown: 1m
+"#;
+        let config: EngineConfig = serde_yaml::from_str(yaml).unwrap();
+        let health_check = config.health_check.unwrap();
+        let active = health_check.active.unwrap();
+        assert_eq!(active.check_type, HealthCheckType::Http);
+        assert_eq!(active.interval, Some(Duration::from_secs(5)));
+        assert_eq!(active.timeout, Some(Duration::from_secs(1)));
+        assert_eq!(active.path.as_deref(), Some("/healthz"));
+        assert_eq!(active.expected_status, Some(204));
+        let passive = health_check.passive.unwrap();
+        assert_eq!(passive.consecutive_failures, Some(3));
+        assert_eq!(passive.cooldown, Some(Duration::from_secs(60)));
+    }
+
+    #[test]
+    fn test_retry_config() {
+        let yaml = r#"
+listen: tcp://0.0.0.0:3128
+target: tcp://127.0.0.1:8080
+proxy_type: http
+retry:
+  attempts: 4
+  retry_on_status: [503]
+  retry_on: [connect_error]
+  methods: [GET, POST]
+  backoff: 10ms
+  per_try_timeout: 2s
+locations:
+  - location: /api
+    mode: Prefix
+    retry:
+      attempts: 1
+"#;
+        let config: EngineConfig = serde_yaml::from_str(yaml).unwrap();
+        let retry = config.retry.unwrap();
+        assert_eq!(retry.attempts, Some(4));
+        assert_eq!(retry.retry_on_status, Some(vec![503]));
+        assert_eq!(retry.retry_on, Some(vec![RetryOn::ConnectError]));
+        assert_eq!(retry.backoff, Some(Duration::from_millis(10)));
+        assert_eq!(retry.per_try_timeout, Some(Duration::from_secs(2)));
+        let location_retry = config.locations.unwrap()[0].retry.clone().unwrap();
+        assert_eq!(location_retry.attempts, Some(1));
+    }
+
+    #[test]
+    fn test_circuit_breaker_config() {
+        let yaml = r#"
+listen: tcp://0.0.0.0:3128
+target: tcp://127.0.0.1:8080
+proxy_type: http
+circuit_breaker:
+  consecutive_failures: 3
+  failure_ratio: 0.5
+  min_requests: 10
+  window: 5s
+  open_duration: 1m
+  half_open_requests: 2
+  fallback:
+    status: 503
+    body:
+      type: static
+      content: '{"error":"upstream unavailable"}'
+"#;
+        let config: EngineConfig = serde_yaml::from_str(yaml).unwrap();
+        let breaker = config.circuit_breaker.unwrap();
+        assert_eq!(breaker.consecutive_failures, Some(3));
+        assert_eq!(breaker.failure_ratio, Some(0.5));
+        assert_eq!(breaker.min_requests, Some(10));
+        assert_eq!(breaker.window, Some(Duration::from_secs(5)));
+        assert_eq!(breaker.open_duration, Some(Duration::from_secs(60)));
+        assert_eq!(breaker.half_open_requests, Some(2));
+        assert_eq!(breaker.fallback.unwrap().status, Some(503));
+    }
+
+    #[test]
+    fn test_rate_limit_config() {
+        let yaml = r#"
+listen: tcp://0.0.0.0:3128
+target: tcp://127.0.0.1:8080
+proxy_type: http
+rate_limit:
+  requests_per_second: 100
+  burst: 200
+  key: header
+  header_name: X-Api-Key
+locations:
+  - location: /login
+    mode: Full
+    rate_limit:
+      requests_per_second: 0.5
+"#;
+        let config: EngineConfig = serde_yaml::from_str(yaml).unwrap();
+        let limit = config.rate_limit.unwrap();
+        assert_eq!(limit.requests_per_second, 100.0);
+        assert_eq!(limit.burst, Some(200));
+        assert_eq!(limit.key, RateLimitKey::Header);
+        assert_eq!(limit.header_name.as_deref(), Some("X-Api-Key"));
+        let location_limit = config.locations.unwrap()[0].rate_limit.clone().unwrap();
+        assert_eq!(location_limit.key, RateLimitKey::ClientIp);
+
+        let yaml = r#"
+listen: tcp://0.0.0.0:3306
+target: tcp://127.0.0.1:3307
+proxy_type: tcp
+connection_limit:
+  max_connections: 500
+  connections_per_second: 50
+"#;
+        let config: EngineConfig = serde_yaml::from_str(yaml).unwrap();
+        let limit = config.connection_limit.unwrap();
+        assert_eq!(limit.max_connections, Some(500));
+        assert_eq!(limit.connections_per_second, Some(50.0));
+    }
+
+    #[test]
+    fn test_proxy_response_transform_config() {
+        let yaml = r#"
+location: /api
+mode: Prefix
+provider: proxy
+response:
+  status_map:
+    502: 503
+    404: 200
+  headers:
+    Server:
+      value: ""
+      action: forceDelete
+  body:
+    json:
+      path: $.source
+      value: gateway
+      action: add
+"#;
+        let location: LocationConfig = serde_yaml::from_str(yaml).unwrap();
+        let response = location.response.unwrap();
+        let status_map = response.status_map.unwrap();
+        assert_eq!(status_map.get(&502), Some(&503));
+        assert_eq!(status_map.get(&404), Some(&200));
+        assert_eq!(response.status, None);
+        assert_eq!(
+            response.headers.unwrap()["Server"].action,
+            HeaderActionType::ForceDelete
+        );
+        let json = response.body.unwrap().json.unwrap();
+        assert_eq!(json.path, "$.source");
+        assert_eq!(json.action, JsonBodyAction::Add);
+    }
+
+    #[test]
+    fn test_compression_config() {
+        let yaml = r#"
+listen: tcp://0.0.0.0:3128
+target: tcp://127.0.0.1:8080
+proxy_type: http
+compression:
+  algorithms: [gzip, brotli]
+  min_size: 256
+locations:
+  - location: /api
+    mode: Prefix
+    compression:
+      content_types: [application/json]
+      decompress_for_transform: true
+  - location: /download
+    mode: Prefix
+    compression:
+      enabled: false
+"#;
+        let config: EngineConfig = serde_yaml::from_str(yaml).unwrap();
+        let compression = config.compression.unwrap();
+        assert_eq!(
+            compression.algorithms,
+            Some(vec![CompressionAlgorithm::Gzip, CompressionAlgorithm::Br])
+        );
+        assert_eq!(compression.min_size, Some(256));
+        assert_eq!(compression.enabled, None);
+        let locations = config.locations.unwrap();
+        let api = locations[0].compression.clone().unwrap();
+        assert_eq!(
+            api.content_types,
+            Some(vec!["application/json".to_string()])
+        );
+        assert_eq!(api.decompress_for_transform, Some(true));
+        assert_eq!(
+            locations[1].compression.as_ref().unwrap().enabled,
+            Some(false)
+        );
+    }
+
+    #[test]
+    fn test_cache_config() {
+        let yaml = r#"
+listen: tcp://0.0.0.0:3128
+target: tcp://127.0.0.1:8080
+proxy_type: http
+cache:
+  max_entries: 500
+  disk_path: /var/cache/mystiproxy
+locations:
+  - location: /static
+    mode: Prefix
+    cache:
+      ttl: 1h
+  - location: /live
+    mode: Prefix
+    cache:
+      bypass: true
+"#;
+        let config: EngineConfig = serde_yaml::from_str(yaml).unwrap();
+        let cache = config.cache.unwrap();
+        assert_eq!(cache.max_entries, Some(500));
+        assert_eq!(cache.max_entry_size, None);
+        assert_eq!(cache.disk_path.as_deref(), Some("/var/cache/mystiproxy"));
+        let locations = config.locations.unwrap();
+        let forced = locations[0].cache.clone().unwrap();
+        assert_eq!(forced.ttl, Some(Duration::from_secs(3600)));
+        assert!(!forced.bypass);
+        assert!(locations[1].cache.as_ref().unwrap().bypass);
+    }
+
+    #[test]
+    fn test_connection_pool_config() {
+        let yaml = r#"
+listen: tcp://0.0.0.0:3128
+target: unix:///var/run/docker.sock
+proxy_type: http
+connection_pool:
+  max_idle: 8
+  idle_timeout: 30s
+  max_lifetime: 10m
+"#;
+        let config: EngineConfig = serde_yaml::from_str(yaml).unwrap();
+        let pool = config.connection_pool.unwrap();
+        assert_eq!(pool.max_idle, Some(8));
+        assert_eq!(pool.idle_timeout, Some(Duration::from_secs(30)));
+        assert_eq!(pool.max_lifetime, Some(Duration::from_secs(600)));
+    }
 }
@@ -137,8 +137,8 @@ impl ConfigUserInterface {
                     ("proxy_type", "http_proxy_requires_tcp_or_unix_listen") => {
                         "HTTP proxy listen must be tcp:// or unix://".to_string()
                     }
-                    ("proxy_type", "http_proxy_requires_tcp_or_unix_target") => {
-                        "HTTP proxy target must be tcp:// or unix://".to_string()
+                    ("proxy_type", "http_proxy_requires_tcp_unix_or_https_target") => {
+                        "HTTP proxy target must be tcp://, unix:// or https://".to_string()
                     }
                     ("proxy_type", "forward_proxy_requires_tcp_listen") => {
                         "Forward proxy requires tcp:// listen address".to_string()
@@ -171,6 +171,86 @@ impl ConfigUserInterface {
                     ("upstream", "invalid_upstream_proxy_url") => {
                         "Invalid upstream proxy URL format".to_string()
                     }
+                    ("upstream_tls", "upstream_tls_cert_and_key_required_together") => {
+                        "upstream_tls cert_path and key_path must be set together".to_string()
+                    }
+                    ("upstream_tls", "upstream_tls_file_not_found") => {
+                        "upstream_tls certificate or key file not found".to_string()
+                    }
+                    ("retry", "retry_attempts_zero") => {
+                        "retry attempts must be at least 1".to_string()
+                    }
+                    ("retry", "retry_invalid_status_code") => {
+                        "retry retry_on_status entries must be in 100..=599".to_string()
+                    }
+                    ("retry", "retry_invalid_method") => {
+                        "retry methods must be valid HTTP method names".to_string()
+                    }
+                    ("retry", "retry_per_try_timeout_zero") => {
+                        "retry per_try_timeout must be greater than zero".to_string()
+                    }
+                    ("circuit_breaker", "circuit_breaker_consecutive_failures_zero") => {
+                        "circuit_breaker consecutive_failures must be at least 1".to_string()
+                    }
+                    ("circuit_breaker", "circuit_breaker_invalid_failure_ratio") => {
+                        "circuit_breaker failure_ratio must be in (0, 1]".to_string()
+                    }
+                    ("circuit_breaker", "circuit_breaker_request_count_zero") => {
+                        "circuit_breaker min_requests and half_open_requests must be at least 1"
+                            .to_string()
+                    }
+                    ("circuit_breaker", "circuit_breaker_duration_zero") => {
+                        "circuit_breaker window and open_duration must be greater than zero"
+                            .to_string()
+                    }
+                    ("circuit_breaker", "circuit_breaker_invalid_fallback_status") => {
+                        "circuit_breaker fallback status must be in 100..=599".to_string()
+                    }
+                    ("rate_limit", "rate_limit_invalid_rate") => {
+                        "rate_limit requests_per_second must be greater than zero".to_string()
+                    }
+                    ("rate_limit", "rate_limit_burst_zero") => {
+                        "rate_limit burst must be at least 1".to_string()
+                    }
+                    ("rate_limit", "rate_limit_header_key_requires_name") => {
+                        "rate_limit key 'header' requires header_name".to_string()
+                    }
+                    ("connection_limit", "connection_limit_max_connections_zero") => {
+                        "connection_limit max_connections must be at least 1".to_string()
+                    }
+                    ("connection_limit", "connection_limit_invalid_rate") => {
+                        "connection_limit connections_per_second must be greater than zero"
+                            .to_string()
+                    }
+                    ("connection_limit", "connection_limit_burst_zero") => {
+                        "connection_limit burst must be at least 1".to_string()
+                    }
+                    ("cache", "cache_size_zero") => {
+                        "cache max_entries and max_entry_size must be at least 1".to_string()
+                    }
+                    ("cache", "cache_disk_path_empty") => {
+                        "cache disk_path cannot be empty".to_string()
+                    }
+                    ("gateway", "gateway_routes_empty") => {
+                        "gateway routes cannot be empty".to_string()
+                    }
+                    ("gateway", "gateway_invalid_uri") => {
+                        "gateway route uri and target_uri must start with /".to_string()
+                    }
+                    ("gateway", "gateway_invalid_variable") => {
+                        "gateway route variables must be valid and defined in uri".to_string()
+                    }
+                    ("gateway", "gateway_unknown_service") => {
+                        "gateway target_service must be a configured service or target address"
+                            .to_string()
+                    }
+                    ("compression", "compression_algorithms_empty") => {
+                        "compression algorithms cannot be empty".to_string()
+                    }
+                    ("compression", "compression_invalid_content_type") => {
+                        "compression content_types must be non-empty MIME types like text/*"
+                            .to_string()
+                    }
                     _ => format!(
                         "Validation error in '{}': {}",
                         field,
@@ -202,7 +282,7 @@ impl ConfigUserInterface {
                     "  {}: {} -> {} ({})",
                     name.cyan(),
                     engine.listen.yellow(),
-                    engine.target.yellow(),
+                    engine.target.to_string().yellow(),
                     format!("{:?}", engine.proxy_type).green()
                 );
             } else {
@@ -5,7 +5,12 @@ use std::str::FromStr;
 use url::Url;
 use validator::{ValidationError, ValidationErrors};
 
-use crate::config::{EngineConfig, LocationConfig, MatchMode, ProviderType, ProxyType, TlsConfig};
+use crate::config::{
+    BodyType, CacheConfig, CircuitBreakerConfig, CompressionConfig, ConnectionLimitConfig,
+    ConnectionPoolConfig, EngineConfig, GatewayConfig, HealthCheckConfig, HealthCheckType,
+    LocationConfig, MatchMode, ProviderType, ProxyType, RateLimitConfig, RateLimitKey,
+    RedirectConfig, ResponseSequence, RetryConfig, SequenceMode, TlsConfig, UpstreamTlsConfig,
+};
 
 /// 验证 EngineConfig
 pub fn validate_engine_config(config: &EngineConfig) -> Result<(), ValidationErrors> {
@@ -16,16 +21,27 @@ pub fn validate_engine_config(config: &EngineConfig) -> Result<(), ValidationErr
         errors.add("listen", e);
     }
 
-    // 验证 target 地址
-    if let Err(e) = validate_target_address(&config.target) {
-        errors.add("target", e);
+    // 验证 target 地址（多目标时逐个验证）
+    let targets = config.target.endpoints();
+    if targets.is_empty() {
+        errors.add("target", ValidationError::new("target_empty"));
+    }
+    for target in &targets {
+        if let Err(e) = validate_target_address(&target.address) {
+            errors.add("target", e);
+        }
+        if target.weight == 0 {
+            errors.add("target", ValidationError::new("target_weight_zero"));
+        }
     }
 
     // 验证代理类型匹配
-    if let Err(e) =
-        validate_proxy_type_match(&config.listen, &config.target, config.proxy_type.clone())
-    {
-        errors.add("proxy_type", e);
+    for target in &targets {
+        if let Err(e) =
+            validate_proxy_type_match(&config.listen, &target.address, config.proxy_type.clone())
+        {
+            errors.add("proxy_type", e);
+        }
     }
 
     // 验证 locations
@@ -55,6 +71,76 @@ pub fn validate_engine_config(config: &EngineConfig) -> Result<(), ValidationErr
         }
     }
 
+    // 验证健康检查配置
+    if let Some(health_check) = &config.health_check {
+        if let Err(e) = validate_health_check_config(health_check, &targets) {
+            errors.add("health_check", e);
+        }
+    }
+
+    // 验证上游连接池配置
+    if let Some(pool) = &config.connection_pool {
+        if let Err(e) = validate_connection_pool_config(pool) {
+            errors.add("connection_pool", e);
+        }
+    }
+
+    // 验证重试配置
+    if let Some(retry) = &config.retry {
+        if let Err(e) = validate_retry_config(retry) {
+            errors.add("retry", e);
+        }
+    }
+
+    // 验证熔断配置
+    if let Some(breaker) = &config.circuit_breaker {
+        if let Err(e) = validate_circuit_breaker_config(breaker) {
+            errors.add("circuit_breaker", e);
+        }
+    }
+
+    // 验证限流配置
+    if let Some(rate_limit) = &config.rate_limit {
+        if let Err(e) = validate_rate_limit_config(rate_limit) {
+            errors.add("rate_limit", e);
+        }
+    }
+
+    // 验证连接数限制配置
+    if let Some(connection_limit) = &config.connection_limit {
+        if let Err(e) = validate_connection_limit_config(connection_limit) {
+            errors.add("connection_limit", e);
+        }
+    }
+
+    // 验证响应缓存配置
+    if let Some(cache) = &config.cache {
+        if let Err(e) = validate_cache_config(cache) {
+            errors.add("cache", e);
+        }
+    }
+
+    // 验证响应压缩配置
+    if let Some(compression) = &config.compression {
+        if let Err(e) = validate_compression_config(compression) {
+            errors.add("compression", e);
+        }
+    }
+
+    // 验证网关路由
+    if let Some(gateway) = &config.gateway {
+        if let Err(e) = validate_gateway_config(gateway) {
+            errors.add("gateway", e);
+        }
+    }
+
+    // 验证上游客户端 TLS 配置
+    if let Some(upstream_tls) = &config.upstream_tls {
+        if let Err(e) = validate_upstream_tls_config(upstream_tls) {
+            errors.add("upstream_tls", e);
+        }
+    }
+
     // 验证上游代理
     if let Some(upstream) = &config.upstream {
         if let Err(e) = validate_upstream_proxy(upstream) {
@@ -146,9 +232,12 @@ fn validate_proxy_type_match(
                     "http_proxy_requires_tcp_or_unix_listen",
                 ));
             }
-            if !target.starts_with("tcp://") && !target.starts_with("unix://") {
+            if !target.starts_with("tcp://")
+                && !target.starts_with("unix://")
+                && !target.starts_with("https://")
+            {
                 return Err(ValidationError::new(
-                    "http_proxy_requires_tcp_or_unix_target",
+                    "http_proxy_requires_tcp_unix_or_https_target",
                 ));
             }
         }
@@ -211,6 +300,28 @@ fn validate_location_config(loc: &LocationConfig) -> Result<(), ValidationError>
             ProviderType::Proxy => {
                 // 代理 provider 使用默认转发，无额外要求
             }
+            ProviderType::Redirect => {
+                let redirect = loc
+                    .redirect
+                    .as_ref()
+                    .ok_or_else(|| ValidationError::new("redirect_provider_requires_redirect"))?;
+                validate_redirect_config(redirect)?;
+            }
+            ProviderType::Grpc => {
+                let grpc = loc.grpc.as_ref();
+                if grpc.is_some_and(|g| g.mock.as_ref().is_some_and(|m| m.body.is_some()))
+                    && grpc.and_then(|g| g.descriptor_set.as_ref()).is_none()
+                {
+                    return Err(ValidationError::new(
+                        "grpc_mock_body_requires_descriptor_set",
+                    ));
+                }
+                if let Some(path) = grpc.and_then(|g| g.descriptor_set.as_ref()) {
+                    if !std::path::Path::new(path).exists() {
+                        return Err(ValidationError::new("grpc_descriptor_set_not_found"));
+                    }
+                }
+            }
         }
     }
 
@@ -223,6 +334,135 @@ fn validate_location_config(loc: &LocationConfig) -> Result<(), ValidationError>
         }
     }
 
+    if let Some(retry) = &loc.retry {
+        validate_retry_config(retry)?;
+    }
+
+    if let Some(rate_limit) = &loc.rate_limit {
+        validate_rate_limit_config(rate_limit)?;
+    }
+
+    if let Some(compression) = &loc.compression {
+        validate_compression_config(compression)?;
+    }
+
+    // 验证 hosts/methods/match_headers 路由条件与 location 级 target
+    let invalid_host = |host: &String| {
+        let name = host.strip_prefix("*.").unwrap_or(host);
+        name.is_empty() || name.contains(['*', '/', ':', ' '])
+    };
+    if loc.hosts.iter().flatten().any(invalid_host) {
+        return Err(ValidationError::new("location_invalid_host"));
+    }
+    if loc
+        .methods
+        .iter()
+        .flatten()
+        .any(|m| hyper::Method::from_bytes(m.to_ascii_uppercase().as_bytes()).is_err())
+    {
+        return Err(ValidationError::new("location_invalid_method"));
+    }
+    if loc
+        .match_headers
+        .iter()
+        .flatten()
+        .any(|(name, _)| hyper::header::HeaderName::from_bytes(name.as_bytes()).is_err())
+    {
+        return Err(ValidationError::new("location_invalid_match_header"));
+    }
+    if let Some(target) = &loc.target {
+        validate_target_address(target)?;
+    }
+
+    // 验证请求路径正则替换规则
+    let rewrite = loc
+        .request
+        .as_ref()
+        .and_then(|r| r.uri.as_ref())
+        .and_then(|u| u.rewrite.as_ref());
+    if let Some(rewrite) = rewrite {
+        regex::Regex::new(&rewrite.pattern)
+            .map_err(|_| ValidationError::new("request_invalid_rewrite_pattern"))?;
+    }
+
+    // 验证响应状态码、状态码重映射、响应序列与文件响应体
+    if let Some(response) = &loc.response {
+        let statuses = response
+            .status
+            .into_iter()
+            .chain(
+                response
+                    .status_map
+                    .iter()
+                    .flat_map(|map| map.iter().flat_map(|(from, to)| [*from, *to])),
+            )
+            .chain(
+                response
+                    .sequence
+                    .iter()
+                    .flat_map(|s| s.responses.iter().filter_map(|step| step.status)),
+            );
+        for status in statuses {
+            if !(100..=599).contains(&status) {
+                return Err(ValidationError::new("response_invalid_status_code"));
+            }
+        }
+        if let Some(sequence) = &response.sequence {
+            validate_response_sequence(sequence)?;
+        }
+        let step_bodies = response
+            .sequence
+            .iter()
+            .flat_map(|s| s.responses.iter().filter_map(|step| step.body.as_ref()));
+        if response.body.iter().chain(step_bodies).any(|body| {
+            body.body_type == Some(BodyType::File)
+                && body.file.as_deref().is_none_or(|f| f.trim().is_empty())
+        }) {
+            return Err(ValidationError::new("response_body_file_missing"));
+        }
+    }
+
+    Ok(())
+}
+
+/// 验证响应序列配置
+fn validate_response_sequence(sequence: &ResponseSequence) -> Result<(), ValidationError> {
+    if sequence.responses.is_empty() {
+        return Err(ValidationError::new("response_sequence_empty"));
+    }
+    if sequence.responses.iter().any(|step| step.repeat == Some(0)) {
+        return Err(ValidationError::new("response_sequence_zero_repeat"));
+    }
+    if sequence.mode == SequenceMode::RandomWeighted
+        && sequence.responses.iter().all(|step| step.weight == Some(0))
+    {
+        return Err(ValidationError::new("response_sequence_zero_weight"));
+    }
+    Ok(())
+}
+
+/// 验证重定向配置
+fn validate_redirect_config(redirect: &RedirectConfig) -> Result<(), ValidationError> {
+    if redirect
+        .status
+        .is_some_and(|status| !crate::http::REDIRECT_STATUSES.contains(&status))
+    {
+        return Err(ValidationError::new("redirect_invalid_status"));
+    }
+    if redirect.location.is_none()
+        && !redirect.force_https
+        && redirect.host.is_none()
+        && redirect.trailing_slash.is_none()
+    {
+        return Err(ValidationError::new("redirect_missing_target"));
+    }
+    if redirect
+        .host
+        .as_ref()
+        .is_some_and(|host| host.is_empty() || host.contains(['/', '?', '#', ' ']))
+    {
+        return Err(ValidationError::new("redirect_invalid_host"));
+    }
     Ok(())
 }
 
@@ -279,6 +519,228 @@ fn validate_auth_config(auth: &crate::config::AuthConfig) -> Result<(), Validati
     Ok(())
 }
 
+/// 验证健康检查配置
+fn validate_health_check_config(
+    health_check: &HealthCheckConfig,
+    targets: &[crate::config::WeightedTarget],
+) -> Result<(), ValidationError> {
+    if let Some(active) = &health_check.active {
+        if active.interval.is_some_and(|d| d.is_zero()) {
+            return Err(ValidationError::new("health_check_interval_zero"));
+        }
+        if let Some(status) = active.expected_status {
+            if !(100..=599).contains(&status) {
+                return Err(ValidationError::new("health_check_invalid_expected_status"));
+            }
+        }
+        if active.check_type == HealthCheckType::Uds
+            && targets.iter().any(|t| !t.address.starts_with("unix://"))
+        {
+            return Err(ValidationError::new(
+                "health_check_uds_requires_unix_target",
+            ));
+        }
+        if active.check_type == HealthCheckType::Http
+            && targets.iter().any(|t| t.address.starts_with("https://"))
+        {
+            return Err(ValidationError::new(
+                "health_check_http_unsupported_for_https_target",
+            ));
+        }
+        if active
+            .path
+            .as_deref()
+            .is_some_and(|path| !path.starts_with('/'))
+        {
+            return Err(ValidationError::new(
+                "health_check_path_must_start_with_slash",
+            ));
+        }
+    }
+    if let Some(passive) = &health_check.passive {
+        if passive.consecutive_failures == Some(0) {
+            return Err(ValidationError::new(
+                "health_check_consecutive_failures_zero",
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// 验证上游连接池配置
+fn validate_connection_pool_config(pool: &ConnectionPoolConfig) -> Result<(), ValidationError> {
+    if pool.idle_timeout.is_some_and(|d| d.is_zero()) {
+        return Err(ValidationError::new("connection_pool_idle_timeout_zero"));
+    }
+    if pool.max_lifetime.is_some_and(|d| d.is_zero()) {
+        return Err(ValidationError::new("connection_pool_max_lifetime_zero"));
+    }
+    Ok(())
+}
+
+/// 验证重试配置
+fn validate_retry_config(retry: &RetryConfig) -> Result<(), ValidationError> {
+    if retry.attempts == Some(0) {
+        return Err(ValidationError::new("retry_attempts_zero"));
+    }
+    if retry
+        .retry_on_status
+        .iter()
+        .flatten()
+        .any(|status| !(100..=599).contains(status))
+    {
+        return Err(ValidationError::new("retry_invalid_status_code"));
+    }
+    if retry
+        .methods
+        .iter()
+        .flatten()
+        .any(|m| hyper::Method::from_bytes(m.to_ascii_uppercase().as_bytes()).is_err())
+    {
+        return Err(ValidationError::new("retry_invalid_method"));
+    }
+    if retry.per_try_timeout.is_some_and(|d| d.is_zero()) {
+        return Err(ValidationError::new("retry_per_try_timeout_zero"));
+    }
+    Ok(())
+}
+
+/// 验证熔断配置
+fn validate_circuit_breaker_config(breaker: &CircuitBreakerConfig) -> Result<(), ValidationError> {
+    if breaker.consecutive_failures == Some(0) {
+        return Err(ValidationError::new(
+            "circuit_breaker_consecutive_failures_zero",
+        ));
+    }
+    if breaker
+        .failure_ratio
+        .is_some_and(|ratio| !(ratio > 0.0 && ratio <= 1.0))
+    {
+        return Err(ValidationError::new(
+            "circuit_breaker_invalid_failure_ratio",
+        ));
+    }
+    if breaker.min_requests == Some(0) || breaker.half_open_requests == Some(0) {
+        return Err(ValidationError::new("circuit_breaker_request_count_zero"));
+    }
+    if breaker.window.is_some_and(|d| d.is_zero())
+        || breaker.open_duration.is_some_and(|d| d.is_zero())
+    {
+        return Err(ValidationError::new("circuit_breaker_duration_zero"));
+    }
+    if let Some(status) = breaker.fallback.as_ref().and_then(|f| f.status) {
+        if !(100..=599).contains(&status) {
+            return Err(ValidationError::new(
+                "circuit_breaker_invalid_fallback_status",
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// 验证限流配置
+fn validate_rate_limit_config(rate_limit: &RateLimitConfig) -> Result<(), ValidationError> {
+    if !(rate_limit.requests_per_second > 0.0 && rate_limit.requests_per_second.is_finite()) {
+        return Err(ValidationError::new("rate_limit_invalid_rate"));
+    }
+    if rate_limit.burst == Some(0) {
+        return Err(ValidationError::new("rate_limit_burst_zero"));
+    }
+    if rate_limit.key == RateLimitKey::Header
+        && rate_limit.header_name.as_deref().is_none_or(str::is_empty)
+    {
+        return Err(ValidationError::new("rate_limit_header_key_requires_name"));
+    }
+    Ok(())
+}
+
+/// 验证连接数限制配置
+fn validate_connection_limit_config(limit: &ConnectionLimitConfig) -> Result<(), ValidationError> {
+    if limit.max_connections == Some(0) {
+        return Err(ValidationError::new(
+            "connection_limit_max_connections_zero",
+        ));
+    }
+    if limit
+        .connections_per_second
+        .is_some_and(|rate| !(rate > 0.0 && rate.is_finite()))
+    {
+        return Err(ValidationError::new("connection_limit_invalid_rate"));
+    }
+    if limit.burst == Some(0) {
+        return Err(ValidationError::new("connection_limit_burst_zero"));
+    }
+    Ok(())
+}
+
+/// 验证响应缓存配置
+fn validate_cache_config(cache: &CacheConfig) -> Result<(), ValidationError> {
+    if cache.max_entries == Some(0) || cache.max_entry_size == Some(0) {
+        return Err(ValidationError::new("cache_size_zero"));
+    }
+    if cache.disk_path.as_deref().is_some_and(str::is_empty) {
+        return Err(ValidationError::new("cache_disk_path_empty"));
+    }
+    Ok(())
+}
+
+/// 验证响应压缩配置
+fn validate_compression_config(compression: &CompressionConfig) -> Result<(), ValidationError> {
+    if compression.algorithms.as_ref().is_some_and(Vec::is_empty) {
+        return Err(ValidationError::new("compression_algorithms_empty"));
+    }
+    if let Some(content_types) = &compression.content_types {
+        if content_types.is_empty() || content_types.iter().any(|t| !t.contains('/')) {
+            return Err(ValidationError::new("compression_invalid_content_type"));
+        }
+    }
+    Ok(())
+}
+
+/// 验证网关配置
+fn validate_gateway_config(gateway: &GatewayConfig) -> Result<(), ValidationError> {
+    if gateway.routes.is_empty() {
+        return Err(ValidationError::new("gateway_routes_empty"));
+    }
+    for address in gateway.services.values() {
+        validate_target_address(address)?;
+    }
+    for route in &gateway.routes {
+        if !route.uri.starts_with('/') || !route.target_uri.starts_with('/') {
+            return Err(ValidationError::new("gateway_invalid_uri"));
+        }
+        if !route.validate_variables() {
+            return Err(ValidationError::new("gateway_invalid_variable"));
+        }
+        // 未登记的服务名须本身是合法的上游地址
+        if let Some(service) = &route.target_service {
+            if !gateway.services.contains_key(service) && validate_target_address(service).is_err()
+            {
+                return Err(ValidationError::new("gateway_unknown_service"));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// 验证上游客户端 TLS 配置
+fn validate_upstream_tls_config(tls: &UpstreamTlsConfig) -> Result<(), ValidationError> {
+    if tls.cert_path.is_some() != tls.key_path.is_some() {
+        return Err(ValidationError::new(
+            "upstream_tls_cert_and_key_required_together",
+        ));
+    }
+    for path in [&tls.ca_path, &tls.cert_path, &tls.key_path]
+        .into_iter()
+        .flatten()
+    {
+        if !std::path::Path::new(path).exists() {
+            return Err(ValidationError::new("upstream_tls_file_not_found"));
+        }
+    }
+    Ok(())
+}
+
 /// 验证上游代理
 fn validate_upstream_proxy(upstream: &str) -> Result<(), ValidationError> {
     Url::parse(upstream).map_err(|_| ValidationError::new("invalid_upstream_proxy_url"))?;
@@ -330,4 +792,345 @@ mod tests {
         assert!(validate_listen_address("").is_err());
         assert!(validate_listen_address("http://localhost").is_err());
     }
+
+    #[test]
+    fn test_validate_https_target_for_http_proxy() {
+        let listen = "tcp://0.0.0.0:8080";
+        assert!(
+            validate_proxy_type_match(listen, "https://api.internal:8443", ProxyType::Http).is_ok()
+        );
+        assert!(
+            validate_proxy_type_match(listen, "https://api.internal:8443", ProxyType::Tcp).is_err()
+        );
+    }
+
+    #[test]
+    fn test_validate_upstream_tls_config() {
+        assert!(validate_upstream_tls_config(&UpstreamTlsConfig::default()).is_ok());
+
+        let cert_only = UpstreamTlsConfig {
+            cert_path: Some("/nonexistent/client.crt".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            validate_upstream_tls_config(&cert_only).unwrap_err().code,
+            "upstream_tls_cert_and_key_required_together"
+        );
+
+        let missing_ca = UpstreamTlsConfig {
+            ca_path: Some("/nonexistent/ca.pem".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            validate_upstream_tls_config(&missing_ca).unwrap_err().code,
+            "upstream_tls_file_not_found"
+        );
+    }
+
+    #[test]
+    fn test_validate_retry_config() {
+        assert!(validate_retry_config(&RetryConfig::default()).is_ok());
+
+        let zero_attempts = RetryConfig {
+            attempts: Some(0),
+            ..Default::default()
+        };
+        assert_eq!(
+            validate_retry_config(&zero_attempts).unwrap_err().code,
+            "retry_attempts_zero"
+        );
+
+        let bad_status = RetryConfig {
+            retry_on_status: Some(vec![503, 700]),
+            ..Default::default()
+        };
+        assert_eq!(
+            validate_retry_config(&bad_status).unwrap_err().code,
+            "retry_invalid_status_code"
+        );
+
+        let bad_method = RetryConfig {
+            methods: Some(vec!["GET POST".to_string()]),
+            ..Default::default()
+        };
+        assert_eq!(
+            validate_retry_config(&bad_method).unwrap_err().code,
+            "retry_invalid_method"
+        );
+    }
+
+    #[test]
+    fn test_validate_circuit_breaker_config() {
+        assert!(validate_circuit_breaker_config(&CircuitBreakerConfig::default()).is_ok());
+
+        let bad_ratio = CircuitBreakerConfig {
+            failure_ratio: Some(1.5),
+            ..Default::default()
+        };
+        assert_eq!(
+            validate_circuit_breaker_config(&bad_ratio)
+                .unwrap_err()
+                .code,
+            "circuit_breaker_invalid_failure_ratio"
+        );
+
+        let zero_open = CircuitBreakerConfig {
+            open_duration: Some(std::time::Duration::ZERO),
+            ..Default::default()
+        };
+        assert_eq!(
+            validate_circuit_breaker_config(&zero_open)
+                .unwrap_err()
+                .code,
+            "circuit_breaker_duration_zero"
+        );
+    }
+
+    #[test]
+    fn test_validate_rate_limit_config() {
+        let valid = RateLimitConfig {
+            requests_per_second: 10.0,
+            burst: None,
+            key: RateLimitKey::ClientIp,
+            header_name: None,
+        };
+        assert!(validate_rate_limit_config(&valid).is_ok());
+
+        let zero_rate = RateLimitConfig {
+            requests_per_second: 0.0,
+            ..valid.clone()
+        };
+        assert_eq!(
+            validate_rate_limit_config(&zero_rate).unwrap_err().code,
+            "rate_limit_invalid_rate"
+        );
+
+        let header_without_name = RateLimitConfig {
+            key: RateLimitKey::Header,
+            ..valid
+        };
+        assert_eq!(
+            validate_rate_limit_config(&header_without_name)
+                .unwrap_err()
+                .code,
+            "rate_limit_header_key_requires_name"
+        );
+
+        let zero_connections = ConnectionLimitConfig {
+            max_connections: Some(0),
+            ..Default::default()
+        };
+        assert_eq!(
+            validate_connection_limit_config(&zero_connections)
+                .unwrap_err()
+                .code,
+            "connection_limit_max_connections_zero"
+        );
+    }
+
+    #[test]
+    fn test_validate_location_response_status() {
+        let location = |response: &str| -> LocationConfig {
+            serde_yaml::from_str(&format!(
+                "location: /api\nmode: Prefix\nresponse:\n{response}"
+            ))
+            .unwrap()
+        };
+
+        let valid = location("  status: 200\n  status_map:\n    502: 503\n");
+        assert!(validate_location_config(&valid).is_ok());
+
+        let invalid_status = location("  status: 600\n");
+        assert_eq!(
+            validate_location_config(&invalid_status).unwrap_err().code,
+            "response_invalid_status_code"
+        );
+
+        let invalid_mapping = location("  status_map:\n    502: 99\n");
+        assert_eq!(
+            validate_location_config(&invalid_mapping).unwrap_err().code,
+            "response_invalid_status_code"
+        );
+    }
+
+    #[test]
+    fn test_validate_location_rewrite_pattern() {
+        let location = |pattern: &str| -> LocationConfig {
+            serde_yaml::from_str(&format!(
+                "location: /api\nmode: Prefix\nrequest:\n  uri:\n    rewrite:\n      \
+                 pattern: '{pattern}'\n      replacement: /v2/$1\n"
+            ))
+            .unwrap()
+        };
+
+        assert!(validate_location_config(&location("^/api/(.*)$")).is_ok());
+        assert_eq!(
+            validate_location_config(&location("^/api/("))
+                .unwrap_err()
+                .code,
+            "request_invalid_rewrite_pattern"
+        );
+    }
+
+    #[test]
+    fn test_validate_response_sequence() {
+        let location = |sequence: &str| -> LocationConfig {
+            serde_yaml::from_str(&format!(
+                "location: /flaky\nmode: Full\nprovider: mock\nresponse:\n  sequence:\n{sequence}"
+            ))
+            .unwrap()
+        };
+
+        let valid = location(
+            "    responses:\n      - status: 503\n        repeat: 2\n      - status: 200\n",
+        );
+        assert!(validate_location_config(&valid).is_ok());
+
+        for (sequence, code) in [
+            ("    responses: []\n", "response_sequence_empty"),
+            (
+                "    responses:\n      - status: 200\n        repeat: 0\n",
+                "response_sequence_zero_repeat",
+            ),
+            (
+                "    mode: random-weighted\n    responses:\n      - weight: 0\n",
+                "response_sequence_zero_weight",
+            ),
+            (
+                "    responses:\n      - status: 700\n",
+                "response_invalid_status_code",
+            ),
+            (
+                "    responses:\n      - body:\n          type: file\n",
+                "response_body_file_missing",
+            ),
+        ] {
+            assert_eq!(
+                validate_location_config(&location(sequence))
+                    .unwrap_err()
+                    .code,
+                code
+            );
+        }
+    }
+
+    #[test]
+    fn test_validate_response_body_file() {
+        let location = |body: &str| -> LocationConfig {
+            serde_yaml::from_str(&format!(
+                "location: /report\nmode: Full\nprovider: mock\nresponse:\n  body:\n{body}"
+            ))
+            .unwrap()
+        };
+
+        let valid = location("    type: file\n    file: fixtures/report.pdf\n");
+        assert!(validate_location_config(&valid).is_ok());
+        for body in ["    type: file\n", "    type: file\n    file: \" \"\n"] {
+            assert_eq!(
+                validate_location_config(&location(body)).unwrap_err().code,
+                "response_body_file_missing"
+            );
+        }
+    }
+
+    #[test]
+    fn test_validate_redirect_location() {
+        let location = |redirect: &str| -> LocationConfig {
+            serde_yaml::from_str(&format!(
+                "location: /old\nmode: Prefix\nprovider: redirect\n{redirect}"
+            ))
+            .unwrap()
+        };
+
+        let valid = location("redirect:\n  status: 308\n  location: /new/{remaining}\n");
+        assert!(validate_location_config(&valid).is_ok());
+        let rules_only = location("redirect:\n  force_https: true\n");
+        assert!(validate_location_config(&rules_only).is_ok());
+
+        for (redirect, code) in [
+            ("", "redirect_provider_requires_redirect"),
+            (
+                "redirect:\n  status: 200\n  location: /new\n",
+                "redirect_invalid_status",
+            ),
+            ("redirect:\n  drop_query: true\n", "redirect_missing_target"),
+            (
+                "redirect:\n  host: example.com/x\n",
+                "redirect_invalid_host",
+            ),
+        ] {
+            assert_eq!(
+                validate_location_config(&location(redirect))
+                    .unwrap_err()
+                    .code,
+                code
+            );
+        }
+    }
+
+    #[test]
+    fn test_validate_location_routing_conditions() {
+        let location = |extra: &str| -> LocationConfig {
+            serde_yaml::from_str(&format!("location: /api\nmode: Prefix\n{extra}")).unwrap()
+        };
+
+        let valid = location(
+            "hosts: [api.example.com, \"*.example.com\"]\nmethods: [get, POST]\n\
+             match_headers:\n  X-Version: \"2\"\ntarget: tcp://127.0.0.1:9000\n",
+        );
+        assert!(validate_location_config(&valid).is_ok());
+
+        for (extra, code) in [
+            ("hosts: [\"api.*.com\"]\n", "location_invalid_host"),
+            ("hosts: [\"*.\"]\n", "location_invalid_host"),
+            ("methods: [\"GET POST\"]\n", "location_invalid_method"),
+            (
+                "match_headers:\n  \"bad header\": x\n",
+                "location_invalid_match_header",
+            ),
+            ("target: ftp://example.com\n", "unsupported_target_protocol"),
+        ] {
+            assert_eq!(
+                validate_location_config(&location(extra)).unwrap_err().code,
+                code
+            );
+        }
+    }
+
+    #[test]
+    fn test_validate_gateway_config() {
+        let gateway = |yaml: &str| -> GatewayConfig { serde_yaml::from_str(yaml).unwrap() };
+
+        let valid = gateway(
+            "services:\n  users: tcp://127.0.0.1:9000\nroutes:\n\
+             - uri: /api/users/{id}\n  target_uri: /v1/users/{id}\n  target_service: users\n\
+             - uri: /api/orders\n  target_uri: /orders\n  target_service: tcp://127.0.0.1:9001\n",
+        );
+        assert!(validate_gateway_config(&valid).is_ok());
+
+        for (yaml, code) in [
+            ("routes: []\n", "gateway_routes_empty"),
+            (
+                "routes:\n- uri: api\n  target_uri: /v1\n",
+                "gateway_invalid_uri",
+            ),
+            (
+                "routes:\n- uri: /api/{id}\n  target_uri: /v1/{name}\n",
+                "gateway_invalid_variable",
+            ),
+            (
+                "routes:\n- uri: /api\n  target_uri: /v1\n  target_service: users\n",
+                "gateway_unknown_service",
+            ),
+            (
+                "services:\n  users: nowhere\nroutes:\n- uri: /api\n  target_uri: /v1\n",
+                "unsupported_target_protocol",
+            ),
+        ] {
+            assert_eq!(
+                validate_gateway_config(&gateway(yaml)).unwrap_err().code,
+                code
+            );
+        }
+    }
 }
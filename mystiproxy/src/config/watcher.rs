@@ -197,7 +197,7 @@ mod tests {
                 format!("e{i}"),
                 EngineConfig {
                     listen: format!("tcp://0.0.0.0:{}", 9000 + i),
-                    target: format!("tcp://127.0.0.1:90{i}"),
+                    target: format!("tcp://127.0.0.1:90{i}").into(),
                     proxy_type: ProxyType::Tcp,
                     request_timeout: None,
                     connection_timeout: None,
@@ -209,6 +209,20 @@ mod tests {
                     allow: None,
                     deny: None,
                     management: None,
+                    max_buffered_body_size: None,
+                    load_balance: None,
+                    health_check: None,
+                    http2: None,
+                    connection_pool: None,
+                    upstream_tls: None,
+                    retry: None,
+                    circuit_breaker: None,
+                    rate_limit: None,
+                    connection_limit: None,
+                    cache: None,
+                    compression: None,
+                    gateway: None,
+                    mock_root: None,
                 },
             );
         }
@@ -63,10 +63,18 @@ pub enum MystiProxyError {
     #[error("地址解析错误: {0}")]
     AddrParse(#[from] std::net::AddrParseError),
 
+    /// Body 超过缓冲上限
+    #[error("Body 超过缓冲上限: {0} 字节")]
+    PayloadTooLarge(usize),
+
     /// 超时错误
     #[error("操作超时")]
     Timeout,
 
+    /// 上游熔断器打开，请求被直接拒绝
+    #[error("上游熔断中: {0}")]
+    CircuitOpen(String),
+
     /// 认证错误
     #[error("认证错误: {0}")]
     Auth(String),
@@ -2,21 +2,24 @@
 //!
 //! Provides URI mapping and routing capabilities for the HTTP proxy.
 
+use crate::config::GatewayConfig;
 use regex::Regex;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::cmp::Reverse;
 use std::collections::HashMap;
 use tracing::debug;
 
 /// URI mapping configuration
-#[derive(Serialize, Deserialize, Default, Debug)]
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
 pub struct UriMapping {
     #[serde(
+        default,
         rename = "method",
         skip_serializing_if = "Vec::is_empty",
         serialize_with = "UriMapping::serialize_method",
         deserialize_with = "UriMapping::deserialize_method"
     )]
-    /// GET, POST, PUT, DELETE. etc
+    /// GET, POST, PUT, DELETE. etc，未配置时允许任意方法
     pub methods: Vec<String>,
     /// 根据/进行 split 然后进行最长的uri进行权重匹配
     pub uri: String,
@@ -77,6 +80,27 @@ impl UriVariable {
     }
 }
 
+/// 预编译的 uri 匹配规则
+///
+/// [`Gateway::new`] 为每个映射编译一次，请求路由时不再重新编译正则。
+#[derive(Debug)]
+enum CompiledUri {
+    /// 不含变量，精确或前缀匹配
+    Plain,
+    /// 含路径变量
+    Variable {
+        in_map: HashMap<String, UriVariable>,
+        /// target_uri 中引用的变量，正则无效时为 None
+        out_map: Option<HashMap<String, UriVariable>>,
+        /// 匹配 uri 及其后的剩余路径
+        regex: Regex,
+        /// 仅匹配 uri 本身，用于截取剩余路径
+        rest_regex: Regex,
+    },
+    /// 变量正则无效，仅精确匹配
+    Invalid,
+}
+
 /// 简化后，实际上是不需要知道是什么匹配模式的
 ///
 /// 提供模式策略，应该是在录入uri后，进行uri预处理的时候，进行提供
@@ -103,6 +127,9 @@ pub enum UriMatch {
 
 impl UriMapping {
     pub fn supports_method(&self, method: &str) -> bool {
+        if self.methods.is_empty() {
+            return true;
+        }
         for mtd in &self.methods {
             if mtd.eq("*") {
                 return true;
@@ -114,7 +141,25 @@ impl UriMapping {
         false
     }
 
-    #[allow(dead_code)]
+    /// 路径权重：按 `/` 切分的段数，`/a/b/c` 为 3，`/a/b/c/` 为 4
+    pub fn weight(&self) -> usize {
+        self.uri.split('/').count() - 1
+    }
+
+    /// uri 中的变量个数
+    fn variable_count(&self) -> usize {
+        Self::uri_variable(&self.uri).map_or(0, |v| v.len())
+    }
+
+    /// 检查 uri 中变量的正则是否有效，且 target_uri 引用的变量都在 uri 中定义
+    pub fn validate_variables(&self) -> bool {
+        let Ok(in_map) = Self::uri_variable(&self.uri) else {
+            return false;
+        };
+        Self::uri_variable(&self.target_uri)
+            .is_ok_and(|out_map| out_map.keys().all(|name| in_map.contains_key(name)))
+    }
+
     fn uri_variable(uri: &str) -> Result<HashMap<String, UriVariable>, regex::Error> {
         // Hardcoded literal regex: always valid. Kept as expect() for clarity.
         let re = Regex::new(r"/\{(\w+):?([^}]*)}").expect("hardcoded regex literal is valid");
@@ -159,8 +204,47 @@ impl UriMapping {
     ///
     /// * `bool` - 如果传入的`uri`与`UriMapping`的配置匹配，则返回`true`；否则返回`false`。
     pub fn match_uri(&self, in_uri: &str) -> Option<UriMatch> {
+        self.match_compiled(in_uri, &self.compile())
+    }
+
+    /// 按目标模式生成转发的 uri，未匹配时返回 None
+    pub fn build_target_uri(&self, in_uri: &str) -> Option<String> {
+        self.build_compiled(in_uri, &self.compile())
+    }
+
+    /// 预编译 uri 的匹配规则
+    fn compile(&self) -> CompiledUri {
+        let in_map = match Self::uri_variable(&self.uri) {
+            Ok(in_map) if in_map.is_empty() => return CompiledUri::Plain,
+            Ok(in_map) => in_map,
+            Err(_) => return CompiledUri::Invalid,
+        };
+
+        // 处理路径变量，支持变量后面跟正则表达式，并识别带路径的前缀匹配
+        let mut processed_base_uri = self.uri.clone();
+        for regex_pattern in in_map.values() {
+            processed_base_uri = processed_base_uri.replace(
+                &regex_pattern.origin(),
+                &format!(r"({})", regex_pattern.to_pattern()),
+            );
+        }
+
+        let (Ok(regex), Ok(rest_regex)) = (
+            Regex::new(&format!("^{processed_base_uri}\\/?.*$")),
+            Regex::new(&processed_base_uri),
+        ) else {
+            return CompiledUri::Invalid;
+        };
+        CompiledUri::Variable {
+            in_map,
+            out_map: Self::uri_variable(&self.target_uri).ok(),
+            regex,
+            rest_regex,
+        }
+    }
+
+    fn match_compiled(&self, in_uri: &str, compiled: &CompiledUri) -> Option<UriMatch> {
         debug!("uri: {}, in_uri: {}", self.uri, in_uri);
-        let base_uri = self.uri.as_str();
         // 精确匹配
         if self.uri == in_uri {
             return Some(UriMatch::Exact);
@@ -170,32 +254,24 @@ impl UriMapping {
             return Some(UriMatch::Prefix);
         }
 
-        let variable_patterns = Self::uri_variable(self.uri.as_str()).ok()?;
-        if variable_patterns.is_empty() {
-            // 前缀匹配
-            let prefix_uri = if self.uri.ends_with("/") {
-                self.uri.to_string()
-            } else {
-                format!("{}/", self.uri)
-            };
-            return if in_uri.starts_with(&prefix_uri) {
-                Some(UriMatch::Prefix)
-            } else {
-                None
-            };
-        }
-
-        // 处理路径变量，支持变量后面跟正则表达式，并识别带路径的前缀匹配
-        let mut processed_base_uri = base_uri.to_string();
-        for (_, regex_pattern) in variable_patterns {
-            processed_base_uri = processed_base_uri.replace(
-                &regex_pattern.origin(),
-                &format!(r"({})", regex_pattern.to_pattern()),
-            );
-        }
+        let regex = match compiled {
+            CompiledUri::Plain => {
+                // 前缀匹配
+                let prefix_uri = if self.uri.ends_with("/") {
+                    self.uri.to_string()
+                } else {
+                    format!("{}/", self.uri)
+                };
+                return if in_uri.starts_with(&prefix_uri) {
+                    Some(UriMatch::Prefix)
+                } else {
+                    None
+                };
+            }
+            CompiledUri::Variable { regex, .. } => regex,
+            CompiledUri::Invalid => return None,
+        };
 
-        // 构造正则表达式并尝试匹配
-        let regex = Regex::new(&format!("^{processed_base_uri}\\/?.*$")).ok()?;
         let mut match_var = HashMap::new();
         if regex.is_match(in_uri) {
             let mut end = 0;
@@ -225,27 +301,25 @@ impl UriMapping {
         }
     }
 
-    pub fn build_target_uri(&self, in_uri: &str) -> Option<String> {
-        let matched = self.match_uri(in_uri)?;
+    fn build_compiled(&self, in_uri: &str, compiled: &CompiledUri) -> Option<String> {
+        let matched = self.match_compiled(in_uri, compiled)?;
         match matched {
             UriMatch::Exact => Some(self.target_uri.clone()),
-            UriMatch::Prefix => Some(in_uri.replace(self.uri.as_str(), self.target_uri.as_str())),
-            // UriMatch::Prefix => Some(in_uri.to_string()),
+            UriMatch::Prefix => {
+                let rest = in_uri.strip_prefix(self.uri.as_str())?;
+                Some(join_path(&self.target_uri, rest))
+            }
             UriMatch::Variable | UriMatch::VariablePrefix => {
-                let in_map = Self::uri_variable(self.uri.as_str()).ok()?;
-                // 处理路径变量，支持变量后面跟正则表达式，并识别带路径的前缀匹配
-                let mut processed_base_uri = self.uri.clone();
-                for regex_pattern in in_map.values() {
-                    processed_base_uri = processed_base_uri.replace(
-                        &regex_pattern.origin(),
-                        &format!(r"({})", regex_pattern.to_pattern().as_str()),
-                    );
-                }
-
-                // 构造正则表达式并尝试匹配
-                let rest_regex = Regex::new(&processed_base_uri).ok()?;
+                let CompiledUri::Variable {
+                    in_map,
+                    out_map,
+                    regex,
+                    rest_regex,
+                } = compiled
+                else {
+                    return None;
+                };
                 let rest = rest_regex.replace(in_uri, "").to_string();
-                let regex = Regex::new(&format!("^{processed_base_uri}\\/?.*$")).ok()?;
                 let mut match_var = HashMap::new();
 
                 for cap in regex.captures_iter(in_uri) {
@@ -263,8 +337,7 @@ impl UriMapping {
 
                 // 通过遍历map，转移target
                 let mut target_uri = self.target_uri.clone();
-                let out_map = Self::uri_variable(self.target_uri.as_str()).ok()?;
-                for regex_pattern in out_map.values() {
+                for regex_pattern in out_map.as_ref()?.values() {
                     let name = regex_pattern.name.as_str();
                     match in_map.get(name) {
                         Some(variable) => {
@@ -276,16 +349,7 @@ impl UriMapping {
                         }
                     }
                 }
-                if rest.is_empty() {
-                    return Some(target_uri);
-                }
-                let rest = rest.strip_prefix('/').unwrap_or(&rest);
-
-                if target_uri.ends_with('/') {
-                    Some(format!("{target_uri}{rest}"))
-                } else {
-                    Some(format!("{target_uri}/{rest}"))
-                }
+                Some(join_path(&target_uri, &rest))
             }
         }
     }
@@ -311,6 +375,82 @@ impl UriMapping {
     }
 }
 
+/// 将匹配剩余的路径拼接到目标路径后
+fn join_path(base: &str, rest: &str) -> String {
+    let rest = rest.strip_prefix('/').unwrap_or(rest);
+    if rest.is_empty() {
+        base.to_string()
+    } else if base.ends_with('/') {
+        format!("{base}{rest}")
+    } else {
+        format!("{base}/{rest}")
+    }
+}
+
+/// 网关路由结果
+#[derive(Debug, PartialEq)]
+pub struct GatewayRoute {
+    /// 上游地址；映射未指定 `target_service` 时为 None，使用引擎级 target
+    pub target: Option<String>,
+    /// 改写后的上游路径（不含查询串）
+    pub path: String,
+}
+
+/// 网关路由表
+#[derive(Debug)]
+pub struct Gateway {
+    /// 按路径权重降序排列的映射及其预编译的匹配规则
+    routes: Vec<(UriMapping, CompiledUri)>,
+    services: HashMap<String, String>,
+}
+
+impl Gateway {
+    pub fn new(config: &GatewayConfig) -> Self {
+        let mut routes = config.routes.clone();
+        // 权重高者优先；同权重时变量少者、uri 更长者优先，其余保持配置顺序
+        routes.sort_by_key(|m| {
+            (
+                Reverse(m.weight()),
+                m.variable_count(),
+                Reverse(m.uri.len()),
+            )
+        });
+        Gateway {
+            routes: routes
+                .into_iter()
+                .map(|m| {
+                    let compiled = m.compile();
+                    (m, compiled)
+                })
+                .collect(),
+            services: config.services.clone(),
+        }
+    }
+
+    /// 按方法与路径选择映射并计算上游地址与路径
+    ///
+    /// `target_service` 优先按 `services` 解析，未登记时视为上游地址本身。
+    pub fn route(&self, method: &str, path: &str) -> Option<GatewayRoute> {
+        self.routes
+            .iter()
+            .filter(|(m, _)| m.supports_method(method))
+            .find_map(|(m, compiled)| {
+                let target_path = m.build_compiled(path, compiled)?;
+                debug!("gateway: {} {} -> {} {}", method, path, m.uri, target_path);
+                let target = m.target_service.as_ref().map(|service| {
+                    self.services
+                        .get(service)
+                        .cloned()
+                        .unwrap_or_else(|| service.clone())
+                });
+                Some(GatewayRoute {
+                    target,
+                    path: target_path,
+                })
+            })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -416,4 +556,92 @@ mod tests {
             "uri_variable should return Err on invalid regex, got: {result:?}"
         );
     }
+
+    #[test]
+    fn test_build_target_uri_prefix_from_root() {
+        let mapping = UriMapping {
+            uri: "/".to_string(),
+            target_uri: "/v1".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            mapping.build_target_uri("/a/b"),
+            Some("/v1/a/b".to_string())
+        );
+    }
+
+    #[test]
+    fn test_gateway_routes_by_weight_and_method() {
+        let mapping =
+            |method: &str, uri: &str, target_uri: &str, service: Option<&str>| UriMapping {
+                methods: if method.is_empty() {
+                    Vec::new()
+                } else {
+                    vec![method.to_string()]
+                },
+                uri: uri.to_string(),
+                target_uri: target_uri.to_string(),
+                target_service: service.map(str::to_string),
+                ..Default::default()
+            };
+        let gateway = Gateway::new(&GatewayConfig {
+            services: HashMap::from([("users".to_string(), "tcp://10.0.0.1:80".to_string())]),
+            routes: vec![
+                mapping("", "/", "/fallback", None),
+                mapping("", "/api/users", "/users", Some("users")),
+                mapping("GET", "/api/users/{id}", "/v1/users/{id}", Some("users")),
+                mapping("", "/api/users/me", "/v1/me", Some("tcp://10.0.0.2:80")),
+            ],
+        });
+
+        let route = |method: &str, path: &str| gateway.route(method, path).unwrap();
+
+        // 更长的映射优先于配置顺序靠前的 `/`
+        assert_eq!(
+            route("GET", "/api/users/7"),
+            GatewayRoute {
+                target: Some("tcp://10.0.0.1:80".to_string()),
+                path: "/v1/users/7".to_string(),
+            }
+        );
+        // 同权重时无变量的映射优先；未登记的服务名按地址使用
+        assert_eq!(
+            route("GET", "/api/users/me"),
+            GatewayRoute {
+                target: Some("tcp://10.0.0.2:80".to_string()),
+                path: "/v1/me".to_string(),
+            }
+        );
+        // 变量映射只允许 GET，回退到前缀映射
+        assert_eq!(route("DELETE", "/api/users/7").path, "/users/7");
+        assert_eq!(
+            route("GET", "/other"),
+            GatewayRoute {
+                target: None,
+                path: "/fallback/other".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_validate_variables() {
+        let mapping = UriMapping {
+            uri: "/api/{id:[0-9]+}".to_string(),
+            target_uri: "/v1/{id}".to_string(),
+            ..Default::default()
+        };
+        assert!(mapping.validate_variables());
+
+        let unknown = UriMapping {
+            target_uri: "/v1/{name}".to_string(),
+            ..mapping.clone()
+        };
+        assert!(!unknown.validate_variables());
+
+        let bad_regex = UriMapping {
+            uri: "/api/{id:[0-9}".to_string(),
+            ..mapping
+        };
+        assert!(!bad_regex.validate_variables());
+    }
 }
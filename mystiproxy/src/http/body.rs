@@ -438,6 +438,43 @@ pub async fn read_json_body(body: Incoming) -> Result<Value> {
     Ok(value)
 }
 
+/// 默认允许缓冲的最大 body 字节数（10 MiB）
+pub const DEFAULT_MAX_BUFFERED_BODY_SIZE: usize = 10 * 1024 * 1024;
+
+/// 读取完整 body，累计超过 `limit` 字节时提前中止
+///
+/// 仅在确实需要完整 body（如 JSON 变换）时使用，其余场景应直接流式转发。
+///
+/// # 参数
+/// - `body`: 任意 hyper body
+/// - `limit`: 允许缓冲的最大字节数
+///
+/// # 返回
+/// 成功返回完整 body，超限返回 `MystiProxyError::PayloadTooLarge`
+pub async fn collect_limited<B>(body: B, limit: usize) -> Result<Bytes>
+where
+    B: hyper::body::Body<Data = Bytes>,
+    B::Error: std::fmt::Display,
+{
+    // Content-Length 已超限时无需读取
+    if body.size_hint().lower() > limit as u64 {
+        return Err(MystiProxyError::PayloadTooLarge(limit));
+    }
+
+    let mut body = std::pin::pin!(body);
+    let mut buf = bytes::BytesMut::new();
+    while let Some(frame) = body.frame().await {
+        let frame = frame.map_err(|e| MystiProxyError::Hyper(e.to_string()))?;
+        if let Ok(data) = frame.into_data() {
+            if buf.len() + data.len() > limit {
+                return Err(MystiProxyError::PayloadTooLarge(limit));
+            }
+            buf.extend_from_slice(&data);
+        }
+    }
+    Ok(buf.freeze())
+}
+
 /// 将 JSON 写入 body
 ///
 /// # 参数
@@ -457,6 +494,20 @@ mod tests {
     use super::*;
     use serde_json::json;
 
+    #[tokio::test]
+    async fn test_collect_limited_within_limit() {
+        let body = Full::new(Bytes::from_static(b"hello"));
+        let bytes = collect_limited(body, 5).await.unwrap();
+        assert_eq!(&bytes[..], b"hello");
+    }
+
+    #[tokio::test]
+    async fn test_collect_limited_exceeds_limit() {
+        let body = Full::new(Bytes::from_static(b"hello world"));
+        let err = collect_limited(body, 5).await.unwrap_err();
+        assert!(matches!(err, MystiProxyError::PayloadTooLarge(5)));
+    }
+
     #[test]
     fn test_transform_overwrite() {
         let mut body = json!({
@@ -473,6 +524,8 @@ mod tests {
             body_type: None,
             content: None,
             template: None,
+            file: None,
+            templated: None,
         };
 
         BodyTransformer::transform(&mut body, &config).unwrap();
@@ -498,6 +551,8 @@ mod tests {
             body_type: None,
             content: None,
             template: None,
+            file: None,
+            templated: None,
         };
 
         BodyTransformer::transform(&mut body, &config).unwrap();
@@ -521,6 +576,8 @@ mod tests {
             body_type: None,
             content: None,
             template: None,
+            file: None,
+            templated: None,
         };
 
         BodyTransformer::transform(&mut body, &config).unwrap();
@@ -543,6 +600,8 @@ mod tests {
             body_type: None,
             content: None,
             template: None,
+            file: None,
+            templated: None,
         };
 
         BodyTransformer::transform(&mut body, &config).unwrap();
@@ -609,6 +668,8 @@ mod tests {
             body_type: None,
             content: None,
             template: None,
+            file: None,
+            templated: None,
         };
 
         BodyTransformer::transform(&mut body, &config).unwrap();
@@ -629,6 +690,8 @@ mod tests {
             body_type: None,
             content: None,
             template: None,
+            file: None,
+            templated: None,
         };
 
         BodyTransformer::transform(&mut body, &config).unwrap();
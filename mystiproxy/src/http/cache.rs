@@ -0,0 +1,983 @@
+//! 代理响应缓存
+//!
+//! 缓存代理 GET 请求的上游响应：按 `Cache-Control`（`s-maxage`/`max-age`/`no-cache`）
+//! 或 `Expires` 计算新鲜期，过期后携带 `If-None-Match`/`If-Modified-Since` 向上游验证，
+//! 并按响应的 `Vary` 区分变体。内存层为有界 LRU；配置 `disk_path` 后响应同时写入磁盘，
+//! 内存未命中时从磁盘加载。
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use bytes::{Bytes, BytesMut};
+use hyper::body::{Body, Frame, SizeHint};
+use hyper::header::{self, HeaderMap, HeaderName, HeaderValue};
+use hyper::{Method, Request, StatusCode};
+use sha2::{Digest, Sha256};
+use tracing::{debug, warn};
+
+use crate::config::CacheConfig;
+
+/// 默认内存缓存条目数
+const DEFAULT_MAX_ENTRIES: usize = 1024;
+/// 默认单个响应体上限
+const DEFAULT_MAX_ENTRY_SIZE: usize = 1024 * 1024;
+/// 默认可缓存的响应状态码
+const CACHEABLE_STATUS: [u16; 8] = [200, 203, 204, 300, 301, 308, 404, 410];
+
+/// 缓存的响应
+#[derive(Debug, Clone)]
+pub struct CachedResponse {
+    /// 状态码
+    pub status: StatusCode,
+    /// 响应头
+    pub headers: HeaderMap,
+    /// 响应体
+    pub body: Bytes,
+    /// 请求路径（按前缀清除时使用）
+    path: String,
+    stored_at: SystemTime,
+    expires_at: SystemTime,
+}
+
+impl CachedResponse {
+    /// 是否仍在新鲜期内
+    pub fn is_fresh(&self, now: SystemTime) -> bool {
+        now < self.expires_at
+    }
+
+    /// 自写入（或最近一次验证）以来的时长，即 `Age` 头
+    pub fn age(&self, now: SystemTime) -> Duration {
+        now.duration_since(self.stored_at).unwrap_or_default()
+    }
+
+    /// 是否带有 `ETag` 或 `Last-Modified` 验证器
+    pub fn has_validator(&self) -> bool {
+        self.headers.contains_key(header::ETAG) || self.headers.contains_key(header::LAST_MODIFIED)
+    }
+
+    /// 向上游验证时附加的条件请求头
+    pub fn conditional_headers(&self) -> Vec<(HeaderName, HeaderValue)> {
+        let mut headers = Vec::new();
+        if let Some(etag) = self.headers.get(header::ETAG) {
+            headers.push((header::IF_NONE_MATCH, etag.clone()));
+        }
+        if let Some(last_modified) = self.headers.get(header::LAST_MODIFIED) {
+            headers.push((header::IF_MODIFIED_SINCE, last_modified.clone()));
+        }
+        headers
+    }
+
+    /// 客户端的 `If-None-Match` 是否命中该响应的 `ETag`
+    pub fn matches_if_none_match(&self, request_headers: &HeaderMap) -> bool {
+        let Some(etag) = self.headers.get(header::ETAG).and_then(|v| v.to_str().ok()) else {
+            return false;
+        };
+        let weak = |tag: &str| tag.trim().trim_start_matches("W/").to_string();
+        request_headers
+            .get_all(header::IF_NONE_MATCH)
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .flat_map(|v| v.split(','))
+            .any(|tag| tag.trim() == "*" || weak(tag) == weak(etag))
+    }
+}
+
+/// 缓存查找结果
+#[derive(Debug)]
+pub enum CacheLookup {
+    /// 新鲜条目，可直接返回
+    Fresh(Arc<CachedResponse>),
+    /// 过期条目，需向上游验证
+    Stale(Arc<CachedResponse>),
+    /// 未命中
+    Miss,
+}
+
+/// 请求侧的缓存语义
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RequestDirective {
+    /// 不读也不写缓存（非 GET、带 `Authorization` 或 `Cache-Control: no-store`）
+    Bypass,
+    /// 缓存条目须先向上游验证（`no-cache`、`max-age=0` 或 `Pragma: no-cache`）
+    Revalidate,
+    /// 正常使用缓存
+    Normal,
+}
+
+impl RequestDirective {
+    /// 按请求方法与请求头判定
+    pub fn from_request(method: &Method, headers: &HeaderMap) -> Self {
+        if method != Method::GET || headers.contains_key(header::AUTHORIZATION) {
+            return Self::Bypass;
+        }
+        let directives = cache_control(headers);
+        if directives.iter().any(|(name, _)| name == "no-store") {
+            return Self::Bypass;
+        }
+        let pragma_no_cache = headers
+            .get(header::PRAGMA)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|v| v.eq_ignore_ascii_case("no-cache"));
+        let revalidate = pragma_no_cache
+            || directives.iter().any(|(name, value)| {
+                name == "no-cache" || (name == "max-age" && value.as_deref() == Some("0"))
+            });
+        if revalidate {
+            Self::Revalidate
+        } else {
+            Self::Normal
+        }
+    }
+}
+
+/// 请求的缓存键
+#[derive(Debug, Clone)]
+pub struct CacheKey {
+    /// 主键：Host + 路径与查询串
+    primary: String,
+    path: String,
+    /// 请求头，用于按 `Vary` 计算变体
+    headers: HeaderMap,
+}
+
+impl CacheKey {
+    /// 由客户端请求生成
+    pub fn from_request<B>(req: &Request<B>) -> Self {
+        let host = req
+            .headers()
+            .get(header::HOST)
+            .and_then(|v| v.to_str().ok())
+            .or_else(|| req.uri().authority().map(|a| a.as_str()))
+            .unwrap_or_default();
+        let path_and_query = req
+            .uri()
+            .path_and_query()
+            .map(|p| p.as_str())
+            .unwrap_or("/");
+        Self {
+            primary: format!("{host}{path_and_query}"),
+            path: req.uri().path().to_string(),
+            headers: req.headers().clone(),
+        }
+    }
+
+    /// 含 `Vary` 请求头取值的完整键
+    fn variant(&self, vary: &[HeaderName]) -> String {
+        let mut key = self.primary.clone();
+        for name in vary {
+            let values: Vec<&str> = self
+                .headers
+                .get_all(name)
+                .iter()
+                .filter_map(|v| v.to_str().ok())
+                .collect();
+            key.push('\n');
+            key.push_str(name.as_str());
+            key.push(':');
+            key.push_str(&values.join(","));
+        }
+        key
+    }
+}
+
+/// 计算响应的新鲜期；返回 None 表示不可缓存
+///
+/// `forced_ttl` 为 location 强制的新鲜期，优先于响应的 `Cache-Control`/`Expires`，
+/// 但 `no-store`、`private`、`Set-Cookie` 与 `Vary: *` 的响应仍不缓存。
+pub fn freshness_lifetime(
+    status: StatusCode,
+    headers: &HeaderMap,
+    forced_ttl: Option<Duration>,
+) -> Option<Duration> {
+    if !CACHEABLE_STATUS.contains(&status.as_u16())
+        || headers.contains_key(header::SET_COOKIE)
+        || vary_names(headers).is_none()
+    {
+        return None;
+    }
+    let directives = cache_control(headers);
+    let has = |name: &str| directives.iter().any(|(n, _)| n == name);
+    if has("no-store") || has("private") {
+        return None;
+    }
+
+    let has_validator =
+        headers.contains_key(header::ETAG) || headers.contains_key(header::LAST_MODIFIED);
+    let lifetime = forced_ttl.or_else(|| {
+        let max_age = ["s-maxage", "max-age"].iter().find_map(|wanted| {
+            directives
+                .iter()
+                .find(|(name, _)| name == wanted)
+                .and_then(|(_, value)| value.as_deref()?.parse::<u64>().ok())
+        });
+        if let Some(max_age) = max_age {
+            let age = headers
+                .get(header::AGE)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.parse::<u64>().ok())
+                .unwrap_or(0);
+            return Some(Duration::from_secs(max_age.saturating_sub(age)));
+        }
+        if has("no-cache") {
+            return Some(Duration::ZERO);
+        }
+        let expires = headers.get(header::EXPIRES)?;
+        // 无法解析的 Expires 视为已过期
+        let Some(expires) = parse_http_date(expires) else {
+            return Some(Duration::ZERO);
+        };
+        let date = headers
+            .get(header::DATE)
+            .and_then(parse_http_date)
+            .unwrap_or_else(SystemTime::now);
+        Some(expires.duration_since(date).unwrap_or_default())
+    })?;
+
+    // 立即过期且无法验证的响应没有缓存价值
+    if lifetime.is_zero() && !has_validator {
+        return None;
+    }
+    Some(lifetime)
+}
+
+/// 解析 `Cache-Control` 指令为 (小写名称, 取值)
+fn cache_control(headers: &HeaderMap) -> Vec<(String, Option<String>)> {
+    headers
+        .get_all(header::CACHE_CONTROL)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .filter(|d| !d.trim().is_empty())
+        .map(|directive| match directive.split_once('=') {
+            Some((name, value)) => (
+                name.trim().to_ascii_lowercase(),
+                Some(value.trim().trim_matches('"').to_string()),
+            ),
+            None => (directive.trim().to_ascii_lowercase(), None),
+        })
+        .collect()
+}
+
+/// 响应 `Vary` 中的请求头名；`Vary: *` 返回 None
+fn vary_names(headers: &HeaderMap) -> Option<Vec<HeaderName>> {
+    let mut names = Vec::new();
+    for value in headers.get_all(header::VARY) {
+        for name in value.to_str().unwrap_or_default().split(',') {
+            let name = name.trim();
+            if name == "*" {
+                return None;
+            }
+            if let Ok(name) = name.parse::<HeaderName>() {
+                if !names.contains(&name) {
+                    names.push(name);
+                }
+            }
+        }
+    }
+    names.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+    Some(names)
+}
+
+fn parse_http_date(value: &HeaderValue) -> Option<SystemTime> {
+    let date = chrono::DateTime::parse_from_rfc2822(value.to_str().ok()?).ok()?;
+    let secs = u64::try_from(date.timestamp()).ok()?;
+    Some(UNIX_EPOCH + Duration::from_secs(secs))
+}
+
+/// 内存 LRU
+#[derive(Default)]
+struct MemoryCache {
+    /// 完整键 -> (条目, 最近访问序号)
+    entries: HashMap<String, (Arc<CachedResponse>, u64)>,
+    /// 访问序号 -> 完整键，最小者最久未用
+    order: BTreeMap<u64, String>,
+    /// 主键 -> (请求路径, Vary 请求头名)
+    vary: HashMap<String, (String, Vec<HeaderName>)>,
+    tick: u64,
+}
+
+impl MemoryCache {
+    fn get(&mut self, key: &str) -> Option<Arc<CachedResponse>> {
+        self.tick += 1;
+        let tick = self.tick;
+        let (entry, last_used) = self.entries.get_mut(key)?;
+        self.order.remove(last_used);
+        self.order.insert(tick, key.to_string());
+        *last_used = tick;
+        Some(entry.clone())
+    }
+
+    fn insert(&mut self, key: String, entry: Arc<CachedResponse>, max_entries: usize) {
+        self.tick += 1;
+        if let Some((_, last_used)) = self.entries.insert(key.clone(), (entry, self.tick)) {
+            self.order.remove(&last_used);
+        }
+        self.order.insert(self.tick, key);
+        while self.entries.len() > max_entries {
+            let Some((_, oldest)) = self.order.pop_first() else {
+                break;
+            };
+            self.entries.remove(&oldest);
+        }
+    }
+
+    fn purge_prefix(&mut self, prefix: &str) -> usize {
+        let purged: Vec<(String, u64)> = self
+            .entries
+            .iter()
+            .filter(|(_, (entry, _))| entry.path.starts_with(prefix))
+            .map(|(key, (_, last_used))| (key.clone(), *last_used))
+            .collect();
+        for (key, last_used) in &purged {
+            self.entries.remove(key);
+            self.order.remove(last_used);
+        }
+        self.vary.retain(|_, (path, _)| !path.starts_with(prefix));
+        purged.len()
+    }
+}
+
+/// 代理响应缓存
+pub struct ResponseCache {
+    engine: String,
+    max_entries: usize,
+    max_entry_size: usize,
+    disk: Option<PathBuf>,
+    memory: Mutex<MemoryCache>,
+}
+
+impl std::fmt::Debug for ResponseCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ResponseCache")
+            .field("engine", &self.engine)
+            .field("max_entries", &self.max_entries)
+            .field("max_entry_size", &self.max_entry_size)
+            .field("disk", &self.disk)
+            .finish()
+    }
+}
+
+impl ResponseCache {
+    /// 由配置创建，引擎名取自当前线程上下文
+    pub fn new(config: &CacheConfig) -> Self {
+        Self {
+            engine: crate::context::get_engine_name().unwrap_or_else(|| "default".to_string()),
+            max_entries: config.max_entries.unwrap_or(DEFAULT_MAX_ENTRIES).max(1),
+            max_entry_size: config.max_entry_size.unwrap_or(DEFAULT_MAX_ENTRY_SIZE),
+            disk: config.disk_path.as_ref().map(PathBuf::from),
+            memory: Mutex::new(MemoryCache::default()),
+        }
+    }
+
+    /// 所属引擎名
+    pub fn engine(&self) -> &str {
+        &self.engine
+    }
+
+    /// 单个响应体上限
+    pub fn max_entry_size(&self) -> usize {
+        self.max_entry_size
+    }
+
+    /// 内存中的条目数
+    pub fn len(&self) -> usize {
+        self.memory.lock().unwrap().entries.len()
+    }
+
+    /// 内存中是否没有条目
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// 查找请求对应的缓存条目
+    pub async fn lookup(&self, key: &CacheKey) -> CacheLookup {
+        let vary = self.memory.lock().unwrap().vary.get(&key.primary).cloned();
+        let vary = match vary {
+            Some((_, names)) => names,
+            None => match self.read_vary(key).await {
+                Some(names) => names,
+                None => return CacheLookup::Miss,
+            },
+        };
+        let full_key = key.variant(&vary);
+
+        let cached = self.memory.lock().unwrap().get(&full_key);
+        let entry = match cached {
+            Some(entry) => entry,
+            None => match self.read_entry(&full_key).await {
+                Some(entry) => {
+                    let entry = Arc::new(entry);
+                    self.memory
+                        .lock()
+                        .unwrap()
+                        .insert(full_key, entry.clone(), self.max_entries);
+                    entry
+                }
+                None => return CacheLookup::Miss,
+            },
+        };
+
+        if entry.is_fresh(SystemTime::now()) {
+            CacheLookup::Fresh(entry)
+        } else {
+            CacheLookup::Stale(entry)
+        }
+    }
+
+    /// 写入上游响应，`lifetime` 由 [`freshness_lifetime`] 计算
+    pub async fn store(
+        &self,
+        key: &CacheKey,
+        status: StatusCode,
+        mut headers: HeaderMap,
+        body: Bytes,
+        lifetime: Duration,
+    ) -> Option<Arc<CachedResponse>> {
+        if body.len() > self.max_entry_size {
+            return None;
+        }
+        let vary = vary_names(&headers)?;
+        // 逐跳头与分块编码不随缓存返回
+        for name in [header::CONNECTION, header::TRANSFER_ENCODING, header::AGE] {
+            headers.remove(name);
+        }
+        headers.insert(header::CONTENT_LENGTH, HeaderValue::from(body.len()));
+
+        let now = SystemTime::now();
+        let entry = Arc::new(CachedResponse {
+            status,
+            headers,
+            body,
+            path: key.path.clone(),
+            stored_at: now,
+            expires_at: now + lifetime,
+        });
+        let full_key = key.variant(&vary);
+        {
+            let mut memory = self.memory.lock().unwrap();
+            memory
+                .vary
+                .insert(key.primary.clone(), (key.path.clone(), vary.clone()));
+            memory.insert(full_key.clone(), entry.clone(), self.max_entries);
+        }
+        debug!("Cached {} for {:?}", full_key, lifetime);
+
+        if let Some(dir) = &self.disk {
+            if let Err(e) = write_disk(dir, key, &vary, &full_key, &entry).await {
+                warn!("Failed to write cache entry to {}: {}", dir.display(), e);
+            }
+        }
+        Some(entry)
+    }
+
+    /// 上游返回 304 后刷新过期条目：合并 304 的响应头并重新计算新鲜期
+    pub async fn refresh(
+        &self,
+        key: &CacheKey,
+        stale: &CachedResponse,
+        not_modified: &HeaderMap,
+        forced_ttl: Option<Duration>,
+    ) -> Arc<CachedResponse> {
+        let mut headers = stale.headers.clone();
+        for name in not_modified.keys() {
+            if name == header::CONTENT_LENGTH || name == header::TRANSFER_ENCODING {
+                continue;
+            }
+            headers.remove(name);
+            for value in not_modified.get_all(name) {
+                headers.append(name.clone(), value.clone());
+            }
+        }
+        let lifetime = freshness_lifetime(stale.status, &headers, forced_ttl).unwrap_or_default();
+        match self
+            .store(
+                key,
+                stale.status,
+                headers.clone(),
+                stale.body.clone(),
+                lifetime,
+            )
+            .await
+        {
+            Some(entry) => entry,
+            None => Arc::new(CachedResponse {
+                headers,
+                ..stale.clone()
+            }),
+        }
+    }
+
+    /// 清除请求路径以 `prefix` 开头的条目（含磁盘），返回清除的条目数
+    pub async fn purge_prefix(&self, prefix: &str) -> usize {
+        let mut purged = self.memory.lock().unwrap().purge_prefix(prefix);
+        if let Some(dir) = &self.disk {
+            match purge_disk(dir, prefix).await {
+                Ok(on_disk) => purged = purged.max(on_disk),
+                Err(e) => warn!("Failed to purge cache directory {}: {}", dir.display(), e),
+            }
+        }
+        purged
+    }
+
+    async fn read_vary(&self, key: &CacheKey) -> Option<Vec<HeaderName>> {
+        let dir = self.disk.as_ref()?;
+        let (meta, _) = read_disk_file(&dir.join(format!("{}.vary", digest(&key.primary)))).await?;
+        let names = meta
+            .get("names")?
+            .as_array()?
+            .iter()
+            .filter_map(|n| n.as_str()?.parse().ok())
+            .collect::<Vec<HeaderName>>();
+        self.memory
+            .lock()
+            .unwrap()
+            .vary
+            .insert(key.primary.clone(), (key.path.clone(), names.clone()));
+        Some(names)
+    }
+
+    async fn read_entry(&self, full_key: &str) -> Option<CachedResponse> {
+        let dir = self.disk.as_ref()?;
+        let (meta, body) = read_disk_file(&dir.join(format!("{}.entry", digest(full_key)))).await?;
+        if meta.get("key")?.as_str()? != full_key {
+            return None;
+        }
+        let mut headers = HeaderMap::new();
+        for pair in meta.get("headers")?.as_array()? {
+            let name: HeaderName = pair.get(0)?.as_str()?.parse().ok()?;
+            let value = HeaderValue::from_str(pair.get(1)?.as_str()?).ok()?;
+            headers.append(name, value);
+        }
+        let millis = |field: &str| {
+            meta.get(field)
+                .and_then(|v| v.as_u64())
+                .map(|ms| UNIX_EPOCH + Duration::from_millis(ms))
+        };
+        Some(CachedResponse {
+            status: StatusCode::from_u16(meta.get("status")?.as_u64()? as u16).ok()?,
+            headers,
+            body,
+            path: meta.get("path")?.as_str()?.to_string(),
+            stored_at: millis("stored_at")?,
+            expires_at: millis("expires_at")?,
+        })
+    }
+}
+
+fn digest(key: &str) -> String {
+    Sha256::digest(key.as_bytes())
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+fn unix_millis(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+/// 磁盘文件格式：首行 JSON 元数据，其后为响应体
+async fn write_disk(
+    dir: &Path,
+    key: &CacheKey,
+    vary: &[HeaderName],
+    full_key: &str,
+    entry: &CachedResponse,
+) -> std::io::Result<()> {
+    tokio::fs::create_dir_all(dir).await?;
+    let vary_meta = serde_json::json!({
+        "path": key.path,
+        "names": vary.iter().map(|n| n.as_str()).collect::<Vec<_>>(),
+    });
+    tokio::fs::write(
+        dir.join(format!("{}.vary", digest(&key.primary))),
+        format!("{vary_meta}\n"),
+    )
+    .await?;
+
+    let headers: Vec<(&str, &str)> = entry
+        .headers
+        .iter()
+        .filter_map(|(name, value)| Some((name.as_str(), value.to_str().ok()?)))
+        .collect();
+    let meta = serde_json::json!({
+        "key": full_key,
+        "path": entry.path,
+        "status": entry.status.as_u16(),
+        "headers": headers,
+        "stored_at": unix_millis(entry.stored_at),
+        "expires_at": unix_millis(entry.expires_at),
+    });
+    let mut contents = format!("{meta}\n").into_bytes();
+    contents.extend_from_slice(&entry.body);
+    // 先写临时文件再改名，避免并发读到半写的条目
+    let path = dir.join(format!("{}.entry", digest(full_key)));
+    let tmp = path.with_extension("tmp");
+    tokio::fs::write(&tmp, contents).await?;
+    tokio::fs::rename(tmp, path).await
+}
+
+async fn read_disk_file(path: &Path) -> Option<(serde_json::Value, Bytes)> {
+    let contents = Bytes::from(tokio::fs::read(path).await.ok()?);
+    let newline = contents.iter().position(|b| *b == b'\n')?;
+    let meta = serde_json::from_slice(&contents[..newline]).ok()?;
+    Some((meta, contents.slice(newline + 1..)))
+}
+
+async fn purge_disk(dir: &Path, prefix: &str) -> std::io::Result<usize> {
+    let mut purged = 0;
+    let mut entries = match tokio::fs::read_dir(dir).await {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => return Err(e),
+    };
+    while let Some(file) = entries.next_entry().await? {
+        let path = file.path();
+        let is_entry = path.extension().is_some_and(|ext| ext == "entry");
+        let Some((meta, _)) = read_disk_file(&path).await else {
+            continue;
+        };
+        let matches = meta
+            .get("path")
+            .and_then(|p| p.as_str())
+            .is_some_and(|p| p.starts_with(prefix));
+        if matches {
+            tokio::fs::remove_file(&path).await?;
+            if is_entry {
+                purged += 1;
+            }
+        }
+    }
+    Ok(purged)
+}
+
+/// 待写入缓存的响应
+pub struct PendingStore {
+    /// 所属缓存
+    pub cache: Arc<ResponseCache>,
+    /// 请求的缓存键
+    pub key: CacheKey,
+    /// 响应状态码
+    pub status: StatusCode,
+    /// 响应头
+    pub headers: HeaderMap,
+    /// 新鲜期
+    pub lifetime: Duration,
+}
+
+/// 边转发边缓存的响应体
+///
+/// 响应体完整结束且不超过上限时写入缓存；超限或出错时只转发不缓存。
+pub struct CachingBody<B> {
+    inner: B,
+    buffer: BytesMut,
+    pending: Option<PendingStore>,
+}
+
+impl<B> CachingBody<B> {
+    /// 包装上游响应体
+    pub fn new(inner: B, pending: PendingStore) -> Self {
+        Self {
+            inner,
+            buffer: BytesMut::new(),
+            pending: Some(pending),
+        }
+    }
+
+    /// 响应体结束：后台写入缓存
+    fn commit(&mut self) {
+        if let Some(pending) = self.pending.take() {
+            let body = std::mem::take(&mut self.buffer).freeze();
+            tokio::spawn(async move {
+                pending
+                    .cache
+                    .store(
+                        &pending.key,
+                        pending.status,
+                        pending.headers,
+                        body,
+                        pending.lifetime,
+                    )
+                    .await;
+            });
+        }
+    }
+}
+
+impl<B> Body for CachingBody<B>
+where
+    B: Body<Data = Bytes> + Unpin,
+{
+    type Data = Bytes;
+    type Error = B::Error;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let this = &mut *self;
+        let polled = Pin::new(&mut this.inner).poll_frame(cx);
+        match &polled {
+            Poll::Ready(Some(Ok(frame))) => {
+                if let (Some(pending), Some(data)) = (&this.pending, frame.data_ref()) {
+                    if this.buffer.len() + data.len() > pending.cache.max_entry_size() {
+                        this.pending = None;
+                        this.buffer = BytesMut::new();
+                    } else {
+                        this.buffer.extend_from_slice(data);
+                    }
+                }
+                // 已知长度的 body 读完最后一帧后不会再被轮询
+                if this.inner.is_end_stream() {
+                    this.commit();
+                }
+            }
+            Poll::Ready(Some(Err(_))) => this.pending = None,
+            Poll::Ready(None) => this.commit(),
+            Poll::Pending => {}
+        }
+        polled
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(pairs: &[(&str, &str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.append(
+                name.parse::<HeaderName>().unwrap(),
+                HeaderValue::from_str(value).unwrap(),
+            );
+        }
+        headers
+    }
+
+    fn key(path: &str, request_headers: &[(&str, &str)]) -> CacheKey {
+        let mut builder = Request::get(path).header("host", "example.com");
+        for (name, value) in request_headers {
+            builder = builder.header(*name, *value);
+        }
+        CacheKey::from_request(&builder.body(()).unwrap())
+    }
+
+    fn cache(max_entries: usize) -> ResponseCache {
+        ResponseCache::new(&CacheConfig {
+            max_entries: Some(max_entries),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_freshness_lifetime() {
+        let ok = StatusCode::OK;
+        let lifetime = |pairs: &[(&str, &str)]| freshness_lifetime(ok, &headers(pairs), None);
+
+        assert_eq!(
+            lifetime(&[("cache-control", "public, max-age=60")]),
+            Some(Duration::from_secs(60))
+        );
+        assert_eq!(
+            lifetime(&[("cache-control", "max-age=60, s-maxage=10"), ("age", "4")]),
+            Some(Duration::from_secs(6))
+        );
+        assert_eq!(
+            lifetime(&[
+                ("date", "Sun, 06 Nov 1994 08:49:37 GMT"),
+                ("expires", "Sun, 06 Nov 1994 08:50:37 GMT"),
+            ]),
+            Some(Duration::from_secs(60))
+        );
+        assert_eq!(lifetime(&[("cache-control", "no-store, max-age=60")]), None);
+        assert_eq!(lifetime(&[("cache-control", "private, max-age=60")]), None);
+        assert_eq!(
+            lifetime(&[("cache-control", "max-age=60"), ("vary", "*")]),
+            None
+        );
+        // 无新鲜度信息或立即过期且无验证器时不缓存
+        assert_eq!(lifetime(&[]), None);
+        assert_eq!(lifetime(&[("cache-control", "no-cache")]), None);
+        assert_eq!(
+            lifetime(&[("cache-control", "no-cache"), ("etag", "\"v1\"")]),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(
+            freshness_lifetime(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                &headers(&[("cache-control", "max-age=60")]),
+                None
+            ),
+            None
+        );
+        assert_eq!(
+            freshness_lifetime(ok, &headers(&[]), Some(Duration::from_secs(5))),
+            Some(Duration::from_secs(5))
+        );
+    }
+
+    #[test]
+    fn test_request_directive() {
+        let get = Method::GET;
+        assert_eq!(
+            RequestDirective::from_request(&get, &headers(&[])),
+            RequestDirective::Normal
+        );
+        assert_eq!(
+            RequestDirective::from_request(&Method::POST, &headers(&[])),
+            RequestDirective::Bypass
+        );
+        assert_eq!(
+            RequestDirective::from_request(&get, &headers(&[("authorization", "Bearer x")])),
+            RequestDirective::Bypass
+        );
+        assert_eq!(
+            RequestDirective::from_request(&get, &headers(&[("cache-control", "max-age=0")])),
+            RequestDirective::Revalidate
+        );
+        assert_eq!(
+            RequestDirective::from_request(&get, &headers(&[("pragma", "no-cache")])),
+            RequestDirective::Revalidate
+        );
+    }
+
+    #[tokio::test]
+    async fn test_store_and_lookup_with_vary() {
+        let cache = cache(10);
+        let response_headers = headers(&[("vary", "Accept-Encoding")]);
+        let gzip = key("/a", &[("accept-encoding", "gzip")]);
+        cache
+            .store(
+                &gzip,
+                StatusCode::OK,
+                response_headers,
+                Bytes::from("gzipped"),
+                Duration::from_secs(60),
+            )
+            .await
+            .unwrap();
+
+        match cache.lookup(&gzip).await {
+            CacheLookup::Fresh(entry) => assert_eq!(entry.body, Bytes::from("gzipped")),
+            other => panic!("expected fresh entry, got {other:?}"),
+        }
+        let identity = key("/a", &[("accept-encoding", "identity")]);
+        assert!(matches!(cache.lookup(&identity).await, CacheLookup::Miss));
+    }
+
+    #[tokio::test]
+    async fn test_lru_eviction_and_purge() {
+        let cache = cache(2);
+        for path in ["/api/a", "/api/b", "/other"] {
+            if path == "/other" {
+                // 访问 /api/a 使 /api/b 成为最久未用
+                assert!(matches!(
+                    cache.lookup(&key("/api/a", &[])).await,
+                    CacheLookup::Fresh(_)
+                ));
+            }
+            cache
+                .store(
+                    &key(path, &[]),
+                    StatusCode::OK,
+                    HeaderMap::new(),
+                    Bytes::from(path),
+                    Duration::from_secs(60),
+                )
+                .await;
+        }
+        assert_eq!(cache.len(), 2);
+        assert!(matches!(
+            cache.lookup(&key("/api/b", &[])).await,
+            CacheLookup::Miss
+        ));
+
+        assert_eq!(cache.purge_prefix("/api").await, 1);
+        assert_eq!(cache.len(), 1);
+        assert!(matches!(
+            cache.lookup(&key("/other", &[])).await,
+            CacheLookup::Fresh(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_refresh_merges_not_modified_headers() {
+        let cache = cache(10);
+        let key = key("/a", &[]);
+        let stale = cache
+            .store(
+                &key,
+                StatusCode::OK,
+                headers(&[("etag", "\"v1\""), ("cache-control", "max-age=0")]),
+                Bytes::from("body"),
+                Duration::ZERO,
+            )
+            .await
+            .unwrap();
+        assert!(!stale.is_fresh(SystemTime::now()));
+        assert_eq!(
+            stale.conditional_headers(),
+            vec![(header::IF_NONE_MATCH, HeaderValue::from_static("\"v1\""))]
+        );
+
+        let refreshed = cache
+            .refresh(
+                &key,
+                &stale,
+                &headers(&[("cache-control", "max-age=60")]),
+                None,
+            )
+            .await;
+        assert!(refreshed.is_fresh(SystemTime::now()));
+        assert_eq!(refreshed.body, Bytes::from("body"));
+        assert!(refreshed.matches_if_none_match(&headers(&[("if-none-match", "W/\"v1\"")])));
+    }
+
+    #[tokio::test]
+    async fn test_disk_tier_survives_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = CacheConfig {
+            disk_path: Some(dir.path().to_string_lossy().to_string()),
+            ..Default::default()
+        };
+        let key = key("/a", &[("accept", "text/plain")]);
+        ResponseCache::new(&config)
+            .store(
+                &key,
+                StatusCode::OK,
+                headers(&[("vary", "accept"), ("x-origin", "disk")]),
+                Bytes::from("persisted"),
+                Duration::from_secs(60),
+            )
+            .await
+            .unwrap();
+
+        let restarted = ResponseCache::new(&config);
+        match restarted.lookup(&key).await {
+            CacheLookup::Fresh(entry) => {
+                assert_eq!(entry.body, Bytes::from("persisted"));
+                assert_eq!(entry.headers["x-origin"], "disk");
+            }
+            other => panic!("expected fresh entry, got {other:?}"),
+        }
+        assert_eq!(restarted.purge_prefix("/a").await, 1);
+        assert!(matches!(
+            ResponseCache::new(&config).lookup(&key).await,
+            CacheLookup::Miss
+        ));
+    }
+}
@@ -1,37 +1,73 @@
 //! HTTP 客户端模块
 //!
-//! 提供 HTTP 客户端功能，支持连接池和请求转发
+//! 提供 HTTP 客户端功能，支持连接池和请求转发。
+//! 上游连接默认使用 HTTP/1.1，可选 prior-knowledge HTTP/2。
+//!
+//! 每个 `HttpClient` 为其目标维护一组空闲的 keep-alive 连接：HTTP/1.1 连接在响应体
+//! 读完后归还，HTTP/2 连接在多个请求间共享。取用时剔除已关闭、未就绪或超出
+//! 空闲时间/最长使用时间的连接。
+//!
+//! `https://` 目标经 TLS 连接，CA、客户端证书、SNI 由引擎的 `upstream_tls` 配置。
 
-use std::convert::Infallible;
-use std::sync::Arc;
-use std::time::Duration;
+use std::path::Path;
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::{Duration, Instant};
 
 use bytes::Bytes;
 use http_body_util::combinators::BoxBody;
 use http_body_util::{BodyExt, Full};
 use hyper::body::Incoming;
-use hyper::client::conn::http1::{Builder, SendRequest};
+use hyper::client::conn::{http1, http2, TrySendError};
 use hyper::{Request, Response};
-use hyper_util::rt::TokioIo;
+use hyper_util::rt::{TokioExecutor, TokioIo};
+use rustls::pki_types::ServerName;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::TcpStream;
 use tokio::sync::Mutex;
+use tokio_rustls::TlsConnector;
 use tracing::{debug, error, info};
 
+use crate::circuit_breaker::{CircuitBreaker, CircuitBreakers};
+use crate::config::{ConnectionPoolConfig, UpstreamTlsConfig};
 use crate::error::{MystiProxyError, Result};
 use crate::http::upstream::{UpstreamProxyConfig, UpstreamProxyConnector};
 use crate::io::SocketStream;
+use crate::metrics::global_metrics;
+use crate::tls::create_upstream_client_config;
+
+/// 每个目标默认保留的最大空闲连接数
+const DEFAULT_MAX_IDLE: usize = 32;
+
+/// 空闲连接默认保留时间
+const DEFAULT_IDLE_TIMEOUT: Duration = Duration::from_secs(90);
 
 fn extract_host_from_target(target: &str) -> Option<String> {
     if target.starts_with("unix://") {
         return Some("localhost".to_string());
     }
-    let addr = target.strip_prefix("tcp://").unwrap_or(target);
+    let addr = match target.strip_prefix("https://") {
+        Some(addr) => addr.trim_end_matches('/'),
+        None => target.strip_prefix("tcp://").unwrap_or(target),
+    };
     if addr.is_empty() {
         return None;
     }
     Some(addr.to_string())
 }
 
+fn is_https_target(target: &str) -> bool {
+    target.starts_with("https://")
+}
+
 fn parse_tcp_target(target: &str) -> Option<(String, u16)> {
+    if let Some(addr) = target.strip_prefix("https://") {
+        let authority: hyper::http::uri::Authority = addr.trim_end_matches('/').parse().ok()?;
+        let host = authority
+            .host()
+            .trim_start_matches('[')
+            .trim_end_matches(']');
+        return Some((host.to_string(), authority.port_u16().unwrap_or(443)));
+    }
     let addr = target.strip_prefix("tcp://")?;
     let colon_pos = addr.rfind(':')?;
     let host = &addr[..colon_pos];
@@ -39,12 +75,259 @@ fn parse_tcp_target(target: &str) -> Option<(String, u16)> {
     Some((host.to_string(), port))
 }
 
-pub type RequestBoxBody = Request<BoxBody<Bytes, Infallible>>;
+/// 不应转发给上游的连接级请求头
+fn is_hop_by_hop(name: &str) -> bool {
+    matches!(name, "connection" | "keep-alive" | "proxy-connection")
+}
+
+fn send_error(e: hyper::Error) -> MystiProxyError {
+    MystiProxyError::Proxy(format!("Failed to send request: {e}"))
+}
+
+pub type RequestBoxBody = Request<BoxBody<Bytes, MystiProxyError>>;
+
+/// `https://` 上游的 TLS 连接设置
+///
+/// 按上游协议分别预置 ALPN（`http/1.1` 或 `h2`）。
+#[derive(Clone)]
+pub struct UpstreamTls {
+    http1: TlsConnector,
+    http2: TlsConnector,
+    sni: Option<String>,
+}
+
+impl UpstreamTls {
+    /// 由引擎的 `upstream_tls` 配置创建；未配置时使用内置根证书校验上游
+    pub fn from_config(config: Option<&UpstreamTlsConfig>) -> Result<Self> {
+        let default = UpstreamTlsConfig::default();
+        let config = config.unwrap_or(&default);
+        let identity = match (&config.cert_path, &config.key_path) {
+            (Some(cert), Some(key)) => Some((Path::new(cert), Path::new(key))),
+            (None, None) => None,
+            _ => {
+                return Err(MystiProxyError::Config(
+                    "upstream_tls requires both cert_path and key_path".to_string(),
+                ))
+            }
+        };
+        let base = create_upstream_client_config(
+            config.ca_path.as_deref().map(Path::new),
+            identity,
+            config.insecure_skip_verify,
+        )?;
+        let connector = |alpn: &[u8]| {
+            let mut client_config = base.clone();
+            client_config.alpn_protocols = vec![alpn.to_vec()];
+            TlsConnector::from(Arc::new(client_config))
+        };
+        Ok(Self {
+            http1: connector(b"http/1.1"),
+            http2: connector(b"h2"),
+            sni: config.sni.clone(),
+        })
+    }
+
+    fn connector(&self, http2: bool) -> &TlsConnector {
+        if http2 {
+            &self.http2
+        } else {
+            &self.http1
+        }
+    }
+}
+
+/// 到上游的单个连接（HTTP/1.1 或 HTTP/2）
+enum UpstreamSender {
+    Http1(http1::SendRequest<BoxBody<Bytes, MystiProxyError>>),
+    Http2(http2::SendRequest<BoxBody<Bytes, MystiProxyError>>),
+}
+
+impl UpstreamSender {
+    async fn send_request(&mut self, request: RequestBoxBody) -> hyper::Result<Response<Incoming>> {
+        match self {
+            Self::Http1(sender) => sender.send_request(request).await,
+            Self::Http2(sender) => sender.send_request(request).await,
+        }
+    }
+
+    /// 发送请求；连接未就绪时原样交还请求
+    async fn try_send_request(
+        &mut self,
+        request: RequestBoxBody,
+    ) -> std::result::Result<Response<Incoming>, TrySendError<RequestBoxBody>> {
+        match self {
+            Self::Http1(sender) => sender.try_send_request(request).await,
+            Self::Http2(sender) => sender.try_send_request(request).await,
+        }
+    }
+
+    fn is_ready(&self) -> bool {
+        match self {
+            Self::Http1(sender) => sender.is_ready(),
+            Self::Http2(sender) => sender.is_ready(),
+        }
+    }
+
+    fn is_closed(&self) -> bool {
+        match self {
+            Self::Http1(sender) => sender.is_closed(),
+            Self::Http2(sender) => sender.is_closed(),
+        }
+    }
+}
+
+/// 池中的空闲连接
+struct IdleConnection {
+    sender: UpstreamSender,
+    created_at: Instant,
+    idle_since: Instant,
+}
+
+/// 单个上游目标的空闲连接池
+struct ConnectionPool {
+    target: String,
+    max_idle: usize,
+    idle_timeout: Duration,
+    max_lifetime: Option<Duration>,
+    idle: StdMutex<Vec<IdleConnection>>,
+}
+
+impl ConnectionPool {
+    fn new(target: String, config: &ConnectionPoolConfig) -> Self {
+        Self {
+            target,
+            max_idle: config.max_idle.unwrap_or(DEFAULT_MAX_IDLE),
+            idle_timeout: config.idle_timeout.unwrap_or(DEFAULT_IDLE_TIMEOUT),
+            max_lifetime: config.max_lifetime,
+            idle: StdMutex::new(Vec::new()),
+        }
+    }
+
+    fn is_expired(&self, created_at: Instant, idle_since: Instant, now: Instant) -> bool {
+        now.duration_since(idle_since) >= self.idle_timeout
+            || self
+                .max_lifetime
+                .is_some_and(|lifetime| now.duration_since(created_at) >= lifetime)
+    }
+
+    /// 剔除不可用的空闲连接，返回剔除数量
+    fn evict(&self, idle: &mut Vec<IdleConnection>, now: Instant) -> usize {
+        let before = idle.len();
+        idle.retain(|entry| {
+            !self.is_expired(entry.created_at, entry.idle_since, now)
+                && !entry.sender.is_closed()
+                && entry.sender.is_ready()
+        });
+        before - idle.len()
+    }
+
+    /// 取出一个可用连接
+    ///
+    /// HTTP/1.1 连接从池中移出，直到响应结束后归还；HTTP/2 连接可多路复用，
+    /// 只取出其句柄的副本，连接本身留在池中。
+    fn checkout(&self) -> Option<(UpstreamSender, Instant)> {
+        let mut idle = self.idle.lock().unwrap_or_else(|e| e.into_inner());
+        let now = Instant::now();
+        let mut removed = self.evict(&mut idle, now);
+        let found = match idle.last_mut() {
+            Some(entry) => match &entry.sender {
+                UpstreamSender::Http2(sender) => {
+                    entry.idle_since = now;
+                    Some((UpstreamSender::Http2(sender.clone()), entry.created_at))
+                }
+                UpstreamSender::Http1(_) => {
+                    removed += 1;
+                    idle.pop().map(|entry| (entry.sender, entry.created_at))
+                }
+            },
+            None => None,
+        };
+        drop(idle);
+        self.record(-(removed as i64), 0);
+        found
+    }
+
+    /// 归还连接；连接已关闭、超出最长使用时间或池已满时直接丢弃
+    fn checkin(&self, sender: UpstreamSender, created_at: Instant) {
+        let now = Instant::now();
+        if self.max_idle == 0 || sender.is_closed() || self.is_expired(created_at, now, now) {
+            return;
+        }
+        let mut idle = self.idle.lock().unwrap_or_else(|e| e.into_inner());
+        let removed = self.evict(&mut idle, now);
+        let added = idle.len() < self.max_idle;
+        if added {
+            idle.push(IdleConnection {
+                sender,
+                created_at,
+                idle_since: now,
+            });
+        }
+        drop(idle);
+        self.record(added as i64 - removed as i64, 0);
+    }
+
+    /// 请求完成后释放连接
+    ///
+    /// HTTP/1.1 连接需等响应体读完才能复用，因此在后台等待连接就绪后再归还；
+    /// HTTP/2 连接一直留在池中，无需归还。
+    fn release(self: &Arc<Self>, sender: UpstreamSender, created_at: Instant, in_use: InUse) {
+        if let UpstreamSender::Http1(mut sender) = sender {
+            if self.max_idle == 0 {
+                return;
+            }
+            let pool = self.clone();
+            tokio::spawn(async move {
+                if sender.ready().await.is_ok() {
+                    pool.checkin(UpstreamSender::Http1(sender), created_at);
+                }
+                drop(in_use);
+            });
+        }
+    }
+
+    fn idle_count(&self) -> usize {
+        self.idle.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    fn record(&self, idle_delta: i64, in_use_delta: i64) {
+        if idle_delta != 0 || in_use_delta != 0 {
+            global_metrics().record_upstream_pool(&self.target, idle_delta, in_use_delta);
+        }
+    }
+}
+
+impl Drop for ConnectionPool {
+    fn drop(&mut self) {
+        let idle = self.idle.get_mut().unwrap_or_else(|e| e.into_inner()).len();
+        self.record(-(idle as i64), 0);
+    }
+}
+
+/// 正在使用的连接计数（drop 时递减）
+struct InUse(Arc<ConnectionPool>);
+
+impl InUse {
+    fn new(pool: Arc<ConnectionPool>) -> Self {
+        pool.record(0, 1);
+        Self(pool)
+    }
+}
+
+impl Drop for InUse {
+    fn drop(&mut self) {
+        self.0.record(0, -1);
+    }
+}
 
 pub struct HttpClient {
     target: String,
     timeout: Option<Duration>,
     upstream_config: Option<UpstreamProxyConfig>,
+    http2: bool,
+    tls: Option<UpstreamTls>,
+    pool: Arc<ConnectionPool>,
+    breaker: Option<Arc<CircuitBreaker>>,
 }
 
 impl HttpClient {
@@ -53,14 +336,56 @@ impl HttpClient {
         timeout: Option<Duration>,
         upstream_config: Option<UpstreamProxyConfig>,
     ) -> Self {
+        let pool = Arc::new(ConnectionPool::new(
+            target.clone(),
+            &ConnectionPoolConfig::default(),
+        ));
         Self {
             target,
             timeout,
             upstream_config,
+            http2: false,
+            tls: None,
+            pool,
+            breaker: None,
         }
     }
 
-    async fn establish_connection(&self) -> Result<SendRequest<BoxBody<Bytes, Infallible>>> {
+    /// 设置该目标的熔断器（链式）
+    pub fn with_circuit_breaker(mut self, breaker: Arc<CircuitBreaker>) -> Self {
+        self.breaker = Some(breaker);
+        self
+    }
+
+    /// 设置 `https://` 目标的 TLS 连接参数（链式）
+    pub fn with_upstream_tls(mut self, tls: UpstreamTls) -> Self {
+        self.tls = Some(tls);
+        self
+    }
+
+    /// 设置上游连接池参数（链式）
+    pub fn with_connection_pool(mut self, config: &ConnectionPoolConfig) -> Self {
+        self.pool = Arc::new(ConnectionPool::new(self.target.clone(), config));
+        self
+    }
+
+    /// 当前空闲连接数
+    pub fn idle_connections(&self) -> usize {
+        self.pool.idle_count()
+    }
+
+    /// 设置是否以 prior-knowledge HTTP/2 连接上游（链式）
+    pub fn with_http2(mut self, enabled: bool) -> Self {
+        self.http2 = enabled;
+        self
+    }
+
+    /// 是否以 HTTP/2 连接上游
+    pub fn is_http2(&self) -> bool {
+        self.http2
+    }
+
+    async fn establish_connection(&self) -> Result<UpstreamSender> {
         if let Some(ref upstream_cfg) = self.upstream_config {
             if let Some((host, port)) = parse_tcp_target(&self.target) {
                 return self.establish_upstream(upstream_cfg, &host, port).await;
@@ -69,25 +394,79 @@ impl HttpClient {
         self.establish_direct().await
     }
 
-    async fn establish_direct(&self) -> Result<SendRequest<BoxBody<Bytes, Infallible>>> {
-        let stream = SocketStream::connect(self.target.clone()).await?;
-        let io = TokioIo::new(stream);
+    async fn establish_direct(&self) -> Result<UpstreamSender> {
+        let sender = if is_https_target(&self.target) {
+            let (host, port) = parse_tcp_target(&self.target).ok_or_else(|| {
+                MystiProxyError::Config(format!("Invalid https target: {}", self.target))
+            })?;
+            let stream = TcpStream::connect((host.as_str(), port)).await?;
+            let tls_stream = self.connect_tls(stream, &host).await?;
+            self.handshake(TokioIo::new(tls_stream)).await
+        } else {
+            let stream = SocketStream::connect(self.target.clone()).await?;
+            self.handshake(TokioIo::new(stream)).await
+        }
+        .map_err(|e| MystiProxyError::Proxy(format!("Failed to establish connection: {e}")))?;
 
-        let (sender, conn) = Builder::new()
-            .preserve_header_case(true)
-            .title_case_headers(true)
-            .handshake(io)
-            .await
-            .map_err(|e| MystiProxyError::Proxy(format!("Failed to establish connection: {e}")))?;
+        debug!("Successfully connected to {}", self.target);
+        Ok(sender)
+    }
 
-        tokio::spawn(async move {
-            if let Err(err) = conn.await {
-                error!("Connection error: {:?}", err);
+    /// 在已建立的连接上完成 TLS 握手（SNI 默认取 target 主机名）
+    async fn connect_tls<S>(
+        &self,
+        stream: S,
+        host: &str,
+    ) -> Result<tokio_rustls::client::TlsStream<S>>
+    where
+        S: AsyncRead + AsyncWrite + Unpin,
+    {
+        let default_tls;
+        let tls = match &self.tls {
+            Some(tls) => tls,
+            None => {
+                default_tls = UpstreamTls::from_config(None)?;
+                &default_tls
             }
-        });
+        };
+        let server_name = ServerName::try_from(tls.sni.as_deref().unwrap_or(host).to_string())
+            .map_err(|e| MystiProxyError::Tls(format!("Invalid upstream server name: {e}")))?;
+        tls.connector(self.http2)
+            .connect(server_name, stream)
+            .await
+            .map_err(|e| {
+                MystiProxyError::Tls(format!("TLS handshake with {} failed: {e}", self.target))
+            })
+    }
 
-        debug!("Successfully connected to {}", self.target);
-        Ok(sender)
+    /// 在已建立的连接上完成 HTTP/1.1 或 HTTP/2 握手，并在后台驱动连接
+    async fn handshake<T>(&self, io: T) -> hyper::Result<UpstreamSender>
+    where
+        T: hyper::rt::Read + hyper::rt::Write + Unpin + Send + 'static,
+    {
+        if self.http2 {
+            let (sender, conn) = http2::Builder::new(TokioExecutor::new())
+                .handshake(io)
+                .await?;
+            tokio::spawn(async move {
+                if let Err(err) = conn.await {
+                    error!("HTTP/2 connection error: {:?}", err);
+                }
+            });
+            Ok(UpstreamSender::Http2(sender))
+        } else {
+            let (sender, conn) = http1::Builder::new()
+                .preserve_header_case(true)
+                .title_case_headers(true)
+                .handshake(io)
+                .await?;
+            tokio::spawn(async move {
+                if let Err(err) = conn.await {
+                    error!("Connection error: {:?}", err);
+                }
+            });
+            Ok(UpstreamSender::Http1(sender))
+        }
     }
 
     async fn establish_upstream(
@@ -95,25 +474,18 @@ impl HttpClient {
         upstream_cfg: &UpstreamProxyConfig,
         host: &str,
         port: u16,
-    ) -> Result<SendRequest<BoxBody<Bytes, Infallible>>> {
+    ) -> Result<UpstreamSender> {
         let connector = UpstreamProxyConnector::new(upstream_cfg.clone());
         let stream = connector.connect_tunnel(host, port).await?;
-        let io = TokioIo::new(stream);
-
-        let (sender, conn) = Builder::new()
-            .preserve_header_case(true)
-            .title_case_headers(true)
-            .handshake(io)
-            .await
-            .map_err(|e| {
-                MystiProxyError::Proxy(format!("Failed to establish upstream connection: {e}"))
-            })?;
-
-        tokio::spawn(async move {
-            if let Err(err) = conn.await {
-                error!("Upstream connection error: {:?}", err);
-            }
-        });
+        let sender = if is_https_target(&self.target) {
+            let tls_stream = self.connect_tls(stream, host).await?;
+            self.handshake(TokioIo::new(tls_stream)).await
+        } else {
+            self.handshake(TokioIo::new(stream)).await
+        }
+        .map_err(|e| {
+            MystiProxyError::Proxy(format!("Failed to establish upstream connection: {e}"))
+        })?;
 
         debug!(
             "Successfully connected to {} via upstream proxy",
@@ -135,19 +507,37 @@ impl HttpClient {
             request.uri()
         );
 
-        let mut sender = self.establish_connection().await?;
+        // 熔断器打开时直接拒绝，不再连接上游
+        let permit = match &self.breaker {
+            Some(breaker) => Some(
+                breaker
+                    .try_acquire()
+                    .ok_or_else(|| MystiProxyError::CircuitOpen(self.target.clone()))?,
+            ),
+            None => None,
+        };
 
-        let response = if let Some(timeout) = self.timeout {
-            tokio::time::timeout(timeout, sender.send_request(request))
-                .await
-                .map_err(|_| MystiProxyError::Timeout)?
-                .map_err(|e| MystiProxyError::Proxy(format!("Failed to send request: {e}")))?
+        let request = if self.http2 {
+            self.to_http2_request(request)?
         } else {
-            sender
-                .send_request(request)
+            request
+        };
+        // 超时后连接随 future 一起丢弃，不会回到池中
+        let sent = if let Some(timeout) = self.timeout {
+            tokio::time::timeout(timeout, self.send_pooled(request))
                 .await
-                .map_err(|e| MystiProxyError::Proxy(format!("Failed to send request: {e}")))?
+                .unwrap_or(Err(MystiProxyError::Timeout))
+        } else {
+            self.send_pooled(request).await
         };
+        // 连接错误、超时与 5xx 计为失败
+        if let Some(permit) = permit {
+            match &sent {
+                Ok(response) if !response.status().is_server_error() => permit.success(),
+                _ => permit.failure(),
+            }
+        }
+        let response = sent?;
 
         info!(
             "Received response: {} from {}",
@@ -157,6 +547,76 @@ impl HttpClient {
         Ok(response)
     }
 
+    /// 优先复用池中连接发送请求
+    ///
+    /// 复用的连接在请求发出前已不可用时，改用新连接重发；请求一旦发出则不再重试。
+    async fn send_pooled(&self, request: RequestBoxBody) -> Result<Response<Incoming>> {
+        let in_use = InUse::new(self.pool.clone());
+        let mut request = request;
+        if let Some((mut sender, created_at)) = self.pool.checkout() {
+            debug!("Reusing pooled connection to {}", self.target);
+            match sender.try_send_request(request).await {
+                Ok(response) => {
+                    self.pool.release(sender, created_at, in_use);
+                    return Ok(response);
+                }
+                Err(mut e) => match e.take_message() {
+                    Some(unsent) => {
+                        debug!(
+                            "Pooled connection to {} not usable, reconnecting",
+                            self.target
+                        );
+                        request = unsent;
+                    }
+                    None => return Err(send_error(e.into_error())),
+                },
+            }
+        }
+
+        let created_at = Instant::now();
+        let mut sender = self.establish_connection().await?;
+        if let UpstreamSender::Http2(shared) = &sender {
+            self.pool
+                .checkin(UpstreamSender::Http2(shared.clone()), created_at);
+        }
+        let response = sender.send_request(request).await.map_err(send_error)?;
+        self.pool.release(sender, created_at, in_use);
+        Ok(response)
+    }
+
+    /// HTTP/2 请求需要 `:scheme` 与 `:authority`：由 Host 头（或目标地址）补全为绝对 URI
+    fn to_http2_request(&self, request: RequestBoxBody) -> Result<RequestBoxBody> {
+        let (mut parts, body) = request.into_parts();
+        if parts.uri.scheme().is_none() {
+            let authority = parts
+                .headers
+                .get(hyper::header::HOST)
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_string)
+                .or_else(|| extract_host_from_target(&self.target))
+                .unwrap_or_else(|| "localhost".to_string());
+            let path_and_query = parts
+                .uri
+                .path_and_query()
+                .map(|pq| pq.as_str())
+                .unwrap_or("/");
+            let scheme = if is_https_target(&self.target) {
+                "https"
+            } else {
+                "http"
+            };
+            parts.uri = hyper::Uri::builder()
+                .scheme(scheme)
+                .authority(authority)
+                .path_and_query(path_and_query)
+                .build()
+                .map_err(MystiProxyError::Http)?;
+        }
+        parts.headers.remove(hyper::header::HOST);
+        parts.version = hyper::Version::HTTP_2;
+        Ok(Request::from_parts(parts, body))
+    }
+
     fn rewrite_uri_and_headers(
         &self,
         method: hyper::http::Method,
@@ -169,35 +629,48 @@ impl HttpClient {
             .build()
             .map_err(MystiProxyError::Http)?;
 
+        // 逐跳头只作用于客户端连接，转发会让上游关闭池中的 keep-alive 连接
+        let connection_tokens: Vec<String> = headers
+            .get_all(hyper::header::CONNECTION)
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .flat_map(|v| v.split(','))
+            .map(|token| token.trim().to_ascii_lowercase())
+            .collect();
+
         let mut builder = Request::builder().method(method).uri(new_uri);
         let mut has_host = false;
         for (name, value) in headers {
             if name == "host" {
                 has_host = true;
             }
+            if is_hop_by_hop(name.as_str())
+                || connection_tokens.iter().any(|token| token == name.as_str())
+            {
+                continue;
+            }
             builder = builder.header(name, value);
         }
         if !has_host {
-            if let Some(host) = extract_host_from_target(&self.target) {
+            // HTTP/2 入站请求以 :authority 代替 Host 头
+            if let Some(authority) = uri.authority() {
+                builder = builder.header("Host", authority.as_str());
+            } else if let Some(host) = extract_host_from_target(&self.target) {
                 builder = builder.header("Host", &host);
             }
         }
         Ok(builder)
     }
 
+    /// 转换入站请求：body 以流式转发，不做缓冲
     pub async fn convert_incoming_request_async(
         &self,
         request: Request<Incoming>,
     ) -> Result<RequestBoxBody> {
         let (parts, body) = request.into_parts();
         let builder = self.rewrite_uri_and_headers(parts.method, &parts.uri, &parts.headers)?;
-        let body_bytes = body
-            .collect()
-            .await
-            .map_err(|e| MystiProxyError::Hyper(e.to_string()))?
-            .to_bytes();
-        let boxed = Full::new(body_bytes)
-            .map_err(|never| match never {})
+        let boxed = body
+            .map_err(|e| MystiProxyError::Hyper(e.to_string()))
             .boxed();
         builder.body(boxed).map_err(MystiProxyError::Http)
     }
@@ -223,15 +696,49 @@ impl HttpClient {
 
 pub struct HttpClientPool {
     clients: Arc<Mutex<Vec<Arc<HttpClient>>>>,
+    http2: bool,
+    connection_pool: ConnectionPoolConfig,
+    upstream_tls: Option<UpstreamTls>,
+    circuit_breakers: Option<Arc<CircuitBreakers>>,
 }
 
 impl HttpClientPool {
     pub fn new() -> Self {
         Self {
             clients: Arc::new(Mutex::new(Vec::new())),
+            http2: false,
+            connection_pool: ConnectionPoolConfig::default(),
+            upstream_tls: None,
+            circuit_breakers: None,
         }
     }
 
+    /// 新建的 client 使用集合中对应目标的熔断器（链式）
+    ///
+    /// 同一集合可由多个 pool 共享，使同一目标只有一个熔断器。
+    pub fn with_circuit_breakers(mut self, breakers: Arc<CircuitBreakers>) -> Self {
+        self.circuit_breakers = Some(breakers);
+        self
+    }
+
+    /// 新建的 client 连接 `https://` 目标时使用的 TLS 参数（链式）
+    pub fn with_upstream_tls(mut self, tls: UpstreamTls) -> Self {
+        self.upstream_tls = Some(tls);
+        self
+    }
+
+    /// 新建的 client 使用的上游连接池参数（链式）
+    pub fn with_connection_pool(mut self, config: ConnectionPoolConfig) -> Self {
+        self.connection_pool = config;
+        self
+    }
+
+    /// 新建的 client 以 prior-knowledge HTTP/2 连接上游（链式）
+    pub fn with_http2(mut self, enabled: bool) -> Self {
+        self.http2 = enabled;
+        self
+    }
+
     pub async fn get_or_create(
         &self,
         target: String,
@@ -278,7 +785,16 @@ impl HttpClientPool {
                 return client.clone();
             }
         }
-        let client = Arc::new(HttpClient::new(target.clone(), timeout, upstream_config));
+        let mut client = HttpClient::new(target.clone(), timeout, upstream_config)
+            .with_http2(self.http2)
+            .with_connection_pool(&self.connection_pool);
+        if let Some(tls) = &self.upstream_tls {
+            client = client.with_upstream_tls(tls.clone());
+        }
+        if let Some(breakers) = &self.circuit_breakers {
+            client = client.with_circuit_breaker(breakers.get(&target));
+        }
+        let client = Arc::new(client);
         clients.push(client.clone());
         info!("Created new HTTP client for {}", target);
         client
@@ -300,6 +816,7 @@ impl Default for HttpClientPool {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
 
     #[test]
     fn test_http_client_creation() {
@@ -307,6 +824,166 @@ mod tests {
         assert_eq!(client.target(), "tcp://127.0.0.1:8080");
     }
 
+    #[test]
+    fn test_http2_request_uses_absolute_uri() {
+        let client = HttpClient::new("tcp://127.0.0.1:8080".to_string(), None, None);
+        let request = client
+            .build_boxed_request(
+                hyper::Method::GET,
+                "/api/items?page=2".parse().unwrap(),
+                hyper::HeaderMap::new(),
+                Bytes::new(),
+            )
+            .unwrap();
+        let request = client.to_http2_request(request).unwrap();
+        assert_eq!(
+            request.uri().to_string(),
+            "http://127.0.0.1:8080/api/items?page=2"
+        );
+        assert_eq!(request.version(), hyper::Version::HTTP_2);
+        assert!(request.headers().get(hyper::header::HOST).is_none());
+    }
+
+    /// 启动 keep-alive 上游，返回地址与已接受的连接数
+    async fn start_keep_alive_upstream() -> (String, Arc<AtomicUsize>) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let accepted = Arc::new(AtomicUsize::new(0));
+        let counter = accepted.clone();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                counter.fetch_add(1, Ordering::SeqCst);
+                tokio::spawn(async move {
+                    let service = hyper::service::service_fn(|_req| async {
+                        Ok::<_, std::convert::Infallible>(Response::new(Full::new(Bytes::from(
+                            "ok",
+                        ))))
+                    });
+                    let _ = hyper::server::conn::http1::Builder::new()
+                        .serve_connection(TokioIo::new(stream), service)
+                        .await;
+                });
+            }
+        });
+        (format!("tcp://{addr}"), accepted)
+    }
+
+    async fn get(client: &HttpClient) -> String {
+        let request = client
+            .build_boxed_request(
+                hyper::Method::GET,
+                "/".parse().unwrap(),
+                hyper::HeaderMap::new(),
+                Bytes::new(),
+            )
+            .unwrap();
+        let response = client.send_boxed(request).await.unwrap();
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        // 等待后台任务归还连接
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        String::from_utf8_lossy(&body).to_string()
+    }
+
+    #[tokio::test]
+    async fn test_keep_alive_connection_is_reused() {
+        let (target, accepted) = start_keep_alive_upstream().await;
+        let client = HttpClient::new(target, Some(Duration::from_secs(5)), None);
+        for _ in 0..3 {
+            assert_eq!(get(&client).await, "ok");
+        }
+        assert_eq!(accepted.load(Ordering::SeqCst), 1);
+        assert_eq!(client.idle_connections(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_idle_timeout_discards_connection() {
+        let (target, accepted) = start_keep_alive_upstream().await;
+        let client =
+            HttpClient::new(target, None, None).with_connection_pool(&ConnectionPoolConfig {
+                idle_timeout: Some(Duration::from_millis(50)),
+                ..Default::default()
+            });
+        get(&client).await;
+        tokio::time::sleep(Duration::from_millis(80)).await;
+        get(&client).await;
+        assert_eq!(accepted.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_max_lifetime_discards_connection() {
+        let (target, accepted) = start_keep_alive_upstream().await;
+        let client =
+            HttpClient::new(target, None, None).with_connection_pool(&ConnectionPoolConfig {
+                max_lifetime: Some(Duration::from_millis(10)),
+                ..Default::default()
+            });
+        get(&client).await;
+        get(&client).await;
+        assert_eq!(accepted.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_max_idle_zero_disables_pooling() {
+        let (target, accepted) = start_keep_alive_upstream().await;
+        let client =
+            HttpClient::new(target, None, None).with_connection_pool(&ConnectionPoolConfig {
+                max_idle: Some(0),
+                ..Default::default()
+            });
+        get(&client).await;
+        get(&client).await;
+        assert_eq!(accepted.load(Ordering::SeqCst), 2);
+        assert_eq!(client.idle_connections(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_closed_connection_is_not_reused() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let target = format!("tcp://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(async move {
+                    let service = hyper::service::service_fn(|_req| async {
+                        Ok::<_, std::convert::Infallible>(Response::new(Full::new(Bytes::from(
+                            "ok",
+                        ))))
+                    });
+                    let _ = hyper::server::conn::http1::Builder::new()
+                        .keep_alive(false)
+                        .serve_connection(TokioIo::new(stream), service)
+                        .await;
+                });
+            }
+        });
+
+        let client = HttpClient::new(target, None, None);
+        assert_eq!(get(&client).await, "ok");
+        assert_eq!(get(&client).await, "ok");
+        assert_eq!(client.idle_connections(), 0);
+    }
+
+    #[test]
+    fn test_hop_by_hop_headers_not_forwarded() {
+        let client = HttpClient::new("tcp://127.0.0.1:8080".to_string(), None, None);
+        let mut headers = hyper::HeaderMap::new();
+        headers.insert("connection", "close, x-trace".parse().unwrap());
+        headers.insert("keep-alive", "timeout=5".parse().unwrap());
+        headers.insert("x-trace", "1".parse().unwrap());
+        headers.insert("x-request-id", "abc".parse().unwrap());
+        let request = client
+            .build_boxed_request(
+                hyper::Method::GET,
+                "/".parse().unwrap(),
+                headers,
+                Bytes::new(),
+            )
+            .unwrap();
+        assert!(request.headers().get("connection").is_none());
+        assert!(request.headers().get("keep-alive").is_none());
+        assert!(request.headers().get("x-trace").is_none());
+        assert_eq!(request.headers()["x-request-id"], "abc");
+    }
+
     #[test]
     fn test_extract_host_tcp() {
         assert_eq!(
@@ -323,6 +1000,39 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_https_target_parsing() {
+        assert_eq!(
+            parse_tcp_target("https://api.internal:8443"),
+            Some(("api.internal".to_string(), 8443))
+        );
+        assert_eq!(
+            parse_tcp_target("https://api.internal/"),
+            Some(("api.internal".to_string(), 443))
+        );
+        assert_eq!(
+            parse_tcp_target("https://[::1]:8443"),
+            Some(("::1".to_string(), 8443))
+        );
+        assert_eq!(
+            extract_host_from_target("https://api.internal:8443/"),
+            Some("api.internal:8443".to_string())
+        );
+    }
+
+    #[test]
+    fn test_upstream_tls_requires_cert_and_key() {
+        let config = UpstreamTlsConfig {
+            cert_path: Some("client.crt".to_string()),
+            ..Default::default()
+        };
+        assert!(matches!(
+            UpstreamTls::from_config(Some(&config)),
+            Err(MystiProxyError::Config(_))
+        ));
+        assert!(UpstreamTls::from_config(None).is_ok());
+    }
+
     #[test]
     fn test_extract_host_empty() {
         assert_eq!(extract_host_from_target(""), None);
@@ -0,0 +1,566 @@
+//! 响应压缩
+//!
+//! 按 `Accept-Encoding` 协商 gzip、Brotli 或 Zstd，对代理与静态文件响应流式压缩；
+//! 每个数据帧压缩后立即 flush，流式响应不会被攒批。另提供按 `Content-Encoding`
+//! 解压完整 body 的工具，供需要读取 JSON body 的变换使用。
+
+use std::io::{Read, Write};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use bytes::Bytes;
+use http_body_util::BodyExt;
+use hyper::body::{Body, Frame};
+use hyper::header::{self, HeaderMap, HeaderValue};
+use hyper::{Method, Response, StatusCode};
+
+use crate::config::{CompressionAlgorithm, CompressionConfig};
+use crate::error::{MystiProxyError, Result};
+use crate::http::handler::BoxBody;
+
+/// 默认最小压缩字节数
+const DEFAULT_MIN_SIZE: usize = 1024;
+/// 默认服务端编码偏好
+const DEFAULT_ALGORITHMS: [CompressionAlgorithm; 3] = [
+    CompressionAlgorithm::Br,
+    CompressionAlgorithm::Zstd,
+    CompressionAlgorithm::Gzip,
+];
+/// 默认允许压缩的 Content-Type
+const DEFAULT_CONTENT_TYPES: [&str; 5] = [
+    "text/*",
+    "application/json",
+    "application/javascript",
+    "application/xml",
+    "image/svg+xml",
+];
+/// 实时压缩的 Brotli 质量（0-11），兼顾速度与压缩率
+const BROTLI_QUALITY: u32 = 5;
+/// Brotli 窗口大小（log2）
+const BROTLI_LGWIN: u32 = 22;
+/// Zstd 压缩级别
+const ZSTD_LEVEL: i32 = 3;
+
+impl CompressionAlgorithm {
+    /// `Content-Encoding` 中的编码名
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Gzip => "gzip",
+            Self::Br => "br",
+            Self::Zstd => "zstd",
+        }
+    }
+
+    /// 由 `Content-Encoding`/`Accept-Encoding` 中的编码名解析
+    pub fn from_token(token: &str) -> Option<Self> {
+        match token.trim().to_ascii_lowercase().as_str() {
+            "gzip" | "x-gzip" => Some(Self::Gzip),
+            "br" => Some(Self::Br),
+            "zstd" => Some(Self::Zstd),
+            _ => None,
+        }
+    }
+}
+
+/// 生效的压缩策略（引擎级与 location 级合并后）
+#[derive(Debug, Clone)]
+pub struct CompressionPolicy {
+    enabled: bool,
+    algorithms: Vec<CompressionAlgorithm>,
+    min_size: usize,
+    content_types: Vec<String>,
+    decompress_for_transform: bool,
+}
+
+impl CompressionPolicy {
+    /// 合并引擎级与 location 级配置；两者都未配置时返回 `None`
+    pub fn resolve(
+        engine: Option<&CompressionConfig>,
+        location: Option<&CompressionConfig>,
+    ) -> Option<Self> {
+        if engine.is_none() && location.is_none() {
+            return None;
+        }
+        Some(Self {
+            enabled: location
+                .and_then(|c| c.enabled)
+                .or(engine.and_then(|c| c.enabled))
+                .unwrap_or(true),
+            algorithms: location
+                .and_then(|c| c.algorithms.clone())
+                .or(engine.and_then(|c| c.algorithms.clone()))
+                .unwrap_or_else(|| DEFAULT_ALGORITHMS.to_vec()),
+            min_size: location
+                .and_then(|c| c.min_size)
+                .or(engine.and_then(|c| c.min_size))
+                .unwrap_or(DEFAULT_MIN_SIZE),
+            content_types: location
+                .and_then(|c| c.content_types.as_ref())
+                .or(engine.and_then(|c| c.content_types.as_ref()))
+                .map(|types| {
+                    types
+                        .iter()
+                        .map(|t| t.trim().to_ascii_lowercase())
+                        .collect()
+                })
+                .unwrap_or_else(|| {
+                    DEFAULT_CONTENT_TYPES
+                        .iter()
+                        .map(|t| t.to_string())
+                        .collect()
+                }),
+            decompress_for_transform: location
+                .and_then(|c| c.decompress_for_transform)
+                .or(engine.and_then(|c| c.decompress_for_transform))
+                .unwrap_or(false),
+        })
+    }
+
+    /// JSON body 变换前是否解压带 `Content-Encoding` 的 body
+    pub fn decompress_for_transform(&self) -> bool {
+        self.decompress_for_transform
+    }
+
+    /// 按 `Accept-Encoding` 在配置的编码中选择
+    pub fn negotiate(&self, accept_encoding: Option<&HeaderValue>) -> Option<CompressionAlgorithm> {
+        negotiate_encoding(accept_encoding, &self.algorithms)
+    }
+
+    /// Content-Type 是否在允许列表中
+    fn allows_content_type(&self, headers: &HeaderMap) -> bool {
+        let Some(content_type) = headers
+            .get(header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+        else {
+            return false;
+        };
+        let media_type = content_type
+            .split(';')
+            .next()
+            .unwrap_or("")
+            .trim()
+            .to_ascii_lowercase();
+        self.content_types
+            .iter()
+            .any(|allowed| match allowed.strip_suffix('*') {
+                Some(prefix) => media_type.starts_with(prefix),
+                None => media_type == *allowed,
+            })
+    }
+
+    /// 响应是否适合压缩（不考虑客户端是否接受）
+    fn is_compressible(&self, method: &Method, status: StatusCode, headers: &HeaderMap) -> bool {
+        if !self.enabled
+            || method == Method::HEAD
+            || status.is_informational()
+            || status == StatusCode::NO_CONTENT
+            || status == StatusCode::PARTIAL_CONTENT
+            || status == StatusCode::NOT_MODIFIED
+        {
+            return false;
+        }
+        let encoded = headers
+            .get(header::CONTENT_ENCODING)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|v| !v.trim().eq_ignore_ascii_case("identity"));
+        let no_transform = headers
+            .get_all(header::CACHE_CONTROL)
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .flat_map(|v| v.split(','))
+            .any(|d| d.trim().eq_ignore_ascii_case("no-transform"));
+        let too_small = headers
+            .get(header::CONTENT_LENGTH)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse::<usize>().ok())
+            .is_some_and(|len| len < self.min_size);
+        !encoded
+            && !no_transform
+            && !too_small
+            && !headers.contains_key(header::CONTENT_RANGE)
+            && self.allows_content_type(headers)
+    }
+
+    /// 按协商结果压缩响应；不适合压缩或客户端不接受时原样返回
+    pub fn apply(
+        &self,
+        method: &Method,
+        accept_encoding: Option<&HeaderValue>,
+        response: Response<BoxBody>,
+    ) -> Response<BoxBody> {
+        if !self.is_compressible(method, response.status(), response.headers()) {
+            return response;
+        }
+
+        let (mut parts, body) = response.into_parts();
+        // 可压缩的响应随 Accept-Encoding 变化，无论本次是否压缩都需声明
+        append_vary_accept_encoding(&mut parts.headers);
+        let Some(algorithm) = self.negotiate(accept_encoding) else {
+            return Response::from_parts(parts, body);
+        };
+        let encoder = match Encoder::new(algorithm) {
+            Ok(encoder) => encoder,
+            Err(e) => {
+                tracing::warn!("Failed to create {} encoder: {}", algorithm.as_str(), e);
+                return Response::from_parts(parts, body);
+            }
+        };
+
+        parts.headers.insert(
+            header::CONTENT_ENCODING,
+            HeaderValue::from_static(algorithm.as_str()),
+        );
+        parts.headers.remove(header::CONTENT_LENGTH);
+        parts.headers.remove(header::ACCEPT_RANGES);
+        // 编码后的表示与原表示字节不同，强 ETag 降为弱 ETag
+        if let Some(etag) = parts
+            .headers
+            .get(header::ETAG)
+            .and_then(|v| v.to_str().ok())
+        {
+            if !etag.starts_with("W/") {
+                if let Ok(weak) = HeaderValue::from_str(&format!("W/{etag}")) {
+                    parts.headers.insert(header::ETAG, weak);
+                }
+            }
+        }
+
+        let body = CompressedBody {
+            inner: body,
+            encoder: Some(encoder),
+            trailers: None,
+        };
+        Response::from_parts(parts, body.boxed())
+    }
+}
+
+/// 按 `Accept-Encoding` 从候选编码中选择：取 q 值最高者，相同时按候选顺序
+///
+/// 未携带 `Accept-Encoding` 或没有可接受的编码时返回 `None`（即 identity）。
+pub fn negotiate_encoding(
+    accept_encoding: Option<&HeaderValue>,
+    candidates: &[CompressionAlgorithm],
+) -> Option<CompressionAlgorithm> {
+    let accept = accept_encoding.and_then(|v| v.to_str().ok())?;
+    let mut wildcard = None;
+    let mut explicit = Vec::new();
+    for item in accept.split(',') {
+        let mut params = item.split(';');
+        let token = params.next().unwrap_or("").trim();
+        let q = params
+            .filter_map(|p| p.trim().strip_prefix("q="))
+            .find_map(|q| q.trim().parse::<f32>().ok())
+            .unwrap_or(1.0);
+        if token == "*" {
+            wildcard = Some(q);
+        } else if let Some(algorithm) = CompressionAlgorithm::from_token(token) {
+            explicit.push((algorithm, q));
+        }
+    }
+
+    let mut best: Option<(CompressionAlgorithm, f32)> = None;
+    for algorithm in candidates {
+        let q = explicit
+            .iter()
+            .find(|(a, _)| a == algorithm)
+            .map(|(_, q)| *q)
+            .or(wildcard)
+            .unwrap_or(0.0);
+        if q > 0.0 && best.is_none_or(|(_, best_q)| q > best_q) {
+            best = Some((*algorithm, q));
+        }
+    }
+    best.map(|(algorithm, _)| algorithm)
+}
+
+/// 追加 `Vary: Accept-Encoding`（已包含时跳过）
+pub(crate) fn append_vary_accept_encoding(headers: &mut HeaderMap) {
+    let present = headers
+        .get_all(header::VARY)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .any(|v| {
+            let v = v.trim();
+            v == "*" || v.eq_ignore_ascii_case("accept-encoding")
+        });
+    if !present {
+        headers.append(header::VARY, HeaderValue::from_static("accept-encoding"));
+    }
+}
+
+/// 按 `Content-Encoding` 解压完整 body，解压后超过 `limit` 字节时返回 `PayloadTooLarge`
+///
+/// 支持 gzip、br、zstd 与 identity；叠加多个编码时不支持。
+pub fn decode_body(content_encoding: &str, body: &[u8], limit: usize) -> Result<Bytes> {
+    let content_encoding = content_encoding.trim();
+    if content_encoding.is_empty() || content_encoding.eq_ignore_ascii_case("identity") {
+        return Ok(Bytes::copy_from_slice(body));
+    }
+    let algorithm = CompressionAlgorithm::from_token(content_encoding).ok_or_else(|| {
+        MystiProxyError::Proxy(format!("Unsupported content-encoding: {content_encoding}"))
+    })?;
+    let reader: Box<dyn Read + '_> = match algorithm {
+        CompressionAlgorithm::Gzip => Box::new(flate2::read::MultiGzDecoder::new(body)),
+        CompressionAlgorithm::Br => Box::new(brotli::Decompressor::new(body, 4096)),
+        CompressionAlgorithm::Zstd => Box::new(zstd::stream::read::Decoder::new(body)?),
+    };
+    let mut decoded = Vec::new();
+    reader.take(limit as u64 + 1).read_to_end(&mut decoded)?;
+    if decoded.len() > limit {
+        return Err(MystiProxyError::PayloadTooLarge(limit));
+    }
+    Ok(Bytes::from(decoded))
+}
+
+/// 写入内存缓冲的流式编码器
+enum Encoder {
+    Gzip(flate2::write::GzEncoder<Vec<u8>>),
+    Br(Box<brotli::CompressorWriter<Vec<u8>>>),
+    Zstd(zstd::stream::write::Encoder<'static, Vec<u8>>),
+}
+
+impl Encoder {
+    fn new(algorithm: CompressionAlgorithm) -> std::io::Result<Self> {
+        Ok(match algorithm {
+            CompressionAlgorithm::Gzip => Self::Gzip(flate2::write::GzEncoder::new(
+                Vec::new(),
+                flate2::Compression::default(),
+            )),
+            CompressionAlgorithm::Br => Self::Br(Box::new(brotli::CompressorWriter::new(
+                Vec::new(),
+                4096,
+                BROTLI_QUALITY,
+                BROTLI_LGWIN,
+            ))),
+            CompressionAlgorithm::Zstd => {
+                Self::Zstd(zstd::stream::write::Encoder::new(Vec::new(), ZSTD_LEVEL)?)
+            }
+        })
+    }
+
+    /// 压缩一段数据并 flush，返回已产生的输出
+    fn encode(&mut self, data: &[u8]) -> std::io::Result<Bytes> {
+        let buffer = match self {
+            Self::Gzip(e) => {
+                e.write_all(data)?;
+                e.flush()?;
+                e.get_mut()
+            }
+            Self::Br(e) => {
+                e.write_all(data)?;
+                e.flush()?;
+                e.get_mut()
+            }
+            Self::Zstd(e) => {
+                e.write_all(data)?;
+                e.flush()?;
+                e.get_mut()
+            }
+        };
+        Ok(Bytes::from(std::mem::take(buffer)))
+    }
+
+    /// 结束压缩流，返回剩余输出
+    fn finish(self) -> std::io::Result<Bytes> {
+        let buffer = match self {
+            Self::Gzip(e) => e.finish()?,
+            Self::Br(e) => e.into_inner(),
+            Self::Zstd(e) => e.finish()?,
+        };
+        Ok(Bytes::from(buffer))
+    }
+}
+
+/// 流式压缩的响应体，trailers 在压缩流结束后原样转发
+struct CompressedBody {
+    inner: BoxBody,
+    encoder: Option<Encoder>,
+    trailers: Option<HeaderMap>,
+}
+
+impl Body for CompressedBody {
+    type Data = Bytes;
+    type Error = MystiProxyError;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<std::result::Result<Frame<Self::Data>, Self::Error>>> {
+        let this = &mut *self;
+        loop {
+            let Some(encoder) = this.encoder.as_mut() else {
+                return Poll::Ready(this.trailers.take().map(|t| Ok(Frame::trailers(t))));
+            };
+            match Pin::new(&mut this.inner).poll_frame(cx) {
+                Poll::Ready(Some(Ok(frame))) => match frame.into_data() {
+                    Ok(data) => {
+                        let encoded = encoder.encode(&data)?;
+                        if !encoded.is_empty() {
+                            return Poll::Ready(Some(Ok(Frame::data(encoded))));
+                        }
+                    }
+                    Err(frame) => {
+                        if let Ok(trailers) = frame.into_trailers() {
+                            this.trailers = Some(trailers);
+                        }
+                        if let Some(frame) = this.finish()? {
+                            return Poll::Ready(Some(Ok(frame)));
+                        }
+                    }
+                },
+                Poll::Ready(Some(Err(e))) => {
+                    this.encoder = None;
+                    return Poll::Ready(Some(Err(e)));
+                }
+                Poll::Ready(None) => {
+                    if let Some(frame) = this.finish()? {
+                        return Poll::Ready(Some(Ok(frame)));
+                    }
+                }
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.encoder.is_none() && self.trailers.is_none()
+    }
+}
+
+impl CompressedBody {
+    /// 结束压缩流；有剩余输出时作为最后一个数据帧返回
+    fn finish(&mut self) -> Result<Option<Frame<Bytes>>> {
+        let Some(encoder) = self.encoder.take() else {
+            return Ok(None);
+        };
+        let tail = encoder.finish()?;
+        Ok((!tail.is_empty()).then(|| Frame::data(tail)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http_body_util::Full;
+
+    fn policy(config: CompressionConfig) -> CompressionPolicy {
+        CompressionPolicy::resolve(Some(&config), None).unwrap()
+    }
+
+    fn response(content_type: &str, body: &'static [u8]) -> Response<BoxBody> {
+        Response::builder()
+            .header(header::CONTENT_TYPE, content_type)
+            .header(header::CONTENT_LENGTH, body.len())
+            .header(header::ETAG, "\"v1\"")
+            .body(
+                Full::new(Bytes::from_static(body))
+                    .map_err(|never| match never {})
+                    .boxed(),
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn test_negotiate_prefers_quality_then_server_order() {
+        let policy = policy(CompressionConfig::default());
+        let negotiate = |v: &'static str| policy.negotiate(Some(&HeaderValue::from_static(v)));
+
+        assert_eq!(negotiate("gzip, br"), Some(CompressionAlgorithm::Br));
+        assert_eq!(
+            negotiate("gzip;q=1, br;q=0.5"),
+            Some(CompressionAlgorithm::Gzip)
+        );
+        assert_eq!(negotiate("*"), Some(CompressionAlgorithm::Br));
+        assert_eq!(negotiate("*, br;q=0"), Some(CompressionAlgorithm::Zstd));
+        assert_eq!(negotiate("identity"), None);
+        assert_eq!(policy.negotiate(None), None);
+    }
+
+    #[test]
+    fn test_resolve_location_overrides_engine() {
+        let engine = CompressionConfig {
+            min_size: Some(10),
+            algorithms: Some(vec![CompressionAlgorithm::Gzip]),
+            ..Default::default()
+        };
+        let location = CompressionConfig {
+            min_size: Some(2048),
+            ..Default::default()
+        };
+        let policy = CompressionPolicy::resolve(Some(&engine), Some(&location)).unwrap();
+        assert_eq!(policy.min_size, 2048);
+        assert_eq!(policy.algorithms, vec![CompressionAlgorithm::Gzip]);
+        assert!(CompressionPolicy::resolve(None, None).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_apply_gzip_roundtrip() {
+        let policy = policy(CompressionConfig {
+            min_size: Some(0),
+            ..Default::default()
+        });
+        let accept = HeaderValue::from_static("gzip");
+        let compressed = policy.apply(
+            &Method::GET,
+            Some(&accept),
+            response("application/json; charset=utf-8", b"{\"hello\":\"world\"}"),
+        );
+
+        let headers = compressed.headers();
+        assert_eq!(headers[header::CONTENT_ENCODING], "gzip");
+        assert_eq!(headers[header::VARY], "accept-encoding");
+        assert_eq!(headers[header::ETAG], "W/\"v1\"");
+        assert!(!headers.contains_key(header::CONTENT_LENGTH));
+
+        let body = compressed.into_body().collect().await.unwrap().to_bytes();
+        let decoded = decode_body("gzip", &body, 1024).unwrap();
+        assert_eq!(&decoded[..], b"{\"hello\":\"world\"}");
+    }
+
+    #[tokio::test]
+    async fn test_apply_brotli_and_zstd_roundtrip() {
+        let policy = policy(CompressionConfig {
+            min_size: Some(0),
+            ..Default::default()
+        });
+        for encoding in ["br", "zstd"] {
+            let accept = HeaderValue::from_static(encoding);
+            let compressed = policy.apply(
+                &Method::GET,
+                Some(&accept),
+                response("text/plain", b"hello"),
+            );
+            assert_eq!(compressed.headers()[header::CONTENT_ENCODING], encoding);
+            let body = compressed.into_body().collect().await.unwrap().to_bytes();
+            assert_eq!(&decode_body(encoding, &body, 1024).unwrap()[..], b"hello");
+        }
+    }
+
+    #[test]
+    fn test_apply_skips_small_and_unlisted_responses() {
+        let policy = policy(CompressionConfig::default());
+        let accept = HeaderValue::from_static("gzip");
+
+        let small = policy.apply(&Method::GET, Some(&accept), response("text/plain", b"tiny"));
+        assert!(!small.headers().contains_key(header::CONTENT_ENCODING));
+
+        let image = policy.apply(&Method::GET, Some(&accept), response("image/png", b"png"));
+        assert!(!image.headers().contains_key(header::CONTENT_ENCODING));
+        assert!(!image.headers().contains_key(header::VARY));
+    }
+
+    #[test]
+    fn test_decode_body_limit() {
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(&[b'a'; 100]).unwrap();
+        let gz = encoder.finish().unwrap();
+
+        assert_eq!(decode_body("gzip", &gz, 100).unwrap().len(), 100);
+        assert!(matches!(
+            decode_body("gzip", &gz, 99),
+            Err(MystiProxyError::PayloadTooLarge(99))
+        ));
+        assert!(decode_body("compress", &gz, 100).is_err());
+    }
+}
@@ -0,0 +1,303 @@
+//! gRPC 模块
+//!
+//! 识别 gRPC 请求（`POST /package.Service/Method`，`content-type: application/grpc`），
+//! 按 FileDescriptorSet 将 JSON 编码为 protobuf，并生成带 `grpc-status` /
+//! `grpc-message` trailers 的一元 mock 响应。代理转发由 handler 经 HTTP/2 完成，
+//! trailers 随响应体帧透传。
+
+use std::path::Path;
+
+use bytes::{BufMut, Bytes, BytesMut};
+use http_body_util::{BodyExt, StreamBody};
+use hyper::body::Frame;
+use hyper::header::{HeaderMap, HeaderValue, CONTENT_TYPE};
+use hyper::{Request, Response, StatusCode};
+use prost_reflect::prost::Message;
+use prost_reflect::{DescriptorPool, DynamicMessage};
+
+use crate::config::GrpcMockConfig;
+use crate::error::{MystiProxyError, Result};
+use crate::http::handler::BoxBody;
+
+/// gRPC 状态码：INTERNAL
+const GRPC_STATUS_INTERNAL: u32 = 13;
+
+/// 检查是否为 gRPC 请求
+pub fn is_grpc_request<B>(req: &Request<B>) -> bool {
+    req.headers()
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|ct| ct.starts_with("application/grpc"))
+}
+
+/// 解析 gRPC 方法路径 `/package.Service/Method`
+///
+/// # 返回
+/// `(服务全名, 方法名)`，格式不符时返回 None
+pub fn parse_method_path(path: &str) -> Option<(&str, &str)> {
+    let (service, method) = path.strip_prefix('/')?.split_once('/')?;
+    if service.is_empty() || method.is_empty() || method.contains('/') {
+        return None;
+    }
+    Some((service, method))
+}
+
+/// 按长度前缀格式封装一条 gRPC 消息（未压缩）
+pub fn encode_message_frame(message: &[u8]) -> Bytes {
+    let mut buf = BytesMut::with_capacity(5 + message.len());
+    buf.put_u8(0);
+    buf.put_u32(message.len() as u32);
+    buf.put_slice(message);
+    buf.freeze()
+}
+
+/// 从 FileDescriptorSet 加载的 protobuf 描述
+#[derive(Debug, Clone)]
+pub struct GrpcDescriptors {
+    pool: DescriptorPool,
+}
+
+impl GrpcDescriptors {
+    /// 从 descriptor set 文件加载
+    pub fn load(path: &Path) -> Result<Self> {
+        let bytes = std::fs::read(path).map_err(|e| {
+            MystiProxyError::Config(format!(
+                "failed to read descriptor set {}: {e}",
+                path.display()
+            ))
+        })?;
+        Self::from_bytes(&bytes)
+    }
+
+    /// 从已编码的 FileDescriptorSet 字节加载
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let pool = DescriptorPool::decode(bytes)
+            .map_err(|e| MystiProxyError::Config(format!("invalid descriptor set: {e}")))?;
+        Ok(Self { pool })
+    }
+
+    /// 将 JSON 编码为指定方法的响应消息（protobuf）
+    ///
+    /// # 参数
+    /// - `path`: gRPC 方法路径 `/package.Service/Method`
+    /// - `json`: 响应消息的 JSON 表示（proto3 JSON 映射）
+    pub fn encode_response(&self, path: &str, json: &serde_json::Value) -> Result<Bytes> {
+        let (service_name, method_name) = parse_method_path(path)
+            .ok_or_else(|| MystiProxyError::Mock(format!("invalid gRPC method path: {path}")))?;
+        let service = self.pool.get_service_by_name(service_name).ok_or_else(|| {
+            MystiProxyError::Mock(format!("unknown gRPC service: {service_name}"))
+        })?;
+        let method = service
+            .methods()
+            .find(|m| m.name() == method_name)
+            .ok_or_else(|| {
+                MystiProxyError::Mock(format!("unknown gRPC method: {service_name}/{method_name}"))
+            })?;
+
+        let message = DynamicMessage::deserialize(method.output(), json)
+            .map_err(|e| MystiProxyError::Mock(format!("invalid gRPC mock message: {e}")))?;
+        Ok(Bytes::from(message.encode_to_vec()))
+    }
+}
+
+/// 一元 gRPC mock 响应
+#[derive(Debug, Clone)]
+pub struct GrpcMockResponse {
+    /// 已编码的响应消息（不含长度前缀）
+    pub message: Option<Bytes>,
+    /// grpc-status
+    pub status: u32,
+    /// grpc-message
+    pub status_message: Option<String>,
+}
+
+impl GrpcMockResponse {
+    /// 根据 mock 配置构建响应；消息编码失败时返回 INTERNAL 状态
+    pub fn build(descriptors: Option<&GrpcDescriptors>, path: &str, mock: &GrpcMockConfig) -> Self {
+        let encoded = match (&mock.body, descriptors) {
+            (None, _) => Ok(None),
+            (Some(json), Some(descriptors)) => descriptors.encode_response(path, json).map(Some),
+            (Some(_), None) => Err(MystiProxyError::Mock(
+                "gRPC mock body requires descriptor_set".to_string(),
+            )),
+        };
+
+        match encoded {
+            Ok(message) => Self {
+                message,
+                status: mock.status,
+                status_message: mock.message.clone(),
+            },
+            Err(e) => Self {
+                message: None,
+                status: GRPC_STATUS_INTERNAL,
+                status_message: Some(e.to_string()),
+            },
+        }
+    }
+
+    /// 转换为 HTTP 响应：HTTP 200，消息作为 data 帧，状态放在 trailers
+    pub fn into_response(self) -> Result<Response<BoxBody>> {
+        let mut trailers = HeaderMap::new();
+        trailers.insert("grpc-status", HeaderValue::from(self.status));
+        if let Some(message) = &self.status_message {
+            let encoded = percent_encode(message);
+            trailers.insert(
+                "grpc-message",
+                HeaderValue::from_str(&encoded)
+                    .map_err(|e| MystiProxyError::Mock(e.to_string()))?,
+            );
+        }
+
+        let mut frames = Vec::with_capacity(2);
+        if let Some(message) = &self.message {
+            frames.push(Frame::data(encode_message_frame(message)));
+        }
+        frames.push(Frame::trailers(trailers));
+
+        let body = StreamBody::new(futures::stream::iter(
+            frames.into_iter().map(Ok::<_, MystiProxyError>),
+        ))
+        .boxed();
+
+        Response::builder()
+            .status(StatusCode::OK)
+            .header(CONTENT_TYPE, "application/grpc")
+            .body(body)
+            .map_err(MystiProxyError::Http)
+    }
+}
+
+/// grpc-message 的百分号编码（保留可打印 ASCII，`%` 与其余字节编码）
+fn percent_encode(message: &str) -> String {
+    let mut out = String::with_capacity(message.len());
+    for byte in message.bytes() {
+        if (0x20..=0x7e).contains(&byte) && byte != b'%' {
+            out.push(byte as char);
+        } else {
+            out.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use prost_reflect::prost_types::{
+        field_descriptor_proto::{Label, Type},
+        DescriptorProto, FieldDescriptorProto, FileDescriptorProto, FileDescriptorSet,
+        MethodDescriptorProto, ServiceDescriptorProto,
+    };
+
+    /// `test.Greeter/SayHello(HelloRequest) -> HelloReply { string message = 1; int32 count = 2; }`
+    fn greeter_descriptor_set() -> Vec<u8> {
+        let field = |name: &str, number: i32, field_type: Type| FieldDescriptorProto {
+            name: Some(name.to_string()),
+            number: Some(number),
+            label: Some(Label::Optional as i32),
+            r#type: Some(field_type as i32),
+            json_name: Some(name.to_string()),
+            ..Default::default()
+        };
+        let file = FileDescriptorProto {
+            name: Some("greeter.proto".to_string()),
+            package: Some("test".to_string()),
+            syntax: Some("proto3".to_string()),
+            message_type: vec![
+                DescriptorProto {
+                    name: Some("HelloRequest".to_string()),
+                    field: vec![field("name", 1, Type::String)],
+                    ..Default::default()
+                },
+                DescriptorProto {
+                    name: Some("HelloReply".to_string()),
+                    field: vec![
+                        field("message", 1, Type::String),
+                        field("count", 2, Type::Int32),
+                    ],
+                    ..Default::default()
+                },
+            ],
+            service: vec![ServiceDescriptorProto {
+                name: Some("Greeter".to_string()),
+                method: vec![MethodDescriptorProto {
+                    name: Some("SayHello".to_string()),
+                    input_type: Some(".test.HelloRequest".to_string()),
+                    output_type: Some(".test.HelloReply".to_string()),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        FileDescriptorSet { file: vec![file] }.encode_to_vec()
+    }
+
+    #[test]
+    fn test_parse_method_path() {
+        assert_eq!(
+            parse_method_path("/test.Greeter/SayHello"),
+            Some(("test.Greeter", "SayHello"))
+        );
+        assert_eq!(parse_method_path("/test.Greeter"), None);
+        assert_eq!(parse_method_path("/a/b/c"), None);
+        assert_eq!(parse_method_path("test.Greeter/SayHello"), None);
+    }
+
+    #[test]
+    fn test_encode_response_from_json() {
+        let descriptors = GrpcDescriptors::from_bytes(&greeter_descriptor_set()).unwrap();
+        let json = serde_json::json!({"message": "hi", "count": 3});
+        let bytes = descriptors
+            .encode_response("/test.Greeter/SayHello", &json)
+            .unwrap();
+        // field 1 (string "hi"), field 2 (varint 3)
+        assert_eq!(&bytes[..], &[0x0a, 0x02, b'h', b'i', 0x10, 0x03]);
+
+        assert!(descriptors
+            .encode_response("/test.Greeter/Missing", &json)
+            .is_err());
+        assert!(descriptors
+            .encode_response("/test.Greeter/SayHello", &serde_json::json!({"nope": 1}))
+            .is_err());
+    }
+
+    #[test]
+    fn test_encode_message_frame() {
+        let frame = encode_message_frame(b"abc");
+        assert_eq!(&frame[..], &[0, 0, 0, 0, 3, b'a', b'b', b'c']);
+    }
+
+    #[test]
+    fn test_mock_without_descriptor_is_internal_error() {
+        let mock = GrpcMockConfig {
+            body: Some(serde_json::json!({"message": "hi"})),
+            status: 0,
+            message: None,
+        };
+        let response = GrpcMockResponse::build(None, "/test.Greeter/SayHello", &mock);
+        assert_eq!(response.status, GRPC_STATUS_INTERNAL);
+        assert!(response.message.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_mock_response_carries_trailers() {
+        let mock = GrpcMockConfig {
+            body: None,
+            status: 5,
+            message: Some("not found: 100%".to_string()),
+        };
+        let response = GrpcMockResponse::build(None, "/test.Greeter/SayHello", &mock)
+            .into_response()
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[CONTENT_TYPE], "application/grpc");
+
+        let collected = response.into_body().collect().await.unwrap();
+        let trailers = collected.trailers().cloned().unwrap();
+        assert_eq!(trailers["grpc-status"], "5");
+        assert_eq!(trailers["grpc-message"], "not found: 100%25");
+        assert!(collected.to_bytes().is_empty());
+    }
+}
@@ -2,9 +2,9 @@
 //!
 //! 提供请求解析、路由匹配和请求转发功能
 
-use std::convert::Infallible;
+use std::collections::{HashMap, HashSet};
 use std::future::Future;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::pin::Pin;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
@@ -16,18 +16,36 @@ use hyper::service::Service;
 use hyper::{Request, Response, StatusCode};
 use tracing::{debug, info, warn};
 
-use crate::config::{EngineConfig, HeaderAction, HeaderActionType, LocationConfig, ProviderType};
+use crate::balancer::{Endpoint, EndpointGuard, LoadBalancer};
+use crate::circuit_breaker::CircuitBreakers;
+use crate::config::{
+    EngineConfig, HeaderAction, HeaderActionType, LocationConfig, ProviderType, ResponseConfig,
+    ResponseStep,
+};
 use crate::error::{MystiProxyError, Result};
+use crate::gateway::Gateway;
 use crate::http::auth::{AuthConfig as AuthModuleConfig, Authenticator};
-use crate::http::client::HttpClientPool;
+use crate::http::cache::{
+    freshness_lifetime, CacheKey, CacheLookup, CachedResponse, CachingBody, PendingStore,
+    RequestDirective, ResponseCache,
+};
+use crate::http::client::{HttpClient, HttpClientPool, UpstreamTls};
+use crate::http::compression::{decode_body, CompressionPolicy};
+use crate::http::grpc::{is_grpc_request, GrpcDescriptors, GrpcMockResponse};
+use crate::http::redirect::{build_redirect, RedirectRequest};
+use crate::http::retry::RetryPolicy;
+use crate::http::server::ClientIp;
 use crate::http::static_files::StaticFileConfig;
+use crate::rate_limit::{RateLimitDecision, RateLimiter};
 
 use crate::metrics::MetricsManager;
-use crate::mock::MockResponse;
-use crate::router::{Route, Router};
+use crate::mock::sequence::{select_step, SequenceCounters};
+use crate::mock::template;
+use crate::mock::{MockBodyFile, MockResponse, TemplateContext};
+use crate::router::{RequestInfo, Route, Router};
 
 /// BoxBody 类型别名
-pub type BoxBody = http_body_util::combinators::BoxBody<Bytes, Infallible>;
+pub type BoxBody = http_body_util::combinators::BoxBody<Bytes, MystiProxyError>;
 
 /// 路由匹配结果
 #[derive(Debug)]
@@ -35,11 +53,18 @@ pub type BoxBody = http_body_util::combinators::BoxBody<Bytes, Infallible>;
 pub enum RouteMatch {
     /// 代理转发
     Proxy {
-        target: String,
+        /// 专用上游地址（location 或网关指定）；None 时使用引擎级 target 与负载均衡
+        target: Option<String>,
         location: Option<LocationConfig>,
+        /// 改写后的上游路径（网关映射），查询串沿用原请求
+        path: Option<String>,
+        /// location 路由捕获的路径参数（含剩余路径 `remaining`），用于请求改写与响应模版
+        params: HashMap<String, String>,
     },
     /// Mock 响应
     Mock(MockResponse),
+    /// gRPC 一元 mock 响应
+    GrpcMock(GrpcMockResponse),
     /// 静态文件服务
     Static {
         config: StaticFileConfig,
@@ -54,19 +79,107 @@ pub enum RouteMatch {
 pub struct HttpRequestHandler {
     config: Arc<EngineConfig>,
     client_pool: Arc<HttpClientPool>,
+    /// gRPC 代理使用的 HTTP/2 客户端池
+    grpc_client_pool: Arc<HttpClientPool>,
+    /// gRPC descriptor set（按文件路径）
+    grpc_descriptors: Arc<HashMap<String, Arc<GrpcDescriptors>>>,
     router: Arc<Router>,
     authenticator: Option<Arc<Authenticator>>,
     metrics: Arc<MetricsManager>,
+    /// 多目标负载均衡器（仅 target 为多个地址时存在）
+    balancer: Option<Arc<LoadBalancer>>,
+    /// 引擎级请求限流
+    rate_limiter: Option<Arc<RateLimiter>>,
+    /// location 级请求限流（按 [`location_key`] 索引）
+    location_rate_limiters: Arc<HashMap<String, Arc<RateLimiter>>>,
+    /// 代理 GET 响应缓存
+    response_cache: Option<Arc<ResponseCache>>,
+    /// 引擎级响应压缩
+    compression: Option<Arc<CompressionPolicy>>,
+    /// 配置了 compression 的 location 与引擎级合并后的压缩策略（按 [`location_key`] 索引）
+    location_compression: Arc<HashMap<String, Arc<CompressionPolicy>>>,
+    /// 配置了 `request.uri.rewrite` 的 location 预编译的正则（按 [`location_key`] 索引）
+    location_rewrites: Arc<HashMap<String, regex::Regex>>,
+    /// 需要缓冲请求体的 mock location（按 [`location_key`] 索引，见 [`mock_needs_body`]）
+    body_mocks: Arc<HashSet<String>>,
+    /// 网关路由表（配置了 gateway 时存在）
+    gateway: Option<Arc<Gateway>>,
+    /// mock 响应序列的调用计数（按 [`location_key`] 索引）
+    response_sequences: Arc<SequenceCounters>,
+    /// 本地管理（SQLite）中的活动 mock，优先于 YAML locations 匹配
+    #[cfg(feature = "local-management")]
+    local_mocks: Option<Arc<crate::management::LocalMockMatcher>>,
 }
 
 impl HttpRequestHandler {
     /// 创建新的请求处理器
     pub fn new(config: Arc<EngineConfig>) -> Result<Self> {
-        let client_pool = Arc::new(HttpClientPool::new());
+        let upstream_http2 = config.http2.as_ref().is_some_and(|h| h.upstream);
+        let connection_pool = config.connection_pool.clone().unwrap_or_default();
+        let upstream_tls = UpstreamTls::from_config(config.upstream_tls.as_ref())?;
+        let mut client_pool = HttpClientPool::new()
+            .with_http2(upstream_http2)
+            .with_connection_pool(connection_pool.clone())
+            .with_upstream_tls(upstream_tls.clone());
+        let mut grpc_client_pool = HttpClientPool::new()
+            .with_http2(true)
+            .with_connection_pool(connection_pool)
+            .with_upstream_tls(upstream_tls);
+        // 两个 pool 共享熔断器，同一目标只有一个熔断状态
+        if let Some(breaker_config) = &config.circuit_breaker {
+            let breakers = Arc::new(CircuitBreakers::new(breaker_config.clone()));
+            client_pool = client_pool.with_circuit_breakers(breakers.clone());
+            grpc_client_pool = grpc_client_pool.with_circuit_breakers(breakers);
+        }
+        let client_pool = Arc::new(client_pool);
+        let grpc_client_pool = Arc::new(grpc_client_pool);
 
         let mut router = Router::new();
+        let mut grpc_descriptors = HashMap::new();
+        let mut location_rate_limiters = HashMap::new();
+        let mut location_compression = HashMap::new();
+        let mut location_rewrites = HashMap::new();
+        let mut body_mocks = HashSet::new();
         if let Some(locations) = &config.locations {
             for location in locations {
+                if mock_needs_body(location) {
+                    body_mocks.insert(location_key(location));
+                }
+                if let Some(rewrite) = location
+                    .request
+                    .as_ref()
+                    .and_then(|r| r.uri.as_ref())
+                    .and_then(|u| u.rewrite.as_ref())
+                {
+                    let regex = regex::Regex::new(&rewrite.pattern)
+                        .map_err(|e| MystiProxyError::InvalidRegex(e.to_string()))?;
+                    location_rewrites.insert(location_key(location), regex);
+                }
+                if let Some(rate_limit) = &location.rate_limit {
+                    location_rate_limiters.insert(
+                        location_key(location),
+                        Arc::new(RateLimiter::from_config(rate_limit)),
+                    );
+                }
+                if let Some(policy) = location
+                    .compression
+                    .as_ref()
+                    .and_then(|c| CompressionPolicy::resolve(config.compression.as_ref(), Some(c)))
+                {
+                    location_compression.insert(location_key(location), Arc::new(policy));
+                }
+
+                if let Some(path) = location
+                    .grpc
+                    .as_ref()
+                    .and_then(|g| g.descriptor_set.as_ref())
+                {
+                    if !grpc_descriptors.contains_key(path) {
+                        let descriptors = GrpcDescriptors::load(Path::new(path))?;
+                        grpc_descriptors.insert(path.clone(), Arc::new(descriptors));
+                    }
+                }
+
                 let route = Route::new(
                     location.location.clone(),
                     location.mode.clone(),
@@ -101,15 +214,75 @@ impl HttpRequestHandler {
         // 使用进程级共享 MetricsManager（与 main.rs 的导出服务同一实例）
         let metrics = crate::metrics::global_metrics();
 
+        let balancer = if config.target.is_multiple() {
+            Some(Arc::new(LoadBalancer::from_engine_config(&config)?))
+        } else {
+            None
+        };
+
+        let rate_limiter = config
+            .rate_limit
+            .as_ref()
+            .map(|c| Arc::new(RateLimiter::from_config(c)));
+        let response_cache = config
+            .cache
+            .as_ref()
+            .map(|c| Arc::new(ResponseCache::new(c)));
+        let compression =
+            CompressionPolicy::resolve(config.compression.as_ref(), None).map(Arc::new);
+        let gateway = config.gateway.as_ref().map(|g| Arc::new(Gateway::new(g)));
+
         Ok(Self {
             config,
             client_pool,
+            grpc_client_pool,
+            grpc_descriptors: Arc::new(grpc_descriptors),
             router: Arc::new(router),
             authenticator,
             metrics,
+            balancer,
+            rate_limiter,
+            location_rate_limiters: Arc::new(location_rate_limiters),
+            response_cache,
+            compression,
+            location_compression: Arc::new(location_compression),
+            location_rewrites: Arc::new(location_rewrites),
+            body_mocks: Arc::new(body_mocks),
+            gateway,
+            response_sequences: Arc::new(SequenceCounters::new()),
+            #[cfg(feature = "local-management")]
+            local_mocks: None,
         })
     }
 
+    /// 使用外部共享的响应序列计数（如本地管理 API 需要查看与重置）
+    pub fn with_response_sequences(mut self, counters: Arc<SequenceCounters>) -> Self {
+        self.response_sequences = counters;
+        self
+    }
+
+    /// mock 响应序列的调用计数
+    pub fn response_sequences(&self) -> Arc<SequenceCounters> {
+        self.response_sequences.clone()
+    }
+
+    /// 挂载本地管理的 mock 匹配器
+    #[cfg(feature = "local-management")]
+    pub fn with_local_mocks(mut self, matcher: Arc<crate::management::LocalMockMatcher>) -> Self {
+        self.local_mocks = Some(matcher);
+        self
+    }
+
+    /// 响应缓存（配置了 cache 时存在）
+    pub fn response_cache(&self) -> Option<Arc<ResponseCache>> {
+        self.response_cache.clone()
+    }
+
+    /// 多目标引擎的负载均衡器
+    pub fn balancer(&self) -> Option<Arc<LoadBalancer>> {
+        self.balancer.clone()
+    }
+
     fn empty_body() -> BoxBody {
         Empty::<Bytes>::new()
             .map_err(|never| match never {})
@@ -119,41 +292,233 @@ impl HttpRequestHandler {
     fn full_body(bytes: Bytes) -> BoxBody {
         Full::new(bytes).map_err(|never| match never {}).boxed()
     }
+
+    /// 生成 mock 响应；文件响应体相对 `mock_root` 打开并流式返回，文件不可读时返回 500
+    async fn mock_to_response(
+        mock: MockResponse,
+        mock_root: Option<&str>,
+    ) -> Result<Response<BoxBody>> {
+        let mut builder = Response::builder().status(
+            StatusCode::from_u16(mock.status)
+                .map_err(|e| MystiProxyError::Proxy(format!("Invalid status code: {e}")))?,
+        );
+
+        for (key, value) in &mock.headers {
+            builder = builder.header(key, value);
+        }
+
+        if let Some(file) = &mock.body_file {
+            let opened = match file.resolve(mock_root).await {
+                Ok(path) => crate::mock::file::open(&path).await,
+                Err(e) => Err(e),
+            };
+            let (body, len) = match opened {
+                Ok(opened) => opened,
+                Err(e) => {
+                    warn!("Failed to open mock body file {:?}: {}", file.path, e);
+                    return Response::builder()
+                        .status(StatusCode::INTERNAL_SERVER_ERROR)
+                        .body(Self::empty_body())
+                        .map_err(MystiProxyError::Http);
+                }
+            };
+            if !crate::mock::file::has_content_type(&mock) {
+                builder = builder.header(hyper::header::CONTENT_TYPE, file.content_type());
+            }
+            return builder
+                .header(hyper::header::CONTENT_LENGTH, len)
+                .body(body)
+                .map_err(MystiProxyError::Http);
+        }
+
+        let body = if mock.body.is_empty() {
+            Self::empty_body()
+        } else {
+            Self::full_body(Bytes::from(mock.body))
+        };
+
+        builder.body(body).map_err(MystiProxyError::Http)
+    }
+
+    /// 由缓存条目生成响应；`not_modified` 时返回无 body 的 304
+    fn cached_to_response(
+        entry: &CachedResponse,
+        not_modified: bool,
+        x_cache: &'static str,
+    ) -> Result<Response<BoxBody>> {
+        let mut builder = Response::builder();
+        if let Some(headers) = builder.headers_mut() {
+            headers.clone_from(&entry.headers);
+            headers.insert(
+                hyper::header::AGE,
+                entry.age(std::time::SystemTime::now()).as_secs().into(),
+            );
+            headers.insert(X_CACHE, hyper::header::HeaderValue::from_static(x_cache));
+            if not_modified {
+                headers.remove(hyper::header::CONTENT_LENGTH);
+            }
+        }
+        if not_modified {
+            builder
+                .status(StatusCode::NOT_MODIFIED)
+                .body(Self::empty_body())
+                .map_err(MystiProxyError::Http)
+        } else {
+            builder
+                .status(entry.status)
+                .body(Self::full_body(entry.body.clone()))
+                .map_err(MystiProxyError::Http)
+        }
+    }
+
+    /// 限流拒绝响应：429 + `Retry-After` 与 `RateLimit-*` 头
+    fn rate_limited_response(decision: &RateLimitDecision) -> Result<Response<BoxBody>> {
+        let ceil_secs = |d: Duration| d.as_secs() + u64::from(d.subsec_nanos() > 0);
+        Response::builder()
+            .status(StatusCode::TOO_MANY_REQUESTS)
+            .header(hyper::header::RETRY_AFTER, ceil_secs(decision.retry_after))
+            .header("RateLimit-Limit", decision.limit)
+            .header("RateLimit-Remaining", decision.remaining)
+            .header("RateLimit-Reset", ceil_secs(decision.reset))
+            .body(Self::empty_body())
+            .map_err(MystiProxyError::Http)
+    }
 }
 
-fn build_mock_response(location: &LocationConfig, uri: &str) -> MockResponse {
-    let mut mock = MockResponse::new();
+/// 标识响应是否来自缓存：HIT、MISS 或 REVALIDATED
+const X_CACHE: &str = "x-cache";
 
-    if let Some(response) = &location.response {
-        if let Some(status) = response.status {
-            mock = mock.status(status);
+/// location 级限流器的索引键（同一路径可按 hosts/methods 等条件区分多个 location）
+fn location_key(location: &LocationConfig) -> String {
+    fn sorted(
+        map: &Option<HashMap<String, String>>,
+    ) -> Option<std::collections::BTreeMap<&String, &String>> {
+        map.as_ref().map(|m| m.iter().collect())
+    }
+    format!(
+        "{:?} {} {:?} {:?} {:?} {:?}",
+        location.mode,
+        location.location,
+        location.hosts,
+        location.methods,
+        sorted(&location.match_headers),
+        sorted(&location.match_query)
+    )
+}
+
+fn build_mock_response(
+    location: &LocationConfig,
+    step: Option<&ResponseStep>,
+    context: &TemplateContext,
+) -> MockResponse {
+    match (&location.response, step) {
+        (Some(response), Some(step)) => {
+            mock_from_response_config(&apply_step(response, step), context)
         }
+        (Some(response), None) => mock_from_response_config(response, context),
+        (None, _) => MockResponse::new(),
+    }
+}
 
-        if let Some(headers) = &response.headers {
-            for (key, action) in headers {
-                if action.action == HeaderActionType::Overwrite {
-                    mock = mock.header(key.clone(), action.value.clone());
-                }
+/// 序列项覆盖外层 response 的状态码与响应体，响应头按名合并
+fn apply_step(response: &ResponseConfig, step: &ResponseStep) -> ResponseConfig {
+    let mut merged = response.clone();
+    if step.status.is_some() {
+        merged.status = step.status;
+    }
+    if let Some(headers) = &step.headers {
+        merged
+            .headers
+            .get_or_insert_with(HashMap::new)
+            .extend(headers.clone());
+    }
+    if step.body.is_some() {
+        merged.body = step.body.clone();
+    }
+    merged
+}
+
+/// mock location 是否需要读取请求体：配置了 body/json 条件，或模版/响应头引用请求体
+///
+/// 引用按解析后的模板判断（`{{body}}`、`{{#each body}}`、`{{#if body}}` 等），字面文本不计。
+fn mock_needs_body(location: &LocationConfig) -> bool {
+    let Some(response) = location
+        .response
+        .as_ref()
+        .filter(|_| location.provider == Some(ProviderType::Mock))
+    else {
+        return false;
+    };
+    let body_condition = response.conditions.iter().flatten().any(|c| {
+        c.condition_type.eq_ignore_ascii_case("body")
+            || c.condition_type.eq_ignore_ascii_case("json")
+    });
+    let step_bodies = response
+        .sequence
+        .iter()
+        .flat_map(|s| &s.responses)
+        .filter_map(|step| step.body.as_ref());
+    let body_template = response.body.iter().chain(step_bodies).any(|b| {
+        b.template
+            .as_deref()
+            .is_some_and(|t| template::references(t, "body"))
+                // 模版文件的内容在请求时才读取，按需缓冲请求体
+                || (b.body_type == Some(crate::config::BodyType::File) && b.templated == Some(true))
+    });
+    let step_headers = response
+        .sequence
+        .iter()
+        .flat_map(|s| &s.responses)
+        .filter_map(|step| step.headers.as_ref());
+    let header_template = response
+        .headers
+        .iter()
+        .chain(step_headers)
+        .flat_map(|h| h.values())
+        .any(|action| template::references(&action.value, "body"));
+    body_condition || body_template || header_template
+}
+
+fn mock_from_response_config(response: &ResponseConfig, context: &TemplateContext) -> MockResponse {
+    let mut mock = MockResponse::new();
+
+    if let Some(status) = response.status {
+        mock = mock.status(status);
+    }
+
+    if let Some(headers) = &response.headers {
+        for (key, action) in headers {
+            if action.action == HeaderActionType::Overwrite {
+                // 响应头值同样按模版渲染
+                mock = mock.header(key.clone(), context.render(&action.value));
             }
         }
+    }
 
-        if let Some(body) = &response.body {
-            match body.body_type.as_ref() {
-                Some(crate::config::BodyType::Static) => {
-                    // 静态内容：优先 content，向后兼容空体
-                    let content = body.content.clone().unwrap_or_default();
-                    mock = mock.body(content);
-                }
-                Some(crate::config::BodyType::Template) => {
-                    // 模版：基于请求 URI 渲染占位符（body 上下文由条件网关按需注入，见 handler）
-                    let tpl = body.template.clone().unwrap_or_default();
-                    mock = mock.body(crate::mock::render_template(&tpl, uri, None));
+    if let Some(body) = &response.body {
+        match body.body_type.as_ref() {
+            Some(crate::config::BodyType::Static) => {
+                // 静态内容：优先 content，向后兼容空体
+                let content = body.content.clone().unwrap_or_default();
+                mock = mock.body(content);
+            }
+            Some(crate::config::BodyType::File) => {
+                // 文件：响应时读取，修改文件无需重启
+                if let Some(file) = &body.file {
+                    mock = mock.body_file(
+                        MockBodyFile::new(file, body.templated.unwrap_or(false)).allow_absolute(),
+                    );
                 }
-                _ => {
-                    // 未指定类型但给了 content：同样作为静态体返回（与 config.example.yaml 对齐）
-                    if let Some(content) = &body.content {
-                        mock = mock.body(content.clone());
-                    }
+            }
+            Some(crate::config::BodyType::Template) => {
+                // 模版：基于请求方法、URI、路径参数、请求头与（已缓冲的）JSON 请求体渲染
+                let tpl = body.template.as_deref().unwrap_or_default();
+                mock = mock.body(context.render(tpl));
+            }
+            _ => {
+                // 未指定类型但给了 content：同样作为静态体返回（与 config.example.yaml 对齐）
+                if let Some(content) = &body.content {
+                    mock = mock.body(content.clone());
                 }
             }
         }
@@ -162,18 +527,43 @@ fn build_mock_response(location: &LocationConfig, uri: &str) -> MockResponse {
     mock
 }
 
+/// 代理路径上的请求体：未读取的 Incoming，或为匹配 mock 条件已缓冲的 body
+type RequestBody = http_body_util::Either<Incoming, Full<Bytes>>;
+
 /// 请求修改结果：未修改 body 或已转换 body
 pub enum ModifiedRequest {
     /// Body 未转换（仅 headers/URI/method 可能已修改）
     Incoming(Request<Incoming>),
-    /// Body 已转换（JSON 变换）
+    /// Body 已转换（JSON 变换）或已缓冲
     Bytes(Request<http_body_util::Full<bytes::Bytes>>),
 }
 
+impl ModifiedRequest {
+    /// 按 body 是否已缓冲选择对应变体
+    fn from_request(request: Request<RequestBody>) -> Self {
+        let (parts, body) = request.into_parts();
+        match body {
+            http_body_util::Either::Left(body) => {
+                ModifiedRequest::Incoming(Request::from_parts(parts, body))
+            }
+            http_body_util::Either::Right(body) => {
+                ModifiedRequest::Bytes(Request::from_parts(parts, body))
+            }
+        }
+    }
+}
+
+/// 按 location 的 `request` 配置改写请求
+///
+/// `uri.path` 与请求头值中的 `{name}` 由路由捕获的 `params` 展开，随后以预编译的 `rewrite`
+/// 正则应用 `uri.rewrite` 替换。
 async fn apply_request_modifications(
     config: &EngineConfig,
-    request: Request<Incoming>,
+    request: Request<RequestBody>,
     location: &LocationConfig,
+    rewrite_regex: Option<&regex::Regex>,
+    params: &HashMap<String, String>,
+    decompress: bool,
 ) -> Result<ModifiedRequest> {
     if let Some(request_config) = &location.request {
         let method = if let Some(m) = &request_config.method {
@@ -184,7 +574,15 @@ async fn apply_request_modifications(
         };
 
         let uri = if let Some(uri_config) = &request_config.uri {
-            let path = uri_config.path.as_deref().unwrap_or(request.uri().path());
+            let mut path = match &uri_config.path {
+                Some(template) => crate::router::expand_params(template, params),
+                None => request.uri().path().to_string(),
+            };
+            if let (Some(rewrite), Some(regex)) = (&uri_config.rewrite, rewrite_regex) {
+                path = regex
+                    .replace(&path, rewrite.replacement.as_str())
+                    .into_owned();
+            }
             let query = uri_config.query.as_deref();
 
             let new_uri = hyper::http::Uri::builder().path_and_query(if let Some(q) = query {
@@ -202,7 +600,24 @@ async fn apply_request_modifications(
 
         // Apply header actions
         if let Some(headers) = &request_config.headers {
-            apply_header_actions(&mut parts.headers, headers);
+            if params.is_empty() {
+                apply_header_actions(&mut parts.headers, headers);
+            } else {
+                let expanded = headers
+                    .iter()
+                    .map(|(name, action)| {
+                        let value = crate::router::expand_params(&action.value, params);
+                        (
+                            name.clone(),
+                            HeaderAction {
+                                value,
+                                ..action.clone()
+                            },
+                        )
+                    })
+                    .collect();
+                apply_header_actions(&mut parts.headers, &expanded);
+            }
         }
         if let Some(headers) = &config.header {
             apply_header_actions(&mut parts.headers, headers);
@@ -214,47 +629,51 @@ async fn apply_request_modifications(
         // Body JSON transformation
         if let Some(body_config) = &request_config.body {
             if let Some(json_config) = &body_config.json {
-                let body_bytes = body
-                    .collect()
-                    .await
-                    .map_err(|e| MystiProxyError::Hyper(e.to_string()))?
-                    .to_bytes();
-
-                if !body_bytes.is_empty() {
-                    if let Ok(mut json_value) =
-                        serde_json::from_slice::<serde_json::Value>(&body_bytes)
-                    {
-                        let transform_config = crate::config::BodyConfig {
-                            json: Some(json_config.clone()),
-                            body_type: None,
-                            content: None,
-                            template: None,
-                        };
-                        if let Err(e) = crate::http::body::BodyTransformer::transform(
-                            &mut json_value,
-                            &transform_config,
-                        ) {
-                            warn!("Body transformation failed: {}", e);
-                        }
+                let limit = config
+                    .max_buffered_body_size
+                    .unwrap_or(crate::http::body::DEFAULT_MAX_BUFFERED_BODY_SIZE);
+                let body_bytes = crate::http::body::collect_limited(body, limit).await?;
+
+                // 压缩的 body 需先解压才能按 JSON 读取；无法读取时不变换，原样转发
+                let decoded = decode_for_transform(&parts.headers, &body_bytes, limit, decompress)?;
+                let json_value = decoded
+                    .filter(|b| !b.is_empty())
+                    .and_then(|b| serde_json::from_slice::<serde_json::Value>(&b).ok());
+
+                if let Some(mut json_value) = json_value {
+                    let transform_config = crate::config::BodyConfig {
+                        json: Some(json_config.clone()),
+                        body_type: None,
+                        content: None,
+                        template: None,
+                        file: None,
+                        templated: None,
+                    };
+                    if let Err(e) = crate::http::body::BodyTransformer::transform(
+                        &mut json_value,
+                        &transform_config,
+                    ) {
+                        warn!("Body transformation failed: {}", e);
+                    }
 
-                        let new_bytes = bytes::Bytes::from(
-                            serde_json::to_vec(&json_value).unwrap_or_else(|_| body_bytes.to_vec()),
-                        );
+                    let new_bytes = bytes::Bytes::from(
+                        serde_json::to_vec(&json_value).unwrap_or_else(|_| body_bytes.to_vec()),
+                    );
 
-                        parts.headers.remove("content-length");
-                        parts.headers.insert(
-                            "content-length",
-                            hyper::header::HeaderValue::from_str(&new_bytes.len().to_string())
-                                .map_err(|e| {
-                                    MystiProxyError::Proxy(format!("Invalid content-length: {e}"))
-                                })?,
-                        );
+                    parts.headers.remove(hyper::header::CONTENT_ENCODING);
+                    parts.headers.remove("content-length");
+                    parts.headers.insert(
+                        "content-length",
+                        hyper::header::HeaderValue::from_str(&new_bytes.len().to_string())
+                            .map_err(|e| {
+                                MystiProxyError::Proxy(format!("Invalid content-length: {e}"))
+                            })?,
+                    );
 
-                        return Ok(ModifiedRequest::Bytes(Request::from_parts(
-                            parts,
-                            http_body_util::Full::new(new_bytes),
-                        )));
-                    }
+                    return Ok(ModifiedRequest::Bytes(Request::from_parts(
+                        parts,
+                        http_body_util::Full::new(new_bytes),
+                    )));
                 }
 
                 // Body consumed but not JSON - return raw bytes
@@ -266,24 +685,28 @@ async fn apply_request_modifications(
         }
 
         // No body transformation configured
-        return Ok(ModifiedRequest::Incoming(Request::from_parts(parts, body)));
+        return Ok(ModifiedRequest::from_request(Request::from_parts(
+            parts, body,
+        )));
     }
 
     // Engine-level headers only (no location match)
     if let Some(headers) = &config.header {
         let (mut parts, body) = request.into_parts();
         apply_header_actions(&mut parts.headers, headers);
-        return Ok(ModifiedRequest::Incoming(Request::from_parts(parts, body)));
+        return Ok(ModifiedRequest::from_request(Request::from_parts(
+            parts, body,
+        )));
     }
 
-    Ok(ModifiedRequest::Incoming(request))
+    Ok(ModifiedRequest::from_request(request))
 }
 
 /// Apply engine-level header modifications when no location matches.
 async fn apply_engine_header_modifications(
     config: &EngineConfig,
-    request: Request<Incoming>,
-) -> Result<Request<Incoming>> {
+    request: Request<RequestBody>,
+) -> Result<Request<RequestBody>> {
     if let Some(headers) = &config.header {
         let (mut parts, body) = request.into_parts();
         apply_header_actions(&mut parts.headers, headers);
@@ -292,6 +715,341 @@ async fn apply_engine_header_modifications(
     Ok(request)
 }
 
+/// JSON body 变换前按 `Content-Encoding` 解压 body
+///
+/// 未压缩时原样返回；已压缩但未启用解压或解压失败时返回 `None`，调用方应跳过变换。
+fn decode_for_transform(
+    headers: &hyper::HeaderMap,
+    body: &Bytes,
+    limit: usize,
+    decompress: bool,
+) -> Result<Option<Bytes>> {
+    let encoding = headers
+        .get(hyper::header::CONTENT_ENCODING)
+        .and_then(|v| v.to_str().ok())
+        .map(str::trim)
+        .filter(|e| !e.eq_ignore_ascii_case("identity"));
+    match encoding {
+        None => Ok(Some(body.clone())),
+        Some(_) if !decompress => Ok(None),
+        Some(encoding) => match decode_body(encoding, body, limit) {
+            Ok(decoded) => Ok(Some(decoded)),
+            Err(MystiProxyError::PayloadTooLarge(limit)) => {
+                Err(MystiProxyError::PayloadTooLarge(limit))
+            }
+            Err(e) => {
+                warn!("Failed to decode {} body for transform: {}", encoding, e);
+                Ok(None)
+            }
+        },
+    }
+}
+
+/// 按 proxy location 的 `response` 配置改写上游响应（未配置时原样返回）
+///
+/// 依次应用状态码重映射、响应头动作与 body 改写；body 改写为 JSON 变换时按上限缓冲上游 body，
+/// 为模版时仅在模版引用 `{{body.*}}` 时读取上游 JSON 作为上下文，为静态内容时直接替换。
+async fn apply_response_modifications(
+    config: &EngineConfig,
+    response_config: Option<&ResponseConfig>,
+    response: Response<BoxBody>,
+    request_uri: &str,
+    template_context: Option<&TemplateContext>,
+    method: &hyper::Method,
+    decompress: bool,
+) -> Result<Response<BoxBody>> {
+    let Some(response_config) = response_config else {
+        return Ok(response);
+    };
+    let (mut parts, body) = response.into_parts();
+    let upstream_status = parts.status;
+
+    let status = response_config
+        .status_map
+        .as_ref()
+        .and_then(|map| map.get(&upstream_status.as_u16()))
+        .copied()
+        .or(response_config.status);
+    if let Some(status) = status {
+        parts.status = StatusCode::from_u16(status)
+            .map_err(|e| MystiProxyError::Proxy(format!("Invalid status code: {e}")))?;
+    }
+
+    if let Some(headers) = &response_config.headers {
+        apply_header_actions(&mut parts.headers, headers);
+    }
+
+    // 无 body 的响应不改写 body
+    let body_config = response_config.body.as_ref().filter(|_| {
+        method != hyper::Method::HEAD
+            && upstream_status != StatusCode::NO_CONTENT
+            && upstream_status != StatusCode::NOT_MODIFIED
+    });
+    let Some(body_config) = body_config else {
+        return Ok(Response::from_parts(parts, body));
+    };
+
+    let limit = config
+        .max_buffered_body_size
+        .unwrap_or(crate::http::body::DEFAULT_MAX_BUFFERED_BODY_SIZE);
+    let read_json = |parts: &hyper::http::response::Parts, bytes: &Bytes| {
+        decode_for_transform(&parts.headers, bytes, limit, decompress).map(|decoded| {
+            decoded
+                .filter(|b| !b.is_empty())
+                .and_then(|b| serde_json::from_slice::<serde_json::Value>(&b).ok())
+        })
+    };
+
+    let replaced = match body_config.body_type.as_ref() {
+        Some(crate::config::BodyType::Template) => {
+            // 模板中的 `body` 为上游响应体，仅在被引用时读取
+            let template = body_config.template.as_deref().unwrap_or_default();
+            let upstream_body = if template::references(template, "body") {
+                crate::http::body::collect_limited(body, limit)
+                    .await
+                    .and_then(|bytes| read_json(&parts, &bytes))
+            } else {
+                Ok(None)
+            };
+            upstream_body.map(|upstream_body| {
+                let context = template_context
+                    .cloned()
+                    .unwrap_or_default()
+                    .with_body(upstream_body.as_ref());
+                Bytes::from(context.render(template))
+            })
+        }
+        Some(crate::config::BodyType::Static) => {
+            Ok(Bytes::from(body_config.content.clone().unwrap_or_default()))
+        }
+        _ => match (&body_config.json, &body_config.content) {
+            (Some(json_config), _) => {
+                let bytes = match crate::http::body::collect_limited(body, limit).await {
+                    Ok(bytes) => bytes,
+                    Err(e) => return response_transform_failed(e, request_uri),
+                };
+                match read_json(&parts, &bytes) {
+                    Ok(Some(mut json_value)) => {
+                        let transform_config = crate::config::BodyConfig {
+                            json: Some(json_config.clone()),
+                            body_type: None,
+                            content: None,
+                            template: None,
+                            file: None,
+                            templated: None,
+                        };
+                        if let Err(e) = crate::http::body::BodyTransformer::transform(
+                            &mut json_value,
+                            &transform_config,
+                        ) {
+                            warn!("Response body transformation failed: {}", e);
+                        }
+                        Ok(Bytes::from(
+                            serde_json::to_vec(&json_value).unwrap_or_else(|_| bytes.to_vec()),
+                        ))
+                    }
+                    // 非 JSON（或无法解压）的 body 原样转发
+                    Ok(None) => {
+                        return Ok(Response::from_parts(
+                            parts,
+                            HttpRequestHandler::full_body(bytes),
+                        ))
+                    }
+                    Err(e) => Err(e),
+                }
+            }
+            // 未指定类型但给了 content：与 mock 一致，作为静态体
+            (None, Some(content)) => Ok(Bytes::from(content.clone())),
+            (None, None) => return Ok(Response::from_parts(parts, body)),
+        },
+    };
+    let new_body = match replaced {
+        Ok(new_body) => new_body,
+        Err(e) => return response_transform_failed(e, request_uri),
+    };
+
+    // 新 body 未压缩，且与上游表示不同
+    parts.headers.remove(hyper::header::CONTENT_ENCODING);
+    parts.headers.remove(hyper::header::ETAG);
+    parts
+        .headers
+        .insert(hyper::header::CONTENT_LENGTH, new_body.len().into());
+    Ok(Response::from_parts(
+        parts,
+        HttpRequestHandler::full_body(new_body),
+    ))
+}
+
+/// 上游 body 无法读取（如超过缓冲上限）时无法改写，返回 502
+fn response_transform_failed(e: MystiProxyError, request_uri: &str) -> Result<Response<BoxBody>> {
+    warn!(
+        "Failed to read upstream response for {} to transform: {}",
+        request_uri, e
+    );
+    Response::builder()
+        .status(StatusCode::BAD_GATEWAY)
+        .body(HttpRequestHandler::empty_body())
+        .map_err(MystiProxyError::Http)
+}
+
+/// 将请求转为可重放形式（parts 与完整 body）
+///
+/// body 长度未知或超过 `limit` 时原样交还，不读取 body。
+async fn into_replayable(
+    request: ModifiedRequest,
+    limit: usize,
+) -> Result<std::result::Result<(hyper::http::request::Parts, Bytes), ModifiedRequest>> {
+    match request {
+        ModifiedRequest::Bytes(request) => {
+            let (parts, body) = request.into_parts();
+            let body = match body.collect().await {
+                Ok(collected) => collected.to_bytes(),
+                Err(never) => match never {},
+            };
+            Ok(Ok((parts, body)))
+        }
+        ModifiedRequest::Incoming(request) => {
+            let replayable = hyper::body::Body::size_hint(request.body())
+                .exact()
+                .is_some_and(|len| len <= limit as u64);
+            if !replayable {
+                return Ok(Err(ModifiedRequest::Incoming(request)));
+            }
+            let (parts, body) = request.into_parts();
+            let body = crate::http::body::collect_limited(body, limit).await?;
+            Ok(Ok((parts, body)))
+        }
+    }
+}
+
+/// 一次代理转发的上游选择上下文
+struct UpstreamTarget<'a> {
+    config: &'a EngineConfig,
+    pool: &'a HttpClientPool,
+    balancer: Option<&'a LoadBalancer>,
+    client_ip: Option<std::net::IpAddr>,
+    hash_headers: Option<&'a hyper::HeaderMap>,
+    /// 单目标引擎的目标地址
+    default_target: &'a str,
+}
+
+impl UpstreamTarget<'_> {
+    /// 选择 endpoint（尽量避开已尝试过的）并取得对应的客户端
+    async fn client(&self, tried: &[Arc<Endpoint>]) -> (Arc<HttpClient>, Option<EndpointGuard>) {
+        // 多目标时按策略选择 endpoint；guard 随响应体释放，供最少连接计数
+        let upstream = self
+            .balancer
+            .map(|lb| lb.select_excluding(self.client_ip, self.hash_headers, tried));
+        let target = upstream
+            .as_ref()
+            .map(|u| u.address())
+            .unwrap_or(self.default_target)
+            .to_string();
+        info!("Proxying request to: {}", target);
+        let client = self
+            .pool
+            .get_or_create_with_upstream(
+                target,
+                self.config.request_timeout,
+                self.config.upstream.as_deref(),
+            )
+            .await;
+        (client, upstream)
+    }
+
+    /// 被动健康检查：连接错误与 5xx 计为失败
+    fn report(&self, upstream: Option<&EndpointGuard>, sent: &Result<Response<Incoming>>) {
+        if let (Some(lb), Some(upstream)) = (self.balancer, upstream) {
+            match sent {
+                Ok(r) if !r.status().is_server_error() => lb.report_success(upstream.endpoint()),
+                // 熔断拒绝的请求未到达上游
+                Err(MystiProxyError::CircuitOpen(_)) => {}
+                _ => lb.report_failure(upstream.endpoint()),
+            }
+        }
+    }
+
+    /// 发送一次，不重试
+    async fn send_once(
+        &self,
+        request: ModifiedRequest,
+    ) -> (Result<Response<Incoming>>, Option<EndpointGuard>) {
+        let (client, upstream) = self.client(&[]).await;
+        let sent = match request {
+            ModifiedRequest::Incoming(r) => client.send_request(r).await,
+            ModifiedRequest::Bytes(r) => {
+                let (parts, body) = r.into_parts();
+                let boxed = body.map_err(|never| match never {}).boxed();
+                client.send_boxed(Request::from_parts(parts, boxed)).await
+            }
+        };
+        self.report(upstream.as_ref(), &sent);
+        (sent, upstream)
+    }
+
+    /// 按重试策略发送；多目标时每次重试换用未尝试过的 endpoint
+    async fn send_with_retry(
+        &self,
+        policy: &RetryPolicy,
+        parts: hyper::http::request::Parts,
+        body: Bytes,
+    ) -> (Result<Response<Incoming>>, Option<EndpointGuard>) {
+        let deadline = self.config.request_timeout.map(|t| Instant::now() + t);
+        let mut tried: Vec<Arc<Endpoint>> = Vec::new();
+        let mut attempt = 1;
+        loop {
+            let (client, upstream) = self.client(&tried).await;
+            let sent = match client.build_boxed_request(
+                parts.method.clone(),
+                parts.uri.clone(),
+                parts.headers.clone(),
+                body.clone(),
+            ) {
+                Ok(request) => match policy.per_try_timeout() {
+                    Some(timeout) => tokio::time::timeout(timeout, client.send_boxed(request))
+                        .await
+                        .unwrap_or(Err(MystiProxyError::Timeout)),
+                    None => client.send_boxed(request).await,
+                },
+                Err(e) => return (Err(e), upstream),
+            };
+            self.report(upstream.as_ref(), &sent);
+
+            let retryable = match &sent {
+                Ok(response) => policy.should_retry_status(response.status()),
+                Err(e) => policy.should_retry_error(e),
+            };
+            let delay = policy.backoff(attempt);
+            let out_of_time = deadline.is_some_and(|d| Instant::now() + delay >= d);
+            if !retryable || attempt >= policy.max_attempts() || out_of_time {
+                return (sent, upstream);
+            }
+
+            match &sent {
+                Ok(response) => warn!(
+                    "Upstream {} returned {}, retrying (attempt {}/{})",
+                    client.target(),
+                    response.status(),
+                    attempt + 1,
+                    policy.max_attempts()
+                ),
+                Err(e) => warn!(
+                    "Request to {} failed: {}, retrying (attempt {}/{})",
+                    client.target(),
+                    e,
+                    attempt + 1,
+                    policy.max_attempts()
+                ),
+            }
+            if let Some(upstream) = &upstream {
+                tried.push(upstream.endpoint().clone());
+            }
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+}
+
 /// Apply header actions (Overwrite, Missed, ForceDelete) to a HeaderMap.
 fn apply_header_actions(
     headers: &mut hyper::HeaderMap,
@@ -332,14 +1090,31 @@ impl Service<Request<Incoming>> for HttpRequestHandler {
     fn call(&self, req: Request<Incoming>) -> Self::Future {
         let config = self.config.clone();
         let client_pool = self.client_pool.clone();
+        let grpc_client_pool = self.grpc_client_pool.clone();
+        let grpc_descriptors = self.grpc_descriptors.clone();
         let router = self.router.clone();
         let authenticator = self.authenticator.clone();
         let metrics = self.metrics.clone();
+        let balancer = self.balancer.clone();
+        let rate_limiter = self.rate_limiter.clone();
+        let location_rate_limiters = self.location_rate_limiters.clone();
+        let response_cache = self.response_cache.clone();
+        let compression = self.compression.clone();
+        let location_compression = self.location_compression.clone();
+        let location_rewrites = self.location_rewrites.clone();
+        let body_mocks = self.body_mocks.clone();
+        let gateway = self.gateway.clone();
+        let response_sequences = self.response_sequences.clone();
+        #[cfg(feature = "local-management")]
+        let local_mocks = self.local_mocks.clone();
 
         Box::pin(async move {
             let start_time = Instant::now();
             let path = req.uri().path().to_string();
             let method = req.method().to_string();
+            let client_ip = req.extensions().get::<ClientIp>().map(|ip| ip.0);
+            let request_method = req.method().clone();
+            let accept_encoding = req.headers().get(hyper::header::ACCEPT_ENCODING).cloned();
             debug!("Handling request: {} {}", req.method(), path);
 
             // 检查是否为 WebSocket 升级请求
@@ -368,12 +1143,17 @@ impl Service<Request<Incoming>> for HttpRequestHandler {
                 }
 
                 // WebSocket 真正代理：转发握手到 engine.target 并桥接
-                let response = crate::http::websocket::proxy_websocket(
-                    req,
-                    &config.target,
-                    config.request_timeout,
-                )
-                .await?;
+                let upstream = balancer
+                    .as_ref()
+                    .map(|lb| lb.select(client_ip, Some(req.headers())));
+                let target = upstream
+                    .as_ref()
+                    .map(|u| u.address())
+                    .unwrap_or(config.target.primary())
+                    .to_string();
+                let response =
+                    crate::http::websocket::proxy_websocket(req, &target, config.request_timeout)
+                        .await?;
 
                 let duration = start_time.elapsed();
                 metrics.record_http_request(&method, &path, response.status().as_u16(), duration);
@@ -386,6 +1166,7 @@ impl Service<Request<Incoming>> for HttpRequestHandler {
             }
 
             // 进行认证
+            let mut jwt_sub = None;
             if let Some(auth) = authenticator {
                 let auth_result = auth.authenticate(req.headers())?;
                 if !auth_result.authenticated {
@@ -405,12 +1186,141 @@ impl Service<Request<Incoming>> for HttpRequestHandler {
                     return Ok(response);
                 }
                 debug!("Authentication successful: {:?}", auth_result.user);
+                jwt_sub = auth_result
+                    .claims
+                    .as_ref()
+                    .and_then(|claims| claims.get("sub"))
+                    .and_then(|sub| sub.as_str())
+                    .map(str::to_string);
+            }
+
+            // 引擎级限流
+            if let Some(limiter) = &rate_limiter {
+                let key = limiter.key_for(client_ip, req.headers(), jwt_sub.as_deref());
+                let decision = limiter.check(&key);
+                if !decision.allowed {
+                    debug!("Request {} {} rate limited (key {})", method, path, key);
+                    let response = Self::rate_limited_response(&decision)?;
+
+                    let duration = start_time.elapsed();
+                    metrics.record_http_request(
+                        &method,
+                        &path,
+                        response.status().as_u16(),
+                        duration,
+                    );
+
+                    return Ok(response);
+                }
             }
 
             // 依序遍历候选 location：mock 条件不命中时回退下一候选，其余 provider 保持第一命中语义
+            let candidates =
+                router.match_request_candidates(&path, &RequestInfo::from_request(&req));
+
+            // mock 的 body 条件或模版需要请求体时按上限缓冲；未命中 mock 时缓冲的 body 重放到上游
+            #[cfg(feature = "local-management")]
+            let local_needs_body = match &local_mocks {
+                Some(local_mocks) => local_mocks.needs_body(&method, &path).await,
+                None => false,
+            };
+            #[cfg(not(feature = "local-management"))]
+            let local_needs_body = false;
+            let (mut req, request_body) = if local_needs_body
+                || candidates
+                    .iter()
+                    .any(|(route, _)| body_mocks.contains(&location_key(&route.location_config)))
+            {
+                let limit = config
+                    .max_buffered_body_size
+                    .unwrap_or(crate::http::body::DEFAULT_MAX_BUFFERED_BODY_SIZE);
+                let (parts, body) = req.into_parts();
+                let body = match crate::http::body::collect_limited(body, limit).await {
+                    Ok(body) => body,
+                    Err(MystiProxyError::PayloadTooLarge(limit)) => {
+                        warn!(
+                            "Request body for {} exceeds buffer limit of {} bytes",
+                            path, limit
+                        );
+                        let response = Response::builder()
+                            .status(StatusCode::PAYLOAD_TOO_LARGE)
+                            .body(Self::empty_body())
+                            .map_err(MystiProxyError::Http)?;
+
+                        let duration = start_time.elapsed();
+                        metrics.record_http_request(
+                            &method,
+                            &path,
+                            response.status().as_u16(),
+                            duration,
+                        );
+
+                        return Ok(response);
+                    }
+                    Err(e) => return Err(e),
+                };
+                let req = Request::from_parts(
+                    parts,
+                    http_body_util::Either::Right(Full::new(body.clone())),
+                );
+                (req, Some(body))
+            } else {
+                (req.map(http_body_util::Either::Left), None)
+            };
+            // 条件与模版按解压后的请求体匹配；JSON 条件与模版读取其 JSON，非 JSON 时为 None
+            let decoded_body = request_body.as_ref().and_then(|body| {
+                let limit = config
+                    .max_buffered_body_size
+                    .unwrap_or(crate::http::body::DEFAULT_MAX_BUFFERED_BODY_SIZE);
+                decode_for_transform(req.headers(), body, limit, true)
+                    .ok()
+                    .flatten()
+            });
+            let request_json = decoded_body
+                .as_ref()
+                .filter(|b| !b.is_empty())
+                .and_then(|b| serde_json::from_slice::<serde_json::Value>(b).ok());
+
             let mut route_match: Option<RouteMatch> = None;
-            for (route, _match_result) in router.match_uri_candidates(&path) {
+
+            // 本地管理的活动 mock 优先；未命中再走 YAML locations
+            #[cfg(feature = "local-management")]
+            if let Some(local_mocks) = &local_mocks {
+                route_match = local_mocks
+                    .find_from(
+                        &method,
+                        &req.uri().to_string(),
+                        req.headers(),
+                        decoded_body.as_deref(),
+                        client_ip,
+                    )
+                    .await
+                    .map(RouteMatch::Mock);
+            }
+
+            // location 级限流，返回拒绝请求时的限流结果
+            let location_rate_limited = |location: &LocationConfig| {
+                let limiter = location_rate_limiters.get(&location_key(location))?;
+                let key = limiter.key_for(client_ip, req.headers(), jwt_sub.as_deref());
+                let decision = limiter.check(&key);
+                if decision.allowed {
+                    return None;
+                }
+                debug!("Request {} {} rate limited (key {})", method, path, key);
+                Some(decision)
+            };
+            // mock 分支在推进响应序列前已执行限流，避免重复计数
+            let mut location_limit_checked = false;
+
+            // 每个分支命中后立即 break，循环结束时 matched_location 即命中的 location
+            let mut matched_location = None;
+            let request_info = RequestInfo::from_request(&req);
+            for (route, match_result) in candidates {
+                if route_match.is_some() {
+                    break;
+                }
                 let location = &route.location_config;
+                matched_location = Some(location);
                 let provider = location.provider.as_ref().unwrap_or(&ProviderType::Proxy);
                 match provider {
                     ProviderType::Mock => {
@@ -432,11 +1342,44 @@ impl Service<Request<Incoming>> for HttpRequestHandler {
                             || crate::mock::MockBuilder::matches_conditions(
                                 &req.uri().to_string(),
                                 req.headers(),
-                                None,
+                                request_json.as_ref(),
                                 &conditions,
                             )
                         {
-                            let mock = build_mock_response(location, &req.uri().to_string());
+                            // 先执行 location 级限流，被限流的请求不推进响应序列
+                            if let Some(decision) = location_rate_limited(location) {
+                                let response = Self::rate_limited_response(&decision)?;
+                                metrics.record_http_request(
+                                    &method,
+                                    &path,
+                                    response.status().as_u16(),
+                                    start_time.elapsed(),
+                                );
+                                return Ok(response);
+                            }
+                            location_limit_checked = true;
+
+                            // 响应序列只在 mock 实际命中时计数
+                            let sequence =
+                                location.response.as_ref().and_then(|r| r.sequence.as_ref());
+                            let step = sequence.and_then(|sequence| {
+                                let call = response_sequences
+                                    .next(&location_key(location), &location.location);
+                                select_step(sequence, call).map(|i| &sequence.responses[i])
+                            });
+                            let context =
+                                TemplateContext::new(req.method().as_str(), &req.uri().to_string())
+                                    .with_headers(req.headers())
+                                    .with_client_ip(client_ip)
+                                    .with_params(&match_result.captures())
+                                    .with_body(request_json.as_ref());
+                            let mock = build_mock_response(location, step, &context);
+                            let mock = crate::mock::file::render_templated(
+                                mock,
+                                &context,
+                                config.mock_root.as_deref(),
+                            )
+                            .await;
                             route_match = Some(RouteMatch::Mock(mock));
                             break;
                         }
@@ -448,11 +1391,66 @@ impl Service<Request<Incoming>> for HttpRequestHandler {
                     }
                     ProviderType::Proxy => {
                         route_match = Some(RouteMatch::Proxy {
-                            target: config.target.clone(),
+                            target: location.target.clone(),
                             location: Some(location.clone()),
+                            path: None,
+                            params: match_result.captures(),
                         });
                         break;
                     }
+                    ProviderType::Grpc => {
+                        if !is_grpc_request(&req) {
+                            debug!(
+                                "Non-gRPC request for gRPC location {}, trying next",
+                                location.location
+                            );
+                            continue;
+                        }
+                        let grpc = location.grpc.as_ref();
+                        route_match = Some(match grpc.and_then(|g| g.mock.as_ref()) {
+                            Some(mock) => {
+                                let descriptors = grpc
+                                    .and_then(|g| g.descriptor_set.as_ref())
+                                    .and_then(|p| grpc_descriptors.get(p));
+                                RouteMatch::GrpcMock(GrpcMockResponse::build(
+                                    descriptors.map(|d| d.as_ref()),
+                                    &path,
+                                    mock,
+                                ))
+                            }
+                            None => RouteMatch::Proxy {
+                                target: location.target.clone(),
+                                location: Some(location.clone()),
+                                path: None,
+                                params: match_result.captures(),
+                            },
+                        });
+                        break;
+                    }
+                    ProviderType::Redirect => {
+                        let Some(redirect) = &location.redirect else {
+                            continue;
+                        };
+                        // 监听 TLS 或前置代理声明 HTTPS 时视为安全请求
+                        let secure = config.tls.is_some()
+                            || req
+                                .headers()
+                                .get("x-forwarded-proto")
+                                .is_some_and(|v| v.as_bytes().eq_ignore_ascii_case(b"https"));
+                        let request = RedirectRequest {
+                            uri: req.uri(),
+                            host: request_info.host,
+                            secure,
+                        };
+                        match build_redirect(redirect, request, &match_result.captures()) {
+                            Some(mock) => {
+                                route_match = Some(RouteMatch::Mock(mock));
+                                break;
+                            }
+                            // 请求已满足重定向规则：尝试下一候选
+                            None => continue,
+                        }
+                    }
                     ProviderType::Static => {
                         let root = location.root.clone().unwrap_or_else(|| ".".to_string());
                         let mut sf_config = StaticFileConfig {
@@ -466,7 +1464,7 @@ impl Service<Request<Incoming>> for HttpRequestHandler {
                             sf_config.enable_directory_listing = enable;
                         }
                         // 前缀匹配时剥离 location 前缀（与上游 09312dd 语义一致）
-                        let stripped = match _match_result.remaining.as_deref() {
+                        let stripped = match match_result.remaining.as_deref() {
                             Some(remaining) if !remaining.is_empty() => {
                                 format!("/{}", remaining.trim_start_matches('/'))
                             }
@@ -480,73 +1478,353 @@ impl Service<Request<Incoming>> for HttpRequestHandler {
                     }
                 }
             }
+
+            // 未命中 location 时按网关映射转发
+            if route_match.is_none() {
+                if let Some(route) = gateway.as_ref().and_then(|g| g.route(&method, &path)) {
+                    matched_location = None;
+                    route_match = Some(RouteMatch::Proxy {
+                        target: route.target,
+                        location: None,
+                        path: Some(route.path),
+                        params: HashMap::new(),
+                    });
+                }
+            }
+
+            // location 级限流
+            let rate_limited = route_match
+                .as_ref()
+                .and(matched_location)
+                .filter(|_| !location_limit_checked)
+                .and_then(location_rate_limited);
+            if let Some(decision) = rate_limited {
+                let response = Self::rate_limited_response(&decision)?;
+
+                let duration = start_time.elapsed();
+                metrics.record_http_request(&method, &path, response.status().as_u16(), duration);
+
+                return Ok(response);
+            }
+
+            // 响应压缩：命中的 location 配置了 compression 时覆盖引擎级
+            let compression = route_match
+                .as_ref()
+                .and(matched_location)
+                .and_then(|location| location_compression.get(&location_key(location)))
+                .or(compression.as_ref())
+                .cloned();
+            let compress = |response: Response<BoxBody>| match &compression {
+                Some(policy) => policy.apply(&request_method, accept_encoding.as_ref(), response),
+                None => response,
+            };
+
             let route_match = route_match.unwrap_or(RouteMatch::Proxy {
-                target: config.target.clone(),
+                target: None,
                 location: None,
+                path: None,
+                params: HashMap::new(),
             });
 
             match route_match {
-                RouteMatch::Proxy { target, location } => {
-                    info!("Proxying request to: {}", target);
-
-                    let client = client_pool
-                        .get_or_create_with_upstream(
-                            target.clone(),
-                            config.request_timeout,
-                            config.upstream.as_deref(),
-                        )
-                        .await;
-
-                    // Get response parts + body bytes, handling both Incoming and Bytes body types
-                    let (resp_parts, body_bytes) = if let Some(loc) = &location {
-                        match apply_request_modifications(&config, req, loc).await? {
-                            ModifiedRequest::Incoming(r) => {
-                                let resp = client.send_request(r).await?;
-                                let (parts, body) = resp.into_parts();
-                                let bytes = body
-                                    .collect()
-                                    .await
-                                    .map_err(|e| MystiProxyError::Hyper(e.to_string()))?
-                                    .to_bytes();
-                                (parts, bytes)
+                RouteMatch::Proxy {
+                    target,
+                    location,
+                    path: upstream_path,
+                    params,
+                } => {
+                    // gRPC 需要 HTTP/2 才能携带 trailers
+                    let is_grpc = location
+                        .as_ref()
+                        .is_some_and(|l| l.provider == Some(ProviderType::Grpc));
+                    let pool = if is_grpc {
+                        &grpc_client_pool
+                    } else {
+                        &client_pool
+                    };
+
+                    // location 的 retry 覆盖引擎级配置；仅对允许的方法生效
+                    let retry_policy = location
+                        .as_ref()
+                        .and_then(|l| l.retry.as_ref())
+                        .or(config.retry.as_ref())
+                        .map(RetryPolicy::from_config)
+                        .filter(|p| p.max_attempts() > 1 && p.allows_method(req.method()));
+
+                    // location 的 response 改写上游响应（gRPC 透传）；缓存保存改写前的上游响应
+                    let response_transform = location
+                        .as_ref()
+                        .filter(|_| !is_grpc)
+                        .and_then(|l| l.response.as_ref());
+                    let decompress = compression
+                        .as_ref()
+                        .is_some_and(|c| c.decompress_for_transform());
+                    // 熔断 fallback 与响应改写的模版按原始 URI 渲染
+                    let request_uri = req.uri().to_string();
+                    // 响应改写的模板可引用请求数据，仅配置了模板 body 时构建上下文
+                    let transform_context = response_transform
+                        .and_then(|r| r.body.as_ref())
+                        .filter(|b| b.body_type == Some(crate::config::BodyType::Template))
+                        .map(|_| {
+                            TemplateContext::new(request_method.as_str(), &request_uri)
+                                .with_headers(req.headers())
+                                .with_client_ip(client_ip)
+                                .with_params(&params)
+                        });
+
+                    // 响应缓存：仅 GET；location 可跳过缓存或强制新鲜期
+                    let location_cache = location.as_ref().and_then(|l| l.cache.as_ref());
+                    let forced_ttl = location_cache.and_then(|c| c.ttl);
+                    let mut cache_state = None;
+                    if let Some(cache) = response_cache
+                        .clone()
+                        .filter(|_| !location_cache.is_some_and(|c| c.bypass))
+                    {
+                        let directive = RequestDirective::from_request(req.method(), req.headers());
+                        if directive == RequestDirective::Bypass {
+                            metrics.record_cache_request(cache.engine(), "bypass");
+                        } else {
+                            let key = CacheKey::from_request(&req);
+                            let cached = match cache.lookup(&key).await {
+                                CacheLookup::Fresh(entry)
+                                    if directive == RequestDirective::Normal =>
+                                {
+                                    metrics.record_cache_request(cache.engine(), "hit");
+                                    let not_modified = entry.matches_if_none_match(req.headers());
+                                    let response = apply_response_modifications(
+                                        &config,
+                                        response_transform,
+                                        Self::cached_to_response(&entry, not_modified, "HIT")?,
+                                        &request_uri,
+                                        transform_context.as_ref(),
+                                        &request_method,
+                                        decompress,
+                                    )
+                                    .await?;
+                                    let response = compress(response);
+
+                                    let duration = start_time.elapsed();
+                                    metrics.record_http_request(
+                                        &method,
+                                        &path,
+                                        response.status().as_u16(),
+                                        duration,
+                                    );
+
+                                    return Ok(response);
+                                }
+                                CacheLookup::Fresh(entry) | CacheLookup::Stale(entry) => {
+                                    Some(entry)
+                                }
+                                CacheLookup::Miss => None,
+                            };
+                            // 过期条目携带验证器向上游发条件请求；客户端自带条件头时原样转发
+                            let client_conditional =
+                                req.headers().contains_key(hyper::header::IF_NONE_MATCH)
+                                    || req.headers().contains_key(hyper::header::IF_MODIFIED_SINCE);
+                            let stale =
+                                cached.filter(|entry| entry.has_validator() && !client_conditional);
+                            if let Some(entry) = &stale {
+                                for (name, value) in entry.conditional_headers() {
+                                    req.headers_mut().insert(name, value);
+                                }
                             }
-                            ModifiedRequest::Bytes(r) => {
-                                let (parts, body) = r.into_parts();
-                                let boxed = body.map_err(|never| match never {}).boxed();
-                                let boxed_req = Request::from_parts(parts, boxed);
-                                let resp = client.send_boxed(boxed_req).await?;
-                                let (parts, body) = resp.into_parts();
-                                let bytes = body
-                                    .collect()
-                                    .await
-                                    .map_err(|e| MystiProxyError::Hyper(e.to_string()))?
-                                    .to_bytes();
-                                (parts, bytes)
+                            cache_state = Some((cache, key, stale));
+                        }
+                    }
+
+                    // 指定了专用 target 时直连该地址，不经引擎级负载均衡
+                    let balancer = balancer.as_deref().filter(|_| target.is_none());
+                    // 一致性哈希按原始请求头选择 endpoint
+                    let hash_headers = balancer.map(|_| req.headers().clone());
+                    let upstream_target = UpstreamTarget {
+                        config: &config,
+                        pool,
+                        balancer,
+                        client_ip,
+                        hash_headers: hash_headers.as_ref(),
+                        default_target: target.as_deref().unwrap_or(config.target.primary()),
+                    };
+
+                    // 网关映射改写上游路径，查询串沿用原请求
+                    if let Some(upstream_path) = upstream_path {
+                        let path_and_query = match req.uri().query() {
+                            Some(query) => format!("{upstream_path}?{query}"),
+                            None => upstream_path,
+                        };
+                        *req.uri_mut() = path_and_query.parse().map_err(|_| {
+                            MystiProxyError::Proxy(format!(
+                                "Invalid gateway target path: {path_and_query}"
+                            ))
+                        })?;
+                    }
+
+                    // 请求/响应 body 默认流式转发；仅 JSON 变换时按上限缓冲请求体
+                    let modified = if let Some(loc) = &location {
+                        let rewrite = location_rewrites.get(&location_key(loc));
+                        match apply_request_modifications(
+                            &config, req, loc, rewrite, &params, decompress,
+                        )
+                        .await
+                        {
+                            Ok(modified) => modified,
+                            Err(MystiProxyError::PayloadTooLarge(limit)) => {
+                                warn!(
+                                    "Request body for {} exceeds buffer limit of {} bytes",
+                                    path, limit
+                                );
+                                let response = Response::builder()
+                                    .status(StatusCode::PAYLOAD_TOO_LARGE)
+                                    .body(Self::empty_body())
+                                    .map_err(MystiProxyError::Http)?;
+
+                                let duration = start_time.elapsed();
+                                metrics.record_http_request(
+                                    &method,
+                                    &path,
+                                    response.status().as_u16(),
+                                    duration,
+                                );
+
+                                return Ok(response);
                             }
+                            Err(e) => return Err(e),
                         }
-                    } else if config.header.is_some() {
-                        let r = apply_engine_header_modifications(&config, req).await?;
-                        let resp = client.send_request(r).await?;
-                        let (parts, body) = resp.into_parts();
-                        let bytes = body
-                            .collect()
-                            .await
-                            .map_err(|e| MystiProxyError::Hyper(e.to_string()))?
-                            .to_bytes();
-                        (parts, bytes)
                     } else {
-                        let resp = client.send_request(req).await?;
-                        let (parts, body) = resp.into_parts();
-                        let bytes = body
-                            .collect()
-                            .await
-                            .map_err(|e| MystiProxyError::Hyper(e.to_string()))?
-                            .to_bytes();
-                        (parts, bytes)
+                        ModifiedRequest::from_request(
+                            apply_engine_header_modifications(&config, req).await?,
+                        )
                     };
 
-                    let new_response =
-                        Response::from_parts(resp_parts, Self::full_body(body_bytes));
+                    // 重试需要重放请求体：body 长度未知或超过缓冲上限时只发送一次
+                    let (sent, upstream) = match retry_policy {
+                        Some(policy) => {
+                            let limit = config
+                                .max_buffered_body_size
+                                .unwrap_or(crate::http::body::DEFAULT_MAX_BUFFERED_BODY_SIZE);
+                            match into_replayable(modified, limit).await? {
+                                Ok((parts, body)) => {
+                                    upstream_target.send_with_retry(&policy, parts, body).await
+                                }
+                                Err(modified) => upstream_target.send_once(modified).await,
+                            }
+                        }
+                        None => upstream_target.send_once(modified).await,
+                    };
+                    let response = match sent {
+                        Ok(response) => response,
+                        // 熔断中：返回配置的 fallback，默认 503
+                        Err(MystiProxyError::CircuitOpen(target)) => {
+                            warn!("Circuit open for {}, rejecting {}", target, path);
+                            let fallback = config
+                                .circuit_breaker
+                                .as_ref()
+                                .and_then(|c| c.fallback.as_ref());
+                            let response = match fallback {
+                                Some(fallback) => {
+                                    let context = TemplateContext::new(&method, &request_uri)
+                                        .with_client_ip(client_ip)
+                                        .with_params(&params);
+                                    let mock = crate::mock::file::render_templated(
+                                        mock_from_response_config(fallback, &context),
+                                        &context,
+                                        config.mock_root.as_deref(),
+                                    )
+                                    .await;
+                                    Self::mock_to_response(mock, config.mock_root.as_deref())
+                                        .await?
+                                }
+                                None => Response::builder()
+                                    .status(StatusCode::SERVICE_UNAVAILABLE)
+                                    .body(Self::empty_body())
+                                    .map_err(MystiProxyError::Http)?,
+                            };
+
+                            let duration = start_time.elapsed();
+                            metrics.record_http_request(
+                                &method,
+                                &path,
+                                response.status().as_u16(),
+                                duration,
+                            );
+
+                            return Ok(response);
+                        }
+                        Err(e) => return Err(e),
+                    };
+
+                    // 上游 304 刷新缓存条目；其余可缓存响应边转发边写入缓存
+                    let mut pending_store = None;
+                    let cache_miss = cache_state.is_some();
+                    if let Some((cache, key, stale)) = cache_state {
+                        let not_modified = response.status() == StatusCode::NOT_MODIFIED;
+                        if let Some(stale) = stale.filter(|_| not_modified) {
+                            let entry = cache
+                                .refresh(&key, &stale, response.headers(), forced_ttl)
+                                .await;
+                            metrics.record_cache_request(cache.engine(), "revalidated");
+                            let response =
+                                compress(Self::cached_to_response(&entry, false, "REVALIDATED")?);
+
+                            let duration = start_time.elapsed();
+                            metrics.record_http_request(
+                                &method,
+                                &path,
+                                response.status().as_u16(),
+                                duration,
+                            );
+
+                            return Ok(response);
+                        }
+
+                        metrics.record_cache_request(cache.engine(), "miss");
+                        let too_large = response
+                            .headers()
+                            .get(hyper::header::CONTENT_LENGTH)
+                            .and_then(|v| v.to_str().ok())
+                            .and_then(|v| v.parse::<usize>().ok())
+                            .is_some_and(|len| len > cache.max_entry_size());
+                        let lifetime =
+                            freshness_lifetime(response.status(), response.headers(), forced_ttl)
+                                .filter(|_| !too_large);
+                        if let Some(lifetime) = lifetime {
+                            pending_store = Some(PendingStore {
+                                status: response.status(),
+                                headers: response.headers().clone(),
+                                cache,
+                                key,
+                                lifetime,
+                            });
+                        }
+                    }
+
+                    let (mut resp_parts, body) = response.into_parts();
+                    if cache_miss {
+                        resp_parts
+                            .headers
+                            .insert(X_CACHE, hyper::header::HeaderValue::from_static("MISS"));
+                    }
+                    let body = body
+                        .map_frame(move |frame| {
+                            let _ = &upstream;
+                            frame
+                        })
+                        .map_err(|e| MystiProxyError::Hyper(e.to_string()));
+                    let body = match pending_store {
+                        Some(pending) => CachingBody::new(body, pending).boxed(),
+                        None => body.boxed(),
+                    };
+                    let new_response = apply_response_modifications(
+                        &config,
+                        response_transform,
+                        Response::from_parts(resp_parts, body),
+                        &request_uri,
+                        transform_context.as_ref(),
+                        &request_method,
+                        decompress,
+                    )
+                    .await?;
+                    let new_response = compress(new_response);
 
                     let duration = start_time.elapsed();
                     metrics.record_http_request(
@@ -558,6 +1836,20 @@ impl Service<Request<Incoming>> for HttpRequestHandler {
 
                     Ok(new_response)
                 }
+                RouteMatch::GrpcMock(mock) => {
+                    info!("Returning gRPC mock response: grpc-status {}", mock.status);
+                    let response = mock.into_response()?;
+
+                    let duration = start_time.elapsed();
+                    metrics.record_http_request(
+                        &method,
+                        &path,
+                        response.status().as_u16(),
+                        duration,
+                    );
+
+                    Ok(response)
+                }
                 RouteMatch::Mock(mock) => {
                     info!("Returning mock response: {}", mock.status);
 
@@ -565,22 +1857,8 @@ impl Service<Request<Incoming>> for HttpRequestHandler {
                         tokio::time::sleep(Duration::from_millis(mock.delay_ms)).await;
                     }
 
-                    let mut builder =
-                        Response::builder().status(StatusCode::from_u16(mock.status).map_err(
-                            |e| MystiProxyError::Proxy(format!("Invalid status code: {e}")),
-                        )?);
-
-                    for (key, value) in &mock.headers {
-                        builder = builder.header(key, value);
-                    }
-
-                    let body = if mock.body.is_empty() {
-                        Self::empty_body()
-                    } else {
-                        Self::full_body(Bytes::from(mock.body))
-                    };
-
-                    let response = builder.body(body).map_err(MystiProxyError::Http)?;
+                    let response =
+                        Self::mock_to_response(mock, config.mock_root.as_deref()).await?;
 
                     let duration = start_time.elapsed();
                     metrics.record_http_request(
@@ -605,11 +1883,15 @@ impl Service<Request<Incoming>> for HttpRequestHandler {
                         .get("range")
                         .and_then(|v| v.to_str().ok())
                         .map(|s| s.to_string());
-                    let response = if let Some(range) = range_header {
-                        service.serve_with_range(&static_path, Some(&range)).await?
-                    } else {
-                        service.serve(&static_path).await?
-                    };
+                    let response = compress(
+                        service
+                            .serve_with_encoding(
+                                &static_path,
+                                range_header.as_deref(),
+                                accept_encoding.as_ref(),
+                            )
+                            .await?,
+                    );
 
                     let duration = start_time.elapsed();
                     metrics.record_http_request(
@@ -665,6 +1947,17 @@ mod tests {
             request: None,
             index_files: None,
             enable_directory_listing: None,
+            grpc: None,
+            redirect: None,
+            retry: None,
+            rate_limit: None,
+            cache: None,
+            compression: None,
+            hosts: None,
+            methods: None,
+            match_headers: None,
+            match_query: None,
+            target: None,
         };
         let route = Route::new("/api/test".to_string(), MatchMode::Full, location).unwrap();
         router.add_route(route);
@@ -687,6 +1980,17 @@ mod tests {
             request: None,
             index_files: None,
             enable_directory_listing: None,
+            grpc: None,
+            redirect: None,
+            retry: None,
+            rate_limit: None,
+            cache: None,
+            compression: None,
+            hosts: None,
+            methods: None,
+            match_headers: None,
+            match_query: None,
+            target: None,
         };
         let route = Route::new("/api".to_string(), MatchMode::Prefix, location).unwrap();
         router.add_route(route);
@@ -716,12 +2020,77 @@ mod tests {
             request: None,
             index_files: None,
             enable_directory_listing: None,
+            grpc: None,
+            redirect: None,
+            retry: None,
+            rate_limit: None,
+            cache: None,
+            compression: None,
+            hosts: None,
+            methods: None,
+            match_headers: None,
+            match_query: None,
+            target: None,
         };
 
-        let mock = build_mock_response(&location, "/test");
+        let mock = build_mock_response(&location, None, &TemplateContext::new("GET", "/test"));
         assert_eq!(mock.status, 200);
     }
 
+    #[test]
+    fn test_mock_needs_body() {
+        let location = |yaml: &str| -> LocationConfig { serde_yaml::from_str(yaml).unwrap() };
+
+        let json_condition = location(
+            "location: /a\nmode: Full\nprovider: mock\nresponse:\n  conditions:\n    \
+             - condition_type: JSON\n      value: $.id\n",
+        );
+        assert!(mock_needs_body(&json_condition));
+
+        let body_template = location(
+            "location: /a\nmode: Full\nprovider: mock\nresponse:\n  body:\n    \
+             type: template\n    template: \"{{body.$.id}}\"\n",
+        );
+        assert!(mock_needs_body(&body_template));
+
+        let query_template = location(
+            "location: /a\nmode: Full\nprovider: mock\nresponse:\n  body:\n    \
+             type: template\n    template: \"{{query.id}}\"\n",
+        );
+        assert!(!mock_needs_body(&query_template));
+
+        for template in [
+            "{{body}}",
+            "{{#each body}}{{this}}{{/each}}",
+            "{{#if body}}y{{/if}}",
+        ] {
+            let whole_body = location(&format!(
+                "location: /a\nmode: Full\nprovider: mock\nresponse:\n  body:\n    \
+                 type: template\n    template: \"{template}\"\n"
+            ));
+            assert!(mock_needs_body(&whole_body), "{template}");
+        }
+
+        let literal_text = location(
+            "location: /a\nmode: Full\nprovider: mock\nresponse:\n  body:\n    \
+             type: template\n    template: \"https://somebody.example/{{query.id}}\"\n",
+        );
+        assert!(!mock_needs_body(&literal_text));
+
+        let header_template = location(
+            "location: /a\nmode: Full\nprovider: mock\nresponse:\n  headers:\n    \
+             X-Id:\n      action: overwrite\n      value: \"{{body.$.id}}\"\n",
+        );
+        assert!(mock_needs_body(&header_template));
+
+        // 代理 location 的响应模版引用的是上游 body，不需要缓冲请求体
+        let proxy_template = location(
+            "location: /a\nmode: Full\nresponse:\n  body:\n    \
+             type: template\n    template: \"{{body.$.id}}\"\n",
+        );
+        assert!(!mock_needs_body(&proxy_template));
+    }
+
     #[test]
     fn test_route_match_static_variant() {
         let route_match = RouteMatch::Static {
@@ -4,11 +4,16 @@
 
 mod auth;
 mod body;
+mod cache;
 mod client;
+mod compression;
+mod grpc;
 mod handler;
 mod header;
 mod ntlm;
 mod proxy;
+mod redirect;
+mod retry;
 mod server;
 mod static_files;
 mod upstream;
@@ -19,17 +24,30 @@ use hyper::body::{Bytes, Incoming};
 
 // 重导出公共接口
 pub use auth::{AuthConfig, AuthResult, AuthType, Authenticator, Claims};
-pub use body::{read_json_body, write_json_body, BodyTransformer};
-pub use client::{HttpClient, HttpClientPool};
+pub use body::{
+    collect_limited, read_json_body, write_json_body, BodyTransformer,
+    DEFAULT_MAX_BUFFERED_BODY_SIZE,
+};
+pub use cache::{
+    freshness_lifetime, CacheKey, CacheLookup, CachedResponse, CachingBody, PendingStore,
+    RequestDirective, ResponseCache,
+};
+pub use client::{HttpClient, HttpClientPool, UpstreamTls};
+pub use compression::{decode_body, negotiate_encoding, CompressionPolicy};
+pub use grpc::{
+    encode_message_frame, is_grpc_request, parse_method_path, GrpcDescriptors, GrpcMockResponse,
+};
 pub use handler::{create_handler, BoxBody, HttpRequestHandler, RouteMatch};
 pub use header::HeaderTransformer;
 pub use ntlm::{NtlmAuthenticator, NtlmConfig, NtlmVersion, Type2Message};
 pub use proxy::{HttpProxyAcceptor, HttpProxyConfig, HttpProxyService, ProxyAuthConfig};
+pub use redirect::{build_redirect, RedirectRequest, DEFAULT_REDIRECT_STATUS, REDIRECT_STATUSES};
+pub use retry::RetryPolicy;
 pub use server::{
-    create_simple_server, BoxBody as ServerBoxBody, HttpProxyService as SimpleHttpProxyService,
-    HttpServer, HttpServerConfig,
+    create_simple_server, BoxBody as ServerBoxBody, ClientIp,
+    HttpProxyService as SimpleHttpProxyService, HttpServer, HttpServerConfig,
 };
-pub use static_files::{StaticFileConfig, StaticFileService};
+pub use static_files::{mime_type, StaticFileConfig, StaticFileService};
 pub use upstream::{
     ProxyConverter, UpstreamAuth, UpstreamProtocol, UpstreamProxyConfig, UpstreamProxyConnector,
 };
@@ -0,0 +1,222 @@
+//! 重定向 provider
+//!
+//! 按 location 的 `redirect` 配置生成 301/302/307/308 响应，`Location` 由模板、
+//! 路由参数与原请求查询串拼接，并支持强制 HTTPS、末尾斜杠与规范主机名规则。
+
+use std::collections::HashMap;
+
+use hyper::Uri;
+
+use crate::config::{RedirectConfig, TrailingSlash};
+use crate::mock::MockResponse;
+use crate::router::expand_params;
+
+/// 未配置状态码时使用的默认值
+pub const DEFAULT_REDIRECT_STATUS: u16 = 302;
+
+/// 允许的重定向状态码
+pub const REDIRECT_STATUSES: [u16; 4] = [301, 302, 307, 308];
+
+/// 重定向所需的请求信息
+#[derive(Debug, Clone, Copy)]
+pub struct RedirectRequest<'a> {
+    /// 请求 URI（路径与查询串）
+    pub uri: &'a Uri,
+    /// 请求主机名（不含端口）
+    pub host: Option<&'a str>,
+    /// 请求是否已经是 HTTPS
+    pub secure: bool,
+}
+
+/// 生成重定向响应
+///
+/// 配置了 `location` 模板时总是重定向；只配置规则时，请求已满足全部规则则返回 None，
+/// 由调用方继续尝试后续 location。
+pub fn build_redirect(
+    config: &RedirectConfig,
+    request: RedirectRequest<'_>,
+    params: &HashMap<String, String>,
+) -> Option<MockResponse> {
+    let target = match &config.location {
+        Some(template) => expand_params(template, params),
+        None => request.uri.path().to_string(),
+    };
+    let (mut origin, rest) = split_origin(&target);
+    let (path, query) = match rest.split_once('?') {
+        Some((path, query)) => (path.to_string(), Some(query.to_string())),
+        None => (rest.to_string(), None),
+    };
+    let mut changed = config.location.is_some();
+
+    let path = match config.trailing_slash {
+        Some(TrailingSlash::Add) if !path.ends_with('/') => format!("{path}/"),
+        Some(TrailingSlash::Strip) if path.len() > 1 && path.ends_with('/') => {
+            path.trim_end_matches('/').to_string()
+        }
+        _ => path,
+    };
+    changed |= config.location.is_none() && path != request.uri.path();
+
+    // 相对路径的目标才按请求补全 scheme 与主机
+    if origin.is_none() {
+        let upgrade = config.force_https && !request.secure;
+        let canonical = config
+            .host
+            .as_deref()
+            .filter(|host| !request.host.is_some_and(|h| h.eq_ignore_ascii_case(host)));
+        if upgrade || canonical.is_some() {
+            let host = canonical.or(request.host)?;
+            let scheme = if config.force_https || request.secure {
+                "https"
+            } else {
+                "http"
+            };
+            origin = Some(format!("{scheme}://{host}"));
+            changed = true;
+        }
+    }
+    if !changed {
+        return None;
+    }
+
+    let query = match (query, request.uri.query().filter(|_| !config.drop_query)) {
+        (Some(own), Some(original)) => Some(format!("{own}&{original}")),
+        (own, original) => own.or(original.map(str::to_string)),
+    };
+    let mut location = origin.unwrap_or_default();
+    location.push_str(&path);
+    if let Some(query) = query.filter(|q| !q.is_empty()) {
+        location.push('?');
+        location.push_str(&query);
+    }
+
+    Some(
+        MockResponse::new()
+            .status(config.status.unwrap_or(DEFAULT_REDIRECT_STATUS))
+            .header("Location".to_string(), location),
+    )
+}
+
+/// 拆分绝对 URL 的 `scheme://authority` 部分
+fn split_origin(target: &str) -> (Option<String>, &str) {
+    let Some((scheme, rest)) = target.split_once("://") else {
+        return (None, target);
+    };
+    if scheme.is_empty() || !scheme.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return (None, target);
+    }
+    let end = rest.find(['/', '?']).unwrap_or(rest.len());
+    let origin = format!("{scheme}://{}", &rest[..end]);
+    let path = &rest[end..];
+    (Some(origin), if path.is_empty() { "/" } else { path })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(yaml: &str) -> RedirectConfig {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    fn redirect(
+        config: &RedirectConfig,
+        uri: &str,
+        host: &str,
+        secure: bool,
+        params: &HashMap<String, String>,
+    ) -> Option<(u16, String)> {
+        let uri: Uri = uri.parse().unwrap();
+        let request = RedirectRequest {
+            uri: &uri,
+            host: Some(host),
+            secure,
+        };
+        build_redirect(config, request, params)
+            .map(|mock| (mock.status, mock.headers["Location"].clone()))
+    }
+
+    #[test]
+    fn test_template_with_params_and_query() {
+        let config = config("status: 301\nlocation: /v2/users/{id}?src=old\n");
+        let params = HashMap::from([("id".to_string(), "7".to_string())]);
+
+        assert_eq!(
+            redirect(&config, "/users/7?lang=en", "example.com", false, &params),
+            Some((301, "/v2/users/7?src=old&lang=en".to_string()))
+        );
+
+        let dropped = RedirectConfig {
+            drop_query: true,
+            ..config
+        };
+        assert_eq!(
+            redirect(&dropped, "/users/7?lang=en", "example.com", false, &params),
+            Some((301, "/v2/users/7?src=old".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_absolute_template() {
+        let config = config("location: https://docs.example.com\n");
+        assert_eq!(
+            redirect(&config, "/docs?q=1", "example.com", false, &HashMap::new()),
+            Some((302, "https://docs.example.com/?q=1".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_force_https_only_for_plain_requests() {
+        let config = config("status: 308\nforce_https: true\n");
+
+        assert_eq!(
+            redirect(&config, "/a?b=1", "example.com", false, &HashMap::new()),
+            Some((308, "https://example.com/a?b=1".to_string()))
+        );
+        assert_eq!(
+            redirect(&config, "/a", "example.com", true, &HashMap::new()),
+            None
+        );
+    }
+
+    #[test]
+    fn test_trailing_slash_rules() {
+        let add = config("trailing_slash: add\n");
+        assert_eq!(
+            redirect(&add, "/docs", "example.com", false, &HashMap::new()),
+            Some((302, "/docs/".to_string()))
+        );
+        assert_eq!(
+            redirect(&add, "/docs/", "example.com", false, &HashMap::new()),
+            None
+        );
+
+        let strip = config("trailing_slash: strip\n");
+        assert_eq!(
+            redirect(&strip, "/docs//?x=1", "example.com", false, &HashMap::new()),
+            Some((302, "/docs?x=1".to_string()))
+        );
+        assert_eq!(
+            redirect(&strip, "/", "example.com", false, &HashMap::new()),
+            None
+        );
+    }
+
+    #[test]
+    fn test_canonical_host() {
+        let config = config("status: 301\nhost: www.example.com\n");
+
+        assert_eq!(
+            redirect(&config, "/p", "example.com", false, &HashMap::new()),
+            Some((301, "http://www.example.com/p".to_string()))
+        );
+        assert_eq!(
+            redirect(&config, "/p", "WWW.example.com", false, &HashMap::new()),
+            None
+        );
+        assert_eq!(
+            redirect(&config, "/p", "example.com", true, &HashMap::new()),
+            Some((301, "https://www.example.com/p".to_string()))
+        );
+    }
+}
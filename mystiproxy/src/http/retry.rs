@@ -0,0 +1,172 @@
+//! 代理请求重试模块
+//!
+//! 按引擎或 location 的 `retry` 配置判断上游失败是否可重试，并计算带抖动的指数退避。
+//! 重试需要重放请求体，因此只对 body 长度已知且不超过缓冲上限的请求生效。
+
+use std::time::Duration;
+
+use hyper::{Method, StatusCode};
+use rand::Rng;
+
+use crate::config::{RetryConfig, RetryOn};
+use crate::error::MystiProxyError;
+
+/// 默认最大尝试次数（含首次）
+const DEFAULT_ATTEMPTS: u32 = 3;
+
+/// 默认退避初始间隔
+const DEFAULT_BACKOFF: Duration = Duration::from_millis(25);
+
+/// 默认退避上限
+const DEFAULT_MAX_BACKOFF: Duration = Duration::from_millis(250);
+
+/// 默认可重试状态码
+const DEFAULT_RETRY_STATUS: [u16; 3] = [502, 503, 504];
+
+/// 默认允许重试的幂等方法
+const IDEMPOTENT_METHODS: [Method; 6] = [
+    Method::GET,
+    Method::HEAD,
+    Method::OPTIONS,
+    Method::PUT,
+    Method::DELETE,
+    Method::TRACE,
+];
+
+/// 已解析的重试策略
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    statuses: Vec<u16>,
+    retry_on: Vec<RetryOn>,
+    methods: Vec<Method>,
+    backoff: Duration,
+    max_backoff: Duration,
+    per_try_timeout: Option<Duration>,
+}
+
+impl RetryPolicy {
+    /// 由配置创建，未配置项取默认值
+    pub fn from_config(config: &RetryConfig) -> Self {
+        let methods = match &config.methods {
+            Some(methods) => methods
+                .iter()
+                .filter_map(|m| Method::from_bytes(m.to_ascii_uppercase().as_bytes()).ok())
+                .collect(),
+            None => IDEMPOTENT_METHODS.to_vec(),
+        };
+        Self {
+            max_attempts: config.attempts.unwrap_or(DEFAULT_ATTEMPTS).max(1),
+            statuses: config
+                .retry_on_status
+                .clone()
+                .unwrap_or_else(|| DEFAULT_RETRY_STATUS.to_vec()),
+            retry_on: config
+                .retry_on
+                .clone()
+                .unwrap_or_else(|| vec![RetryOn::ConnectError, RetryOn::Timeout]),
+            methods,
+            backoff: config.backoff.unwrap_or(DEFAULT_BACKOFF),
+            max_backoff: config.max_backoff.unwrap_or(DEFAULT_MAX_BACKOFF),
+            per_try_timeout: config.per_try_timeout,
+        }
+    }
+
+    /// 最大尝试次数（含首次）
+    pub fn max_attempts(&self) -> u32 {
+        self.max_attempts
+    }
+
+    /// 单次尝试超时
+    pub fn per_try_timeout(&self) -> Option<Duration> {
+        self.per_try_timeout
+    }
+
+    /// 该方法的请求是否允许重试
+    pub fn allows_method(&self, method: &Method) -> bool {
+        self.methods.contains(method)
+    }
+
+    /// 上游响应状态码是否触发重试
+    pub fn should_retry_status(&self, status: StatusCode) -> bool {
+        self.statuses.contains(&status.as_u16())
+    }
+
+    /// 上游错误是否触发重试
+    pub fn should_retry_error(&self, error: &MystiProxyError) -> bool {
+        let kind = match error {
+            MystiProxyError::Timeout => RetryOn::Timeout,
+            MystiProxyError::Io(_)
+            | MystiProxyError::Tls(_)
+            | MystiProxyError::Proxy(_)
+            | MystiProxyError::Hyper(_) => RetryOn::ConnectError,
+            _ => return false,
+        };
+        self.retry_on.contains(&kind)
+    }
+
+    /// 第 `retry` 次重试（从 1 开始）前的等待时间
+    ///
+    /// 间隔按 `backoff * 2^(retry-1)` 增长并以 `max_backoff` 封顶，
+    /// 实际等待在 [间隔/2, 间隔] 内随机取值，避免多个请求同时重试。
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let exponent = retry.saturating_sub(1).min(16);
+        let delay = self
+            .backoff
+            .saturating_mul(1 << exponent)
+            .min(self.max_backoff);
+        let half = delay / 2;
+        half + delay.mul_f64(rand::thread_rng().gen_range(0.0..=0.5))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_defaults() {
+        let policy = RetryPolicy::from_config(&RetryConfig::default());
+        assert_eq!(policy.max_attempts(), 3);
+        assert!(policy.allows_method(&Method::GET));
+        assert!(policy.allows_method(&Method::PUT));
+        assert!(!policy.allows_method(&Method::POST));
+        assert!(policy.should_retry_status(StatusCode::BAD_GATEWAY));
+        assert!(!policy.should_retry_status(StatusCode::INTERNAL_SERVER_ERROR));
+        assert!(policy.should_retry_error(&MystiProxyError::Timeout));
+        assert!(policy.should_retry_error(&MystiProxyError::Proxy("reset".to_string())));
+        assert!(!policy.should_retry_error(&MystiProxyError::Config("bad".to_string())));
+    }
+
+    #[test]
+    fn test_configured_methods_and_error_kinds() {
+        let policy = RetryPolicy::from_config(&RetryConfig {
+            methods: Some(vec!["post".to_string()]),
+            retry_on: Some(vec![RetryOn::Timeout]),
+            retry_on_status: Some(vec![500]),
+            ..Default::default()
+        });
+        assert!(policy.allows_method(&Method::POST));
+        assert!(!policy.allows_method(&Method::GET));
+        assert!(policy.should_retry_error(&MystiProxyError::Timeout));
+        assert!(!policy.should_retry_error(&MystiProxyError::Proxy("reset".to_string())));
+        assert!(policy.should_retry_status(StatusCode::INTERNAL_SERVER_ERROR));
+    }
+
+    #[test]
+    fn test_backoff_grows_with_jitter_and_cap() {
+        let policy = RetryPolicy::from_config(&RetryConfig {
+            backoff: Some(Duration::from_millis(100)),
+            max_backoff: Some(Duration::from_millis(300)),
+            ..Default::default()
+        });
+        for _ in 0..20 {
+            let first = policy.backoff(1);
+            assert!(first >= Duration::from_millis(50) && first <= Duration::from_millis(100));
+            let second = policy.backoff(2);
+            assert!(second >= Duration::from_millis(100) && second <= Duration::from_millis(200));
+            let capped = policy.backoff(10);
+            assert!(capped >= Duration::from_millis(150) && capped <= Duration::from_millis(300));
+        }
+    }
+}
@@ -1,9 +1,12 @@
 //! HTTP 服务器模块
 //!
-//! 提供 HTTP 服务器功能，支持 TCP 和 UDS 监听
+//! 提供 HTTP 服务器功能，支持 TCP 和 UDS 监听。
+//!
+//! 连接协议：默认仅 HTTP/1.1。开启 HTTP/2 后，TLS 连接按 ALPN 协商结果选择 HTTP/1.1 或
+//! HTTP/2，明文连接按首包自动识别 HTTP/1.1 与 prior-knowledge h2c。
 
-use std::convert::Infallible;
 use std::future::Future;
+use std::net::IpAddr;
 use std::pin::Pin;
 use std::sync::Arc;
 use std::time::Duration;
@@ -11,10 +14,10 @@ use std::time::Duration;
 use bytes::Bytes;
 use http_body_util::{BodyExt, Full};
 use hyper::body::Incoming;
-use hyper::server::conn::http1;
 use hyper::service::Service;
 use hyper::{Request, Response};
-use hyper_util::rt::TokioIo;
+use hyper_util::rt::{TokioExecutor, TokioIo};
+use hyper_util::server::conn::auto;
 use tracing::{error, info, warn};
 
 use crate::error::{MystiProxyError, Result};
@@ -22,7 +25,48 @@ use crate::io::{SocketStream, StreamListener};
 use crate::tls::{TlsConfig as TlsModuleConfig, TlsServer};
 
 /// BoxBody 类型别名
-pub type BoxBody = http_body_util::combinators::BoxBody<Bytes, Infallible>;
+pub type BoxBody = http_body_util::combinators::BoxBody<Bytes, MystiProxyError>;
+
+/// 客户端 IP，由服务器写入请求扩展（UDS 连接无此项）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClientIp(pub IpAddr);
+
+/// 为每个请求注入 `ClientIp` 扩展的服务包装
+#[derive(Clone)]
+struct WithClientIp<S> {
+    inner: S,
+    client_ip: Option<IpAddr>,
+}
+
+impl<S> Service<Request<Incoming>> for WithClientIp<S>
+where
+    S: Service<Request<Incoming>>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    fn call(&self, mut req: Request<Incoming>) -> Self::Future {
+        if let Some(ip) = self.client_ip {
+            req.extensions_mut().insert(ClientIp(ip));
+        }
+        self.inner.call(req)
+    }
+}
+
+/// 连接出错时的错误
+fn connection_error(e: impl std::fmt::Display) -> MystiProxyError {
+    MystiProxyError::Proxy(format!("Connection error: {e}"))
+}
+
+/// 单个连接使用的 HTTP 协议
+#[derive(Debug, Clone, Copy)]
+enum ConnectionProtocol {
+    Http1,
+    Http2,
+    /// 按连接首包识别 HTTP/1.1 或 h2c
+    Auto,
+}
 
 /// HTTP 服务器配置
 #[derive(Debug, Clone)]
@@ -31,12 +75,24 @@ pub struct HttpServerConfig {
     pub listen: String,
     /// 超时时间
     pub timeout: Option<Duration>,
+    /// 是否接受 HTTP/2（TLS ALPN h2 与明文 h2c）
+    pub http2: bool,
 }
 
 impl HttpServerConfig {
-    /// 创建新的服务器配置
+    /// 创建新的服务器配置（默认仅 HTTP/1.1，需经 `with_http2` 开启 HTTP/2）
     pub fn new(listen: String, timeout: Option<Duration>) -> Self {
-        Self { listen, timeout }
+        Self {
+            listen,
+            timeout,
+            http2: false,
+        }
+    }
+
+    /// 设置是否接受 HTTP/2（链式）
+    pub fn with_http2(mut self, enabled: bool) -> Self {
+        self.http2 = enabled;
+        self
     }
 }
 
@@ -44,7 +100,7 @@ impl HttpServerConfig {
 pub struct HttpServer<S>
 where
     S: Service<Request<Incoming>, Response = Response<BoxBody>> + Clone + Send + 'static,
-    S::Future: Send,
+    S::Future: Send + 'static,
     S::Error: std::error::Error + Send + Sync + 'static,
 {
     /// 配置
@@ -62,7 +118,7 @@ where
 impl<S> HttpServer<S>
 where
     S: Service<Request<Incoming>, Response = Response<BoxBody>> + Clone + Send + 'static,
-    S::Future: Send,
+    S::Future: Send + 'static,
     S::Error: std::error::Error + Send + Sync + 'static,
 {
     /// 创建新的 HTTP 服务器
@@ -93,6 +149,13 @@ where
             std::path::Path::new(&tls_config.key_path),
         )?;
 
+        // 未启用 HTTP/2 时仅通告 http/1.1，避免客户端协商到不受支持的 h2
+        let tls_module_config = if config.http2 {
+            tls_module_config.with_alpn_protocols(vec![b"h2", b"http/1.1"])
+        } else {
+            tls_module_config.with_alpn_protocols(vec![b"http/1.1"])
+        };
+
         let tls_module_config = if tls_config.mutual_auth {
             if let Some(client_ca_path) = &tls_config.client_ca_path {
                 tls_module_config.with_client_ca(std::path::Path::new(client_ca_path))?
@@ -148,14 +211,19 @@ where
 
                     info!("Accepted HTTP connection from {}", addr);
 
-                    let service = self.service.clone();
+                    let service = WithClientIp {
+                        inner: self.service.clone(),
+                        client_ip: addr.ip(),
+                    };
                     let timeout = self.config.timeout;
+                    let http2 = self.config.http2;
                     let tls_server = self.tls_server.clone();
 
                     // 为每个连接创建新任务
                     tokio::spawn(async move {
                         if let Err(e) =
-                            Self::handle_connection(stream, service, timeout, tls_server).await
+                            Self::handle_connection(stream, service, timeout, http2, tls_server)
+                                .await
                         {
                             error!("Connection error: {}", e);
                         }
@@ -171,47 +239,79 @@ where
     /// 处理单个连接
     async fn handle_connection(
         stream: SocketStream,
-        service: S,
+        service: WithClientIp<S>,
         timeout: Option<Duration>,
+        http2: bool,
         tls_server: Option<Arc<TlsServer>>,
     ) -> Result<()> {
         if let Some(tls_server) = tls_server {
             let tls_stream = tls_server.accept(stream).await?;
+            let protocol = match tls_stream.get_ref().1.alpn_protocol() {
+                Some(b"h2") => ConnectionProtocol::Http2,
+                _ => ConnectionProtocol::Http1,
+            };
             let io = TokioIo::new(tls_stream);
-            Self::serve_connection(io, service, timeout).await
+            Self::serve_connection(io, service, timeout, protocol).await
         } else {
+            let protocol = if http2 {
+                ConnectionProtocol::Auto
+            } else {
+                ConnectionProtocol::Http1
+            };
             let io = TokioIo::new(stream);
-            Self::serve_connection(io, service, timeout).await
+            Self::serve_connection(io, service, timeout, protocol).await
         }
     }
 
     /// 处理连接服务
     async fn serve_connection(
         io: impl hyper::rt::Read + hyper::rt::Write + Unpin + Send + 'static,
-        service: S,
+        service: WithClientIp<S>,
         timeout: Option<Duration>,
+        protocol: ConnectionProtocol,
     ) -> Result<()> {
-        // 创建 HTTP/1.1 服务
-        let conn = http1::Builder::new()
-            .preserve_header_case(true)
-            .title_case_headers(true)
-            .serve_connection(io, service)
-            .with_upgrades();
+        // auto 的 serve_connection_with_upgrades 不理会 http1_only/http2_only，
+        // 因此单协议连接直接使用对应版本的 Builder
+        let conn: Pin<Box<dyn Future<Output = Result<()>> + Send>> = match protocol {
+            ConnectionProtocol::Http1 => {
+                let conn = hyper::server::conn::http1::Builder::new()
+                    .preserve_header_case(true)
+                    .title_case_headers(true)
+                    .serve_connection(io, service)
+                    .with_upgrades();
+                Box::pin(async move { conn.await.map_err(connection_error) })
+            }
+            ConnectionProtocol::Http2 => {
+                let conn = hyper::server::conn::http2::Builder::new(TokioExecutor::new())
+                    .serve_connection(io, service);
+                Box::pin(async move { conn.await.map_err(connection_error) })
+            }
+            ConnectionProtocol::Auto => {
+                let mut builder = auto::Builder::new(TokioExecutor::new());
+                builder
+                    .http1()
+                    .preserve_header_case(true)
+                    .title_case_headers(true);
+                Box::pin(async move {
+                    builder
+                        .serve_connection_with_upgrades(io, service)
+                        .await
+                        .map_err(connection_error)
+                })
+            }
+        };
 
         // 应用超时
         if let Some(duration) = timeout {
             match tokio::time::timeout(duration, conn).await {
-                Ok(result) => {
-                    result.map_err(|e| MystiProxyError::Proxy(format!("Connection error: {e}")))?;
-                }
+                Ok(result) => result?,
                 Err(_) => {
                     warn!("Connection timed out after {:?}", duration);
                     return Err(MystiProxyError::Timeout);
                 }
             }
         } else {
-            conn.await
-                .map_err(|e| MystiProxyError::Proxy(format!("Connection error: {e}")))?;
+            conn.await?;
         }
 
         Ok(())
@@ -341,6 +441,7 @@ mod tests {
     fn test_server_config_creation() {
         let config = HttpServerConfig::new("tcp://0.0.0.0:8080".to_string(), None);
         assert_eq!(config.listen, "tcp://0.0.0.0:8080");
+        assert!(!config.http2);
     }
 
     #[test]
@@ -1,17 +1,27 @@
 //! 静态文件服务模块
 //!
-//! 提供静态文件服务功能，包括目录映射、文件读取和 MIME 类型检测
+//! 提供静态文件服务功能，包括目录映射、文件读取和 MIME 类型检测；
+//! 客户端接受对应编码时优先返回预压缩的同名 `.br`/`.gz` 文件
 
 use std::path::{Path, PathBuf};
 
 use bytes::Bytes;
 use http_body_util::{BodyExt, Full};
+use hyper::header::HeaderValue;
 use hyper::{header, Response, StatusCode};
 use tracing::{debug, warn};
 
+use crate::config::CompressionAlgorithm;
 use crate::error::{MystiProxyError, Result};
+use crate::http::compression::{append_vary_accept_encoding, negotiate_encoding};
 use crate::http::handler::BoxBody;
 
+/// 预压缩文件的编码与扩展名，按偏好排序
+const PRECOMPRESSED: [(CompressionAlgorithm, &str); 2] = [
+    (CompressionAlgorithm::Br, "br"),
+    (CompressionAlgorithm::Gzip, "gz"),
+];
+
 /// 静态文件服务配置
 #[derive(Debug, Clone)]
 pub struct StaticFileConfig {
@@ -120,7 +130,26 @@ impl StaticFileService {
         uri: &str,
         range_header: Option<&str>,
     ) -> Result<Response<BoxBody>> {
-        self.serve_internal(uri, range_header).await
+        self.serve_internal(uri, range_header, None).await
+    }
+
+    /// 提供静态文件服务（支持范围请求与预压缩文件）
+    ///
+    /// # 参数
+    /// - `uri`: 请求的 URI 路径
+    /// - `range_header`: 可选的 Range 头部；范围请求总是返回原文件
+    /// - `accept_encoding`: 可选的 Accept-Encoding 头部
+    ///
+    /// # 返回
+    /// 返回 HTTP 响应
+    pub async fn serve_with_encoding(
+        &self,
+        uri: &str,
+        range_header: Option<&str>,
+        accept_encoding: Option<&HeaderValue>,
+    ) -> Result<Response<BoxBody>> {
+        self.serve_internal(uri, range_header, accept_encoding)
+            .await
     }
 
     /// 内部实现：提供静态文件服务
@@ -128,6 +157,7 @@ impl StaticFileService {
         &self,
         uri: &str,
         range_header: Option<&str>,
+        accept_encoding: Option<&HeaderValue>,
     ) -> Result<Response<BoxBody>> {
         let path = self.uri_to_path(uri);
 
@@ -186,6 +216,31 @@ impl StaticFileService {
                 .await;
         }
 
+        // 查找客户端可接受的预压缩文件
+        let siblings = self.precompressed_siblings(&canonical_path, &canonical_root);
+        let candidates: Vec<CompressionAlgorithm> = siblings.iter().map(|(a, _)| *a).collect();
+        if let Some(algorithm) = negotiate_encoding(accept_encoding, &candidates) {
+            if let Some((_, sibling)) = siblings.iter().find(|(a, _)| *a == algorithm) {
+                let content = tokio::fs::read(sibling).await?;
+                debug!(
+                    "Serving precompressed file: {:?}, encoding: {}, size: {} bytes",
+                    sibling,
+                    algorithm.as_str(),
+                    content.len()
+                );
+
+                let mut response = Response::builder()
+                    .status(StatusCode::OK)
+                    .header(header::CONTENT_TYPE, mime_type)
+                    .header(header::CONTENT_ENCODING, algorithm.as_str())
+                    .header(header::CONTENT_LENGTH, content.len())
+                    .body(Self::full_body(Bytes::from(content)))
+                    .map_err(MystiProxyError::Http)?;
+                append_vary_accept_encoding(response.headers_mut());
+                return Ok(response);
+            }
+        }
+
         // 读取整个文件
         let content = tokio::fs::read(&canonical_path).await?;
 
@@ -197,17 +252,40 @@ impl StaticFileService {
         );
 
         // 构建响应
-        let response = Response::builder()
+        let mut response = Response::builder()
             .status(StatusCode::OK)
             .header(header::CONTENT_TYPE, mime_type)
             .header(header::CONTENT_LENGTH, content.len())
             .header(header::ACCEPT_RANGES, "bytes")
             .body(Self::full_body(Bytes::from(content)))
             .map_err(MystiProxyError::Http)?;
+        // 存在预压缩文件时，响应内容随 Accept-Encoding 变化
+        if !siblings.is_empty() {
+            append_vary_accept_encoding(response.headers_mut());
+        }
 
         Ok(response)
     }
 
+    /// 查找文件旁的预压缩文件（如 `app.js.br`、`app.js.gz`），仅返回根目录内的普通文件
+    fn precompressed_siblings(
+        &self,
+        path: &Path,
+        canonical_root: &Path,
+    ) -> Vec<(CompressionAlgorithm, PathBuf)> {
+        PRECOMPRESSED
+            .iter()
+            .filter_map(|(algorithm, extension)| {
+                let mut sibling = path.as_os_str().to_owned();
+                sibling.push(".");
+                sibling.push(extension);
+                let sibling = PathBuf::from(sibling).canonicalize().ok()?;
+                (sibling.starts_with(canonical_root) && sibling.is_file())
+                    .then_some((*algorithm, sibling))
+            })
+            .collect()
+    }
+
     /// 处理范围请求
     fn serve_range<'a>(
         &'a self,
@@ -357,44 +435,7 @@ impl StaticFileService {
     ///
     /// 根据文件扩展名确定 Content-Type
     fn get_mime_type(&self, path: &Path) -> &'static str {
-        match path.extension().and_then(|e| e.to_str()) {
-            Some("html") | Some("htm") => "text/html; charset=utf-8",
-            Some("css") => "text/css; charset=utf-8",
-            Some("js") => "application/javascript",
-            Some("json") => "application/json",
-            Some("xml") => "application/xml",
-            Some("txt") => "text/plain; charset=utf-8",
-            Some("png") => "image/png",
-            Some("jpg") | Some("jpeg") => "image/jpeg",
-            Some("gif") => "image/gif",
-            Some("svg") => "image/svg+xml",
-            Some("ico") => "image/x-icon",
-            Some("webp") => "image/webp",
-            Some("woff") => "font/woff",
-            Some("woff2") => "font/woff2",
-            Some("ttf") => "font/ttf",
-            Some("otf") => "font/otf",
-            Some("eot") => "application/vnd.ms-fontobject",
-            Some("pdf") => "application/pdf",
-            Some("zip") => "application/zip",
-            Some("tar") => "application/x-tar",
-            Some("gz") => "application/gzip",
-            Some("mp3") => "audio/mpeg",
-            Some("mp4") => "video/mp4",
-            Some("webm") => "video/webm",
-            Some("avi") => "video/x-msvideo",
-            Some("doc") => "application/msword",
-            Some("docx") => {
-                "application/vnd.openxmlformats-officedocument.wordprocessingml.document"
-            }
-            Some("xls") => "application/vnd.ms-excel",
-            Some("xlsx") => "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
-            Some("ppt") => "application/vnd.ms-powerpoint",
-            Some("pptx") => {
-                "application/vnd.openxmlformats-officedocument.presentationml.presentation"
-            }
-            _ => "application/octet-stream",
-        }
+        mime_type(path)
     }
 
     /// 创建完整响应体
@@ -403,6 +444,44 @@ impl StaticFileService {
     }
 }
 
+/// 按文件扩展名推断 MIME 类型，未知扩展名为 `application/octet-stream`
+pub fn mime_type(path: &Path) -> &'static str {
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("html") | Some("htm") => "text/html; charset=utf-8",
+        Some("css") => "text/css; charset=utf-8",
+        Some("js") => "application/javascript",
+        Some("json") => "application/json",
+        Some("xml") => "application/xml",
+        Some("txt") => "text/plain; charset=utf-8",
+        Some("png") => "image/png",
+        Some("jpg") | Some("jpeg") => "image/jpeg",
+        Some("gif") => "image/gif",
+        Some("svg") => "image/svg+xml",
+        Some("ico") => "image/x-icon",
+        Some("webp") => "image/webp",
+        Some("woff") => "font/woff",
+        Some("woff2") => "font/woff2",
+        Some("ttf") => "font/ttf",
+        Some("otf") => "font/otf",
+        Some("eot") => "application/vnd.ms-fontobject",
+        Some("pdf") => "application/pdf",
+        Some("zip") => "application/zip",
+        Some("tar") => "application/x-tar",
+        Some("gz") => "application/gzip",
+        Some("mp3") => "audio/mpeg",
+        Some("mp4") => "video/mp4",
+        Some("webm") => "video/webm",
+        Some("avi") => "video/x-msvideo",
+        Some("doc") => "application/msword",
+        Some("docx") => "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+        Some("xls") => "application/vnd.ms-excel",
+        Some("xlsx") => "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+        Some("ppt") => "application/vnd.ms-powerpoint",
+        Some("pptx") => "application/vnd.openxmlformats-officedocument.presentationml.presentation",
+        _ => "application/octet-stream",
+    }
+}
+
 /// URL 解码（简单实现）
 fn url_decode(s: &str) -> String {
     let mut result = String::new();
@@ -613,6 +692,58 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_serve_precompressed_sibling() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = temp_dir.path().to_path_buf();
+        fs::write(root.join("app.js"), "console.log(1)").unwrap();
+        fs::write(root.join("app.js.gz"), "gzip-bytes").unwrap();
+        fs::write(root.join("app.js.br"), "br-bytes").unwrap();
+
+        let service = StaticFileService::new(root);
+        let collect = |response: Response<BoxBody>| async move {
+            response.into_body().collect().await.unwrap().to_bytes()
+        };
+
+        let accept = HeaderValue::from_static("gzip, br");
+        let response = service
+            .serve_with_encoding("/app.js", None, Some(&accept))
+            .await
+            .unwrap();
+        assert_eq!(response.headers()[header::CONTENT_ENCODING], "br");
+        assert_eq!(
+            response.headers()[header::CONTENT_TYPE],
+            "application/javascript"
+        );
+        assert_eq!(response.headers()[header::VARY], "accept-encoding");
+        assert_eq!(&collect(response).await[..], b"br-bytes");
+
+        let accept = HeaderValue::from_static("gzip");
+        let response = service
+            .serve_with_encoding("/app.js", None, Some(&accept))
+            .await
+            .unwrap();
+        assert_eq!(response.headers()[header::CONTENT_ENCODING], "gzip");
+        assert_eq!(&collect(response).await[..], b"gzip-bytes");
+
+        // 不接受压缩或范围请求时返回原文件
+        let response = service
+            .serve_with_encoding("/app.js", None, None)
+            .await
+            .unwrap();
+        assert!(!response.headers().contains_key(header::CONTENT_ENCODING));
+        assert_eq!(response.headers()[header::VARY], "accept-encoding");
+        assert_eq!(&collect(response).await[..], b"console.log(1)");
+
+        let accept = HeaderValue::from_static("br");
+        let response = service
+            .serve_with_encoding("/app.js", Some("bytes=0-6"), Some(&accept))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+        assert!(!response.headers().contains_key(header::CONTENT_ENCODING));
+    }
+
     #[tokio::test]
     async fn test_uri_to_path() {
         let temp_dir = tempfile::tempdir().unwrap();
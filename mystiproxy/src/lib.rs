@@ -1,5 +1,7 @@
 //! MystiProxy - 灵活的 HTTP 代理服务器，支持 Mock 功能
 
+pub mod balancer;
+pub mod circuit_breaker;
 pub mod config;
 pub mod context;
 pub mod error;
@@ -9,6 +11,7 @@ pub mod ip_filter;
 pub mod metrics;
 pub mod mock;
 pub mod proxy;
+pub mod rate_limit;
 pub mod router;
 pub mod tls;
 
@@ -2,6 +2,7 @@ use std::net::SocketAddr;
 use std::sync::Arc;
 
 use clap::Parser;
+use mystiproxy::balancer::{spawn_active_health_checks, LoadBalancer};
 use mystiproxy::config::{EngineConfig, MystiConfig, ProxyType};
 use mystiproxy::http::{
     create_handler, HttpProxyAcceptor, HttpProxyConfig, HttpServer, HttpServerConfig,
@@ -144,9 +145,15 @@ async fn main() -> Result<()> {
 
     for (name, engine_config) in engines {
         let name_clone = name.clone();
+        // 构建期间（如熔断器）按当前线程的引擎名记录日志与指标
+        set_engine_name(&name_clone);
 
         // F9: 本地管理模块（feature local-management；FR-068）
         #[cfg(feature = "local-management")]
+        let mut local_mocks = None;
+        #[cfg(feature = "local-management")]
+        let mut response_sequences = None;
+        #[cfg(feature = "local-management")]
         if let Some(mgmt) = engine_config
             .management
             .as_ref()
@@ -168,6 +175,8 @@ async fn main() -> Result<()> {
                 Ok(lm) => {
                     let listen = mgmt.listen.clone().unwrap();
                     let router = lm.create_router();
+                    local_mocks = Some(lm.mock_matcher());
+                    response_sequences = Some(lm.response_sequences());
                     lm.start_sync().await.ok();
                     let mgmt_name = name_clone.clone();
                     tasks.spawn(async move {
@@ -214,6 +223,10 @@ async fn main() -> Result<()> {
                         server.target_addr(),
                     );
 
+                    if let Some(balancer) = server.balancer() {
+                        watch_upstreams(&name_clone, balancer, &engine_config);
+                    }
+
                     let engine_name = name_clone.clone();
                     tasks.spawn(async move {
                         set_engine_name(&engine_name);
@@ -232,6 +245,28 @@ async fn main() -> Result<()> {
                         continue;
                     }
                 };
+                // 本地管理的 mock 在数据面生效（API 增删改与同步拉取无需重启）
+                #[cfg(feature = "local-management")]
+                let handler = match local_mocks.take() {
+                    Some(matcher) => handler.with_local_mocks(matcher),
+                    None => handler,
+                };
+                // 响应序列计数与本地管理 API 共享，便于查看与重置
+                #[cfg(feature = "local-management")]
+                let handler = match response_sequences.take() {
+                    Some(counters) => handler.with_response_sequences(counters),
+                    None => handler,
+                };
+                let balancer = handler.balancer();
+                // 指标服务无鉴权，仅在显式开启时开放缓存清除
+                if let Some(cache) = handler.response_cache().filter(|_| {
+                    engine_config
+                        .cache
+                        .as_ref()
+                        .is_some_and(|c| c.purge_endpoint)
+                }) {
+                    mystiproxy::metrics::global_metrics().register_cache(&name_clone, cache);
+                }
 
                 let ip_filter = match mystiproxy::ip_filter::IpFilter::from_config(
                     &engine_config.allow,
@@ -244,15 +279,14 @@ async fn main() -> Result<()> {
                     }
                 };
 
+                let server_config = HttpServerConfig::new(
+                    engine_config.listen.clone(),
+                    engine_config.request_timeout,
+                )
+                .with_http2(engine_config.http2.as_ref().is_some_and(|h| h.server));
+
                 let mut server = if let Some(tls_config) = &engine_config.tls {
-                    match HttpServer::new_with_tls(
-                        HttpServerConfig::new(
-                            engine_config.listen.clone(),
-                            engine_config.request_timeout,
-                        ),
-                        handler,
-                        tls_config,
-                    ) {
+                    match HttpServer::new_with_tls(server_config, handler, tls_config) {
                         Ok(s) => s,
                         Err(e) => {
                             error!("创建 HTTPS 服务器 '{}' 失败: {}", name_clone, e);
@@ -260,14 +294,7 @@ async fn main() -> Result<()> {
                         }
                     }
                 } else {
-                    HttpServer::new(
-                        HttpServerConfig::new(
-                            engine_config.listen.clone(),
-                            engine_config.request_timeout,
-                        ),
-                        handler,
-                        None,
-                    )
+                    HttpServer::new(server_config, handler, None)
                 }
                 .with_ip_filter(ip_filter);
 
@@ -288,6 +315,10 @@ async fn main() -> Result<()> {
                     }
                 );
 
+                if let Some(balancer) = balancer {
+                    watch_upstreams(&name_clone, balancer, &engine_config);
+                }
+
                 let engine_name = name_clone.clone();
                 tasks.spawn(async move {
                     set_engine_name(&engine_name);
@@ -376,6 +407,22 @@ async fn main() -> Result<()> {
     Ok(())
 }
 
+/// 导出多目标引擎的上游健康状态，并按配置启动主动健康检查
+fn watch_upstreams(name: &str, balancer: Arc<LoadBalancer>, engine_config: &EngineConfig) {
+    mystiproxy::metrics::global_metrics().register_upstreams(name, balancer.clone());
+    if let Some(active) = engine_config
+        .health_check
+        .as_ref()
+        .and_then(|h| h.active.clone())
+    {
+        info!(
+            "引擎 '{}' 已启用主动健康检查 ({:?})",
+            name, active.check_type
+        );
+        spawn_active_health_checks(name.to_string(), balancer, active);
+    }
+}
+
 /// 解析 tcp://host:port 形式的监听地址
 fn parse_tcp_listen(addr: &str) -> std::result::Result<SocketAddr, mystiproxy::MystiProxyError> {
     let stripped = addr.strip_prefix("tcp://").ok_or_else(|| {
@@ -413,7 +460,7 @@ fn load_config(args: &MystiArg) -> Result<MystiConfig> {
 
         let engine_config = EngineConfig {
             listen: listen.clone(),
-            target: target.clone(),
+            target: target.clone().into(),
             proxy_type: ProxyType::Tcp,
             request_timeout: None,
             connection_timeout: None,
@@ -425,6 +472,20 @@ fn load_config(args: &MystiArg) -> Result<MystiConfig> {
             allow: None,
             deny: None,
             management: None,
+            max_buffered_body_size: None,
+            load_balance: None,
+            health_check: None,
+            http2: None,
+            connection_pool: None,
+            upstream_tls: None,
+            retry: None,
+            circuit_breaker: None,
+            rate_limit: None,
+            connection_limit: None,
+            cache: None,
+            compression: None,
+            gateway: None,
+            mock_root: None,
         };
 
         let mut engine_map = HashMap::new();
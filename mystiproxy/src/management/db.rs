@@ -10,7 +10,7 @@ use tracing::{info, warn};
 use super::error::Result;
 
 /// Database schema version
-const SCHEMA_VERSION: i32 = 1;
+const SCHEMA_VERSION: i32 = 2;
 
 /// Create a new SQLite connection pool
 pub async fn create_pool(db_path: &Path) -> Result<SqlitePool> {
@@ -94,6 +94,7 @@ async fn run_migrations(pool: &SqlitePool) -> Result<()> {
 async fn migrate_to_version(pool: &SqlitePool, version: i32) -> Result<()> {
     match version {
         1 => migrate_v1(pool).await,
+        2 => migrate_v2(pool).await,
         _ => {
             warn!("Unknown migration version: {}", version);
             Ok(())
@@ -176,6 +177,16 @@ async fn migrate_v1(pool: &SqlitePool) -> Result<()> {
     Ok(())
 }
 
+/// Migration to version 2: State machine configuration for stateful mocks
+async fn migrate_v2(pool: &SqlitePool) -> Result<()> {
+    info!("Applying migration v2: state_config column");
+
+    pool.execute("ALTER TABLE mock_configurations ADD COLUMN state_config TEXT")
+        .await?;
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -18,6 +18,8 @@ use super::error::ManagementError;
 type Result<T, E = ApiError> = std::result::Result<T, E>;
 use super::models::{CreateMockRequest, MockConfiguration, MockFilter, UpdateMockRequest};
 use super::repository::{LocalMockRepository, MockRepository};
+use super::scenario::{ScenarioStore, SessionState};
+use crate::mock::sequence::{SequenceCounter, SequenceCounters};
 
 /// API response wrapper
 #[derive(Debug, Serialize)]
@@ -113,6 +115,10 @@ pub struct HandlerState {
     pub last_sync: Option<String>,
     /// Current sync status
     pub status: String,
+    /// Stateful mock scenarios
+    pub scenarios: Arc<ScenarioStore>,
+    /// Response sequence counters of YAML mock locations
+    pub sequences: Arc<SequenceCounters>,
 }
 
 impl HandlerState {
@@ -122,9 +128,23 @@ impl HandlerState {
             sync_enabled: false,
             last_sync: None,
             status: "local".to_string(),
+            scenarios: Arc::new(ScenarioStore::new()),
+            sequences: Arc::new(SequenceCounters::new()),
         }
     }
 
+    /// Share scenario state with the data-path matcher
+    pub fn with_scenarios(mut self, scenarios: Arc<ScenarioStore>) -> Self {
+        self.scenarios = scenarios;
+        self
+    }
+
+    /// Share response sequence counters with the HTTP handler
+    pub fn with_sequences(mut self, sequences: Arc<SequenceCounters>) -> Self {
+        self.sequences = sequences;
+        self
+    }
+
     /// Create state with sync information
     pub fn with_sync(
         repository: LocalMockRepository,
@@ -137,6 +157,8 @@ impl HandlerState {
             sync_enabled,
             last_sync,
             status,
+            scenarios: Arc::new(ScenarioStore::new()),
+            sequences: Arc::new(SequenceCounters::new()),
         }
     }
 }
@@ -231,12 +253,74 @@ pub async fn delete_mock(
     let deleted = state.repository.delete(id).await?;
 
     if deleted {
+        state.scenarios.reset(id, None);
         Ok(StatusCode::NO_CONTENT)
     } else {
         Err(ManagementError::not_found(id).into())
     }
 }
 
+/// Get the scenario state of a stateful mock
+///
+/// GET /api/v1/mocks/:id/scenario
+pub async fn get_scenario(
+    State(state): State<HandlerState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<ScenarioResponse>>, ApiError> {
+    let config = state
+        .repository
+        .find_by_id(id)
+        .await?
+        .ok_or_else(|| ManagementError::not_found(id))?;
+    let state_config = config
+        .state_config
+        .ok_or_else(|| ApiError::Validation("Mock has no state_config".to_string()))?;
+
+    Ok(Json(ApiResponse::success(ScenarioResponse {
+        mock_id: id,
+        initial_state: state_config.initial_state,
+        sessions: state.scenarios.sessions(id),
+    })))
+}
+
+/// Reset the scenario of a mock to its initial state
+///
+/// DELETE /api/v1/mocks/:id/scenario[?session=...]
+pub async fn reset_scenario(
+    State(state): State<HandlerState>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<ScenarioQuery>,
+) -> Result<StatusCode, ApiError> {
+    state
+        .repository
+        .find_by_id(id)
+        .await?
+        .ok_or_else(|| ManagementError::not_found(id))?;
+
+    state.scenarios.reset(id, query.session.as_deref());
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// List response sequence counters of mock locations
+///
+/// GET /api/v1/sequences
+pub async fn list_sequences(
+    State(state): State<HandlerState>,
+) -> Json<ApiResponse<Vec<SequenceCounter>>> {
+    Json(ApiResponse::success(state.sequences.counters()))
+}
+
+/// Reset response sequence counters
+///
+/// DELETE /api/v1/sequences[?location=...]
+pub async fn reset_sequences(
+    State(state): State<HandlerState>,
+    Query(query): Query<SequenceQuery>,
+) -> StatusCode {
+    state.sequences.reset(query.location.as_deref());
+    StatusCode::NO_CONTENT
+}
+
 /// Batch create mock configurations
 ///
 /// POST /api/v1/mocks/batch
@@ -356,6 +440,29 @@ pub async fn trigger_sync(
     })))
 }
 
+/// Scenario state response
+#[derive(Debug, Serialize)]
+pub struct ScenarioResponse {
+    pub mock_id: Uuid,
+    pub initial_state: String,
+    /// Sessions that left the initial state
+    pub sessions: Vec<SessionState>,
+}
+
+/// Query parameters for scenario reset
+#[derive(Debug, Deserialize)]
+pub struct ScenarioQuery {
+    /// Reset only this session
+    pub session: Option<String>,
+}
+
+/// Query parameters for sequence reset
+#[derive(Debug, Deserialize)]
+pub struct SequenceQuery {
+    /// Reset only the counters of this location path
+    pub location: Option<String>,
+}
+
 /// Sync status response
 #[derive(Debug, Serialize)]
 pub struct SyncStatusResponse {
@@ -415,12 +522,20 @@ pub fn create_management_router(state: HandlerState) -> Router {
             "/api/v1/mocks/:id",
             get(get_mock).put(update_mock).delete(delete_mock),
         )
+        .route(
+            "/api/v1/mocks/:id/scenario",
+            get(get_scenario).delete(reset_scenario),
+        )
         .route(
             "/api/v1/mocks/batch",
             post(batch_create_mocks)
                 .put(batch_update_mocks)
                 .delete(batch_delete_mocks),
         )
+        .route(
+            "/api/v1/sequences",
+            get(list_sequences).delete(reset_sequences),
+        )
         .route("/api/v1/sync/status", get(get_sync_status))
         .route("/api/v1/sync/trigger", post(trigger_sync))
         .with_state(state)
@@ -458,6 +573,7 @@ mod tests {
             method: HttpMethod::Get,
             matching_rules: Default::default(),
             response_config: Default::default(),
+            state_config: None,
             is_active: true,
         };
 
@@ -490,6 +606,7 @@ mod tests {
                 method: HttpMethod::Get,
                 matching_rules: Default::default(),
                 response_config: Default::default(),
+                state_config: None,
                 is_active: true,
             })
             .await
@@ -509,4 +626,112 @@ mod tests {
 
         assert_eq!(response.status(), StatusCode::OK);
     }
+
+    #[tokio::test]
+    async fn test_inspect_and_reset_scenario() {
+        let state = create_test_state().await;
+        let state_config = serde_json::from_value(serde_json::json!({
+            "initial_state": "pending",
+            "transitions": [{"from_state": "pending", "to_state": "shipped"}]
+        }))
+        .unwrap();
+        let config = state
+            .repository
+            .create(CreateMockRequest {
+                name: "Order".to_string(),
+                path: "/orders/1".to_string(),
+                method: HttpMethod::Get,
+                matching_rules: Default::default(),
+                response_config: Default::default(),
+                state_config: Some(state_config),
+                is_active: true,
+            })
+            .await
+            .unwrap();
+
+        let matcher = crate::management::LocalMockMatcher::new(state.repository.clone())
+            .with_scenarios(state.scenarios.clone());
+        let headers = axum::http::HeaderMap::new();
+        matcher
+            .find("GET", "/orders/1", &headers, None)
+            .await
+            .unwrap();
+
+        let app = create_management_router(state);
+        let uri = format!("/api/v1/mocks/{}/scenario", config.id);
+        let get = |app: Router| {
+            let uri = uri.clone();
+            async move {
+                let response = app
+                    .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+                    .await
+                    .unwrap();
+                assert_eq!(response.status(), StatusCode::OK);
+                let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                    .await
+                    .unwrap();
+                serde_json::from_slice::<serde_json::Value>(&body).unwrap()
+            }
+        };
+
+        let body = get(app.clone()).await;
+        assert_eq!(body["data"]["initial_state"], "pending");
+        assert_eq!(body["data"]["sessions"][0]["state"], "shipped");
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method(Method::DELETE)
+                    .uri(&uri)
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+        let body = get(app).await;
+        assert_eq!(body["data"]["sessions"], serde_json::json!([]));
+    }
+
+    #[tokio::test]
+    async fn test_list_and_reset_sequences() {
+        let state = create_test_state().await;
+        state.sequences.next("a", "/a");
+        state.sequences.next("b", "/b");
+
+        let app = create_management_router(state.clone());
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v1/sequences")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["data"][0]["location"], "/a");
+        assert_eq!(body["data"][0]["count"], 1);
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(Method::DELETE)
+                    .uri("/api/v1/sequences?location=/a")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        assert_eq!(state.sequences.counters().len(), 1);
+        assert_eq!(state.sequences.counters()[0].location, "/b");
+    }
 }
@@ -8,7 +8,7 @@ use tracing::{info, warn};
 use super::error::{ManagementError, Result};
 use super::models::{
     BodyMatchType, CreateMockRequest, MatchingRules, MockConfiguration, ResponseBody,
-    ResponseBodyType, ResponseConfig,
+    ResponseBodyType, ResponseConfig, StateConfig,
 };
 use super::repository::MockRepository;
 
@@ -47,6 +47,10 @@ pub struct MockEntry {
     /// Response configuration
     pub response: ResponseEntry,
 
+    /// State machine (optional, for stateful scenarios)
+    #[serde(default)]
+    pub state: Option<StateConfig>,
+
     /// Whether this mock is active
     #[serde(default = "default_active")]
     pub active: bool,
@@ -172,6 +176,7 @@ impl MockEntry {
                 .map(|m| m.to_matching_rules(&self.path))
                 .unwrap_or_default(),
             response_config: self.response.to_response_config()?,
+            state_config: self.state.clone(),
             is_active: self.active,
         })
     }
@@ -352,6 +357,7 @@ pub async fn import_from_file<R: MockRepository>(
                     request.matching_rules.clone(),
                     request.response_config.clone(),
                 );
+                config.state_config = request.state_config;
                 config.is_active = request.is_active;
                 config.update_content_hash();
 
@@ -8,8 +8,11 @@ use tracing::info;
 use super::config::LocalManagementConfig;
 use super::db;
 use super::handlers::{create_management_router, HandlerState};
+use super::matcher::LocalMockMatcher;
 use super::repository::LocalMockRepository;
+use super::scenario::ScenarioStore;
 use super::sync::SyncClient;
+use crate::mock::sequence::SequenceCounters;
 
 /// Local management integration
 pub struct LocalManagement {
@@ -19,6 +22,10 @@ pub struct LocalManagement {
     repository: Arc<LocalMockRepository>,
     /// Sync client (if enabled)
     sync_client: Option<std::sync::Arc<SyncClient<LocalMockRepository>>>,
+    /// Stateful mock scenarios, shared by the data path and the API
+    scenarios: Arc<ScenarioStore>,
+    /// Response sequence counters of the engine's mock locations
+    sequences: Arc<SequenceCounters>,
 }
 
 impl LocalManagement {
@@ -32,6 +39,8 @@ impl LocalManagement {
                     db::create_memory_pool().await?,
                 )),
                 sync_client: None,
+                scenarios: Arc::new(ScenarioStore::new()),
+                sequences: Arc::new(SequenceCounters::new()),
             });
         }
 
@@ -65,6 +74,8 @@ impl LocalManagement {
             config,
             repository,
             sync_client,
+            scenarios: Arc::new(ScenarioStore::new()),
+            sequences: Arc::new(SequenceCounters::new()),
         })
     }
 
@@ -73,6 +84,18 @@ impl LocalManagement {
         self.repository.clone()
     }
 
+    /// Create a matcher serving the active mocks of this repository on the data path
+    pub fn mock_matcher(&self) -> Arc<LocalMockMatcher> {
+        Arc::new(
+            LocalMockMatcher::new(self.repository.clone()).with_scenarios(self.scenarios.clone()),
+        )
+    }
+
+    /// Response sequence counters to share with the engine's HTTP handler
+    pub fn response_sequences(&self) -> Arc<SequenceCounters> {
+        self.sequences.clone()
+    }
+
     /// Get the configuration
     pub fn config(&self) -> &LocalManagementConfig {
         &self.config
@@ -80,10 +103,10 @@ impl LocalManagement {
 
     /// Create the management API router
     pub fn create_router(&self) -> axum::Router {
-        let state = HandlerState::new(LocalMockRepository::new(
-            self.repository.pool().clone(),
-            self.repository.instance_id(),
-        ));
+        // 共享同一个 repository，使 API 修改能推进数据面的 generation
+        let state = HandlerState::new((*self.repository).clone())
+            .with_scenarios(self.scenarios.clone())
+            .with_sequences(self.sequences.clone());
         create_management_router(state)
     }
 
@@ -0,0 +1,1007 @@
+//! Data-path matching for locally stored mock configurations
+//!
+//! `LocalMockMatcher` keeps an in-memory snapshot of the active mocks in
+//! `LocalMockRepository` and answers "does this request hit a mock?" for the
+//! HTTP handler. The snapshot is reloaded whenever the repository generation
+//! changes, so API CRUD and sync pulls take effect without a restart. Mocks
+//! with a `state_config` answer through the shared `ScenarioStore`.
+
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::Arc;
+
+use hyper::header::HeaderMap;
+use regex::Regex;
+use serde_json::Value;
+use tokio::sync::RwLock;
+use tracing::{debug, warn};
+
+use super::models::{
+    BodyMatchType, HttpMethod, MatchType, MatchingRules, MockConfiguration, MockFilter,
+    PathPatternType, ResponseBodyType, ResponseConfig, StateTriggerType, TemplateVar,
+    TemplateVarSource,
+};
+use super::repository::{LocalMockRepository, MockRepository};
+use super::scenario::{ScenarioRequest, ScenarioStore};
+use crate::mock::{template, Condition, MockBodyFile, MockBuilder, MockResponse, TemplateContext};
+
+/// A mock with its path pattern compiled once per snapshot
+struct CompiledMock {
+    config: MockConfiguration,
+    /// Compiled pattern for Exact (with `:param`/`{param}` segments) and Regex types
+    path_regex: Option<Regex>,
+    /// Non-regex header/query/body rules translated to `mock::Condition`
+    conditions: Vec<Condition>,
+    /// Regex header/query/body rules, compiled once per snapshot
+    regex_rules: Vec<RegexRule>,
+    /// Body rule, body-triggered state transition or body template present
+    needs_body: bool,
+}
+
+/// A regex matching rule with its pattern compiled
+enum RegexRule {
+    Header {
+        name: String,
+        regex: Regex,
+    },
+    Query {
+        name: String,
+        regex: Regex,
+    },
+    /// JSONPath field of the JSON body
+    Json {
+        path: String,
+        regex: Regex,
+    },
+    /// Raw body text, for body rules without a JSONPath
+    Body(Regex),
+}
+
+/// Snapshot of active mocks at a given repository generation
+struct Snapshot {
+    generation: Option<u64>,
+    mocks: Vec<CompiledMock>,
+}
+
+/// Matches live requests against the active mocks of a local repository
+pub struct LocalMockMatcher {
+    repository: Arc<LocalMockRepository>,
+    snapshot: RwLock<Arc<Snapshot>>,
+    scenarios: Arc<ScenarioStore>,
+}
+
+impl LocalMockMatcher {
+    /// Create a matcher over the given repository
+    pub fn new(repository: Arc<LocalMockRepository>) -> Self {
+        Self {
+            repository,
+            snapshot: RwLock::new(Arc::new(Snapshot {
+                generation: None,
+                mocks: Vec::new(),
+            })),
+            scenarios: Arc::new(ScenarioStore::new()),
+        }
+    }
+
+    /// Share scenario state with another component (e.g. the management API)
+    pub fn with_scenarios(mut self, scenarios: Arc<ScenarioStore>) -> Self {
+        self.scenarios = scenarios;
+        self
+    }
+
+    /// Scenario state of the stateful mocks served by this matcher
+    pub fn scenarios(&self) -> Arc<ScenarioStore> {
+        self.scenarios.clone()
+    }
+
+    /// Find the first active mock matching the request and build its response
+    ///
+    /// Mocks are tried most-recently-updated first. `body` is the request body
+    /// when the caller has buffered it, already decoded from its
+    /// `Content-Encoding`; mocks with a body rule never match without it.
+    pub async fn find(
+        &self,
+        method: &str,
+        uri: &str,
+        headers: &HeaderMap,
+        body: Option<&[u8]>,
+    ) -> Option<MockResponse> {
+        self.find_from(method, uri, headers, body, None).await
+    }
+
+    /// Same as [`find`](Self::find), exposing the client IP to response templates
+    pub async fn find_from(
+        &self,
+        method: &str,
+        uri: &str,
+        headers: &HeaderMap,
+        body: Option<&[u8]>,
+        client_ip: Option<IpAddr>,
+    ) -> Option<MockResponse> {
+        let snapshot = self.current().await;
+        if snapshot.mocks.is_empty() {
+            return None;
+        }
+
+        let path = uri.split('?').next().unwrap_or(uri);
+        let json_body = body.and_then(|b| serde_json::from_slice::<Value>(b).ok());
+
+        for mock in &snapshot.mocks {
+            if !method_matches(mock.config.method, method) || !mock.matches_path(path) {
+                continue;
+            }
+            if !MockBuilder::matches_conditions(uri, headers, json_body.as_ref(), &mock.conditions)
+            {
+                continue;
+            }
+            if !mock
+                .regex_rules
+                .iter()
+                .all(|rule| rule.matches(uri, headers, json_body.as_ref(), body))
+            {
+                continue;
+            }
+            if let Some(rule) = &mock.config.matching_rules.body {
+                let compiled = rule.match_type == BodyMatchType::Regex && rule.value.is_some();
+                if rule.json_path.is_none() && !compiled && !raw_body_matches(body, rule) {
+                    continue;
+                }
+            }
+
+            debug!(
+                "Local mock '{}' ({}) matched {} {}",
+                mock.config.name, mock.config.id, method, uri
+            );
+            let mut response = &mock.config.response_config;
+            if let Some(state_config) = &mock.config.state_config {
+                let request = ScenarioRequest {
+                    method,
+                    uri,
+                    headers,
+                    body: json_body.as_ref(),
+                };
+                if let Some(state_response) =
+                    self.scenarios
+                        .advance(mock.config.id, state_config, &request)
+                {
+                    response = state_response;
+                }
+            }
+            let context = TemplateContext::new(method, uri)
+                .with_headers(headers)
+                .with_client_ip(client_ip)
+                .with_params(&mock.path_params(path))
+                .with_body(json_body.as_ref());
+            return Some(build_response(response, context));
+        }
+
+        None
+    }
+
+    /// Whether an active mock matching this method and path inspects the body
+    ///
+    /// The data path buffers the body for `find` only when this is true, so
+    /// requests to unrelated routes keep streaming.
+    pub async fn needs_body(&self, method: &str, path: &str) -> bool {
+        let path = path.split('?').next().unwrap_or(path);
+        self.current().await.mocks.iter().any(|mock| {
+            mock.needs_body && method_matches(mock.config.method, method) && mock.matches_path(path)
+        })
+    }
+
+    /// Return the current snapshot, reloading it if the repository changed
+    async fn current(&self) -> Arc<Snapshot> {
+        let generation = self.repository.generation();
+        {
+            let snapshot = self.snapshot.read().await;
+            if snapshot.generation == Some(generation) {
+                return snapshot.clone();
+            }
+        }
+
+        let mut guard = self.snapshot.write().await;
+        if guard.generation == Some(generation) {
+            return guard.clone();
+        }
+
+        let filter = MockFilter {
+            is_active: Some(true),
+            ..Default::default()
+        };
+        match self.repository.find_all(filter).await {
+            Ok(configs) => {
+                let mocks: Vec<CompiledMock> =
+                    configs.into_iter().filter_map(CompiledMock::new).collect();
+                self.scenarios.retain(mocks.iter().filter_map(|mock| {
+                    let state_config = mock.config.state_config.as_ref()?;
+                    Some((mock.config.id, state_config))
+                }));
+                *guard = Arc::new(Snapshot {
+                    generation: Some(generation),
+                    mocks,
+                });
+                debug!(
+                    "Reloaded {} active local mocks (generation {})",
+                    guard.mocks.len(),
+                    generation
+                );
+            }
+            Err(e) => {
+                // 保留旧快照，下个请求重试
+                warn!("Failed to reload local mocks: {}", e);
+            }
+        }
+        guard.clone()
+    }
+}
+
+impl CompiledMock {
+    fn new(config: MockConfiguration) -> Option<Self> {
+        let rules = &config.matching_rules;
+        let pattern = rules.path_pattern.as_deref().unwrap_or(&config.path);
+
+        let path_regex = match rules.path_pattern_type {
+            // `:name` is a parameter only at the start of a segment, so literal
+            // colons such as `/v1/items:batchGet` still match exactly
+            PathPatternType::Exact
+                if pattern.contains('{') || pattern.split('/').any(|seg| seg.starts_with(':')) =>
+            {
+                let normalized = pattern
+                    .split('/')
+                    .map(|seg| match seg.strip_prefix(':') {
+                        Some(name) => format!("{{{name}}}"),
+                        None => seg.to_string(),
+                    })
+                    .collect::<Vec<_>>()
+                    .join("/");
+                match crate::router::pattern_to_regex(&normalized, true) {
+                    Ok(re) => Some(re),
+                    Err(e) => {
+                        warn!("Skipping local mock '{}': {}", config.name, e);
+                        return None;
+                    }
+                }
+            }
+            PathPatternType::Regex => match Regex::new(pattern) {
+                Ok(re) => Some(re),
+                Err(e) => {
+                    warn!(
+                        "Skipping local mock '{}': invalid regex: {}",
+                        config.name, e
+                    );
+                    return None;
+                }
+            },
+            _ => None,
+        };
+
+        let conditions = rules_to_conditions(rules);
+        let regex_rules = match compile_regex_rules(rules) {
+            Ok(regex_rules) => regex_rules,
+            Err(e) => {
+                warn!(
+                    "Skipping local mock '{}': invalid regex: {}",
+                    config.name, e
+                );
+                return None;
+            }
+        };
+        let state_responses = config
+            .state_config
+            .iter()
+            .flat_map(|state| &state.transitions)
+            .filter_map(|t| t.response.as_ref());
+        // 模板引用按解析结果判断；响应头总是按模板渲染
+        let body_template = std::iter::once(&config.response_config)
+            .chain(state_responses)
+            .any(|response| {
+                response
+                    .headers
+                    .values()
+                    .any(|value| template::references(value, "body"))
+                    || response
+                        .body
+                        .as_ref()
+                        .filter(|body| body.body_type == ResponseBodyType::Template)
+                        .is_some_and(|body| {
+                            body.content
+                                .as_deref()
+                                .is_some_and(|c| template::references(c, "body"))
+                                || body
+                                    .template_vars
+                                    .iter()
+                                    .any(|var| var.source == TemplateVarSource::Body)
+                        })
+            });
+        let needs_body = rules.body.is_some()
+            || body_template
+            || config.state_config.as_ref().is_some_and(|state| {
+                state.transitions.iter().any(|t| {
+                    t.trigger
+                        .as_ref()
+                        .is_some_and(|trigger| trigger.trigger_type == StateTriggerType::Body)
+                })
+            });
+
+        Some(Self {
+            config,
+            path_regex,
+            conditions,
+            regex_rules,
+            needs_body,
+        })
+    }
+
+    fn matches_path(&self, path: &str) -> bool {
+        let rules = &self.config.matching_rules;
+        let pattern = rules.path_pattern.as_deref().unwrap_or(&self.config.path);
+        match (&rules.path_pattern_type, &self.path_regex) {
+            (_, Some(re)) => re.is_match(path),
+            (PathPatternType::Prefix, None) => path.starts_with(pattern),
+            (_, None) => path == pattern,
+        }
+    }
+
+    /// Named captures of the path pattern, exposed to templates as `path.*`
+    fn path_params(&self, path: &str) -> HashMap<String, String> {
+        let Some(re) = &self.path_regex else {
+            return HashMap::new();
+        };
+        let Some(captures) = re.captures(path) else {
+            return HashMap::new();
+        };
+        re.capture_names()
+            .flatten()
+            .filter_map(|name| Some((name.to_string(), captures.name(name)?.as_str().to_string())))
+            .collect()
+    }
+}
+
+fn method_matches(expected: HttpMethod, actual: &str) -> bool {
+    expected == HttpMethod::Any || expected.to_string().eq_ignore_ascii_case(actual)
+}
+
+/// 将 MatchingRules 的非正则 header/query/body(JSONPath) 规则映射为 mock::Condition
+///
+/// 正则规则由 [`compile_regex_rules`] 预编译，不在此映射。
+fn rules_to_conditions(rules: &MatchingRules) -> Vec<Condition> {
+    let mut conditions = Vec::new();
+
+    let pattern = |name: &str, value: &str, match_type: MatchType| match match_type {
+        MatchType::Exists => Some(name.to_string()),
+        MatchType::Regex => None,
+        MatchType::Exact | MatchType::JsonPath => Some(format!("{name}={value}")),
+    };
+
+    for header in &rules.headers {
+        if let Some(value) = pattern(&header.name, &header.value, header.match_type) {
+            conditions.push(Condition {
+                condition_type: "header".to_string(),
+                value,
+            });
+        }
+    }
+    for param in &rules.query_params {
+        if let Some(value) = pattern(&param.name, &param.value, param.match_type) {
+            conditions.push(Condition {
+                condition_type: "query".to_string(),
+                value,
+            });
+        }
+    }
+    if let Some(body) = &rules.body {
+        if let Some(path) = &body.json_path {
+            let value = match (&body.value, body.match_type) {
+                (Some(_), BodyMatchType::Regex) => None,
+                (Some(v), _) => Some(format!("{path}={v}")),
+                (None, _) => Some(path.clone()),
+            };
+            if let Some(value) = value {
+                conditions.push(Condition {
+                    condition_type: "json".to_string(),
+                    value,
+                });
+            }
+        }
+    }
+
+    conditions
+}
+
+/// 编译 MatchingRules 中的正则规则（header/query、JSONPath 字段与原始 body）
+fn compile_regex_rules(rules: &MatchingRules) -> Result<Vec<RegexRule>, regex::Error> {
+    let mut regex_rules = Vec::new();
+    for header in rules
+        .headers
+        .iter()
+        .filter(|h| h.match_type == MatchType::Regex)
+    {
+        regex_rules.push(RegexRule::Header {
+            name: header.name.clone(),
+            regex: Regex::new(&header.value)?,
+        });
+    }
+    for param in rules
+        .query_params
+        .iter()
+        .filter(|p| p.match_type == MatchType::Regex)
+    {
+        regex_rules.push(RegexRule::Query {
+            name: param.name.clone(),
+            regex: Regex::new(&param.value)?,
+        });
+    }
+    if let Some(body) = rules
+        .body
+        .as_ref()
+        .filter(|b| b.match_type == BodyMatchType::Regex)
+    {
+        match (&body.json_path, &body.value) {
+            (Some(path), Some(value)) => regex_rules.push(RegexRule::Json {
+                path: path.clone(),
+                regex: Regex::new(value)?,
+            }),
+            (None, Some(value)) => regex_rules.push(RegexRule::Body(Regex::new(value)?)),
+            // 无值的正则规则等同于存在检查，由 conditions / raw_body_matches 处理
+            (_, None) => {}
+        }
+    }
+    Ok(regex_rules)
+}
+
+impl RegexRule {
+    /// 与 `MockBuilder::matches_conditions` 的 `regex:` 条件语义一致
+    fn matches(
+        &self,
+        uri: &str,
+        headers: &HeaderMap,
+        json_body: Option<&Value>,
+        body: Option<&[u8]>,
+    ) -> bool {
+        match self {
+            Self::Header { name, regex } => headers
+                .get(name.as_str())
+                .is_some_and(|v| regex.is_match(v.to_str().unwrap_or(""))),
+            Self::Query { name, regex } => MockBuilder::query_params(uri)
+                .get(name)
+                .is_some_and(|v| regex.is_match(v)),
+            Self::Json { path, regex } => json_body
+                .and_then(|b| MockBuilder::get_value_by_path(b, path))
+                .is_some_and(|v| match v {
+                    Value::String(s) => regex.is_match(&s),
+                    other => regex.is_match(&other.to_string()),
+                }),
+            Self::Body(regex) => body.is_some_and(|b| regex.is_match(&String::from_utf8_lossy(b))),
+        }
+    }
+}
+
+/// 无 JSONPath 的非正则 body 规则：对原始 body 文本做精确匹配
+fn raw_body_matches(body: Option<&[u8]>, rule: &super::models::BodyMatch) -> bool {
+    let Some(body) = body else {
+        return false;
+    };
+    let Some(expected) = &rule.value else {
+        return !body.is_empty();
+    };
+    String::from_utf8_lossy(body) == expected.as_str()
+}
+
+/// 将 mysti-common 的 ResponseConfig 转为数据面 MockResponse
+fn build_response(config: &ResponseConfig, mut context: TemplateContext) -> MockResponse {
+    let mut mock = MockResponse::new()
+        .status(config.status)
+        .delay(config.delay_ms.unwrap_or(0) as u64);
+
+    if let Some(body) = &config.body {
+        bind_template_vars(&mut context, &body.template_vars);
+    }
+
+    for (key, value) in &config.headers {
+        mock = mock.header(key.clone(), context.render(value));
+    }
+
+    if let Some(response_body) = &config.body {
+        let content = response_body.content.clone().unwrap_or_default();
+        match response_body.body_type {
+            ResponseBodyType::Static => mock = mock.body(content),
+            ResponseBodyType::Template => {
+                mock = mock.body(context.render(&content));
+            }
+            // content 为文件路径，基于引擎的 mock_root 解析且不得越出该目录（不允许绝对路径），响应时流式读取
+            ResponseBodyType::File => {
+                mock = mock.body_file(MockBodyFile::new(content, false));
+            }
+            ResponseBodyType::Script => {
+                warn!(
+                    "Response body type {:?} is not supported on the data path",
+                    response_body.body_type
+                );
+            }
+        }
+    }
+
+    mock
+}
+
+/// 将 TemplateVar 绑定为模板变量：`path` 缺省时取变量名
+fn bind_template_vars(context: &mut TemplateContext, vars: &[TemplateVar]) {
+    for var in vars {
+        let path = var.path.as_deref().unwrap_or(&var.name);
+        let prefix = match var.source {
+            TemplateVarSource::Path => "path",
+            TemplateVarSource::Query => "query",
+            TemplateVarSource::Header => "header",
+            TemplateVarSource::Body => "body",
+        };
+        match context.lookup(&format!("{prefix}.{path}")) {
+            Some(value) => context.set_var(var.name.clone(), value),
+            None => debug!("Template var '{}' not found in {}", var.name, prefix),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::management::db::create_memory_pool;
+    use crate::management::models::{
+        BodyMatch, CreateMockRequest, HeaderMatch, ResponseBody, UpdateMockRequest,
+    };
+    use hyper::header::HeaderValue;
+
+    async fn setup() -> (Arc<LocalMockRepository>, LocalMockMatcher) {
+        let pool = create_memory_pool().await.unwrap();
+        let repo = Arc::new(LocalMockRepository::with_random_instance_id(pool));
+        let matcher = LocalMockMatcher::new(repo.clone());
+        (repo, matcher)
+    }
+
+    fn request(path: &str, method: HttpMethod, body: &str) -> CreateMockRequest {
+        CreateMockRequest {
+            name: format!("mock {path}"),
+            path: path.to_string(),
+            method,
+            matching_rules: MatchingRules::default(),
+            response_config: ResponseConfig {
+                status: 201,
+                body: Some(ResponseBody {
+                    content: Some(body.to_string()),
+                    ..Default::default()
+                }),
+                delay_ms: Some(5),
+                ..Default::default()
+            },
+            state_config: None,
+            is_active: true,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_find_exact_path_and_method() {
+        let (repo, matcher) = setup().await;
+        repo.create(request("/api/users", HttpMethod::Get, "users"))
+            .await
+            .unwrap();
+
+        let headers = HeaderMap::new();
+        let mock = matcher
+            .find("GET", "/api/users?x=1", &headers, None)
+            .await
+            .expect("match");
+        assert_eq!(mock.status, 201);
+        assert_eq!(mock.body, "users");
+        assert_eq!(mock.delay_ms, 5);
+
+        assert!(matcher
+            .find("POST", "/api/users", &headers, None)
+            .await
+            .is_none());
+        assert!(matcher
+            .find("GET", "/api/users/1", &headers, None)
+            .await
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_changes_visible_without_restart() {
+        let (repo, matcher) = setup().await;
+        let headers = HeaderMap::new();
+        assert!(matcher.find("GET", "/a", &headers, None).await.is_none());
+
+        let created = repo
+            .create(request("/a", HttpMethod::Any, "v1"))
+            .await
+            .unwrap();
+        assert_eq!(
+            matcher
+                .find("GET", "/a", &headers, None)
+                .await
+                .unwrap()
+                .body,
+            "v1"
+        );
+
+        let update = UpdateMockRequest {
+            response_config: Some(ResponseConfig {
+                body: Some(ResponseBody {
+                    content: Some("v2".to_string()),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        repo.update(created.id, update).await.unwrap();
+        assert_eq!(
+            matcher
+                .find("GET", "/a", &headers, None)
+                .await
+                .unwrap()
+                .body,
+            "v2"
+        );
+
+        repo.delete(created.id).await.unwrap();
+        assert!(matcher.find("GET", "/a", &headers, None).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_inactive_mock_ignored() {
+        let (repo, matcher) = setup().await;
+        let mut req = request("/off", HttpMethod::Get, "x");
+        req.is_active = false;
+        repo.create(req).await.unwrap();
+
+        let headers = HeaderMap::new();
+        assert!(matcher.find("GET", "/off", &headers, None).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_path_pattern_types() {
+        let (repo, matcher) = setup().await;
+
+        let mut param = request("/users/:id", HttpMethod::Get, "param");
+        param.matching_rules.path_pattern_type = PathPatternType::Exact;
+        repo.create(param).await.unwrap();
+
+        let mut prefix = request("/static/", HttpMethod::Get, "prefix");
+        prefix.matching_rules.path_pattern_type = PathPatternType::Prefix;
+        repo.create(prefix).await.unwrap();
+
+        let mut regex = request("/ignored", HttpMethod::Get, "regex");
+        regex.matching_rules.path_pattern = Some(r"^/orders/\d+$".to_string());
+        regex.matching_rules.path_pattern_type = PathPatternType::Regex;
+        repo.create(regex).await.unwrap();
+
+        let headers = HeaderMap::new();
+        let body_of = |m: Option<MockResponse>| m.map(|m| m.body);
+        assert_eq!(
+            body_of(matcher.find("GET", "/users/42", &headers, None).await),
+            Some("param".to_string())
+        );
+        assert_eq!(
+            body_of(matcher.find("GET", "/static/a/b.js", &headers, None).await),
+            Some("prefix".to_string())
+        );
+        assert_eq!(
+            body_of(matcher.find("GET", "/orders/7", &headers, None).await),
+            Some("regex".to_string())
+        );
+        assert!(matcher
+            .find("GET", "/orders/x", &headers, None)
+            .await
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_header_query_and_body_rules() {
+        let (repo, matcher) = setup().await;
+
+        let mut req = request("/rules", HttpMethod::Post, "hit");
+        req.matching_rules.headers.push(HeaderMatch {
+            name: "X-Tenant".to_string(),
+            value: "^acme".to_string(),
+            match_type: MatchType::Regex,
+        });
+        req.matching_rules
+            .query_params
+            .push(crate::management::QueryParamMatch {
+                name: "v".to_string(),
+                value: "2".to_string(),
+                match_type: MatchType::Exact,
+            });
+        req.matching_rules.body = Some(BodyMatch {
+            json_path: Some("$.kind".to_string()),
+            value: Some("order".to_string()),
+            match_type: BodyMatchType::Exact,
+        });
+        repo.create(req).await.unwrap();
+
+        let mut headers = HeaderMap::new();
+        headers.insert("X-Tenant", HeaderValue::from_static("acme-eu"));
+        let body = br#"{"kind":"order"}"#;
+
+        assert!(matcher
+            .find("POST", "/rules?v=2", &headers, Some(body))
+            .await
+            .is_some());
+        // 缺少 body 时 body 规则不命中
+        assert!(matcher
+            .find("POST", "/rules?v=2", &headers, None)
+            .await
+            .is_none());
+        assert!(matcher
+            .find("POST", "/rules?v=3", &headers, Some(body))
+            .await
+            .is_none());
+        assert!(matcher
+            .find("POST", "/rules?v=2", &HeaderMap::new(), Some(body))
+            .await
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_regex_rules_compiled_per_snapshot() {
+        let (repo, matcher) = setup().await;
+
+        let mut raw = request("/raw", HttpMethod::Post, "raw");
+        raw.matching_rules.body = Some(BodyMatch {
+            json_path: None,
+            value: Some(r"^id=\d+$".to_string()),
+            match_type: BodyMatchType::Regex,
+        });
+        repo.create(raw).await.unwrap();
+
+        let mut json = request("/json", HttpMethod::Post, "json");
+        json.matching_rules
+            .query_params
+            .push(crate::management::QueryParamMatch {
+                name: "v".to_string(),
+                value: "^[12]$".to_string(),
+                match_type: MatchType::Regex,
+            });
+        json.matching_rules.body = Some(BodyMatch {
+            json_path: Some("$.total".to_string()),
+            value: Some(r"^\d{3}$".to_string()),
+            match_type: BodyMatchType::Regex,
+        });
+        repo.create(json).await.unwrap();
+
+        // 正则无效的 mock 在构建快照时跳过
+        let mut invalid = request("/invalid", HttpMethod::Any, "invalid");
+        invalid.matching_rules.headers.push(HeaderMatch {
+            name: "X-Id".to_string(),
+            value: "(unclosed".to_string(),
+            match_type: MatchType::Regex,
+        });
+        repo.create(invalid).await.unwrap();
+
+        let headers = HeaderMap::new();
+        let find = |uri: &'static str, body: &'static [u8]| {
+            let matcher = &matcher;
+            let headers = &headers;
+            async move {
+                matcher
+                    .find("POST", uri, headers, Some(body))
+                    .await
+                    .map(|m| m.body)
+            }
+        };
+        assert_eq!(find("/raw", b"id=42").await.as_deref(), Some("raw"));
+        assert_eq!(find("/raw", b"id=x").await, None);
+        assert_eq!(
+            find("/json?v=2", br#"{"total":125}"#).await.as_deref(),
+            Some("json")
+        );
+        assert_eq!(find("/json?v=3", br#"{"total":125}"#).await, None);
+        assert_eq!(find("/json?v=1", br#"{"total":12}"#).await, None);
+        assert_eq!(find("/invalid", b"").await, None);
+        assert_eq!(matcher.current().await.mocks.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_template_body_rendered() {
+        let (repo, matcher) = setup().await;
+        let mut req = request("/tpl", HttpMethod::Get, "");
+        req.response_config.body = Some(ResponseBody {
+            body_type: ResponseBodyType::Template,
+            content: Some("hello {{query.name}}".to_string()),
+            template_vars: vec![],
+        });
+        req.response_config
+            .headers
+            .insert("Content-Type".to_string(), "text/plain".to_string());
+        repo.create(req).await.unwrap();
+
+        let mock = matcher
+            .find("GET", "/tpl?name=ada", &HeaderMap::new(), None)
+            .await
+            .unwrap();
+        assert_eq!(mock.body, "hello ada");
+        assert_eq!(
+            mock.headers.get("Content-Type"),
+            Some(&"text/plain".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_file_body_served_from_path() {
+        let (repo, matcher) = setup().await;
+        let mut req = request("/report", HttpMethod::Get, "");
+        req.response_config.body = Some(ResponseBody {
+            body_type: ResponseBodyType::File,
+            content: Some("fixtures/report.pdf".to_string()),
+            template_vars: vec![],
+        });
+        repo.create(req).await.unwrap();
+
+        let mock = matcher
+            .find("GET", "/report", &HeaderMap::new(), None)
+            .await
+            .unwrap();
+        assert_eq!(
+            mock.body_file,
+            Some(MockBodyFile::new("fixtures/report.pdf", false))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_template_vars_and_path_params() {
+        let (repo, matcher) = setup().await;
+        let mut req = request("/users/{id}", HttpMethod::Post, "");
+        req.response_config.body = Some(ResponseBody {
+            body_type: ResponseBodyType::Template,
+            content: Some("{{id}} {{plan}} {{token}} {{path.id}} {{method}}".to_string()),
+            template_vars: vec![
+                TemplateVar {
+                    name: "id".to_string(),
+                    source: TemplateVarSource::Path,
+                    path: None,
+                },
+                TemplateVar {
+                    name: "plan".to_string(),
+                    source: TemplateVarSource::Body,
+                    path: Some("$.account.plan".to_string()),
+                },
+                TemplateVar {
+                    name: "token".to_string(),
+                    source: TemplateVarSource::Header,
+                    path: Some("X-Token".to_string()),
+                },
+            ],
+        });
+        req.response_config
+            .headers
+            .insert("X-User".to_string(), "{{path.id}}".to_string());
+        repo.create(req).await.unwrap();
+        assert!(matcher.needs_body("POST", "/users/42").await);
+
+        let mut headers = HeaderMap::new();
+        headers.insert("x-token", HeaderValue::from_static("t-1"));
+        let body = br#"{"account": {"plan": "pro"}}"#;
+        let mock = matcher
+            .find("POST", "/users/42", &headers, Some(body))
+            .await
+            .unwrap();
+        assert_eq!(mock.body, "42 pro t-1 42 POST");
+        assert_eq!(mock.headers.get("X-User"), Some(&"42".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_literal_colon_in_exact_path() {
+        let (repo, matcher) = setup().await;
+        repo.create(request("/v1/items:batchGet", HttpMethod::Post, "batch"))
+            .await
+            .unwrap();
+        repo.create(request("/v1/:resource", HttpMethod::Get, "param"))
+            .await
+            .unwrap();
+
+        let headers = HeaderMap::new();
+        let mock = matcher
+            .find("POST", "/v1/items:batchGet", &headers, None)
+            .await
+            .unwrap();
+        assert_eq!(mock.body, "batch");
+        assert!(matcher
+            .find("POST", "/v1/items:batchDelete", &headers, None)
+            .await
+            .is_none());
+
+        // A leading colon still declares a parameter
+        let mock = matcher
+            .find("GET", "/v1/orders", &headers, None)
+            .await
+            .unwrap();
+        assert_eq!(mock.body, "param");
+    }
+
+    #[tokio::test]
+    async fn test_stateful_mock_follows_scenario() {
+        let (repo, matcher) = setup().await;
+        let mut req = request("/orders/1", HttpMethod::Get, "base");
+        req.state_config = Some(
+            serde_json::from_value(serde_json::json!({
+                "initial_state": "pending",
+                "transitions": [
+                    {"from_state": "pending", "to_state": "shipped",
+                     "trigger": {"type": "header", "condition": "X-Poll=1"},
+                     "response": {"status": 200, "body": {"type": "static", "content": "shipped"}}}
+                ],
+                "session_key": {"source": "header", "name": "X-Session"}
+            }))
+            .unwrap(),
+        );
+        repo.create(req).await.unwrap();
+
+        let mut headers = HeaderMap::new();
+        headers.insert("X-Session", HeaderValue::from_static("s1"));
+        let mock = matcher
+            .find("GET", "/orders/1", &headers, None)
+            .await
+            .unwrap();
+        assert_eq!((mock.status, mock.body.as_str()), (201, "base"));
+
+        headers.insert("X-Poll", HeaderValue::from_static("1"));
+        let mock = matcher
+            .find("GET", "/orders/1", &headers, None)
+            .await
+            .unwrap();
+        assert_eq!((mock.status, mock.body.as_str()), (200, "shipped"));
+
+        // 另一个会话仍处于初始状态
+        let mut other = HeaderMap::new();
+        other.insert("X-Session", HeaderValue::from_static("s2"));
+        let mock = matcher
+            .find("GET", "/orders/1", &other, None)
+            .await
+            .unwrap();
+        assert_eq!(mock.body, "base");
+    }
+
+    #[tokio::test]
+    async fn test_needs_body() {
+        let (repo, matcher) = setup().await;
+        repo.create(request("/plain", HttpMethod::Get, "x"))
+            .await
+            .unwrap();
+        assert!(!matcher.needs_body("GET", "/plain").await);
+
+        let mut req = request("/orders", HttpMethod::Post, "x");
+        req.state_config = Some(
+            serde_json::from_value(serde_json::json!({
+                "initial_state": "new",
+                "transitions": [{"from_state": "new", "to_state": "paid",
+                                 "trigger": {"type": "body", "condition": "$.paid=true"}}]
+            }))
+            .unwrap(),
+        );
+        repo.create(req).await.unwrap();
+        assert!(matcher.needs_body("POST", "/orders").await);
+        // Requests to other methods or paths keep streaming
+        assert!(!matcher.needs_body("GET", "/orders").await);
+        assert!(!matcher.needs_body("POST", "/upload").await);
+    }
+
+    #[tokio::test]
+    async fn test_needs_body_from_parsed_templates() {
+        let (repo, matcher) = setup().await;
+        // 字面文本中的 `body.` 不算引用
+        let mut req = request(
+            "/literal",
+            HttpMethod::Get,
+            "https://somebody.example/{{method}}",
+        );
+        req.response_config.body.as_mut().unwrap().body_type = ResponseBodyType::Template;
+        repo.create(req).await.unwrap();
+        assert!(!matcher.needs_body("GET", "/literal").await);
+
+        let mut req = request("/echo", HttpMethod::Post, "x");
+        req.response_config
+            .headers
+            .insert("X-Echo".to_string(), "{{#if body}}yes{{/if}}".to_string());
+        repo.create(req).await.unwrap();
+        assert!(matcher.needs_body("POST", "/echo").await);
+    }
+}
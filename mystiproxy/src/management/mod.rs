@@ -11,6 +11,8 @@
 //! ├─────────────────────────────────────────────────────────────┤
 //! │  handlers.rs    - HTTP API handlers (Axum compatible)       │
 //! │  repository.rs  - MockRepository trait & SQLite impl        │
+//! │  matcher.rs     - Serves active mocks on the data path      │
+//! │  scenario.rs    - Stateful mock scenario engine             │
 //! │  db.rs          - SQLite connection & migrations            │
 //! │  config.rs      - Configuration management                  │
 //! │  import.rs      - YAML/JSON config file import              │
@@ -31,8 +33,10 @@ mod error;
 mod handlers;
 mod import;
 mod integration;
+mod matcher;
 mod models;
 mod repository;
+mod scenario;
 mod sync;
 
 pub use config::{LocalManagementConfig, SyncConfig};
@@ -40,8 +44,10 @@ pub use error::{ManagementError, Result};
 pub use handlers::create_management_router;
 pub use import::import_from_file;
 pub use integration::{LocalManagement, LocalManagementBuilder};
+pub use matcher::LocalMockMatcher;
 pub use models::*;
 pub use repository::{LocalMockRepository, MockRepository};
+pub use scenario::{ScenarioRequest, ScenarioStore, SessionState};
 pub use sync::{OfflineQueueEntry, OfflineQueueManager, RetryPolicy, SyncClient, SyncOperation};
 
 use sqlx::SqlitePool;
@@ -26,6 +26,13 @@ pub use mysti_common::{
     ResponseBodyType,
     // Response configuration
     ResponseConfig,
+    SessionKey,
+    SessionKeySource,
+    // State machine
+    StateConfig,
+    StateTransition,
+    StateTrigger,
+    StateTriggerType,
     SyncMessage,
     // Sync types
     SyncStatus,
@@ -52,6 +59,9 @@ pub struct CreateMockRequest {
     /// Response configuration (optional, uses defaults if not provided)
     #[serde(default)]
     pub response_config: ResponseConfig,
+    /// State machine configuration (for stateful mocks)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state_config: Option<StateConfig>,
     /// Whether this configuration is active
     #[serde(default = "default_active")]
     pub is_active: bool,
@@ -71,7 +81,7 @@ impl From<CreateMockRequest> for MockCreateRequest {
             environment_id: None,
             matching_rules: req.matching_rules,
             response_config: req.response_config,
-            state_config: None,
+            state_config: req.state_config,
             is_active: req.is_active,
         }
     }
@@ -95,6 +105,9 @@ pub struct UpdateMockRequest {
     /// Response configuration
     #[serde(skip_serializing_if = "Option::is_none")]
     pub response_config: Option<ResponseConfig>,
+    /// State machine configuration
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state_config: Option<StateConfig>,
     /// Whether this configuration is active
     #[serde(skip_serializing_if = "Option::is_none")]
     pub is_active: Option<bool>,
@@ -108,7 +121,7 @@ impl From<UpdateMockRequest> for MockUpdateRequest {
             method: req.method,
             matching_rules: req.matching_rules,
             response_config: req.response_config,
-            state_config: None,
+            state_config: req.state_config,
             version_vector: None,
             is_active: req.is_active,
         }
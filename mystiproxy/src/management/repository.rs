@@ -2,6 +2,9 @@
 //!
 //! Provides the core data access layer for mock configurations.
 
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use sqlx::{Row, SqlitePool};
@@ -64,15 +67,24 @@ pub trait MockRepository: Send + Sync {
 }
 
 /// SQLite implementation of MockRepository
+///
+/// Clones share the pool and the change generation, so writes made through
+/// any clone (API handlers, sync client) are visible to every reader.
+#[derive(Clone)]
 pub struct LocalMockRepository {
     pool: SqlitePool,
     instance_id: Uuid,
+    generation: Arc<AtomicU64>,
 }
 
 impl LocalMockRepository {
     /// Create a new SQLite repository
     pub fn new(pool: SqlitePool, instance_id: Uuid) -> Self {
-        Self { pool, instance_id }
+        Self {
+            pool,
+            instance_id,
+            generation: Arc::new(AtomicU64::new(0)),
+        }
     }
 
     /// Get the pool
@@ -82,10 +94,7 @@ impl LocalMockRepository {
 
     /// Create a new SQLite repository with a random instance ID
     pub fn with_random_instance_id(pool: SqlitePool) -> Self {
-        Self {
-            pool,
-            instance_id: Uuid::new_v4(),
-        }
+        Self::new(pool, Uuid::new_v4())
     }
 
     /// Get the instance ID
@@ -93,6 +102,17 @@ impl LocalMockRepository {
         self.instance_id
     }
 
+    /// Change generation, bumped on every save/delete
+    ///
+    /// Readers that cache active mocks compare it to decide when to reload.
+    pub fn generation(&self) -> u64 {
+        self.generation.load(Ordering::Acquire)
+    }
+
+    fn bump_generation(&self) {
+        self.generation.fetch_add(1, Ordering::AcqRel);
+    }
+
     /// Convert a database row to MockConfiguration
     fn row_to_config(row: &sqlx::sqlite::SqliteRow) -> Result<MockConfiguration> {
         let id: String = row.try_get("id")?;
@@ -115,6 +135,11 @@ impl LocalMockRepository {
         let response_config_json: String = row.try_get("response_config")?;
         let response_config: ResponseConfig = serde_json::from_str(&response_config_json)?;
 
+        let state_config_json: Option<String> = row.try_get("state_config")?;
+        let state_config = state_config_json
+            .map(|json| serde_json::from_str(&json))
+            .transpose()?;
+
         let version_vector_json: String = row.try_get("version_vector")?;
         let version_vector: VersionVector = serde_json::from_str(&version_vector_json)?;
 
@@ -139,7 +164,7 @@ impl LocalMockRepository {
             environment_id: None, // Local storage doesn't track environment
             matching_rules,
             response_config,
-            state_config: None, // Local storage doesn't use state config
+            state_config,
             source,
             version_vector,
             content_hash: row.try_get("content_hash")?,
@@ -246,6 +271,11 @@ impl MockRepository for LocalMockRepository {
         };
         let matching_rules_json = serde_json::to_string(&config.matching_rules)?;
         let response_config_json = serde_json::to_string(&config.response_config)?;
+        let state_config_json = config
+            .state_config
+            .as_ref()
+            .map(serde_json::to_string)
+            .transpose()?;
         let version_vector_json = serde_json::to_string(&config.version_vector)?;
         let is_active = if config.is_active { 1i32 } else { 0i32 };
         let created_at_str = config.created_at.to_rfc3339();
@@ -254,15 +284,16 @@ impl MockRepository for LocalMockRepository {
         sqlx::query(
             r#"
             INSERT INTO mock_configurations (
-                id, name, path, method, matching_rules, response_config,
+                id, name, path, method, matching_rules, response_config, state_config,
                 source, version_vector, content_hash, is_active, created_at, updated_at
-            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
             ON CONFLICT(id) DO UPDATE SET
                 name = excluded.name,
                 path = excluded.path,
                 method = excluded.method,
                 matching_rules = excluded.matching_rules,
                 response_config = excluded.response_config,
+                state_config = excluded.state_config,
                 source = excluded.source,
                 version_vector = excluded.version_vector,
                 content_hash = excluded.content_hash,
@@ -276,6 +307,7 @@ impl MockRepository for LocalMockRepository {
         .bind(&method_str)
         .bind(&matching_rules_json)
         .bind(&response_config_json)
+        .bind(&state_config_json)
         .bind(source_str)
         .bind(&version_vector_json)
         .bind(&config.content_hash)
@@ -285,6 +317,7 @@ impl MockRepository for LocalMockRepository {
         .execute(&self.pool)
         .await?;
 
+        self.bump_generation();
         Ok(())
     }
 
@@ -296,7 +329,11 @@ impl MockRepository for LocalMockRepository {
             .execute(&self.pool)
             .await?;
 
-        Ok(result.rows_affected() > 0)
+        let deleted = result.rows_affected() > 0;
+        if deleted {
+            self.bump_generation();
+        }
+        Ok(deleted)
     }
 
     async fn count(&self) -> Result<u64> {
@@ -389,6 +426,7 @@ impl LocalMockRepository {
             request.matching_rules,
             request.response_config,
         );
+        config.state_config = request.state_config;
         config.is_active = request.is_active;
         config.touch(self.instance_id);
 
@@ -418,6 +456,9 @@ impl LocalMockRepository {
         if let Some(response_config) = request.response_config {
             config.response_config = response_config;
         }
+        if let Some(state_config) = request.state_config {
+            config.state_config = Some(state_config);
+        }
         if let Some(is_active) = request.is_active {
             config.is_active = is_active;
         }
@@ -551,6 +592,7 @@ impl MockRepository for InMemoryMockRepository {
                 request.matching_rules,
                 request.response_config,
             );
+            config.state_config = request.state_config;
             config.is_active = request.is_active;
             config.touch(self.instance_id);
 
@@ -585,6 +627,9 @@ impl MockRepository for InMemoryMockRepository {
                 if let Some(response_config) = request.response_config {
                     config.response_config = response_config;
                 }
+                if let Some(state_config) = request.state_config {
+                    config.state_config = Some(state_config);
+                }
                 if let Some(is_active) = request.is_active {
                     config.is_active = is_active;
                 }
@@ -627,6 +672,7 @@ mod tests {
             method: HttpMethod::Get,
             matching_rules: MatchingRules::default(),
             response_config: ResponseConfig::default(),
+            state_config: None,
             is_active: true,
         };
 
@@ -649,6 +695,7 @@ mod tests {
             method: HttpMethod::Get,
             matching_rules: MatchingRules::default(),
             response_config: ResponseConfig::default(),
+            state_config: None,
             is_active: true,
         };
 
@@ -674,6 +721,7 @@ mod tests {
             method: HttpMethod::Get,
             matching_rules: MatchingRules::default(),
             response_config: ResponseConfig::default(),
+            state_config: None,
             is_active: true,
         };
 
@@ -697,6 +745,7 @@ mod tests {
             method: HttpMethod::Get,
             matching_rules: MatchingRules::default(),
             response_config: ResponseConfig::default(),
+            state_config: None,
             is_active: true,
         };
 
@@ -714,4 +763,31 @@ mod tests {
             .unwrap();
         assert!(not_found.is_empty());
     }
+
+    #[tokio::test]
+    async fn test_state_config_persisted() {
+        let pool = create_memory_pool().await.unwrap();
+        let repo = LocalMockRepository::with_random_instance_id(pool);
+
+        let state_config: crate::management::StateConfig =
+            serde_json::from_value(serde_json::json!({
+                "initial_state": "created",
+                "transitions": [{"from_state": "created", "to_state": "shipped"}],
+                "session_key": {"source": "header", "name": "X-Session"}
+            }))
+            .unwrap();
+        let request = CreateMockRequest {
+            name: "Order".to_string(),
+            path: "/orders/1".to_string(),
+            method: HttpMethod::Get,
+            matching_rules: MatchingRules::default(),
+            response_config: ResponseConfig::default(),
+            state_config: Some(state_config.clone()),
+            is_active: true,
+        };
+
+        let config = repo.create(request).await.unwrap();
+        let found = repo.find_by_id(config.id).await.unwrap().unwrap();
+        assert_eq!(found.state_config, Some(state_config));
+    }
 }
@@ -0,0 +1,462 @@
+//! Stateful mock scenarios
+//!
+//! `ScenarioStore` executes the `state_config` of local mocks. It keeps the
+//! current state of every stateful mock in memory, optionally one state per
+//! session (header or cookie value), fires the first transition whose trigger
+//! matches the request and picks the response for the resulting state. The
+//! same store is shared with the management API so scenarios can be inspected
+//! and reset.
+//!
+//! Session keys come from the client, so the store is bounded: idle sessions
+//! expire after `SESSION_TTL`, each mock keeps at most `MAX_SESSIONS_PER_MOCK`
+//! sessions, and a mock's state is dropped when it is deleted or its
+//! `state_config` changes.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use hyper::header::{HeaderMap, COOKIE};
+use serde::Serialize;
+use serde_json::Value;
+use tracing::debug;
+use uuid::Uuid;
+
+use super::models::{
+    ResponseConfig, SessionKey, SessionKeySource, StateConfig, StateTrigger, StateTriggerType,
+};
+use crate::mock::{Condition, MockBuilder};
+
+/// `from_state` wildcard matching any current state
+pub const ANY_STATE: &str = "*";
+
+/// Maximum number of sessions kept per mock; the least recently used is evicted
+pub const MAX_SESSIONS_PER_MOCK: usize = 10_000;
+
+/// Sessions idle for longer than this return to the initial state
+pub const SESSION_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// Request data evaluated by state triggers
+#[derive(Debug, Clone, Copy)]
+pub struct ScenarioRequest<'a> {
+    pub method: &'a str,
+    pub uri: &'a str,
+    pub headers: &'a HeaderMap,
+    /// Request body parsed as JSON, if buffered and valid
+    pub body: Option<&'a Value>,
+}
+
+/// Current state of one scenario session
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SessionState {
+    /// Session key value, `None` for the shared session
+    pub session: Option<String>,
+    pub state: String,
+}
+
+/// State of one session and when it was last used
+struct SessionEntry {
+    state: String,
+    last_seen: Instant,
+}
+
+/// Sessions of one mock, tied to the `state_config` they were created under
+struct MockScenario {
+    config: StateConfig,
+    sessions: HashMap<Option<String>, SessionEntry>,
+}
+
+impl MockScenario {
+    /// When full, drop expired sessions, then the least recently used one
+    fn make_room(&mut self, now: Instant) {
+        if self.sessions.len() < MAX_SESSIONS_PER_MOCK {
+            return;
+        }
+        self.sessions
+            .retain(|_, entry| now.duration_since(entry.last_seen) < SESSION_TTL);
+        if self.sessions.len() >= MAX_SESSIONS_PER_MOCK {
+            let oldest = self
+                .sessions
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_seen)
+                .map(|(session, _)| session.clone());
+            if let Some(oldest) = oldest {
+                self.sessions.remove(&oldest);
+            }
+        }
+    }
+}
+
+/// In-memory state of all stateful mocks, keyed by mock ID and session
+#[derive(Default)]
+pub struct ScenarioStore {
+    states: Mutex<HashMap<Uuid, MockScenario>>,
+}
+
+impl ScenarioStore {
+    /// Create an empty store; every scenario starts in its initial state
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Advance the scenario of a mock for this request
+    ///
+    /// Fires the first transition leaving the current state whose trigger
+    /// matches, then returns the response to serve: the transition's own
+    /// response, else the response attached to the (new) current state.
+    /// `None` means the mock's base `response_config` applies.
+    pub fn advance<'a>(
+        &self,
+        mock_id: Uuid,
+        config: &'a StateConfig,
+        request: &ScenarioRequest<'_>,
+    ) -> Option<&'a ResponseConfig> {
+        let session = config
+            .session_key
+            .as_ref()
+            .and_then(|key| session_id(key, request.headers));
+
+        let now = Instant::now();
+        let mut states = self.states.lock().unwrap();
+        let scenario = states.entry(mock_id).or_insert_with(|| MockScenario {
+            config: config.clone(),
+            sessions: HashMap::new(),
+        });
+        if scenario.config != *config {
+            // The state machine changed: restart every session
+            scenario.config = config.clone();
+            scenario.sessions.clear();
+        }
+        let current = scenario
+            .sessions
+            .get(&session)
+            .filter(|entry| now.duration_since(entry.last_seen) < SESSION_TTL)
+            .map(|entry| entry.state.clone())
+            .unwrap_or_else(|| config.initial_state.clone());
+
+        let fired = config.transitions.iter().find(|t| {
+            (t.from_state == current || t.from_state == ANY_STATE)
+                && trigger_matches(t.trigger.as_ref(), request)
+        });
+
+        match fired {
+            Some(transition) => {
+                debug!(
+                    "Scenario {} (session {:?}): {} -> {}",
+                    mock_id, session, current, transition.to_state
+                );
+                if !scenario.sessions.contains_key(&session) {
+                    scenario.make_room(now);
+                }
+                scenario.sessions.insert(
+                    session,
+                    SessionEntry {
+                        state: transition.to_state.clone(),
+                        last_seen: now,
+                    },
+                );
+                transition
+                    .response
+                    .as_ref()
+                    .or_else(|| state_response(config, &transition.to_state))
+            }
+            None => {
+                if let Some(entry) = scenario.sessions.get_mut(&session) {
+                    entry.last_seen = now;
+                }
+                state_response(config, &current)
+            }
+        }
+    }
+
+    /// Drop the state of mocks that are gone or whose `state_config` changed
+    ///
+    /// `active` lists the stateful mocks currently served; called whenever the
+    /// matcher reloads its snapshot.
+    pub fn retain<'a>(&self, active: impl IntoIterator<Item = (Uuid, &'a StateConfig)>) {
+        let active: HashMap<Uuid, &StateConfig> = active.into_iter().collect();
+        let mut states = self.states.lock().unwrap();
+        states.retain(|id, scenario| active.get(id).is_some_and(|c| **c == scenario.config));
+    }
+
+    /// Sessions of a mock that have left the initial state, sorted by session
+    pub fn sessions(&self, mock_id: Uuid) -> Vec<SessionState> {
+        let now = Instant::now();
+        let states = self.states.lock().unwrap();
+        let mut sessions: Vec<SessionState> = states
+            .get(&mock_id)
+            .into_iter()
+            .flat_map(|scenario| &scenario.sessions)
+            .filter(|(_, entry)| now.duration_since(entry.last_seen) < SESSION_TTL)
+            .map(|(session, entry)| SessionState {
+                session: session.clone(),
+                state: entry.state.clone(),
+            })
+            .collect();
+        sessions.sort_by(|a, b| a.session.cmp(&b.session));
+        sessions
+    }
+
+    /// Reset a mock's scenario to its initial state
+    ///
+    /// With `session`, only that session is reset.
+    pub fn reset(&self, mock_id: Uuid, session: Option<&str>) {
+        let mut states = self.states.lock().unwrap();
+        match session {
+            Some(session) => {
+                if let Some(scenario) = states.get_mut(&mock_id) {
+                    scenario.sessions.remove(&Some(session.to_string()));
+                }
+            }
+            None => {
+                states.remove(&mock_id);
+            }
+        }
+    }
+}
+
+/// Read the session ID from a request header or cookie
+fn session_id(key: &SessionKey, headers: &HeaderMap) -> Option<String> {
+    match key.source {
+        SessionKeySource::Header => headers
+            .get(key.name.as_str())
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string),
+        SessionKeySource::Cookie => headers
+            .get_all(COOKIE)
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .flat_map(|v| v.split(';'))
+            .filter_map(|pair| pair.trim().split_once('='))
+            .find(|(name, _)| *name == key.name)
+            .map(|(_, value)| value.to_string()),
+    }
+}
+
+/// Whether a trigger fires; a missing trigger or condition fires on any request
+///
+/// - `request`: space-separated HTTP method and/or path pattern, e.g. `POST /orders/*`
+/// - `body`: JSONPath condition, e.g. `$.status=paid`; without one the body must be JSON
+/// - `header`: header condition, e.g. `X-Step=next`
+fn trigger_matches(trigger: Option<&StateTrigger>, request: &ScenarioRequest<'_>) -> bool {
+    let Some(trigger) = trigger else {
+        return true;
+    };
+    let condition = trigger.condition.as_deref().map(str::trim);
+
+    match (trigger.trigger_type, condition) {
+        (StateTriggerType::Request, Some(condition)) => condition.split_whitespace().all(|token| {
+            if token.starts_with('/') || token.starts_with("regex:") {
+                matches(request, "uri", token)
+            } else {
+                token.eq_ignore_ascii_case(request.method)
+            }
+        }),
+        (StateTriggerType::Body, None) => request.body.is_some(),
+        (StateTriggerType::Body, Some(condition)) => matches(request, "json", condition),
+        (StateTriggerType::Header, Some(condition)) => matches(request, "header", condition),
+        (_, None) => true,
+    }
+}
+
+fn matches(request: &ScenarioRequest<'_>, condition_type: &str, value: &str) -> bool {
+    MockBuilder::matches_conditions(
+        request.uri,
+        request.headers,
+        request.body,
+        &[Condition {
+            condition_type: condition_type.to_string(),
+            value: value.to_string(),
+        }],
+    )
+}
+
+/// Response of a state: that of the first transition into it that has one
+fn state_response<'a>(config: &'a StateConfig, state: &str) -> Option<&'a ResponseConfig> {
+    config
+        .transitions
+        .iter()
+        .filter(|t| t.to_state == state)
+        .find_map(|t| t.response.as_ref())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hyper::header::HeaderValue;
+    use serde_json::json;
+
+    fn config(value: Value) -> StateConfig {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn status(response: Option<&ResponseConfig>) -> Option<u16> {
+        response.map(|r| r.status)
+    }
+
+    fn request<'a>(method: &'a str, headers: &'a HeaderMap) -> ScenarioRequest<'a> {
+        ScenarioRequest {
+            method,
+            uri: "/orders/1",
+            headers,
+            body: None,
+        }
+    }
+
+    #[test]
+    fn test_transitions_and_state_responses() {
+        let config = config(json!({
+            "initial_state": "pending",
+            "transitions": [
+                {"from_state": "pending", "to_state": "processing",
+                 "response": {"status": 202}},
+                {"from_state": "processing", "to_state": "shipped",
+                 "trigger": {"type": "header", "condition": "X-Step=ship"},
+                 "response": {"status": 200}}
+            ]
+        }));
+        let store = ScenarioStore::new();
+        let id = Uuid::new_v4();
+        let plain = HeaderMap::new();
+        let mut ship = HeaderMap::new();
+        ship.insert("X-Step", HeaderValue::from_static("ship"));
+
+        assert_eq!(
+            status(store.advance(id, &config, &request("GET", &plain))),
+            Some(202)
+        );
+        // Trigger does not match: stay in processing and serve its response
+        assert_eq!(
+            status(store.advance(id, &config, &request("GET", &plain))),
+            Some(202)
+        );
+        assert_eq!(
+            status(store.advance(id, &config, &request("GET", &ship))),
+            Some(200)
+        );
+        assert_eq!(
+            status(store.advance(id, &config, &request("GET", &plain))),
+            Some(200)
+        );
+        assert_eq!(
+            store.sessions(id),
+            vec![SessionState {
+                session: None,
+                state: "shipped".to_string()
+            }]
+        );
+
+        store.reset(id, None);
+        assert!(store.sessions(id).is_empty());
+        assert_eq!(
+            status(store.advance(id, &config, &request("GET", &plain))),
+            Some(202)
+        );
+    }
+
+    #[test]
+    fn test_request_and_body_triggers() {
+        let config = config(json!({
+            "initial_state": "empty",
+            "transitions": [
+                {"from_state": "empty", "to_state": "created",
+                 "trigger": {"type": "request", "condition": "POST /orders/*"}},
+                {"from_state": "*", "to_state": "paid",
+                 "trigger": {"type": "body", "condition": "$.status=paid"},
+                 "response": {"status": 204}}
+            ]
+        }));
+        let store = ScenarioStore::new();
+        let id = Uuid::new_v4();
+        let headers = HeaderMap::new();
+
+        // No transition or state response: the base response applies
+        assert_eq!(store.advance(id, &config, &request("GET", &headers)), None);
+        assert_eq!(store.advance(id, &config, &request("POST", &headers)), None);
+        assert_eq!(store.sessions(id)[0].state, "created");
+
+        let body = json!({"status": "paid"});
+        let paid = ScenarioRequest {
+            body: Some(&body),
+            ..request("PUT", &headers)
+        };
+        assert_eq!(status(store.advance(id, &config, &paid)), Some(204));
+        assert_eq!(store.sessions(id)[0].state, "paid");
+    }
+
+    #[test]
+    fn test_sessions_are_isolated() {
+        let config = config(json!({
+            "initial_state": "a",
+            "transitions": [{"from_state": "a", "to_state": "b", "response": {"status": 201}}],
+            "session_key": {"source": "cookie", "name": "sid"}
+        }));
+        let store = ScenarioStore::new();
+        let id = Uuid::new_v4();
+        let mut alice = HeaderMap::new();
+        alice.insert(COOKIE, HeaderValue::from_static("theme=dark; sid=alice"));
+        let mut bob = HeaderMap::new();
+        bob.insert(COOKIE, HeaderValue::from_static("sid=bob"));
+
+        store.advance(id, &config, &request("GET", &alice));
+        assert_eq!(
+            store.sessions(id),
+            vec![SessionState {
+                session: Some("alice".to_string()),
+                state: "b".to_string()
+            }]
+        );
+
+        store.advance(id, &config, &request("GET", &bob));
+        store.reset(id, Some("alice"));
+        assert_eq!(store.sessions(id).len(), 1);
+        assert_eq!(store.sessions(id)[0].session.as_deref(), Some("bob"));
+    }
+
+    #[test]
+    fn test_sessions_per_mock_are_capped() {
+        let config = config(json!({
+            "initial_state": "a",
+            "transitions": [{"from_state": "a", "to_state": "b"}],
+            "session_key": {"source": "header", "name": "X-Session"}
+        }));
+        let store = ScenarioStore::new();
+        let id = Uuid::new_v4();
+        for i in 0..=MAX_SESSIONS_PER_MOCK {
+            let mut headers = HeaderMap::new();
+            headers.insert("X-Session", HeaderValue::from(i));
+            store.advance(id, &config, &request("GET", &headers));
+        }
+        let sessions = store.sessions(id);
+        assert_eq!(sessions.len(), MAX_SESSIONS_PER_MOCK);
+        // The least recently used session was evicted
+        assert!(!sessions.iter().any(|s| s.session.as_deref() == Some("0")));
+    }
+
+    #[test]
+    fn test_state_dropped_when_config_changes_or_mock_removed() {
+        let first = config(json!({
+            "initial_state": "a",
+            "transitions": [{"from_state": "a", "to_state": "b"}]
+        }));
+        let second = config(json!({
+            "initial_state": "a",
+            "transitions": [{"from_state": "a", "to_state": "c"}]
+        }));
+        let store = ScenarioStore::new();
+        let (kept, changed, removed) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let headers = HeaderMap::new();
+        for id in [kept, changed, removed] {
+            store.advance(id, &first, &request("GET", &headers));
+        }
+
+        store.retain([(kept, &first), (changed, &second)]);
+        assert_eq!(store.sessions(kept)[0].state, "b");
+        assert!(store.sessions(changed).is_empty());
+        assert!(store.sessions(removed).is_empty());
+
+        // Advancing with a new config restarts from the initial state
+        store.advance(kept, &second, &request("GET", &headers));
+        assert_eq!(store.sessions(kept)[0].state, "c");
+    }
+}
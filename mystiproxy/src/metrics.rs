@@ -3,10 +3,12 @@
 //! 提供 Prometheus 指标收集和导出功能。
 //!
 //! 指标注册到进程级 Registry（prometheus 全局 default registry 的独立实例封装），
-//! 通过 `/metrics` 端点以 exposition 格式导出。
+//! 通过 `/metrics` 端点以 exposition 格式导出；`/upstreams` 端点以 JSON 返回
+//! 各引擎上游 endpoint 的健康状态；`POST /cache/purge?prefix=` 按路径前缀清除响应缓存（`prefix` 必填，
+//! 仅对配置了 `cache.purge_endpoint: true` 的引擎生效）。
 
 use std::net::SocketAddr;
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
 use std::time::Duration;
 
 use bytes::Bytes;
@@ -15,11 +17,15 @@ use hyper::body::Incoming;
 use hyper::service::service_fn;
 use hyper::{Request, Response, StatusCode};
 use prometheus::{
-    core::Collector, Encoder, Gauge, Histogram, HistogramOpts, IntCounter, IntCounterVec, Opts,
-    Registry, TextEncoder,
+    core::Collector, Encoder, Gauge, Histogram, HistogramOpts, IntCounter, IntCounterVec,
+    IntGaugeVec, Opts, Registry, TextEncoder,
 };
 use tracing::info;
 
+use crate::balancer::LoadBalancer;
+use crate::circuit_breaker::CircuitState;
+use crate::http::ResponseCache;
+
 /// 监控指标管理器
 pub struct MetricsManager {
     registry: Registry,
@@ -28,6 +34,17 @@ pub struct MetricsManager {
     tcp_connection_duration_seconds: Histogram,
     errors_total: IntCounter,
     memory_usage_bytes: Gauge,
+    upstream_healthy: IntGaugeVec,
+    upstream_active_connections: IntGaugeVec,
+    upstream_pool_idle_connections: IntGaugeVec,
+    upstream_pool_in_use_connections: IntGaugeVec,
+    circuit_breaker_state: IntGaugeVec,
+    circuit_breaker_transitions_total: IntCounterVec,
+    cache_requests_total: IntCounterVec,
+    /// 已登记的多目标引擎：(引擎名, 负载均衡器)
+    upstreams: RwLock<Vec<(String, Arc<LoadBalancer>)>>,
+    /// 已登记的响应缓存：(引擎名, 缓存)
+    caches: RwLock<Vec<(String, Arc<ResponseCache>)>>,
 }
 
 fn register<C: Collector + Clone + 'static>(registry: &Registry, c: C) -> C {
@@ -81,6 +98,90 @@ impl MetricsManager {
             Gauge::new("memory_usage_bytes", "Memory usage in bytes").unwrap(),
         );
 
+        let upstream_healthy = register(
+            &registry,
+            IntGaugeVec::new(
+                Opts::new(
+                    "upstream_healthy",
+                    "Whether an upstream target is eligible for selection (1) or not (0)",
+                ),
+                &["engine", "target"],
+            )
+            .unwrap(),
+        );
+
+        let upstream_active_connections = register(
+            &registry,
+            IntGaugeVec::new(
+                Opts::new(
+                    "upstream_active_connections",
+                    "Active connections per upstream target",
+                ),
+                &["engine", "target"],
+            )
+            .unwrap(),
+        );
+
+        let upstream_pool_idle_connections = register(
+            &registry,
+            IntGaugeVec::new(
+                Opts::new(
+                    "upstream_pool_idle_connections",
+                    "Idle keep-alive connections pooled per upstream target",
+                ),
+                &["target"],
+            )
+            .unwrap(),
+        );
+
+        let upstream_pool_in_use_connections = register(
+            &registry,
+            IntGaugeVec::new(
+                Opts::new(
+                    "upstream_pool_in_use_connections",
+                    "Pooled upstream connections currently serving a request",
+                ),
+                &["target"],
+            )
+            .unwrap(),
+        );
+
+        let circuit_breaker_state = register(
+            &registry,
+            IntGaugeVec::new(
+                Opts::new(
+                    "circuit_breaker_state",
+                    "Circuit breaker state per upstream target (0 closed, 1 open, 2 half-open)",
+                ),
+                &["engine", "target"],
+            )
+            .unwrap(),
+        );
+
+        let circuit_breaker_transitions_total = register(
+            &registry,
+            IntCounterVec::new(
+                Opts::new(
+                    "circuit_breaker_transitions_total",
+                    "Circuit breaker state changes by new state",
+                ),
+                &["engine", "target", "state"],
+            )
+            .unwrap(),
+        );
+
+        let cache_requests_total = register(
+            &registry,
+            IntCounterVec::new(
+                Opts::new(
+                    "cache_requests_total",
+                    "Response cache lookups by result (hit, miss, revalidated, bypass)",
+                ),
+                &["engine", "result"],
+            )
+            .unwrap(),
+        );
+
         // CounterVec exposes no children until a label combination is used;
         // pre-touch a neutral combination so the metric always shows up in gather().
         http_requests_total.with_label_values(&["none", "0"]);
@@ -92,6 +193,15 @@ impl MetricsManager {
             tcp_connection_duration_seconds,
             errors_total,
             memory_usage_bytes,
+            upstream_healthy,
+            upstream_active_connections,
+            upstream_pool_idle_connections,
+            upstream_pool_in_use_connections,
+            circuit_breaker_state,
+            circuit_breaker_transitions_total,
+            cache_requests_total,
+            upstreams: RwLock::new(Vec::new()),
+            caches: RwLock::new(Vec::new()),
         }
     }
 
@@ -102,6 +212,7 @@ impl MetricsManager {
 
     /// Gather all registered metrics as Prometheus exposition text.
     pub fn gather(&self) -> String {
+        self.refresh_upstream_gauges();
         let mut buf = Vec::new();
         let encoder = TextEncoder::new();
         if encoder.encode(&self.registry.gather(), &mut buf).is_err() {
@@ -135,24 +246,35 @@ impl MetricsManager {
                 let service = service_fn(move |req: Request<Incoming>| {
                     let metrics = metrics.clone();
                     async move {
-                        let resp: Response<Full<Bytes>> = if req.method()
-                            == hyper::http::Method::GET
-                            && req.uri().path() == "/metrics"
-                        {
-                            Response::builder()
-                                .status(StatusCode::OK)
-                                .header(
-                                    hyper::http::header::CONTENT_TYPE,
-                                    "text/plain; version=0.0.4",
-                                )
-                                .body(Full::new(Bytes::from(metrics.gather())))
-                                .unwrap()
-                        } else {
-                            Response::builder()
-                                .status(StatusCode::NOT_FOUND)
-                                .body(Full::new(Bytes::new()))
-                                .unwrap()
-                        };
+                        let is_get = req.method() == hyper::http::Method::GET;
+                        let resp: Response<Full<Bytes>> =
+                            if is_get && req.uri().path() == "/metrics" {
+                                Response::builder()
+                                    .status(StatusCode::OK)
+                                    .header(
+                                        hyper::http::header::CONTENT_TYPE,
+                                        "text/plain; version=0.0.4",
+                                    )
+                                    .body(Full::new(Bytes::from(metrics.gather())))
+                                    .unwrap()
+                            } else if is_get && req.uri().path() == "/upstreams" {
+                                Response::builder()
+                                    .status(StatusCode::OK)
+                                    .header(hyper::http::header::CONTENT_TYPE, "application/json")
+                                    .body(Full::new(Bytes::from(
+                                        metrics.upstream_status().to_string(),
+                                    )))
+                                    .unwrap()
+                            } else if req.method() == hyper::http::Method::POST
+                                && req.uri().path() == "/cache/purge"
+                            {
+                                metrics.purge_request(req.uri().query()).await
+                            } else {
+                                Response::builder()
+                                    .status(StatusCode::NOT_FOUND)
+                                    .body(Full::new(Bytes::new()))
+                                    .unwrap()
+                            };
                         Ok::<_, std::convert::Infallible>(resp)
                     }
                 });
@@ -188,6 +310,142 @@ impl MetricsManager {
         self.errors_total.inc();
     }
 
+    /// 登记多目标引擎的负载均衡器，导出其 endpoint 健康状态
+    pub fn register_upstreams(&self, engine: &str, balancer: Arc<LoadBalancer>) {
+        let mut upstreams = self.upstreams.write().unwrap_or_else(|e| e.into_inner());
+        upstreams.retain(|(name, _)| name != engine);
+        upstreams.push((engine.to_string(), balancer));
+    }
+
+    /// 各引擎上游 endpoint 的健康状态（JSON）
+    pub fn upstream_status(&self) -> serde_json::Value {
+        let upstreams = self.upstreams.read().unwrap_or_else(|e| e.into_inner());
+        let engines: serde_json::Map<String, serde_json::Value> = upstreams
+            .iter()
+            .map(|(engine, balancer)| {
+                let targets = balancer
+                    .endpoints()
+                    .iter()
+                    .map(|endpoint| {
+                        serde_json::json!({
+                            "target": endpoint.address(),
+                            "weight": endpoint.weight(),
+                            "healthy": endpoint.is_healthy(),
+                            "probe_healthy": endpoint.is_probe_healthy(),
+                            "ejected": endpoint.is_ejected(),
+                            "active_connections": endpoint.active_connections(),
+                        })
+                    })
+                    .collect();
+                (engine.clone(), serde_json::Value::Array(targets))
+            })
+            .collect();
+        serde_json::Value::Object(engines)
+    }
+
+    fn refresh_upstream_gauges(&self) {
+        let upstreams = self.upstreams.read().unwrap_or_else(|e| e.into_inner());
+        for (engine, balancer) in upstreams.iter() {
+            for endpoint in balancer.endpoints() {
+                let labels = [engine.as_str(), endpoint.address()];
+                self.upstream_healthy
+                    .with_label_values(&labels)
+                    .set(endpoint.is_healthy() as i64);
+                self.upstream_active_connections
+                    .with_label_values(&labels)
+                    .set(endpoint.active_connections() as i64);
+            }
+        }
+    }
+
+    /// 记录上游连接池大小变化（增量；同一目标的多个连接池累加）
+    pub fn record_upstream_pool(&self, target: &str, idle_delta: i64, in_use_delta: i64) {
+        if idle_delta != 0 {
+            self.upstream_pool_idle_connections
+                .with_label_values(&[target])
+                .add(idle_delta);
+        }
+        if in_use_delta != 0 {
+            self.upstream_pool_in_use_connections
+                .with_label_values(&[target])
+                .add(in_use_delta);
+        }
+    }
+
+    /// 记录熔断器状态变化
+    pub fn record_circuit_breaker(&self, engine: &str, target: &str, state: CircuitState) {
+        let value = match state {
+            CircuitState::Closed => 0,
+            CircuitState::Open => 1,
+            CircuitState::HalfOpen => 2,
+        };
+        self.circuit_breaker_state
+            .with_label_values(&[engine, target])
+            .set(value);
+        self.circuit_breaker_transitions_total
+            .with_label_values(&[engine, target, state.as_str()])
+            .inc();
+    }
+
+    /// 处理 `POST /cache/purge`：必须给出非空的 `prefix`，否则返回 400
+    async fn purge_request(&self, query: Option<&str>) -> Response<Full<Bytes>> {
+        let params: std::collections::HashMap<String, String> = query
+            .map(|q| {
+                url::form_urlencoded::parse(q.as_bytes())
+                    .into_owned()
+                    .collect()
+            })
+            .unwrap_or_default();
+        let (status, body) = match params.get("prefix").filter(|p| !p.is_empty()) {
+            Some(prefix) => {
+                let purged = self
+                    .purge_cache(params.get("engine").map(String::as_str), prefix)
+                    .await;
+                (StatusCode::OK, serde_json::json!({ "purged": purged }))
+            }
+            None => (
+                StatusCode::BAD_REQUEST,
+                serde_json::json!({ "error": "missing prefix" }),
+            ),
+        };
+        Response::builder()
+            .status(status)
+            .header(hyper::http::header::CONTENT_TYPE, "application/json")
+            .body(Full::new(Bytes::from(body.to_string())))
+            .unwrap()
+    }
+
+    /// 登记引擎的响应缓存，供 `/cache/purge` 清除（需引擎配置 `cache.purge_endpoint: true`）
+    pub fn register_cache(&self, engine: &str, cache: Arc<ResponseCache>) {
+        let mut caches = self.caches.write().unwrap_or_else(|e| e.into_inner());
+        caches.retain(|(name, _)| name != engine);
+        caches.push((engine.to_string(), cache));
+    }
+
+    /// 按路径前缀清除响应缓存（`engine` 为 None 时清除所有引擎），返回清除的条目数
+    pub async fn purge_cache(&self, engine: Option<&str>, prefix: &str) -> usize {
+        let caches: Vec<Arc<ResponseCache>> = self
+            .caches
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .filter(|(name, _)| engine.is_none_or(|engine| engine == name))
+            .map(|(_, cache)| cache.clone())
+            .collect();
+        let mut purged = 0;
+        for cache in caches {
+            purged += cache.purge_prefix(prefix).await;
+        }
+        purged
+    }
+
+    /// 记录响应缓存查找结果
+    pub fn record_cache_request(&self, engine: &str, result: &str) {
+        self.cache_requests_total
+            .with_label_values(&[engine, result])
+            .inc();
+    }
+
     /// 记录内存使用指标
     pub fn record_memory_usage(&self, used: u64, _total: u64) {
         self.memory_usage_bytes.set(used as f64);
@@ -294,6 +552,114 @@ mod tests {
         assert!(out.contains("errors_total 2"), "{out}");
     }
 
+    #[test]
+    fn test_upstream_health_exported() {
+        use crate::config::{TargetConfig, WeightedTarget};
+
+        let target = TargetConfig::Weighted(
+            ["tcp://a:1", "tcp://b:1"]
+                .iter()
+                .map(|address| WeightedTarget {
+                    address: address.to_string(),
+                    weight: 1,
+                })
+                .collect(),
+        );
+        let balancer = Arc::new(LoadBalancer::new(&target, None).unwrap());
+        balancer.endpoints()[1].set_probe_healthy(false);
+
+        let m = MetricsManager::new();
+        m.register_upstreams("api", balancer);
+        let out = m.gather();
+        assert!(
+            out.contains(r#"upstream_healthy{engine="api",target="tcp://a:1"} 1"#),
+            "{out}"
+        );
+        assert!(
+            out.contains(r#"upstream_healthy{engine="api",target="tcp://b:1"} 0"#),
+            "{out}"
+        );
+
+        let status = m.upstream_status();
+        assert_eq!(status["api"][0]["healthy"], true);
+        assert_eq!(status["api"][1]["healthy"], false);
+        assert_eq!(status["api"][1]["target"], "tcp://b:1");
+    }
+
+    #[test]
+    fn test_upstream_pool_gauges_track_deltas() {
+        let m = MetricsManager::new();
+        m.record_upstream_pool("tcp://a:1", 2, 1);
+        m.record_upstream_pool("tcp://a:1", -1, 0);
+        let out = m.gather();
+        assert!(
+            out.contains(r#"upstream_pool_idle_connections{target="tcp://a:1"} 1"#),
+            "{out}"
+        );
+        assert!(
+            out.contains(r#"upstream_pool_in_use_connections{target="tcp://a:1"} 1"#),
+            "{out}"
+        );
+    }
+
+    #[test]
+    fn test_circuit_breaker_metrics() {
+        let m = MetricsManager::new();
+        m.record_circuit_breaker("api", "tcp://a:1", CircuitState::Open);
+        m.record_circuit_breaker("api", "tcp://a:1", CircuitState::HalfOpen);
+        let out = m.gather();
+        assert!(
+            out.contains(r#"circuit_breaker_state{engine="api",target="tcp://a:1"} 2"#),
+            "{out}"
+        );
+        assert!(
+            out.contains(
+                r#"circuit_breaker_transitions_total{engine="api",state="open",target="tcp://a:1"} 1"#
+            ),
+            "{out}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cache_metrics_and_purge() {
+        let m = MetricsManager::new();
+        m.record_cache_request("api", "hit");
+        let out = m.gather();
+        assert!(
+            out.contains(r#"cache_requests_total{engine="api",result="hit"} 1"#),
+            "{out}"
+        );
+
+        let cache = Arc::new(ResponseCache::new(&Default::default()));
+        let req = Request::get("/docs/a").body(()).unwrap();
+        cache
+            .store(
+                &crate::http::CacheKey::from_request(&req),
+                StatusCode::OK,
+                Default::default(),
+                Bytes::from("a"),
+                Duration::from_secs(60),
+            )
+            .await;
+        m.register_cache("api", cache.clone());
+        assert_eq!(m.purge_cache(Some("other"), "/").await, 0);
+        assert_eq!(m.purge_cache(None, "/docs").await, 1);
+        assert!(cache.is_empty());
+
+        for query in [None, Some("engine=api"), Some("prefix=")] {
+            assert_eq!(
+                m.purge_request(query).await.status(),
+                StatusCode::BAD_REQUEST
+            );
+        }
+        assert_eq!(
+            m.purge_request(Some("prefix=%2Fdocs&engine=api"))
+                .await
+                .status(),
+            StatusCode::OK
+        );
+    }
+
     #[tokio::test]
     async fn test_metrics_server_serves_exposition() {
         use tokio::io::{AsyncReadExt, AsyncWriteExt};
@@ -328,6 +694,10 @@ mod tests {
         assert!(resp.contains("http_requests_total"), "{resp}");
         assert!(resp.contains("method=\"GET\""), "{resp}");
 
+        let resp = http_get("/upstreams").await;
+        assert!(resp.starts_with("HTTP/1.1 200"), "{resp}");
+        assert!(resp.contains("application/json"), "{resp}");
+
         let resp = http_get("/nope").await;
         assert!(resp.starts_with("HTTP/1.1 404"), "{resp}");
 
@@ -0,0 +1,484 @@
+//! Mock 模板的假数据生成
+//!
+//! 为模板引擎提供 `faker.*` helper：姓名、邮箱、地址、公司、lorem 文本、IBAN、电话与日期。
+//! 数据按 [`Locale`] 区分，随机数来自模板渲染器的 RNG，在 `{{#faker seed=...}}` 块内可复现。
+
+use chrono::{Duration, NaiveDate, Utc};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use serde_json::Value;
+
+/// 假数据的语言区域
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Locale {
+    #[default]
+    En,
+    De,
+    Fr,
+}
+
+impl Locale {
+    /// 解析 `en`、`de_DE`、`fr-FR` 等写法，只看语言部分
+    pub fn parse(value: &str) -> Option<Self> {
+        let lang = value.split(['_', '-']).next()?.to_ascii_lowercase();
+        match lang.as_str() {
+            "en" => Some(Self::En),
+            "de" => Some(Self::De),
+            "fr" => Some(Self::Fr),
+            _ => None,
+        }
+    }
+
+    fn data(self) -> &'static LocaleData {
+        match self {
+            Self::En => &EN,
+            Self::De => &DE,
+            Self::Fr => &FR,
+        }
+    }
+}
+
+/// 单个语言区域的词表与格式
+struct LocaleData {
+    first_names: &'static [&'static str],
+    last_names: &'static [&'static str],
+    streets: &'static [&'static str],
+    cities: &'static [&'static str],
+    company_suffixes: &'static [&'static str],
+    email_domains: &'static [&'static str],
+    /// `#` 替换为随机数字
+    phone_formats: &'static [&'static str],
+    /// `#` 替换为随机数字
+    zip_format: &'static str,
+    /// IBAN 国家代码与 BBAN 格式（`#` 为数字）
+    iban: (&'static str, &'static str),
+}
+
+static EN: LocaleData = LocaleData {
+    first_names: &[
+        "James",
+        "Mary",
+        "John",
+        "Patricia",
+        "Robert",
+        "Jennifer",
+        "Michael",
+        "Linda",
+        "David",
+        "Elizabeth",
+        "William",
+        "Susan",
+        "Richard",
+        "Jessica",
+        "Thomas",
+        "Sarah",
+    ],
+    last_names: &[
+        "Smith", "Johnson", "Williams", "Brown", "Jones", "Miller", "Davis", "Wilson", "Taylor",
+        "Clark", "Lewis", "Walker", "Hall", "Young", "King", "Wright",
+    ],
+    streets: &[
+        "Oak Street",
+        "Maple Avenue",
+        "Cedar Lane",
+        "Park Road",
+        "Elm Street",
+        "Washington Avenue",
+        "Lake Drive",
+        "Hill Road",
+    ],
+    cities: &[
+        "Springfield",
+        "Riverside",
+        "Franklin",
+        "Greenville",
+        "Bristol",
+        "Clinton",
+        "Fairview",
+        "Salem",
+    ],
+    company_suffixes: &["Inc.", "LLC", "Group", "Ltd.", "& Sons"],
+    email_domains: &["example.com", "example.org", "mail.test"],
+    phone_formats: &["+1 (###) ###-####", "+1 ###-###-####"],
+    zip_format: "#####",
+    iban: ("GB", "NWBK##############"),
+};
+
+static DE: LocaleData = LocaleData {
+    first_names: &[
+        "Lukas", "Anna", "Leon", "Lena", "Finn", "Marie", "Jonas", "Sophie", "Paul", "Laura",
+        "Felix", "Julia", "Max", "Hannah", "Elias", "Lea",
+    ],
+    last_names: &[
+        "Müller",
+        "Schmidt",
+        "Schneider",
+        "Fischer",
+        "Weber",
+        "Meyer",
+        "Wagner",
+        "Becker",
+        "Schulz",
+        "Hoffmann",
+        "Koch",
+        "Richter",
+        "Klein",
+        "Wolf",
+        "Neumann",
+        "Braun",
+    ],
+    streets: &[
+        "Hauptstraße",
+        "Schulstraße",
+        "Gartenstraße",
+        "Bahnhofstraße",
+        "Dorfstraße",
+        "Bergstraße",
+        "Lindenstraße",
+        "Kirchweg",
+    ],
+    cities: &[
+        "Berlin",
+        "Hamburg",
+        "München",
+        "Köln",
+        "Frankfurt",
+        "Stuttgart",
+        "Leipzig",
+        "Dresden",
+    ],
+    company_suffixes: &["GmbH", "AG", "KG", "GmbH & Co. KG"],
+    email_domains: &["beispiel.de", "example.com", "mail.test"],
+    phone_formats: &["+49 30 #######", "+49 89 #######", "+49 151 ########"],
+    zip_format: "#####",
+    iban: ("DE", "##################"),
+};
+
+static FR: LocaleData = LocaleData {
+    first_names: &[
+        "Louis", "Emma", "Gabriel", "Jade", "Raphaël", "Louise", "Arthur", "Alice", "Jules",
+        "Chloé", "Hugo", "Léa", "Lucas", "Manon", "Adam", "Camille",
+    ],
+    last_names: &[
+        "Martin", "Bernard", "Thomas", "Petit", "Robert", "Richard", "Durand", "Dubois", "Moreau",
+        "Laurent", "Simon", "Michel", "Lefebvre", "Leroy", "Roux", "David",
+    ],
+    streets: &[
+        "rue de la Paix",
+        "rue Victor Hugo",
+        "avenue Jean Jaurès",
+        "boulevard Voltaire",
+        "rue de la République",
+        "place de la Mairie",
+        "rue du Moulin",
+        "chemin des Vignes",
+    ],
+    cities: &[
+        "Paris",
+        "Lyon",
+        "Marseille",
+        "Toulouse",
+        "Nantes",
+        "Bordeaux",
+        "Lille",
+        "Strasbourg",
+    ],
+    company_suffixes: &["SA", "SARL", "SAS", "et Fils"],
+    email_domains: &["exemple.fr", "example.com", "mail.test"],
+    phone_formats: &["+33 1 ## ## ## ##", "+33 6 ## ## ## ##"],
+    zip_format: "#####",
+    iban: ("FR", "#######################"),
+};
+
+static LOREM: &[&str] = &[
+    "lorem",
+    "ipsum",
+    "dolor",
+    "sit",
+    "amet",
+    "consectetur",
+    "adipiscing",
+    "elit",
+    "sed",
+    "do",
+    "eiusmod",
+    "tempor",
+    "incididunt",
+    "ut",
+    "labore",
+    "et",
+    "dolore",
+    "magna",
+    "aliqua",
+    "enim",
+    "ad",
+    "minim",
+    "veniam",
+    "quis",
+    "nostrud",
+    "exercitation",
+    "ullamco",
+    "laboris",
+    "nisi",
+    "aliquip",
+    "ex",
+    "ea",
+    "commodo",
+    "consequat",
+];
+
+/// 生成 `faker.<kind>` 的值；未知的 kind 或参数无效时返回 None
+///
+/// `args` 为 helper 的位置参数：`lorem` 取词数，`date` 取起止日期与格式。
+pub fn fake(kind: &str, args: &[Option<Value>], locale: Locale, rng: &mut StdRng) -> Option<Value> {
+    let data = locale.data();
+    let text = |i: usize| match args.get(i).cloned().flatten()? {
+        Value::String(s) => Some(s),
+        other => Some(other.to_string()),
+    };
+
+    let value = match kind {
+        "firstName" => pick(data.first_names, rng).to_string(),
+        "lastName" => pick(data.last_names, rng).to_string(),
+        "name" => format!(
+            "{} {}",
+            pick(data.first_names, rng),
+            pick(data.last_names, rng)
+        ),
+        "email" => {
+            let user = format!(
+                "{}.{}{}",
+                ascii_fold(pick(data.first_names, rng)),
+                ascii_fold(pick(data.last_names, rng)),
+                rng.gen_range(1..100)
+            );
+            format!("{}@{}", user, pick(data.email_domains, rng))
+        }
+        "street" => street(locale, rng),
+        "city" => pick(data.cities, rng).to_string(),
+        "zip" => digits(data.zip_format, rng),
+        "address" => {
+            let street = street(locale, rng);
+            let zip = digits(data.zip_format, rng);
+            let city = pick(data.cities, rng);
+            match locale {
+                Locale::En => format!("{street}, {city} {zip}"),
+                Locale::De | Locale::Fr => format!("{street}, {zip} {city}"),
+            }
+        }
+        "company" => format!(
+            "{} {}",
+            pick(data.last_names, rng),
+            pick(data.company_suffixes, rng)
+        ),
+        "phone" => digits(pick(data.phone_formats, rng), rng),
+        "iban" => iban(data.iban, rng),
+        "lorem" => {
+            let words = text(0).and_then(|n| n.parse().ok()).unwrap_or(8usize);
+            (0..words.min(1000))
+                .map(|_| pick(LOREM, rng))
+                .collect::<Vec<_>>()
+                .join(" ")
+        }
+        "date" => {
+            let today = Utc::now().date_naive();
+            let from = match text(0) {
+                Some(s) => NaiveDate::parse_from_str(&s, "%Y-%m-%d").ok()?,
+                None => today - Duration::days(365),
+            };
+            let to = match text(1) {
+                Some(s) => NaiveDate::parse_from_str(&s, "%Y-%m-%d").ok()?,
+                None => today,
+            };
+            let (from, to) = (from.min(to), from.max(to));
+            let day = from + Duration::days(rng.gen_range(0..=(to - from).num_days()));
+            let format = text(2).unwrap_or_else(|| "%Y-%m-%d".to_string());
+            if !super::template::is_valid_strftime(&format) {
+                return None;
+            }
+            day.format(&format).to_string()
+        }
+        _ => return None,
+    };
+    Some(Value::String(value))
+}
+
+fn pick(items: &[&'static str], rng: &mut StdRng) -> &'static str {
+    items.choose(rng).expect("non-empty word list")
+}
+
+fn street(locale: Locale, rng: &mut StdRng) -> String {
+    let name = pick(locale.data().streets, rng);
+    let number = rng.gen_range(1..200);
+    match locale {
+        Locale::De => format!("{name} {number}"),
+        Locale::En | Locale::Fr => format!("{number} {name}"),
+    }
+}
+
+/// 将格式中的 `#` 替换为随机数字
+fn digits(format: &str, rng: &mut StdRng) -> String {
+    format
+        .chars()
+        .map(|c| match c {
+            '#' => char::from(b'0' + rng.gen_range(0..10)),
+            c => c,
+        })
+        .collect()
+}
+
+/// 生成校验位正确的 IBAN（ISO 13616，mod 97）
+fn iban((country, bban_format): (&str, &str), rng: &mut StdRng) -> String {
+    let bban = digits(bban_format, rng);
+    // 校验位：BBAN + 国家代码 + "00" 转为数字后 mod 97，再以 98 减之
+    let remainder = format!("{bban}{country}00").chars().fold(0u32, |acc, c| {
+        let n = c.to_digit(36).unwrap_or(0);
+        if n >= 10 {
+            (acc * 100 + n) % 97
+        } else {
+            (acc * 10 + n) % 97
+        }
+    });
+    format!("{country}{:02}{bban}", 98 - remainder)
+}
+
+/// 邮箱用户名：小写并去掉变音符号
+fn ascii_fold(name: &str) -> String {
+    name.chars()
+        .flat_map(|c| match c {
+            'ä' | 'Ä' => "ae".chars().collect::<Vec<_>>(),
+            'ö' | 'Ö' => "oe".chars().collect(),
+            'ü' | 'Ü' => "ue".chars().collect(),
+            'ß' => "ss".chars().collect(),
+            'é' | 'è' | 'ê' | 'ë' | 'É' => vec!['e'],
+            'à' | 'â' => vec!['a'],
+            'ï' | 'î' => vec!['i'],
+            'ç' => vec!['c'],
+            c if c.is_ascii_alphanumeric() => vec![c.to_ascii_lowercase()],
+            _ => Vec::new(),
+        })
+        .collect()
+}
+
+/// 由任意值得到稳定的种子：整数直接使用，其余按文本做 FNV-1a 哈希
+pub fn seed_from(value: &Value) -> u64 {
+    if let Some(n) = value.as_u64() {
+        return n;
+    }
+    let text = match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    };
+    text.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    fn rng() -> StdRng {
+        StdRng::seed_from_u64(7)
+    }
+
+    #[test]
+    fn test_locale_parse() {
+        assert_eq!(Locale::parse("de_DE"), Some(Locale::De));
+        assert_eq!(Locale::parse("fr-FR"), Some(Locale::Fr));
+        assert_eq!(Locale::parse("EN"), Some(Locale::En));
+        assert_eq!(Locale::parse("xx"), None);
+    }
+
+    #[test]
+    fn test_seeded_output_is_reproducible() {
+        let generate = || {
+            let mut rng = rng();
+            ["name", "email", "address", "company", "phone", "iban"]
+                .iter()
+                .map(|kind| fake(kind, &[], Locale::De, &mut rng).unwrap())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(generate(), generate());
+    }
+
+    #[test]
+    fn test_iban_check_digits() {
+        let mut rng = rng();
+        for locale in [Locale::En, Locale::De, Locale::Fr] {
+            let iban = fake("iban", &[], locale, &mut rng).unwrap();
+            let iban = iban.as_str().unwrap();
+            // 移到末尾后整体 mod 97 应为 1
+            let rearranged = format!("{}{}", &iban[4..], &iban[..4]);
+            let remainder = rearranged.chars().fold(0u32, |acc, c| {
+                let n = c.to_digit(36).unwrap();
+                if n >= 10 {
+                    (acc * 100 + n) % 97
+                } else {
+                    (acc * 10 + n) % 97
+                }
+            });
+            assert_eq!(remainder, 1, "{iban}");
+        }
+        assert_eq!(
+            fake("iban", &[], Locale::De, &mut rng)
+                .unwrap()
+                .as_str()
+                .unwrap()
+                .len(),
+            22
+        );
+    }
+
+    #[test]
+    fn test_email_and_lorem() {
+        let mut rng = rng();
+        let email = fake("email", &[], Locale::De, &mut rng).unwrap();
+        let email = email.as_str().unwrap();
+        assert!(email.is_ascii() && email.contains('@'), "{email}");
+
+        let lorem = fake("lorem", &[Some(Value::from(5))], Locale::En, &mut rng).unwrap();
+        assert_eq!(lorem.as_str().unwrap().split(' ').count(), 5);
+    }
+
+    #[test]
+    fn test_date_in_range() {
+        let mut rng = rng();
+        let args = [
+            Some(Value::from("2024-02-01")),
+            Some(Value::from("2024-02-03")),
+            Some(Value::from("%d")),
+        ];
+        for _ in 0..20 {
+            let day = fake("date", &args, Locale::En, &mut rng).unwrap();
+            assert!(["01", "02", "03"].contains(&day.as_str().unwrap()));
+        }
+        assert_eq!(
+            fake("date", &[Some(Value::from("bad"))], Locale::En, &mut rng),
+            None
+        );
+        // 无效的格式串不生成
+        let invalid_format = [
+            Some(Value::from("2024-01-01")),
+            Some(Value::from("2024-01-03")),
+            Some(Value::from("%Q")),
+        ];
+        assert_eq!(fake("date", &invalid_format, Locale::En, &mut rng), None);
+        assert_eq!(fake("unknown", &[], Locale::En, &mut rng), None);
+    }
+
+    #[test]
+    fn test_seed_from() {
+        assert_eq!(seed_from(&Value::from(42)), 42);
+        assert_eq!(
+            seed_from(&Value::from("user-1")),
+            seed_from(&Value::from("user-1"))
+        );
+        assert_ne!(
+            seed_from(&Value::from("user-1")),
+            seed_from(&Value::from("user-2"))
+        );
+    }
+}
@@ -0,0 +1,280 @@
+//! Mock 文件响应体
+//!
+//! `type: file` 的响应体在每次请求时从磁盘读取，修改文件后无需重启即可生效。
+//! 相对路径基于引擎的 `mock_root`，解析后不得越出该目录；绝对路径以及未配置 `mock_root`
+//! 时的相对路径仅允许来自引擎配置。
+//! 未开启模版渲染的文件按块流式返回，未配置 `Content-Type` 时按扩展名推断（与静态文件服务一致）。
+
+use std::io;
+use std::path::{Path, PathBuf};
+
+use bytes::Bytes;
+use http_body_util::{BodyExt, StreamBody};
+use hyper::body::Frame;
+use tokio::io::AsyncReadExt;
+use tracing::warn;
+
+use super::{BoxBody, MockResponse, TemplateContext};
+use crate::error::MystiProxyError;
+
+/// 流式读取文件的块大小
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// mock 响应体引用的文件
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MockBodyFile {
+    /// 配置中的路径
+    pub path: PathBuf,
+    /// 是否将文件内容作为模版渲染
+    pub templated: bool,
+    /// 是否允许绝对路径（仅引擎配置中的 mock，管理 API 来源的 mock 不允许）
+    pub allow_absolute: bool,
+}
+
+impl MockBodyFile {
+    /// 创建文件响应体
+    pub fn new(path: impl Into<PathBuf>, templated: bool) -> Self {
+        Self {
+            path: path.into(),
+            templated,
+            allow_absolute: false,
+        }
+    }
+
+    /// 允许使用绝对路径
+    pub fn allow_absolute(mut self) -> Self {
+        self.allow_absolute = true;
+        self
+    }
+
+    /// 实际读取的路径
+    ///
+    /// 相对路径基于 `root` 解析并规范化，结果不在 `root` 之下时拒绝；绝对路径仅在
+    /// `allow_absolute` 时原样使用。未配置 `root` 时，引擎配置中的 mock 基于工作目录解析，
+    /// 管理 API 来源的 mock 直接拒绝，避免读取进程工作目录下的任意文件。
+    pub async fn resolve(&self, root: Option<&str>) -> io::Result<PathBuf> {
+        if self.path.is_absolute() {
+            if self.allow_absolute {
+                return Ok(self.path.clone());
+            }
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                "absolute mock body file path not allowed",
+            ));
+        }
+        let root = match root {
+            Some(root) => root,
+            None if self.allow_absolute => ".",
+            None => {
+                return Err(io::Error::new(
+                    io::ErrorKind::PermissionDenied,
+                    "mock body file requires mock_root",
+                ))
+            }
+        };
+        let root = tokio::fs::canonicalize(root).await?;
+        let path = tokio::fs::canonicalize(root.join(&self.path)).await?;
+        if !path.starts_with(&root) {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                "mock body file path escapes mock_root",
+            ));
+        }
+        Ok(path)
+    }
+
+    /// 按扩展名推断的 Content-Type
+    pub fn content_type(&self) -> &'static str {
+        crate::http::mime_type(&self.path)
+    }
+}
+
+/// 渲染需要模版处理的文件响应体
+///
+/// 读取成功后文件内容渲染为 `body`，不再走流式返回；文件不是 UTF-8 文本时原样流式返回。
+pub async fn render_templated(
+    mut mock: MockResponse,
+    context: &TemplateContext,
+    root: Option<&str>,
+) -> MockResponse {
+    let Some(file) = mock.body_file.as_ref().filter(|f| f.templated) else {
+        return mock;
+    };
+    let read = match file.resolve(root).await {
+        Ok(path) => tokio::fs::read_to_string(&path).await,
+        Err(e) => Err(e),
+    };
+    match read {
+        Ok(raw) => {
+            let content_type = file.content_type();
+            mock.body = context.render(&raw);
+            mock.body_file = None;
+            if !has_content_type(&mock) {
+                mock = mock.header("Content-Type".to_string(), content_type.to_string());
+            }
+        }
+        Err(e) => warn!("Mock body file {:?} not rendered: {}", file.path, e),
+    }
+    mock
+}
+
+/// 打开文件并返回按块读取的响应体与文件大小
+pub async fn open(path: &Path) -> std::io::Result<(BoxBody, u64)> {
+    let file = tokio::fs::File::open(path).await?;
+    let len = file.metadata().await?.len();
+
+    let stream = futures::stream::unfold(Some(file), |file| async move {
+        let mut file = file?;
+        let mut buf = vec![0u8; CHUNK_SIZE];
+        match file.read(&mut buf).await {
+            Ok(0) => None,
+            Ok(n) => {
+                buf.truncate(n);
+                Some((Ok(Frame::data(Bytes::from(buf))), Some(file)))
+            }
+            // 读取出错后结束流
+            Err(e) => Some((Err(MystiProxyError::from(e)), None)),
+        }
+    });
+    Ok((StreamBody::new(stream).boxed(), len))
+}
+
+/// 响应头中是否已配置 Content-Type
+pub fn has_content_type(mock: &MockResponse) -> bool {
+    mock.headers
+        .keys()
+        .any(|k| k.eq_ignore_ascii_case("content-type"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_resolve_against_root() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("data")).unwrap();
+        std::fs::write(dir.path().join("data/users.json"), "[]").unwrap();
+        let root = dir.path().to_str();
+        let canonical_root = dir.path().canonicalize().unwrap();
+
+        let file = MockBodyFile::new("data/users.json", false);
+        assert_eq!(
+            file.resolve(root).await.unwrap(),
+            canonical_root.join("data/users.json")
+        );
+        assert_eq!(file.content_type(), "application/json");
+
+        // 仍在根目录内的 `..` 允许
+        let inner = MockBodyFile::new("data/../data/users.json", false);
+        assert_eq!(
+            inner.resolve(root).await.unwrap(),
+            canonical_root.join("data/users.json")
+        );
+
+        assert!(MockBodyFile::new("data/missing.json", false)
+            .resolve(root)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_resolve_rejects_escaping_paths() {
+        let outer = tempfile::tempdir().unwrap();
+        let root = outer.path().join("mocks");
+        std::fs::create_dir(&root).unwrap();
+        std::fs::write(outer.path().join("secret.txt"), "secret").unwrap();
+        let root = root.to_str();
+
+        let err = MockBodyFile::new("../secret.txt", false)
+            .resolve(root)
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::PermissionDenied);
+
+        let absolute = outer.path().join("secret.txt");
+        let err = MockBodyFile::new(&absolute, false)
+            .resolve(root)
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::PermissionDenied);
+
+        // 引擎配置中的 mock 可使用绝对路径
+        assert_eq!(
+            MockBodyFile::new(&absolute, false)
+                .allow_absolute()
+                .resolve(root)
+                .await
+                .unwrap(),
+            absolute
+        );
+    }
+
+    #[tokio::test]
+    async fn test_resolve_without_root() {
+        // 管理 API 来源的 mock 未配置 mock_root 时不回退到工作目录
+        let err = MockBodyFile::new("Cargo.toml", false)
+            .resolve(None)
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::PermissionDenied);
+
+        // 引擎配置中的 mock 基于工作目录解析
+        assert_eq!(
+            MockBodyFile::new("Cargo.toml", false)
+                .allow_absolute()
+                .resolve(None)
+                .await
+                .unwrap(),
+            std::env::current_dir()
+                .unwrap()
+                .canonicalize()
+                .unwrap()
+                .join("Cargo.toml")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_templated_file_rendered_and_reloaded() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("greeting.html");
+        std::fs::write(&path, "hi {{query.name}}").unwrap();
+
+        let context = TemplateContext::new("GET", "/greet?name=ada");
+        let mock = || {
+            let mut mock = MockResponse::new();
+            mock.body_file = Some(MockBodyFile::new("greeting.html", true));
+            mock
+        };
+
+        let root = dir.path().to_str();
+        let rendered = render_templated(mock(), &context, root).await;
+        assert_eq!(rendered.body, "hi ada");
+        assert!(rendered.body_file.is_none());
+        assert_eq!(
+            rendered.headers.get("Content-Type").map(String::as_str),
+            Some("text/html; charset=utf-8")
+        );
+
+        // 文件修改后下次请求即生效
+        std::fs::write(&path, "bye {{query.name}}").unwrap();
+        assert_eq!(
+            render_templated(mock(), &context, root).await.body,
+            "bye ada"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_open_streams_in_chunks() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("blob.bin");
+        let data: Vec<u8> = (0..CHUNK_SIZE * 2 + 10).map(|i| i as u8).collect();
+        std::fs::write(&path, &data).unwrap();
+
+        let (body, len) = open(&path).await.unwrap();
+        assert_eq!(len, data.len() as u64);
+        assert_eq!(body.collect().await.unwrap().to_bytes(), data);
+
+        assert!(open(&dir.path().join("missing")).await.is_err());
+    }
+}
@@ -3,7 +3,6 @@
 //! 提供 Mock 响应构建和条件匹配功能
 
 use std::collections::HashMap;
-use std::convert::Infallible;
 
 use bytes::Bytes;
 use http_body_util::{BodyExt, Empty, Full};
@@ -16,8 +15,16 @@ use tracing::{debug, info, warn};
 use crate::config::{BodyType, HeaderActionType, LocationConfig, MatchMode, ResponseConfig};
 use crate::error::{MystiProxyError, Result};
 
+pub mod faker;
+pub mod file;
+pub mod sequence;
+pub mod template;
+
+pub use file::MockBodyFile;
+pub use template::TemplateContext;
+
 /// BoxBody 类型别名
-pub type BoxBody = http_body_util::combinators::BoxBody<Bytes, Infallible>;
+pub type BoxBody = http_body_util::combinators::BoxBody<Bytes, MystiProxyError>;
 
 #[derive(Debug, Clone)]
 pub struct Condition {
@@ -36,6 +43,8 @@ pub struct MockResponse {
     pub body: String,
     /// 延迟（毫秒）
     pub delay_ms: u64,
+    /// 文件响应体（type=file），设置时替代 `body`
+    pub body_file: Option<MockBodyFile>,
 }
 
 impl Default for MockResponse {
@@ -45,6 +54,7 @@ impl Default for MockResponse {
             headers: HashMap::new(),
             body: String::new(),
             delay_ms: 0,
+            body_file: None,
         }
     }
 }
@@ -78,6 +88,12 @@ impl MockResponse {
         self.delay_ms = delay_ms;
         self
     }
+
+    /// 设置文件响应体
+    pub fn body_file(mut self, file: MockBodyFile) -> Self {
+        self.body_file = Some(file);
+        self
+    }
 }
 
 /// Mock 响应构建器
@@ -184,29 +200,7 @@ impl MockBuilder {
     /// - 值匹配: `key=value`
     /// - 正则匹配: `key=regex:pattern`
     fn matches_query(uri: &str, pattern: &str) -> bool {
-        // 解析 URI，提取查询参数
-        let query_string = if let Ok(parsed) = url::Url::parse(&format!("http://localhost{uri}")) {
-            parsed.query().unwrap_or("").to_string()
-        } else {
-            // 尝试从 URI 中提取查询部分
-            if let Some(pos) = uri.find('?') {
-                uri[pos + 1..].to_string()
-            } else {
-                String::new()
-            }
-        };
-
-        // 解析查询参数
-        let params: HashMap<String, String> = urlencoding::decode(&query_string)
-            .unwrap_or_default()
-            .split('&')
-            .filter_map(|pair| {
-                let mut parts = pair.splitn(2, '=');
-                let key = parts.next()?.to_string();
-                let value = parts.next().unwrap_or("").to_string();
-                Some((key, value))
-            })
-            .collect();
+        let params = Self::query_params(uri);
 
         // 解析模式
         if let Some(eq_pos) = pattern.find('=') {
@@ -234,6 +228,32 @@ impl MockBuilder {
         }
     }
 
+    /// 解析 URI 中的查询参数
+    pub(crate) fn query_params(uri: &str) -> HashMap<String, String> {
+        // 解析 URI，提取查询参数
+        let query_string = if let Ok(parsed) = url::Url::parse(&format!("http://localhost{uri}")) {
+            parsed.query().unwrap_or("").to_string()
+        } else {
+            // 尝试从 URI 中提取查询部分
+            if let Some(pos) = uri.find('?') {
+                uri[pos + 1..].to_string()
+            } else {
+                String::new()
+            }
+        };
+
+        urlencoding::decode(&query_string)
+            .unwrap_or_default()
+            .split('&')
+            .filter_map(|pair| {
+                let mut parts = pair.splitn(2, '=');
+                let key = parts.next()?.to_string();
+                let value = parts.next().unwrap_or("").to_string();
+                Some((key, value))
+            })
+            .collect()
+    }
+
     /// 匹配 Header
     ///
     /// 支持格式：
@@ -312,7 +332,7 @@ impl MockBuilder {
     }
 
     /// 根据 JSONPath 获取值
-    fn get_value_by_path(body: &Value, path: &str) -> Option<Value> {
+    pub(crate) fn get_value_by_path(body: &Value, path: &str) -> Option<Value> {
         if path == "$" {
             return Some(body.clone());
         }
@@ -412,6 +432,12 @@ impl MockBuilder {
                         let tpl = body_config.template.clone().unwrap_or_default();
                         Self::full_body(Bytes::from(render_template(&tpl, "", None)))
                     }
+                    BodyType::File => {
+                        // 文件响应体需要引擎的 mock_root 与异步读取，仅由引擎的 mock 处理支持
+                        return Err(MystiProxyError::Mock(
+                            "file body is only supported by engine mock locations".to_string(),
+                        ));
+                    }
                     BodyType::Json => {
                         // JSON 响应体
                         if let Some(json_config) = &body_config.json {
@@ -486,7 +512,9 @@ impl MockLocation {
             status: Some(200),
             headers: None,
             body: None,
+            status_map: None,
             conditions: None,
+            sequence: None,
         });
 
         Ok(MockLocation {
@@ -649,121 +677,23 @@ mod urlencoding {
     }
 }
 
-/// 模版占位符渲染：{{query.name}} 与 {{body.$.a.b}} / {{body.$.list[0].x}}
+/// 模版渲染：{{query.name}}、{{body.$.a.b}} / {{body.$.list[0].x}} 等，语法见 [`template`]
 /// 未解析的占位符保留原文并记录 warn。
 pub fn render_template(template: &str, uri: &str, body: Option<&Value>) -> String {
-    let mut out = String::with_capacity(template.len());
-    let mut rest = template;
-
-    while let Some(start) = rest.find("{{") {
-        out.push_str(&rest[..start]);
-        let after = &rest[start + 2..];
-        match after.find("}}") {
-            Some(end) => {
-                let key = &after[..end];
-                match resolve_placeholder(key, uri, body) {
-                    Some(v) => out.push_str(&v),
-                    None => {
-                        warn!("template placeholder unresolved: {{{{{key}}}}}");
-                        out.push_str("{{");
-                        out.push_str(key);
-                        out.push_str("}}");
-                    }
-                }
-                rest = &after[end + 2..];
-            }
-            None => {
-                // 未闭合，原样保留剩余（从占位符开始处）
-                out.push_str("{{");
-                out.push_str(after);
-                rest = "";
-            }
-        }
-    }
-    out.push_str(rest);
-    out
-}
-
-fn resolve_placeholder(key: &str, uri: &str, body: Option<&Value>) -> Option<String> {
-    let key = key.trim();
-    if let Some(name) = key.strip_prefix("query.") {
-        return query_param(uri, name);
-    }
-    if let Some(path) = key.strip_prefix("body.") {
-        let path = path.strip_prefix('$')?;
-        return body.and_then(|b| walk_json(b, path));
-    }
-    None
-}
-
-fn query_param(uri: &str, name: &str) -> Option<String> {
-    let q = uri.split_once('?')?.1;
-    for pair in q.split('&') {
-        let (k, v) = pair.split_once('=')?;
-        if k == name {
-            return Some(url_decode(v));
-        }
-    }
-    None
-}
-
-fn url_decode(s: &str) -> String {
-    let bytes = s.as_bytes();
-    let mut out = Vec::with_capacity(bytes.len());
-    let mut i = 0;
-    while i < bytes.len() {
-        match bytes[i] {
-            b'%' if i + 2 < bytes.len() => {
-                if let Ok(hex) = std::str::from_utf8(&bytes[i + 1..i + 3]) {
-                    if let Ok(byte) = u8::from_str_radix(hex, 16) {
-                        out.push(byte);
-                        i += 3;
-                        continue;
-                    }
-                }
-                out.push(bytes[i]);
-                i += 1;
-            }
-            b'+' => {
-                out.push(b' ');
-                i += 1;
-            }
-            b => {
-                out.push(b);
-                i += 1;
-            }
-        }
-    }
-    String::from_utf8_lossy(&out).into_owned()
+    render_template_with_params(template, uri, body, &HashMap::new())
 }
 
-/// 轻量 JSONPath walker：对象键与数组下标，如 "a.b[0].c"
-fn walk_json(value: &Value, path: &str) -> Option<String> {
-    let mut cur = value;
-    for seg in path.split('.').filter(|s| !s.is_empty()) {
-        // 拆出可能的数组下标
-        let (key, idx) = match seg.find('[') {
-            Some(i) => {
-                let close = seg.find(']')?;
-                let idx: usize = seg[i + 1..close].parse().ok()?;
-                (&seg[..i], Some(idx))
-            }
-            None => (seg, None),
-        };
-        if !key.is_empty() {
-            cur = cur.get(key)?;
-        }
-        if let Some(i) = idx {
-            cur = cur.get(i)?;
-        }
-    }
-    match cur {
-        Value::String(s) => Some(s.clone()),
-        Value::Number(n) => Some(n.to_string()),
-        Value::Bool(b) => Some(b.to_string()),
-        Value::Null => Some("null".to_string()),
-        _ => None, // 对象/数组不内联
-    }
+/// 同 [`render_template`]，额外支持 {{path.name}} 引用路由捕获的路径参数
+pub fn render_template_with_params(
+    template: &str,
+    uri: &str,
+    body: Option<&Value>,
+    params: &HashMap<String, String>,
+) -> String {
+    TemplateContext::from_uri(uri)
+        .with_body(body)
+        .with_params(params)
+        .render(template)
 }
 
 #[cfg(test)]
@@ -838,6 +768,18 @@ mod render_tests {
         assert_eq!(out, "id=7 tier=gold end");
     }
 
+    #[test]
+    fn test_path_param_placeholder() {
+        let params = HashMap::from([("id".to_string(), "42".to_string())]);
+        let out = render_template_with_params(
+            "user {{path.id}} {{path.ghost}}",
+            "/users/42",
+            None,
+            &params,
+        );
+        assert_eq!(out, "user 42 {{path.ghost}}");
+    }
+
     #[test]
     fn test_no_placeholder_returns_asis() {
         assert_eq!(render_template("plain text", "/p", None), "plain text");
@@ -849,12 +791,14 @@ mod render_tests {
     }
 
     #[test]
-    fn test_walk_json_object_and_array() {
+    fn test_body_array_and_object_inlined() {
         let v = json!({"k": [10, 20]});
-        assert_eq!(walk_json(&v, "k[0]"), Some("10".to_string()));
-        assert_eq!(walk_json(&v, "k[1]"), Some("20".to_string()));
-        assert_eq!(walk_json(&v, "k[5]"), None);
-        assert_eq!(walk_json(&v, "missing"), None);
+        let out = render_template(
+            "{{body.$.k[0]}},{{body.$.k[1]}},{{body.$.k[5]}},{{body.$.missing}},{{body.$.k}}",
+            "/p",
+            Some(&v),
+        );
+        assert_eq!(out, "10,20,{{body.$.k[5]}},{{body.$.missing}},[10,20]");
     }
 }
 
@@ -1031,7 +975,9 @@ mod tests {
                 status: Some(200),
                 headers: None,
                 body: None,
+                status_map: None,
                 conditions: None,
+                sequence: None,
             },
         };
 
@@ -1051,7 +997,9 @@ mod tests {
                 status: Some(201),
                 headers: None,
                 body: None,
+                status_map: None,
                 conditions: None,
+                sequence: None,
             },
         };
 
@@ -1085,7 +1033,9 @@ mod tests {
                 map
             }),
             body: None,
+            status_map: None,
             conditions: None,
+            sequence: None,
         };
 
         let response = MockBuilder::build_response(&config).unwrap();
@@ -1094,6 +1044,20 @@ mod tests {
             response.headers().get("Content-Type").unwrap(),
             "application/json"
         );
+
+        // 文件响应体在此构建路径上被拒绝，不做阻塞读取
+        let config = ResponseConfig {
+            body: Some(crate::config::BodyConfig {
+                body_type: Some(BodyType::File),
+                json: None,
+                content: None,
+                template: None,
+                file: Some("Cargo.toml".to_string()),
+                templated: None,
+            }),
+            ..config
+        };
+        assert!(MockBuilder::build_response(&config).is_err());
     }
 
     #[test]
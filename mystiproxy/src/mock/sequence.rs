@@ -0,0 +1,187 @@
+//! Mock 响应序列
+//!
+//! `response.sequence` 配置了多个响应时，按 location 的调用次数选择本次返回哪一项：
+//! sequential 依次返回并停留在最后一项，cycle 循环返回，random-weighted 按权重随机。
+//! 计数保存在 [`SequenceCounters`] 中，可经本地管理 API 查看与重置。
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use rand::Rng;
+use serde::Serialize;
+
+use crate::config::{ResponseSequence, SequenceMode};
+
+/// 单个 location 的调用计数
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SequenceCounter {
+    /// location 路径
+    pub location: String,
+    /// 已返回的序列响应次数
+    pub count: u64,
+}
+
+/// 按 location 保存的响应序列计数
+#[derive(Debug, Default)]
+pub struct SequenceCounters {
+    counters: Mutex<HashMap<String, SequenceCounter>>,
+}
+
+impl SequenceCounters {
+    /// 创建空计数表
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 返回本次调用的序号（从 0 开始）并递增计数
+    ///
+    /// `key` 区分同一路径下按 hosts/methods 等条件拆分的 location。
+    pub fn next(&self, key: &str, location: &str) -> u64 {
+        let mut counters = self.counters.lock().unwrap();
+        let counter = counters
+            .entry(key.to_string())
+            .or_insert_with(|| SequenceCounter {
+                location: location.to_string(),
+                count: 0,
+            });
+        let call = counter.count;
+        counter.count += 1;
+        call
+    }
+
+    /// 当前计数，按 location 排序
+    pub fn counters(&self) -> Vec<SequenceCounter> {
+        let counters = self.counters.lock().unwrap();
+        let mut list: Vec<SequenceCounter> = counters.values().cloned().collect();
+        list.sort_by(|a, b| a.location.cmp(&b.location));
+        list
+    }
+
+    /// 重置计数；指定 location 时只重置该路径的计数
+    pub fn reset(&self, location: Option<&str>) {
+        let mut counters = self.counters.lock().unwrap();
+        match location {
+            Some(location) => counters.retain(|_, c| c.location != location),
+            None => counters.clear(),
+        }
+    }
+}
+
+/// 按模式与调用序号选择序列中的响应下标；序列为空（或权重全为 0）时返回 None
+pub fn select_step(sequence: &ResponseSequence, call: u64) -> Option<usize> {
+    let steps = &sequence.responses;
+    if steps.is_empty() {
+        return None;
+    }
+
+    match sequence.mode {
+        SequenceMode::Sequential | SequenceMode::Cycle => {
+            let repeats = || {
+                steps
+                    .iter()
+                    .map(|s| u64::from(s.repeat.unwrap_or(1).max(1)))
+            };
+            let total: u64 = repeats().sum();
+            let mut call = match sequence.mode {
+                SequenceMode::Cycle => call % total,
+                _ => call.min(total - 1),
+            };
+            for (index, repeat) in repeats().enumerate() {
+                if call < repeat {
+                    return Some(index);
+                }
+                call -= repeat;
+            }
+            Some(steps.len() - 1)
+        }
+        SequenceMode::RandomWeighted => {
+            let weight = |index: usize| u64::from(steps[index].weight.unwrap_or(1));
+            let total: u64 = (0..steps.len()).map(weight).sum();
+            if total == 0 {
+                return None;
+            }
+            let mut pick = rand::thread_rng().gen_range(0..total);
+            (0..steps.len()).find(|&index| {
+                if pick < weight(index) {
+                    return true;
+                }
+                pick -= weight(index);
+                false
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ResponseStep;
+
+    fn sequence(mode: SequenceMode, steps: &[(Option<u32>, Option<u32>)]) -> ResponseSequence {
+        ResponseSequence {
+            mode,
+            responses: steps
+                .iter()
+                .map(|&(repeat, weight)| ResponseStep {
+                    repeat,
+                    weight,
+                    ..Default::default()
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_sequential_sticks_on_last() {
+        // 前两次 503，之后一直 200
+        let seq = sequence(SequenceMode::Sequential, &[(Some(2), None), (None, None)]);
+        let picks: Vec<_> = (0..5).map(|call| select_step(&seq, call)).collect();
+        assert_eq!(picks, [Some(0), Some(0), Some(1), Some(1), Some(1)]);
+    }
+
+    #[test]
+    fn test_cycle_wraps_around() {
+        let seq = sequence(
+            SequenceMode::Cycle,
+            &[(None, None), (Some(2), None), (None, None)],
+        );
+        let picks: Vec<_> = (0..6).map(|call| select_step(&seq, call)).collect();
+        assert_eq!(
+            picks,
+            [Some(0), Some(1), Some(1), Some(2), Some(0), Some(1)]
+        );
+    }
+
+    #[test]
+    fn test_random_weighted_respects_zero_weights() {
+        let seq = sequence(
+            SequenceMode::RandomWeighted,
+            &[(None, Some(0)), (None, Some(3)), (None, Some(0))],
+        );
+        assert!((0..50).all(|call| select_step(&seq, call) == Some(1)));
+
+        let none = sequence(SequenceMode::RandomWeighted, &[(None, Some(0))]);
+        assert_eq!(select_step(&none, 0), None);
+    }
+
+    #[test]
+    fn test_counters_per_location() {
+        let counters = SequenceCounters::new();
+        assert_eq!(counters.next("GET /a", "/a"), 0);
+        assert_eq!(counters.next("GET /a", "/a"), 1);
+        assert_eq!(counters.next("POST /a", "/a"), 0);
+        assert_eq!(counters.next("GET /b", "/b"), 0);
+        assert_eq!(counters.counters().len(), 3);
+
+        counters.reset(Some("/a"));
+        assert_eq!(
+            counters.counters(),
+            vec![SequenceCounter {
+                location: "/b".to_string(),
+                count: 1
+            }]
+        );
+        counters.reset(None);
+        assert!(counters.counters().is_empty());
+    }
+}
@@ -0,0 +1,1135 @@
+//! Mock 模板引擎
+//!
+//! Handlebars 风格的轻量模板，用于 mock 响应体与响应头：
+//! - `{{path.id}}`、`{{query.q}}`、`{{header.x-token}}`、`{{cookie.sid}}`、`{{method}}`、
+//!   `{{client_ip}}`、`{{url}}`、`{{body.$.items[0].id}}` 读取请求数据
+//! - helper 调用如 `{{uuid}}`、`{{now "%Y-%m-%d"}}`、`{{randomInt 1 10}}`，参数可以是字面量、
+//!   路径或 `( )` 子表达式
+//! - `{{#if}}`、`{{#unless}}`、`{{#each}}` 块，支持 `{{else}}`；`{{#each}}` 内可用 `this`、
+//!   `@index`、`@key`、`@first`、`@last`，并可直接引用当前元素的字段
+//! - `faker.*` 假数据 helper（见 [`super::faker`]）、`{{#faker locale=... seed=...}}` 块固定
+//!   语言区域与随机种子，`{{#repeat N}}` 块按子模板生成 N 项
+//!
+//! 无法解析的 `{{...}}` 原样保留并记录 warn，与早期只做占位符替换的行为一致；
+//! `\{{` 输出字面量 `{{`。
+
+use std::collections::HashMap;
+use std::net::IpAddr;
+
+use base64::Engine as _;
+use chrono::format::{Item, StrftimeItems};
+use chrono::Utc;
+use hyper::header::{HeaderMap, COOKIE};
+use rand::distributions::Alphanumeric;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde_json::{Map, Value};
+use tracing::warn;
+
+use super::faker::{self, Locale};
+
+/// `{{#repeat}}` 的最大生成数量
+const MAX_REPEAT: u64 = 10_000;
+
+/// 单次渲染的循环迭代总数上限（`each` 与 `repeat` 合计，含嵌套）
+const MAX_TOTAL_ITERATIONS: usize = 100_000;
+
+/// 单次渲染的输出字节上限
+const MAX_OUTPUT_BYTES: usize = 16 * 1024 * 1024;
+
+/// 模板渲染上下文：请求数据与自定义变量
+#[derive(Debug, Clone, Default)]
+pub struct TemplateContext {
+    root: Map<String, Value>,
+    vars: Map<String, Value>,
+}
+
+impl TemplateContext {
+    /// 以请求方法与 URI（含查询串）创建上下文
+    pub fn new(method: &str, uri: &str) -> Self {
+        let mut context = Self::from_uri(uri);
+        context
+            .root
+            .insert("method".to_string(), Value::String(method.to_string()));
+        context
+    }
+
+    /// 仅以 URI 创建上下文（不含请求方法，如改写上游响应时）
+    pub fn from_uri(uri: &str) -> Self {
+        let mut query = Map::new();
+        if let Some((_, q)) = uri.split_once('?') {
+            for pair in q.split('&').filter(|p| !p.is_empty()) {
+                let (k, v) = pair.split_once('=').unwrap_or((pair, ""));
+                query
+                    .entry(url_decode(k))
+                    .or_insert_with(|| Value::String(url_decode(v)));
+            }
+        }
+
+        let mut root = Map::new();
+        root.insert("url".to_string(), Value::String(uri.to_string()));
+        root.insert("query".to_string(), Value::Object(query));
+        root.insert("path".to_string(), Value::Object(Map::new()));
+        Self {
+            root,
+            vars: Map::new(),
+        }
+    }
+
+    /// 请求头（名称小写，同名多值以 `, ` 连接）与 Cookie
+    pub fn with_headers(mut self, headers: &HeaderMap) -> Self {
+        let mut header = Map::new();
+        for name in headers.keys() {
+            let values: Vec<&str> = headers
+                .get_all(name)
+                .iter()
+                .filter_map(|v| v.to_str().ok())
+                .collect();
+            header.insert(name.as_str().to_string(), Value::String(values.join(", ")));
+        }
+
+        let mut cookie = Map::new();
+        for pair in headers
+            .get_all(COOKIE)
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .flat_map(|v| v.split(';'))
+        {
+            if let Some((name, value)) = pair.trim().split_once('=') {
+                cookie
+                    .entry(name.to_string())
+                    .or_insert_with(|| Value::String(value.to_string()));
+            }
+        }
+
+        self.root
+            .insert("header".to_string(), Value::Object(header));
+        self.root
+            .insert("cookie".to_string(), Value::Object(cookie));
+        self
+    }
+
+    /// 客户端 IP
+    pub fn with_client_ip(mut self, ip: Option<IpAddr>) -> Self {
+        if let Some(ip) = ip {
+            self.root
+                .insert("client_ip".to_string(), Value::String(ip.to_string()));
+        }
+        self
+    }
+
+    /// JSON 请求体
+    pub fn with_body(mut self, body: Option<&Value>) -> Self {
+        if let Some(body) = body {
+            self.root.insert("body".to_string(), body.clone());
+        }
+        self
+    }
+
+    /// 路由捕获的路径参数
+    pub fn with_params(mut self, params: &HashMap<String, String>) -> Self {
+        let path = params
+            .iter()
+            .map(|(k, v)| (k.clone(), Value::String(v.clone())))
+            .collect();
+        self.root.insert("path".to_string(), Value::Object(path));
+        self
+    }
+
+    /// 自定义变量，模板中以 `{{name}}` 或 `{{vars.name}}` 引用
+    pub fn set_var(&mut self, name: impl Into<String>, value: Value) {
+        self.vars.insert(name.into(), value);
+    }
+
+    /// 按路径读取上下文中的值，如 `query.q`、`header.X-Token`、`body.$.a[0]`
+    pub fn lookup(&self, path: &str) -> Option<Value> {
+        let path = path.strip_prefix("request.").unwrap_or(path);
+        let mut segments = parse_path(path)?;
+        let first = match segments.first()? {
+            Segment::Key(key) => key.clone(),
+            Segment::Index(_) => return None,
+        };
+        // 请求头名不区分大小写
+        if first == "header" {
+            if let Some(Segment::Key(name)) = segments.get_mut(1) {
+                *name = name.to_ascii_lowercase();
+            }
+        }
+
+        if first == "vars" {
+            return walk(&Value::Object(self.vars.clone()), &segments[1..]).cloned();
+        }
+        match self.root.get(&first) {
+            Some(value) => walk(value, &segments[1..]).cloned(),
+            None => walk(self.vars.get(&first)?, &segments[1..]).cloned(),
+        }
+    }
+
+    /// 渲染模板
+    pub fn render(&self, template: &str) -> String {
+        if !template.contains("{{") {
+            return template.to_string();
+        }
+        match parse(template) {
+            Ok(nodes) => {
+                let mut renderer = Renderer {
+                    ctx: self,
+                    rng: StdRng::from_entropy(),
+                    locale: Locale::default(),
+                    scopes: Vec::new(),
+                    iterations: 0,
+                    exhausted: false,
+                };
+                let mut out = String::with_capacity(template.len());
+                renderer.render_nodes(&nodes, &mut out);
+                out
+            }
+            Err(e) => {
+                warn!("template not rendered: {}", e);
+                template.to_string()
+            }
+        }
+    }
+}
+
+/// 模板是否引用了名为 `root` 的请求数据（如 `body`）
+///
+/// 按解析结果判断：输出、块参数、hash 参数与子表达式中的路径都会计入，字面文本不计；
+/// 解析失败的模板原样输出，视为无引用。
+pub fn references(template: &str, root: &str) -> bool {
+    if !template.contains("{{") {
+        return false;
+    }
+    parse(template).is_ok_and(|nodes| nodes_reference(&nodes, root))
+}
+
+fn nodes_reference(nodes: &[Node], root: &str) -> bool {
+    nodes.iter().any(|node| match node {
+        Node::Text(_) => false,
+        Node::Output { expr, .. } => expr_references(expr, root),
+        Node::Block {
+            args,
+            hash,
+            body,
+            inverse,
+            ..
+        } => {
+            args.iter().any(|a| expr_references(a, root))
+                || hash.iter().any(|(_, v)| expr_references(v, root))
+                || nodes_reference(body, root)
+                || nodes_reference(inverse, root)
+        }
+    })
+}
+
+fn expr_references(expr: &Expr, root: &str) -> bool {
+    match expr {
+        Expr::Literal(_) => false,
+        Expr::Path(path) => {
+            let path = path.strip_prefix("request.").unwrap_or(path);
+            matches!(
+                parse_path(path).as_deref(),
+                Some([Segment::Key(first), ..]) if first == root
+            )
+        }
+        Expr::Call { args, hash, .. } => {
+            args.iter().any(|a| expr_references(a, root))
+                || hash.iter().any(|(_, v)| expr_references(v, root))
+        }
+    }
+}
+
+// ============================================================================
+// 解析
+// ============================================================================
+
+#[derive(Debug, Clone)]
+enum Expr {
+    Literal(Value),
+    Path(String),
+    Call {
+        name: String,
+        args: Vec<Expr>,
+        hash: Vec<(String, Expr)>,
+    },
+}
+
+#[derive(Debug)]
+enum Node {
+    Text(String),
+    /// `{{expr}}`；`raw` 为原始标签内容，解析失败时原样输出
+    Output {
+        raw: String,
+        expr: Expr,
+    },
+    Block {
+        name: String,
+        args: Vec<Expr>,
+        hash: Vec<(String, Expr)>,
+        body: Vec<Node>,
+        inverse: Vec<Node>,
+    },
+}
+
+/// 解析中尚未闭合的块
+struct OpenBlock {
+    name: String,
+    args: Vec<Expr>,
+    hash: Vec<(String, Expr)>,
+    body: Vec<Node>,
+    inverse: Option<Vec<Node>>,
+}
+
+fn parse(template: &str) -> Result<Vec<Node>, String> {
+    let mut stack: Vec<OpenBlock> = Vec::new();
+    let mut nodes: Vec<Node> = Vec::new();
+    let mut rest = template;
+
+    fn current<'a>(stack: &'a mut [OpenBlock], nodes: &'a mut Vec<Node>) -> &'a mut Vec<Node> {
+        match stack.last_mut() {
+            Some(block) => block.inverse.as_mut().unwrap_or(&mut block.body),
+            None => nodes,
+        }
+    }
+
+    while let Some(start) = rest.find("{{") {
+        // `\{{` 转义为字面量
+        if rest[..start].ends_with('\\') {
+            let text = format!("{}{{{{", &rest[..start - 1]);
+            current(&mut stack, &mut nodes).push(Node::Text(text));
+            rest = &rest[start + 2..];
+            continue;
+        }
+        if start > 0 {
+            current(&mut stack, &mut nodes).push(Node::Text(rest[..start].to_string()));
+        }
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("}}") else {
+            // 未闭合，原样保留剩余
+            current(&mut stack, &mut nodes).push(Node::Text(rest[start..].to_string()));
+            rest = "";
+            break;
+        };
+        let raw = &after[..end];
+        rest = &after[end + 2..];
+        let tag = raw.trim();
+
+        if let Some(open) = tag.strip_prefix('#') {
+            let tokens = tokenize(open)?;
+            let (name, args, hash) = split_call(tokens)?;
+            stack.push(OpenBlock {
+                name,
+                args,
+                hash,
+                body: Vec::new(),
+                inverse: None,
+            });
+        } else if let Some(close) = tag.strip_prefix('/') {
+            let block = stack
+                .pop()
+                .ok_or_else(|| format!("unexpected {{{{/{close}}}}}"))?;
+            if block.name != close.trim() {
+                return Err(format!(
+                    "{{{{#{}}}}} closed by {{{{/{}}}}}",
+                    block.name,
+                    close.trim()
+                ));
+            }
+            current(&mut stack, &mut nodes).push(Node::Block {
+                name: block.name,
+                args: block.args,
+                hash: block.hash,
+                body: block.body,
+                inverse: block.inverse.unwrap_or_default(),
+            });
+        } else if tag == "else" && !stack.is_empty() {
+            let block = stack.last_mut().expect("non-empty stack");
+            if block.inverse.is_some() {
+                return Err(format!("duplicate {{{{else}}}} in {{{{#{}}}}}", block.name));
+            }
+            block.inverse = Some(Vec::new());
+        } else {
+            let node = match tokenize(tag).and_then(|tokens| parse_expr(&tokens)) {
+                Ok(expr) => Node::Output {
+                    raw: raw.to_string(),
+                    expr,
+                },
+                Err(_) => Node::Text(format!("{{{{{raw}}}}}")),
+            };
+            current(&mut stack, &mut nodes).push(node);
+        }
+    }
+    if let Some(block) = stack.last() {
+        return Err(format!("unclosed {{{{#{}}}}}", block.name));
+    }
+    if !rest.is_empty() {
+        nodes.push(Node::Text(rest.to_string()));
+    }
+    Ok(nodes)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Str(String),
+    Word(String),
+    Open,
+    Close,
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' => {
+                chars.next();
+                tokens.push(Token::Open);
+            }
+            ')' => {
+                chars.next();
+                tokens.push(Token::Close);
+            }
+            '"' | '\'' => {
+                chars.next();
+                let mut s = String::new();
+                loop {
+                    match chars.next() {
+                        Some('\\') => s.extend(chars.next()),
+                        Some(ch) if ch == c => break,
+                        Some(ch) => s.push(ch),
+                        None => return Err("unterminated string".to_string()),
+                    }
+                }
+                tokens.push(Token::Str(s));
+            }
+            _ => {
+                let mut word = String::new();
+                while let Some(&ch) = chars.peek() {
+                    if ch.is_whitespace() || ch == '(' || ch == ')' {
+                        break;
+                    }
+                    word.push(ch);
+                    chars.next();
+                    // `key="value"`：等号后紧跟字符串时在此断开
+                    if ch == '=' && matches!(chars.peek(), Some('"' | '\'')) {
+                        break;
+                    }
+                }
+                tokens.push(Token::Word(word));
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+/// 解析完整表达式：单个值，或 `helper arg... key=value...`
+fn parse_expr(tokens: &[Token]) -> Result<Expr, String> {
+    match tokens {
+        [] => Err("empty expression".to_string()),
+        [single] => parse_value(single, &mut std::iter::empty()),
+        _ => {
+            let (name, args, hash) = split_call(tokens.to_vec())?;
+            Ok(Expr::Call { name, args, hash })
+        }
+    }
+}
+
+type Call = (String, Vec<Expr>, Vec<(String, Expr)>);
+
+fn split_call(tokens: Vec<Token>) -> Result<Call, String> {
+    let mut iter = tokens.into_iter();
+    let name = match iter.next() {
+        Some(Token::Word(name)) => name,
+        _ => return Err("expected helper name".to_string()),
+    };
+    let mut args = Vec::new();
+    let mut hash = Vec::new();
+    while let Some(token) = iter.next() {
+        if let Token::Word(word) = &token {
+            if let Some((key, value)) = word.split_once('=') {
+                if !key.is_empty() && !key.contains('.') {
+                    let expr = if value.is_empty() {
+                        let next = iter.next().ok_or("missing hash value")?;
+                        parse_value(&next, &mut iter)?
+                    } else {
+                        parse_value(&Token::Word(value.to_string()), &mut iter)?
+                    };
+                    hash.push((key.to_string(), expr));
+                    continue;
+                }
+            }
+        }
+        args.push(parse_value(&token, &mut iter)?);
+    }
+    Ok((name, args, hash))
+}
+
+/// 解析一个参数：字面量、路径或 `( )` 子表达式（从 `rest` 继续消费）
+fn parse_value(token: &Token, rest: &mut dyn Iterator<Item = Token>) -> Result<Expr, String> {
+    match token {
+        Token::Str(s) => Ok(Expr::Literal(Value::String(s.clone()))),
+        Token::Word(word) => Ok(match word.as_str() {
+            "true" => Expr::Literal(Value::Bool(true)),
+            "false" => Expr::Literal(Value::Bool(false)),
+            "null" => Expr::Literal(Value::Null),
+            _ => match word.parse::<serde_json::Number>() {
+                Ok(n) if word.starts_with(|c: char| c.is_ascii_digit() || c == '-') => {
+                    Expr::Literal(Value::Number(n))
+                }
+                _ => Expr::Path(word.clone()),
+            },
+        }),
+        Token::Open => {
+            let mut inner = Vec::new();
+            let mut depth = 1;
+            for token in &mut *rest {
+                match token {
+                    Token::Open => depth += 1,
+                    Token::Close => {
+                        depth -= 1;
+                        if depth == 0 {
+                            break;
+                        }
+                    }
+                    _ => {}
+                }
+                inner.push(token);
+            }
+            if depth != 0 {
+                return Err("unbalanced parentheses".to_string());
+            }
+            match inner.as_slice() {
+                [Token::Word(name)] => Ok(Expr::Call {
+                    name: name.clone(),
+                    args: Vec::new(),
+                    hash: Vec::new(),
+                }),
+                _ => {
+                    let (name, args, hash) = split_call(inner)?;
+                    Ok(Expr::Call { name, args, hash })
+                }
+            }
+        }
+        Token::Close => Err("unexpected ')'".to_string()),
+    }
+}
+
+// ============================================================================
+// 渲染
+// ============================================================================
+
+/// `{{#each}}` 的迭代作用域
+struct Scope {
+    this: Value,
+    index: usize,
+    key: Option<String>,
+    len: usize,
+}
+
+struct Renderer<'a> {
+    ctx: &'a TemplateContext,
+    rng: StdRng,
+    locale: Locale,
+    scopes: Vec<Scope>,
+    /// 已执行的循环迭代次数
+    iterations: usize,
+    /// 渲染预算耗尽后不再产生输出
+    exhausted: bool,
+}
+
+impl Renderer<'_> {
+    /// 检查渲染预算；`iteration` 为 true 时计入一次循环迭代
+    fn within_budget(&mut self, out: &str, iteration: bool) -> bool {
+        if self.exhausted {
+            return false;
+        }
+        if iteration {
+            self.iterations += 1;
+        }
+        if self.iterations > MAX_TOTAL_ITERATIONS || out.len() > MAX_OUTPUT_BYTES {
+            warn!(
+                "template rendering stopped: budget exhausted ({} iterations, {} bytes)",
+                self.iterations,
+                out.len()
+            );
+            self.exhausted = true;
+            return false;
+        }
+        true
+    }
+
+    fn render_nodes(&mut self, nodes: &[Node], out: &mut String) {
+        for node in nodes {
+            if !self.within_budget(out, false) {
+                return;
+            }
+            match node {
+                Node::Text(text) => out.push_str(text),
+                Node::Output { raw, expr } => match self.eval(expr) {
+                    Some(value) => out.push_str(&to_text(&value)),
+                    None => {
+                        warn!("template placeholder unresolved: {{{{{raw}}}}}");
+                        out.push_str("{{");
+                        out.push_str(raw);
+                        out.push_str("}}");
+                    }
+                },
+                Node::Block {
+                    name,
+                    args,
+                    hash,
+                    body,
+                    inverse,
+                } => self.render_block(name, args, hash, body, inverse, out),
+            }
+        }
+    }
+
+    fn render_block(
+        &mut self,
+        name: &str,
+        args: &[Expr],
+        hash: &[(String, Expr)],
+        body: &[Node],
+        inverse: &[Node],
+        out: &mut String,
+    ) {
+        let arg = args.first().and_then(|a| self.eval(a));
+        match name {
+            "if" | "unless" => {
+                let truthy = arg.as_ref().is_some_and(is_truthy);
+                if truthy == (name == "if") {
+                    self.render_nodes(body, out);
+                } else {
+                    self.render_nodes(inverse, out);
+                }
+            }
+            "each" => {
+                let items: Vec<(Option<String>, Value)> = match arg {
+                    Some(Value::Array(items)) => items.into_iter().map(|v| (None, v)).collect(),
+                    Some(Value::Object(map)) => {
+                        map.into_iter().map(|(k, v)| (Some(k), v)).collect()
+                    }
+                    _ => Vec::new(),
+                };
+                if items.is_empty() {
+                    self.render_nodes(inverse, out);
+                    return;
+                }
+                let len = items.len();
+                for (index, (key, this)) in items.into_iter().enumerate() {
+                    if !self.within_budget(out, true) {
+                        return;
+                    }
+                    self.scopes.push(Scope {
+                        this,
+                        index,
+                        key,
+                        len,
+                    });
+                    self.render_nodes(body, out);
+                    self.scopes.pop();
+                }
+            }
+            "repeat" => {
+                // {{#repeat 3}} 或 {{#repeat min=1 max=5}}，各项以 separator（默认 ","）分隔
+                let hash = self.eval_hash(hash);
+                let count = match (
+                    arg.as_ref().and_then(as_f64),
+                    hash.get("min"),
+                    hash.get("max"),
+                ) {
+                    (Some(n), _, _) => n as u64,
+                    (None, min, max) => {
+                        let min = min.and_then(as_f64).unwrap_or(0.0) as u64;
+                        let max = max.and_then(as_f64).map_or(min, |m| m as u64);
+                        self.rng.gen_range(min.min(max)..=max.max(min))
+                    }
+                }
+                .min(MAX_REPEAT) as usize;
+                let separator = hash.get("separator").map_or(",".to_string(), to_text);
+                if count == 0 {
+                    self.render_nodes(inverse, out);
+                    return;
+                }
+                for index in 0..count {
+                    if !self.within_budget(out, true) {
+                        return;
+                    }
+                    if index > 0 {
+                        out.push_str(&separator);
+                    }
+                    self.scopes.push(Scope {
+                        this: Value::from(index),
+                        index,
+                        key: None,
+                        len: count,
+                    });
+                    self.render_nodes(body, out);
+                    self.scopes.pop();
+                }
+            }
+            "faker" => {
+                // 块内固定语言区域；给定 seed 时块内的随机输出可复现
+                let hash = self.eval_hash(hash);
+                let locale = hash
+                    .get("locale")
+                    .and_then(|l| Locale::parse(&to_text(l)))
+                    .unwrap_or(self.locale);
+                let saved_locale = std::mem::replace(&mut self.locale, locale);
+                let saved_rng = hash.get("seed").map(|seed| {
+                    std::mem::replace(&mut self.rng, StdRng::seed_from_u64(faker::seed_from(seed)))
+                });
+                self.render_nodes(body, out);
+                self.locale = saved_locale;
+                if let Some(rng) = saved_rng {
+                    self.rng = rng;
+                }
+            }
+            _ => {
+                warn!("unknown template block helper: #{}", name);
+            }
+        }
+    }
+
+    fn eval(&mut self, expr: &Expr) -> Option<Value> {
+        match expr {
+            Expr::Literal(value) => Some(value.clone()),
+            Expr::Path(path) => self.lookup(path).or_else(|| self.call(path, &[], &[])),
+            Expr::Call { name, args, hash } => self.call(name, args, hash),
+        }
+    }
+
+    fn lookup(&self, path: &str) -> Option<Value> {
+        if let Some(scope) = self.scopes.last() {
+            match path {
+                "this" => return Some(scope.this.clone()),
+                "@index" => return Some(Value::from(scope.index)),
+                "@first" => return Some(Value::Bool(scope.index == 0)),
+                "@last" => return Some(Value::Bool(scope.index + 1 == scope.len)),
+                "@key" => return scope.key.clone().map(Value::String),
+                _ => {}
+            }
+            let segments = parse_path(path.strip_prefix("this.").unwrap_or(path))?;
+            if path.starts_with("this.") {
+                return walk(&scope.this, &segments).cloned();
+            }
+            // 先查当前元素的字段，再回退到请求上下文
+            if let Some(Segment::Key(first)) = segments.first() {
+                if scope.this.get(first).is_some() {
+                    return walk(&scope.this, &segments).cloned();
+                }
+            }
+        }
+        self.ctx.lookup(path)
+    }
+
+    fn eval_hash<'h>(&mut self, hash: &'h [(String, Expr)]) -> HashMap<&'h str, Value> {
+        hash.iter()
+            .filter_map(|(k, e)| Some((k.as_str(), self.eval(e)?)))
+            .collect()
+    }
+
+    fn call(&mut self, name: &str, args: &[Expr], hash: &[(String, Expr)]) -> Option<Value> {
+        let args: Vec<Option<Value>> = args.iter().map(|a| self.eval(a)).collect();
+        let hash = self.eval_hash(hash);
+        if let Some(kind) = name.strip_prefix("faker.") {
+            let locale = hash
+                .get("locale")
+                .and_then(|l| Locale::parse(&to_text(l)))
+                .unwrap_or(self.locale);
+            return faker::fake(kind, &args, locale, &mut self.rng);
+        }
+        let arg = |i: usize| args.get(i).cloned().flatten();
+        let number = |i: usize| arg(i).as_ref().and_then(as_f64);
+
+        Some(match name {
+            "uuid" => {
+                let uuid = uuid::Builder::from_random_bytes(self.rng.gen()).into_uuid();
+                Value::String(uuid.to_string())
+            }
+            "now" => {
+                let now = Utc::now();
+                match arg(0).or_else(|| hash.get("format").cloned()) {
+                    None => Value::String(now.to_rfc3339()),
+                    Some(Value::String(f)) if f == "unix" => Value::from(now.timestamp()),
+                    Some(Value::String(f)) if f == "millis" => Value::from(now.timestamp_millis()),
+                    Some(format) => {
+                        let format = to_text(&format);
+                        if !is_valid_strftime(&format) {
+                            warn!("invalid time format in template: {}", format);
+                            return None;
+                        }
+                        Value::String(now.format(&format).to_string())
+                    }
+                }
+            }
+            "randomInt" => {
+                let min = number(0).unwrap_or(0.0) as i64;
+                let max = number(1).unwrap_or(100.0) as i64;
+                Value::from(self.rng.gen_range(min.min(max)..=max.max(min)))
+            }
+            "randomString" => {
+                // 与 faker.lorem 一样限制长度，避免请求数据导致一次性大量分配
+                let len = (number(0).unwrap_or(16.0) as usize).min(1000);
+                let s: String = (&mut self.rng)
+                    .sample_iter(&Alphanumeric)
+                    .take(len)
+                    .map(char::from)
+                    .collect();
+                Value::String(s)
+            }
+            "base64" => {
+                Value::String(base64::engine::general_purpose::STANDARD.encode(to_text(&arg(0)?)))
+            }
+            "base64Decode" => {
+                let bytes = base64::engine::general_purpose::STANDARD
+                    .decode(to_text(&arg(0)?))
+                    .ok()?;
+                Value::String(String::from_utf8_lossy(&bytes).into_owned())
+            }
+            "jsonEscape" => {
+                let quoted = serde_json::to_string(&to_text(&arg(0)?)).ok()?;
+                Value::String(quoted[1..quoted.len() - 1].to_string())
+            }
+            "json" => Value::String(serde_json::to_string(&arg(0)?).ok()?),
+            "eq" => Value::Bool(compare(&arg(0), &arg(1)) == Some(std::cmp::Ordering::Equal)),
+            "ne" => Value::Bool(compare(&arg(0), &arg(1)) != Some(std::cmp::Ordering::Equal)),
+            "gt" => Value::Bool(compare(&arg(0), &arg(1)) == Some(std::cmp::Ordering::Greater)),
+            "lt" => Value::Bool(compare(&arg(0), &arg(1)) == Some(std::cmp::Ordering::Less)),
+            "and" => Value::Bool(args.iter().all(|a| a.as_ref().is_some_and(is_truthy))),
+            "or" => Value::Bool(args.iter().any(|a| a.as_ref().is_some_and(is_truthy))),
+            "not" => Value::Bool(!arg(0).as_ref().is_some_and(is_truthy)),
+            "default" => match arg(0) {
+                Some(value) if is_truthy(&value) => value,
+                _ => arg(1)?,
+            },
+            _ => return None,
+        })
+    }
+}
+
+// ============================================================================
+// 值与路径
+// ============================================================================
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Key(String),
+    Index(usize),
+}
+
+/// 拆分路径：`a.b[0].c`；JSONPath 的 `$` 段被忽略
+fn parse_path(path: &str) -> Option<Vec<Segment>> {
+    let mut segments = Vec::new();
+    for part in path.split('.').filter(|s| !s.is_empty() && *s != "$") {
+        let (key, mut indexes) = match part.find('[') {
+            Some(i) => (&part[..i], &part[i..]),
+            None => (part, ""),
+        };
+        if !key.is_empty() && key != "$" {
+            segments.push(Segment::Key(key.to_string()));
+        }
+        while let Some(rest) = indexes.strip_prefix('[') {
+            let close = rest.find(']')?;
+            segments.push(Segment::Index(rest[..close].parse().ok()?));
+            indexes = &rest[close + 1..];
+        }
+        if !indexes.is_empty() {
+            return None;
+        }
+    }
+    Some(segments)
+}
+
+fn walk<'v>(value: &'v Value, segments: &[Segment]) -> Option<&'v Value> {
+    segments.iter().try_fold(value, |cur, seg| match seg {
+        Segment::Key(key) => cur.get(key),
+        Segment::Index(i) => cur.get(i),
+    })
+}
+
+/// strftime 格式串是否有效；chrono 格式化无效格式串时会 panic，格式化前须先检查
+pub(crate) fn is_valid_strftime(format: &str) -> bool {
+    StrftimeItems::new(format).all(|item| !matches!(item, Item::Error))
+}
+
+/// 输出文本：字符串原样，对象/数组序列化为 JSON
+fn to_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn is_truthy(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::Bool(b) => *b,
+        Value::Number(n) => n.as_f64() != Some(0.0),
+        Value::String(s) => !s.is_empty(),
+        Value::Array(a) => !a.is_empty(),
+        Value::Object(_) => true,
+    }
+}
+
+fn as_f64(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+/// 比较两个值：都能转为数字时按数值比较，否则按文本比较
+fn compare(a: &Option<Value>, b: &Option<Value>) -> Option<std::cmp::Ordering> {
+    let (a, b) = (a.as_ref()?, b.as_ref()?);
+    match (as_f64(a), as_f64(b)) {
+        (Some(x), Some(y)) => x.partial_cmp(&y),
+        _ => Some(to_text(a).cmp(&to_text(b))),
+    }
+}
+
+fn url_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' if i + 2 < bytes.len() => {
+                if let Ok(hex) = std::str::from_utf8(&bytes[i + 1..i + 3]) {
+                    if let Ok(byte) = u8::from_str_radix(hex, 16) {
+                        out.push(byte);
+                        i += 3;
+                        continue;
+                    }
+                }
+                out.push(bytes[i]);
+                i += 1;
+            }
+            b'+' => {
+                out.push(b' ');
+                i += 1;
+            }
+            b => {
+                out.push(b);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hyper::header::HeaderValue;
+    use serde_json::json;
+
+    fn ctx() -> TemplateContext {
+        let mut headers = HeaderMap::new();
+        headers.insert("X-Token", HeaderValue::from_static("t-1"));
+        headers.insert(COOKIE, HeaderValue::from_static("theme=dark; sid=abc"));
+        let body = json!({
+            "name": "Ada \"Lovelace\"",
+            "vip": true,
+            "items": [{"sku": "A1", "qty": 2}, {"sku": "B2", "qty": 0}]
+        });
+        let params = HashMap::from([("id".to_string(), "42".to_string())]);
+        TemplateContext::new("POST", "/orders/42?page=2&q=a%20b")
+            .with_headers(&headers)
+            .with_client_ip(Some("10.0.0.7".parse().unwrap()))
+            .with_body(Some(&body))
+            .with_params(&params)
+    }
+
+    #[test]
+    fn test_request_data() {
+        let out = ctx().render(
+            "{{method}} {{path.id}} {{query.q}} {{header.x-token}} {{header.X-Token}} \
+             {{cookie.sid}} {{client_ip}} {{body.$.items[1].sku}} {{request.path.id}}",
+        );
+        assert_eq!(out, "POST 42 a b t-1 t-1 abc 10.0.0.7 B2 42");
+    }
+
+    #[test]
+    fn test_unresolved_kept_verbatim() {
+        let ctx = ctx();
+        assert_eq!(ctx.render("a {{query.ghost}} b"), "a {{query.ghost}} b");
+        assert_eq!(ctx.render("{{ nope 1 }}"), "{{ nope 1 }}");
+        assert_eq!(ctx.render("a {{oops"), "a {{oops");
+        assert_eq!(ctx.render("\\{{method}}"), "{{method}}");
+    }
+
+    #[test]
+    fn test_conditionals_and_loops() {
+        let ctx = ctx();
+        assert_eq!(
+            ctx.render("{{#if body.$.vip}}vip{{else}}regular{{/if}}"),
+            "vip"
+        );
+        assert_eq!(
+            ctx.render("{{#if (eq method \"GET\")}}read{{else}}write{{/if}}"),
+            "write"
+        );
+        assert_eq!(ctx.render("{{#unless query.ghost}}none{{/unless}}"), "none");
+        assert_eq!(
+            ctx.render(
+                "[{{#each body.$.items}}{\"i\":{{@index}},\"sku\":\"{{sku}}\",\"in_stock\":\
+                 {{gt qty 0}}}{{#unless @last}},{{/unless}}{{/each}}]"
+            ),
+            r#"[{"i":0,"sku":"A1","in_stock":true},{"i":1,"sku":"B2","in_stock":false}]"#
+        );
+        assert_eq!(
+            ctx.render("{{#each query.ghost}}x{{else}}empty{{/each}}"),
+            "empty"
+        );
+        assert_eq!(
+            ctx.render("{{#each query}}{{@key}}={{this}};{{/each}}"),
+            "page=2;q=a b;"
+        );
+    }
+
+    #[test]
+    fn test_helpers() {
+        let ctx = ctx();
+        assert_eq!(
+            ctx.render("{{jsonEscape body.$.name}}"),
+            r#"Ada \"Lovelace\""#
+        );
+        assert_eq!(ctx.render("{{base64 \"hi\"}}"), "aGk=");
+        assert_eq!(ctx.render("{{base64Decode (base64 path.id)}}"), "42");
+        assert_eq!(
+            ctx.render("{{json body.$.items[0]}}"),
+            r#"{"qty":2,"sku":"A1"}"#
+        );
+        assert_eq!(ctx.render("{{default query.ghost \"n/a\"}}"), "n/a");
+        assert_eq!(ctx.render("{{uuid}}").len(), 36);
+        assert_eq!(ctx.render("{{randomString 8}}").len(), 8);
+        let n: i64 = ctx.render("{{randomInt 5 7}}").parse().unwrap();
+        assert!((5..=7).contains(&n));
+        assert_eq!(ctx.render("{{now \"%Y\"}}").len(), 4);
+        assert!(ctx.render("{{now format=\"unix\"}}").parse::<i64>().is_ok());
+        // 无效的格式串不渲染，占位符原样保留
+        assert_eq!(ctx.render("{{now \"%Q\"}}"), "{{now \"%Q\"}}");
+        assert_eq!(ctx.render("{{randomString 1000000000}}").len(), 1000);
+        assert_eq!(
+            ctx.render("{{now format=\"%Y-%\"}}"),
+            "{{now format=\"%Y-%\"}}"
+        );
+    }
+
+    #[test]
+    fn test_vars() {
+        let mut ctx = ctx();
+        ctx.set_var("order", json!("o-9"));
+        assert_eq!(ctx.render("{{order}} {{vars.order}}"), "o-9 o-9");
+        // 变量不覆盖内置名称
+        ctx.set_var("method", json!("x"));
+        assert_eq!(ctx.render("{{method}}"), "POST");
+    }
+
+    #[test]
+    fn test_parse_errors_leave_template() {
+        let ctx = ctx();
+        assert_eq!(ctx.render("{{#if vip}}x"), "{{#if vip}}x");
+        assert_eq!(ctx.render("{{#if vip}}x{{/each}}"), "{{#if vip}}x{{/each}}");
+    }
+
+    #[test]
+    fn test_faker_seeded_by_request_field() {
+        let ctx = ctx();
+        let tpl = "{{#faker locale=\"de_DE\" seed=path.id}}{{faker.name}}|{{faker.iban}}|\
+                   {{randomInt 1 1000}}{{/faker}}";
+        let first = ctx.render(tpl);
+        assert_eq!(first, ctx.render(tpl));
+        assert!(first.split('|').nth(1).unwrap().starts_with("DE"));
+
+        let other = TemplateContext::new("GET", "/")
+            .with_params(&HashMap::from([("id".to_string(), "43".to_string())]));
+        assert_ne!(first, other.render(tpl));
+        // 未知的 faker 类型原样保留
+        assert_eq!(ctx.render("{{faker.nope}}"), "{{faker.nope}}");
+    }
+
+    #[test]
+    fn test_repeat_generates_array() {
+        let ctx = ctx();
+        let out = ctx.render(
+            "[{{#faker seed=1}}{{#repeat 3}}{\"id\":{{@index}},\"email\":\"{{faker.email}}\"}\
+             {{/repeat}}{{/faker}}]",
+        );
+        let items: Value = serde_json::from_str(&out).unwrap();
+        assert_eq!(items.as_array().unwrap().len(), 3);
+        assert_eq!(items[2]["id"], 2);
+
+        assert_eq!(
+            ctx.render("{{#repeat 3 separator=\"-\"}}x{{/repeat}}"),
+            "x-x-x"
+        );
+        assert_eq!(ctx.render("{{#repeat 0}}x{{else}}none{{/repeat}}"), "none");
+        let n = ctx
+            .render("{{#repeat min=2 max=4 separator=\"\"}}x{{/repeat}}")
+            .len();
+        assert!((2..=4).contains(&n));
+    }
+
+    #[test]
+    fn test_render_budget() {
+        let ctx = ctx();
+        // 嵌套循环每层都不超过 MAX_REPEAT，但总迭代数受预算限制
+        let out = ctx.render(
+            "{{#repeat 10000 separator=\"\"}}{{#repeat 10000 separator=\"\"}}x{{/repeat}}{{/repeat}}",
+        );
+        assert!(!out.is_empty());
+        assert!(out.len() <= MAX_TOTAL_ITERATIONS);
+
+        let chunk = "y".repeat(4096);
+        let out = ctx.render(&format!(
+            "{{{{#repeat 10000 separator=\"\"}}}}{chunk}{{{{/repeat}}}}"
+        ));
+        assert!(out.len() > MAX_OUTPUT_BYTES);
+        assert!(out.len() <= MAX_OUTPUT_BYTES + chunk.len());
+    }
+
+    #[test]
+    fn test_references() {
+        for template in [
+            "{{body}}",
+            "{{body.$.id}}",
+            "{{request.body.id}}",
+            "{{#each body}}{{this}}{{/each}}",
+            "{{#if body.$.vip}}vip{{/if}}",
+            "{{#if x}}{{else}}{{jsonEscape (default body.name \"n\")}}{{/if}}",
+            "{{#repeat count=body.$.n}}x{{/repeat}}",
+        ] {
+            assert!(references(template, "body"), "{template}");
+        }
+        for template in [
+            "https://somebody.example/{{query.id}}",
+            "{{query.body}}",
+            "{{bodyText}}",
+            "\\{{body}}",
+            "{{#if body}}unclosed",
+        ] {
+            assert!(!references(template, "body"), "{template}");
+        }
+    }
+
+    #[test]
+    fn test_parse_path() {
+        assert_eq!(
+            parse_path("$.k[0][1].v"),
+            Some(vec![
+                Segment::Key("k".to_string()),
+                Segment::Index(0),
+                Segment::Index(1),
+                Segment::Key("v".to_string())
+            ])
+        );
+        assert_eq!(parse_path("k[x]"), None);
+    }
+}
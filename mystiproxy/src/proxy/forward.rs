@@ -2,11 +2,16 @@
 //!
 //! 提供双向数据转发功能，支持 TCP 到 TCP、TCP 到 UDS 的转发
 
+use std::net::IpAddr;
+use std::sync::Arc;
 use std::time::Duration;
 
 use tokio::io::{self, AsyncRead, AsyncWrite};
 use tokio::time::timeout;
+use tracing::info;
 
+use crate::balancer::LoadBalancer;
+use crate::circuit_breaker::{CircuitBreaker, CircuitBreakers};
 use crate::error::{MystiProxyError, Result};
 use crate::io::SocketStream;
 
@@ -111,6 +116,37 @@ pub async fn connect_to_target(target_addr: &str) -> Result<SocketStream> {
         .map_err(MystiProxyError::Io)
 }
 
+/// 经熔断器连接到目标地址
+///
+/// 熔断器打开时不发起连接，直接返回 [`MystiProxyError::CircuitOpen`]；
+/// 建连成功或失败计入熔断统计
+///
+/// # Arguments
+///
+/// * `target_addr` - 目标地址
+/// * `breaker` - 目标的熔断器（None = 不熔断）
+pub async fn connect_with_breaker(
+    target_addr: &str,
+    breaker: Option<&Arc<CircuitBreaker>>,
+) -> Result<SocketStream> {
+    let Some(breaker) = breaker else {
+        return connect_to_target(target_addr).await;
+    };
+    let permit = breaker
+        .try_acquire()
+        .ok_or_else(|| MystiProxyError::CircuitOpen(target_addr.to_string()))?;
+    match connect_to_target(target_addr).await {
+        Ok(stream) => {
+            permit.success();
+            Ok(stream)
+        }
+        Err(e) => {
+            permit.failure();
+            Err(e)
+        }
+    }
+}
+
 /// TCP 到 TCP 的转发
 ///
 /// 接受一个 TCP 连接，连接到目标 TCP 地址，并进行双向数据转发
@@ -175,6 +211,55 @@ pub async fn forward_to_target_with_timeout(
     forward_bidirectional_with_timeout(client, target, timeout_duration).await
 }
 
+/// 经负载均衡选择目标后转发
+///
+/// 选中的 endpoint 在整个连接期间计入活动连接数；
+/// 连接目标的成功/失败会上报给被动健康检查
+///
+/// # Arguments
+///
+/// * `client` - 客户端连接
+/// * `balancer` - 负载均衡器
+/// * `client_ip` - 客户端 IP（一致性哈希使用）
+/// * `timeout_duration` - 超时时间（可选）
+/// * `breakers` - 按目标的熔断器（可选）；熔断中的目标不建连、不上报健康检查
+pub async fn forward_to_balanced_target(
+    client: impl AsyncRead + AsyncWrite + Send + 'static,
+    balancer: &LoadBalancer,
+    client_ip: Option<IpAddr>,
+    timeout_duration: Option<Duration>,
+    breakers: Option<&CircuitBreakers>,
+) -> Result<ForwardResult> {
+    let upstream = balancer.select(client_ip, None);
+    info!("Forwarding connection to {}", upstream.address());
+
+    let breaker = breakers.map(|b| b.get(upstream.address()));
+    let target = match connect_with_breaker(upstream.address(), breaker.as_ref()).await {
+        Ok(target) => {
+            balancer.report_success(upstream.endpoint());
+            target
+        }
+        Err(e @ MystiProxyError::CircuitOpen(_)) => return Err(e),
+        Err(e) => {
+            balancer.report_failure(upstream.endpoint());
+            return Err(e);
+        }
+    };
+
+    let result = match timeout_duration {
+        Some(timeout) => forward_bidirectional_with_timeout(client, target, timeout).await,
+        None => forward_bidirectional(client, target).await,
+    };
+
+    if let Ok(forward_result) = &result {
+        info!(
+            "Connection closed: sent {} bytes to target, {} bytes to client",
+            forward_result.stats.client_to_target, forward_result.stats.target_to_client
+        );
+    }
+    result
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -11,9 +11,9 @@ pub mod unix;
 
 pub use address::Address;
 pub use forward::{
-    connect_to_target, forward_bidirectional, forward_bidirectional_with_timeout,
-    forward_tcp_to_tcp, forward_to_target, forward_to_target_with_timeout, ForwardResult,
-    TransferStats,
+    connect_to_target, connect_with_breaker, forward_bidirectional,
+    forward_bidirectional_with_timeout, forward_tcp_to_tcp, forward_to_balanced_target,
+    forward_to_target, forward_to_target_with_timeout, ForwardResult, TransferStats,
 };
 
 #[cfg(unix)]
@@ -21,43 +21,69 @@ pub use forward::forward_tcp_to_uds;
 
 pub use tcp::TcpProxyListener;
 
+use std::sync::Arc;
 use std::time::Duration;
 
 use tracing::{error, info, warn};
 
+use crate::balancer::LoadBalancer;
+use crate::circuit_breaker::{CircuitBreaker, CircuitBreakers};
 use crate::config::{EngineConfig, ProxyType};
 use crate::error::{MystiProxyError, Result};
 use crate::io::StreamListener;
+use crate::rate_limit::ConnectionLimiter;
 
 /// 代理服务器配置
 #[derive(Debug, Clone)]
 pub struct ProxyConfig {
     /// 监听地址
     pub listen: Address,
-    /// 目标地址
+    /// 目标地址（多目标时为第一个）
     pub target: Address,
+    /// 多目标负载均衡器（None = 固定转发到 target）
+    pub balancer: Option<Arc<LoadBalancer>>,
     /// 代理类型
     pub proxy_type: ProxyType,
     /// 超时时间
     pub timeout: Option<Duration>,
     /// 入站 IP 过滤（None = 不过滤）
     pub ip_filter: Option<crate::ip_filter::IpFilter>,
+    /// 按目标的熔断器（None = 不熔断）
+    pub circuit_breakers: Option<Arc<CircuitBreakers>>,
+    /// 入站连接数限制（None = 不限制）
+    pub connection_limiter: Option<Arc<ConnectionLimiter>>,
 }
 
 impl ProxyConfig {
     /// 从 EngineConfig 创建 ProxyConfig
     pub fn from_engine_config(config: &EngineConfig) -> Result<Self> {
         let listen = Address::parse(&config.listen)?;
-        let target = Address::parse(&config.target)?;
+        let target = Address::parse(config.target.primary())?;
+        let balancer = if config.target.is_multiple() {
+            Some(Arc::new(LoadBalancer::from_engine_config(config)?))
+        } else {
+            None
+        };
 
         let ip_filter = crate::ip_filter::IpFilter::from_config(&config.allow, &config.deny)?;
+        let circuit_breakers = config
+            .circuit_breaker
+            .clone()
+            .map(|c| Arc::new(CircuitBreakers::new(c)));
+        let connection_limiter = config
+            .connection_limit
+            .as_ref()
+            .map(|c| Arc::new(ConnectionLimiter::from_config(c)));
 
         Ok(Self {
             listen,
             target,
+            balancer,
             proxy_type: config.proxy_type.clone(),
             timeout: config.request_timeout,
             ip_filter,
+            circuit_breakers,
+            connection_limiter,
         })
     }
 }
@@ -85,6 +111,11 @@ impl ProxyServer {
         Ok(Self::new(proxy_config))
     }
 
+    /// 多目标引擎的负载均衡器
+    pub fn balancer(&self) -> Option<Arc<LoadBalancer>> {
+        self.config.balancer.clone()
+    }
+
     /// 从简单配置创建代理服务器
     pub fn from_simple_config(
         listen: String,
@@ -97,9 +128,12 @@ impl ProxyServer {
         Ok(Self::new(ProxyConfig {
             listen: listen_addr,
             target: target_addr,
+            balancer: None,
             proxy_type: ProxyType::Tcp,
             timeout,
             ip_filter: None,
+            circuit_breakers: None,
+            connection_limiter: None,
         }))
     }
 
@@ -138,14 +172,49 @@ impl ProxyServer {
                         }
                     }
 
+                    let permit = match &self.config.connection_limiter {
+                        Some(limiter) => match limiter.try_acquire() {
+                            Some(permit) => Some(permit),
+                            None => {
+                                warn!("Connection from {} rejected by connection limit", addr);
+                                continue;
+                            }
+                        },
+                        None => None,
+                    };
+
                     info!("Accepted connection from {}", addr);
 
-                    let target_addr = self.config.target.to_string();
                     let timeout_duration = self.config.timeout;
 
+                    let breakers = self.config.circuit_breakers.clone();
+
+                    if let Some(balancer) = self.config.balancer.clone() {
+                        let client_ip = addr.ip();
+                        tokio::spawn(async move {
+                            let _permit = permit;
+                            if let Err(e) = forward_to_balanced_target(
+                                stream,
+                                &balancer,
+                                client_ip,
+                                timeout_duration,
+                                breakers.as_deref(),
+                            )
+                            .await
+                            {
+                                error!("Connection error: {}", e);
+                            }
+                        });
+                        continue;
+                    }
+
+                    let target_addr = self.config.target.to_string();
+                    let breaker = breakers.map(|b| b.get(&target_addr));
                     tokio::spawn(async move {
+                        let _permit = permit;
                         if let Err(e) =
-                            Self::handle_connection(stream, target_addr, timeout_duration).await
+                            Self::handle_connection(stream, target_addr, timeout_duration, breaker)
+                                .await
                         {
                             error!("Connection error: {}", e);
                         }
@@ -163,11 +232,14 @@ impl ProxyServer {
         stream: impl tokio::io::AsyncRead + tokio::io::AsyncWrite + Send + 'static,
         target_addr: String,
         timeout_duration: Option<Duration>,
+        breaker: Option<Arc<CircuitBreaker>>,
     ) -> Result<()> {
-        let result = if let Some(timeout) = timeout_duration {
-            forward_to_target_with_timeout(stream, &target_addr, timeout).await
-        } else {
-            forward_to_target(stream, &target_addr).await
+        let result = match connect_with_breaker(&target_addr, breaker.as_ref()).await {
+            Ok(target) => match timeout_duration {
+                Some(timeout) => forward_bidirectional_with_timeout(stream, target, timeout).await,
+                None => forward_bidirectional(stream, target).await,
+            },
+            Err(e) => Err(e),
         };
 
         match result {
@@ -289,7 +361,7 @@ mod tests {
     fn test_proxy_config_from_engine_config() {
         let engine_config = EngineConfig {
             listen: "tcp://0.0.0.0:3128".to_string(),
-            target: "unix:///var/run/docker.sock".to_string(),
+            target: "unix:///var/run/docker.sock".to_string().into(),
             proxy_type: ProxyType::Tcp,
             request_timeout: Some(Duration::from_secs(10)),
             connection_timeout: None,
@@ -301,6 +373,20 @@ mod tests {
             allow: None,
             deny: None,
             management: None,
+            max_buffered_body_size: None,
+            load_balance: None,
+            health_check: None,
+            http2: None,
+            connection_pool: None,
+            upstream_tls: None,
+            retry: None,
+            circuit_breaker: None,
+            rate_limit: None,
+            connection_limit: None,
+            cache: None,
+            compression: None,
+            gateway: None,
+            mock_root: None,
         };
 
         let proxy_config = ProxyConfig::from_engine_config(&engine_config).unwrap();
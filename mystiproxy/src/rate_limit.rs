@@ -0,0 +1,329 @@
+//! 限流模块
+//!
+//! HTTP 引擎按令牌桶限制请求速率，限流键可取客户端 IP、请求头、JWT `sub` 或全局；
+//! TCP 引擎限制并发连接数与每秒新建连接数。
+
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use hyper::HeaderMap;
+
+use crate::config::{ConnectionLimitConfig, RateLimitConfig, RateLimitKey};
+
+/// 超过该数量的限流键时清理已回满的令牌桶
+const MAX_TRACKED_KEYS: usize = 10_000;
+
+/// 令牌桶
+#[derive(Debug, Clone)]
+struct TokenBucket {
+    tokens: f64,
+    updated_at: Instant,
+}
+
+impl TokenBucket {
+    fn full(capacity: f64, now: Instant) -> Self {
+        Self {
+            tokens: capacity,
+            updated_at: now,
+        }
+    }
+
+    /// 按流逝时间补充令牌（不超过容量）
+    fn refill(&mut self, rate: f64, capacity: f64, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated_at).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * rate).min(capacity);
+        self.updated_at = now;
+    }
+}
+
+/// 限流判定结果
+#[derive(Debug, Clone, PartialEq)]
+pub struct RateLimitDecision {
+    /// 是否放行
+    pub allowed: bool,
+    /// 桶容量（`RateLimit-Limit`）
+    pub limit: u32,
+    /// 剩余令牌数（`RateLimit-Remaining`）
+    pub remaining: u32,
+    /// 令牌桶回满所需时间（`RateLimit-Reset`）
+    pub reset: Duration,
+    /// 拒绝时距离下一个可用令牌的时间（`Retry-After`）
+    pub retry_after: Duration,
+}
+
+/// 按键的令牌桶限流器
+#[derive(Debug)]
+pub struct RateLimiter {
+    rate: f64,
+    capacity: f64,
+    key: RateLimitKey,
+    header_name: Option<String>,
+    buckets: Mutex<HashMap<String, TokenBucket>>,
+}
+
+impl RateLimiter {
+    /// 由配置创建；`burst` 未配置时取每秒请求数（至少 1）
+    pub fn from_config(config: &RateLimitConfig) -> Self {
+        let capacity = config
+            .burst
+            .map(f64::from)
+            .unwrap_or_else(|| config.requests_per_second.ceil())
+            .max(1.0);
+        Self {
+            rate: config.requests_per_second,
+            capacity,
+            key: config.key,
+            header_name: config.header_name.clone(),
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 计算请求的限流键
+    ///
+    /// 请求头或 JWT `sub` 缺失时退化为按客户端 IP 限流。
+    pub fn key_for(
+        &self,
+        client_ip: Option<IpAddr>,
+        headers: &HeaderMap,
+        jwt_sub: Option<&str>,
+    ) -> String {
+        let by_ip = || {
+            client_ip
+                .map(|ip| format!("ip:{ip}"))
+                .unwrap_or_else(|| "ip:unknown".to_string())
+        };
+        match self.key {
+            RateLimitKey::Global => "global".to_string(),
+            RateLimitKey::ClientIp => by_ip(),
+            RateLimitKey::Header => self
+                .header_name
+                .as_deref()
+                .and_then(|name| headers.get(name))
+                .and_then(|v| v.to_str().ok())
+                .map(|v| format!("header:{v}"))
+                .unwrap_or_else(by_ip),
+            RateLimitKey::JwtSub => jwt_sub
+                .map(|sub| format!("sub:{sub}"))
+                .unwrap_or_else(by_ip),
+        }
+    }
+
+    /// 为 `key` 取一个令牌
+    pub fn check(&self, key: &str) -> RateLimitDecision {
+        self.check_at(key, Instant::now())
+    }
+
+    fn check_at(&self, key: &str, now: Instant) -> RateLimitDecision {
+        let mut buckets = self.buckets.lock().unwrap();
+        if buckets.len() >= MAX_TRACKED_KEYS && !buckets.contains_key(key) {
+            let (rate, capacity) = (self.rate, self.capacity);
+            buckets.retain(|_, bucket| {
+                bucket.refill(rate, capacity, now);
+                bucket.tokens < capacity
+            });
+        }
+        let bucket = buckets
+            .entry(key.to_string())
+            .or_insert_with(|| TokenBucket::full(self.capacity, now));
+        bucket.refill(self.rate, self.capacity, now);
+
+        let allowed = bucket.tokens >= 1.0;
+        if allowed {
+            bucket.tokens -= 1.0;
+        }
+        let retry_after = if allowed {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64((1.0 - bucket.tokens) / self.rate)
+        };
+        RateLimitDecision {
+            allowed,
+            limit: self.capacity as u32,
+            remaining: bucket.tokens.floor() as u32,
+            reset: Duration::from_secs_f64((self.capacity - bucket.tokens) / self.rate),
+            retry_after,
+        }
+    }
+}
+
+/// TCP 引擎的连接数限制
+#[derive(Debug)]
+pub struct ConnectionLimiter {
+    max_connections: Option<usize>,
+    active: Arc<AtomicUsize>,
+    /// 每秒新建连接数限制：(每秒速率, 桶容量, 令牌桶)
+    rate: Option<(f64, f64, Mutex<TokenBucket>)>,
+}
+
+impl ConnectionLimiter {
+    /// 由配置创建
+    pub fn from_config(config: &ConnectionLimitConfig) -> Self {
+        let rate = config.connections_per_second.map(|rate| {
+            let capacity = config
+                .burst
+                .map(f64::from)
+                .unwrap_or_else(|| rate.ceil())
+                .max(1.0);
+            (
+                rate,
+                capacity,
+                Mutex::new(TokenBucket::full(capacity, Instant::now())),
+            )
+        });
+        Self {
+            max_connections: config.max_connections,
+            active: Arc::new(AtomicUsize::new(0)),
+            rate,
+        }
+    }
+
+    /// 当前活动连接数
+    pub fn active_connections(&self) -> usize {
+        self.active.load(Ordering::SeqCst)
+    }
+
+    /// 申请接入一个新连接；超限时返回 None
+    ///
+    /// 返回的许可在连接结束（drop）时释放并发名额。
+    pub fn try_acquire(&self) -> Option<ConnectionPermit> {
+        if let Some(max) = self.max_connections {
+            let reserved = self
+                .active
+                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| {
+                    (n < max).then_some(n + 1)
+                });
+            if reserved.is_err() {
+                return None;
+            }
+        } else {
+            self.active.fetch_add(1, Ordering::SeqCst);
+        }
+        let permit = ConnectionPermit {
+            active: self.active.clone(),
+        };
+
+        if let Some((rate, capacity, bucket)) = &self.rate {
+            let mut bucket = bucket.lock().unwrap();
+            bucket.refill(*rate, *capacity, Instant::now());
+            if bucket.tokens < 1.0 {
+                return None;
+            }
+            bucket.tokens -= 1.0;
+        }
+        Some(permit)
+    }
+}
+
+/// 连接许可，drop 时释放并发名额
+#[derive(Debug)]
+pub struct ConnectionPermit {
+    active: Arc<AtomicUsize>,
+}
+
+impl Drop for ConnectionPermit {
+    fn drop(&mut self) {
+        self.active.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limiter(rps: f64, burst: Option<u32>, key: RateLimitKey) -> RateLimiter {
+        RateLimiter::from_config(&RateLimitConfig {
+            requests_per_second: rps,
+            burst,
+            key,
+            header_name: Some("X-Api-Key".to_string()),
+        })
+    }
+
+    #[test]
+    fn test_token_bucket_allows_burst_then_refills() {
+        let limiter = limiter(2.0, Some(3), RateLimitKey::Global);
+        let start = Instant::now();
+        for remaining in [2, 1, 0] {
+            let decision = limiter.check_at("k", start);
+            assert!(decision.allowed);
+            assert_eq!(decision.remaining, remaining);
+            assert_eq!(decision.limit, 3);
+        }
+        let rejected = limiter.check_at("k", start);
+        assert!(!rejected.allowed);
+        assert_eq!(rejected.retry_after, Duration::from_millis(500));
+        assert_eq!(rejected.reset, Duration::from_millis(1500));
+
+        // 半秒补充一个令牌
+        assert!(
+            limiter
+                .check_at("k", start + Duration::from_millis(500))
+                .allowed
+        );
+        assert!(
+            !limiter
+                .check_at("k", start + Duration::from_millis(500))
+                .allowed
+        );
+    }
+
+    #[test]
+    fn test_keys_have_separate_buckets() {
+        let limiter = limiter(1.0, Some(1), RateLimitKey::ClientIp);
+        let now = Instant::now();
+        assert!(limiter.check_at("a", now).allowed);
+        assert!(!limiter.check_at("a", now).allowed);
+        assert!(limiter.check_at("b", now).allowed);
+    }
+
+    #[test]
+    fn test_key_selection_with_fallback() {
+        let ip: IpAddr = "10.0.0.1".parse().unwrap();
+        let mut headers = HeaderMap::new();
+
+        let by_header = limiter(1.0, None, RateLimitKey::Header);
+        assert_eq!(by_header.key_for(Some(ip), &headers, None), "ip:10.0.0.1");
+        headers.insert("X-Api-Key", "abc".parse().unwrap());
+        assert_eq!(by_header.key_for(Some(ip), &headers, None), "header:abc");
+
+        let by_sub = limiter(1.0, None, RateLimitKey::JwtSub);
+        assert_eq!(
+            by_sub.key_for(Some(ip), &headers, Some("alice")),
+            "sub:alice"
+        );
+        assert_eq!(by_sub.key_for(None, &headers, None), "ip:unknown");
+
+        let global = limiter(1.0, None, RateLimitKey::Global);
+        assert_eq!(global.key_for(Some(ip), &headers, None), "global");
+    }
+
+    #[test]
+    fn test_connection_limiter_caps_concurrency() {
+        let limiter = ConnectionLimiter::from_config(&ConnectionLimitConfig {
+            max_connections: Some(2),
+            ..Default::default()
+        });
+        let first = limiter.try_acquire().unwrap();
+        let _second = limiter.try_acquire().unwrap();
+        assert!(limiter.try_acquire().is_none());
+        drop(first);
+        assert!(limiter.try_acquire().is_some());
+        assert_eq!(limiter.active_connections(), 1);
+    }
+
+    #[test]
+    fn test_connection_limiter_caps_new_connection_rate() {
+        let limiter = ConnectionLimiter::from_config(&ConnectionLimitConfig {
+            connections_per_second: Some(1.0),
+            burst: Some(2),
+            ..Default::default()
+        });
+        assert!(limiter.try_acquire().is_some());
+        assert!(limiter.try_acquire().is_some());
+        assert!(limiter.try_acquire().is_none());
+        assert_eq!(limiter.active_connections(), 0);
+    }
+}
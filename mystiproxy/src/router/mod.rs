@@ -1,6 +1,8 @@
 //! 路由模块
 
 use crate::config::{LocationConfig, MatchMode};
+use hyper::header::{HeaderMap, HeaderName};
+use hyper::{Method, Request};
 use regex::Regex;
 use std::collections::HashMap;
 
@@ -51,6 +53,206 @@ impl MatchResult {
             remaining: Some(remaining),
         }
     }
+
+    /// 捕获的路径参数，剩余路径以 `remaining` 为名一并返回（同名参数优先）
+    pub fn captures(&self) -> HashMap<String, String> {
+        let mut captures = self.params.clone();
+        if let Some(remaining) = &self.remaining {
+            captures
+                .entry("remaining".to_string())
+                .or_insert_with(|| remaining.clone());
+        }
+        captures
+    }
+}
+
+/// 用捕获的参数展开 `{name}` 占位符，未知名称保留原文
+///
+/// 例如模板 `/v2/users/{id}/{remaining}`，参数 `id=7, remaining=posts/1` 时得到 `/v2/users/7/posts/1`
+pub fn expand_params(template: &str, params: &HashMap<String, String>) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        match after.find('}').map(|end| (end, &after[..end])) {
+            Some((end, name)) if params.contains_key(name) => {
+                out.push_str(&params[name]);
+                rest = &after[end + 1..];
+            }
+            _ => {
+                out.push('{');
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// 路径之外参与路由匹配的请求信息
+#[derive(Debug, Clone, Copy)]
+pub struct RequestInfo<'a> {
+    /// 请求主机名（来自 Host 头或 URI authority，不含端口）
+    pub host: Option<&'a str>,
+    /// 请求方法
+    pub method: &'a Method,
+    /// 请求头
+    pub headers: &'a HeaderMap,
+    /// 原始查询字符串
+    pub query: Option<&'a str>,
+}
+
+impl<'a> RequestInfo<'a> {
+    /// 从请求提取匹配信息；Host 头优先于 URI authority（HTTP/2 使用后者）
+    pub fn from_request<B>(req: &'a Request<B>) -> Self {
+        let host = req
+            .headers()
+            .get(hyper::header::HOST)
+            .and_then(|v| v.to_str().ok())
+            .or_else(|| req.uri().host())
+            .map(strip_port);
+        RequestInfo {
+            host,
+            method: req.method(),
+            headers: req.headers(),
+            query: req.uri().query(),
+        }
+    }
+}
+
+/// 去掉 `host:port` 中的端口（兼容 `[::1]:8080`）
+fn strip_port(host: &str) -> &str {
+    if host.starts_with('[') {
+        return host.find(']').map_or(host, |end| &host[..=end]);
+    }
+    match host.rsplit_once(':') {
+        Some((name, _)) if !name.contains(':') => name,
+        _ => host,
+    }
+}
+
+/// 主机名匹配规则
+#[derive(Debug)]
+enum HostPattern {
+    /// 精确主机名
+    Exact(String),
+    /// `*.example.com`：匹配任意层级的子域名（保存 `.example.com`），不匹配 `example.com` 本身
+    Wildcard(String),
+}
+
+impl HostPattern {
+    fn parse(pattern: &str) -> Self {
+        let pattern = pattern.trim_end_matches('.').to_ascii_lowercase();
+        match pattern.strip_prefix('*') {
+            Some(suffix) => HostPattern::Wildcard(suffix.to_string()),
+            None => HostPattern::Exact(pattern),
+        }
+    }
+
+    fn matches(&self, host: &str) -> bool {
+        match self {
+            HostPattern::Exact(name) => host == name,
+            HostPattern::Wildcard(suffix) => host.len() > suffix.len() && host.ends_with(suffix),
+        }
+    }
+}
+
+/// location 上路径之外的匹配条件（未配置的维度不参与匹配）
+#[derive(Debug, Default)]
+struct RouteConditions {
+    hosts: Vec<HostPattern>,
+    methods: Vec<Method>,
+    headers: Vec<(HeaderName, String)>,
+    query: Vec<(String, String)>,
+}
+
+/// 表示“只要求存在”的匹配值
+const MATCH_ANY: &str = "*";
+
+impl RouteConditions {
+    fn from_location(location: &LocationConfig) -> crate::Result<Self> {
+        let invalid = |what: &str, value: &str| {
+            crate::MystiProxyError::Config(format!(
+                "Invalid {} '{}' in location {}",
+                what, value, location.location
+            ))
+        };
+
+        let hosts = location
+            .hosts
+            .iter()
+            .flatten()
+            .map(|h| HostPattern::parse(h))
+            .collect();
+        let methods = location
+            .methods
+            .iter()
+            .flatten()
+            .map(|m| {
+                Method::from_bytes(m.to_ascii_uppercase().as_bytes())
+                    .map_err(|_| invalid("method", m))
+            })
+            .collect::<crate::Result<_>>()?;
+        let headers = location
+            .match_headers
+            .iter()
+            .flatten()
+            .map(|(name, value)| {
+                HeaderName::from_bytes(name.as_bytes())
+                    .map(|name| (name, value.clone()))
+                    .map_err(|_| invalid("header name", name))
+            })
+            .collect::<crate::Result<_>>()?;
+        let query = location
+            .match_query
+            .iter()
+            .flatten()
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect();
+
+        Ok(RouteConditions {
+            hosts,
+            methods,
+            headers,
+            query,
+        })
+    }
+
+    fn matches(&self, info: &RequestInfo<'_>) -> bool {
+        if !self.hosts.is_empty() {
+            let Some(host) = info.host else {
+                return false;
+            };
+            let host = host.trim_end_matches('.').to_ascii_lowercase();
+            if !self.hosts.iter().any(|p| p.matches(&host)) {
+                return false;
+            }
+        }
+
+        if !self.methods.is_empty() && !self.methods.contains(info.method) {
+            return false;
+        }
+
+        let headers_match = self.headers.iter().all(|(name, expected)| {
+            let mut values = info.headers.get_all(name).iter();
+            if expected == MATCH_ANY {
+                values.next().is_some()
+            } else {
+                values.any(|v| v.as_bytes() == expected.as_bytes())
+            }
+        });
+        if !headers_match {
+            return false;
+        }
+
+        self.query.iter().all(|(key, expected)| {
+            url::form_urlencoded::parse(info.query.unwrap_or("").as_bytes()).any(|(k, v)| {
+                k == key.as_str() && (expected == MATCH_ANY || v == expected.as_str())
+            })
+        })
+    }
 }
 
 /// 路由规则
@@ -64,6 +266,8 @@ pub struct Route {
     pub location_config: LocationConfig,
     /// 编译后的正则表达式（用于 Regex 和 PrefixRegex 模式）
     compiled_regex: Option<Regex>,
+    /// 路径之外的匹配条件
+    conditions: RouteConditions,
 }
 
 impl Route {
@@ -78,33 +282,189 @@ impl Route {
         } else {
             None
         };
+        let conditions = RouteConditions::from_location(&location_config)?;
 
         Ok(Route {
             pattern,
             mode,
             location_config,
             compiled_regex,
+            conditions,
         })
     }
+
+    /// 请求是否满足 hosts/methods/match_headers/match_query 条件
+    pub fn matches_request(&self, info: &RequestInfo<'_>) -> bool {
+        self.conditions.matches(info)
+    }
+}
+
+/// Full/Prefix 路由的 radix tree 节点（按字节切分路径）
+#[derive(Debug, Default)]
+struct RadixNode {
+    /// 子节点：边标签与子树，各标签首字节互不相同
+    children: Vec<(Vec<u8>, RadixNode)>,
+    /// 路径恰为该节点 key 的 Full 路由（路由下标）
+    full: Vec<usize>,
+    /// 以该节点 key 为前缀的 Prefix 路由（路由下标）
+    prefix: Vec<usize>,
+}
+
+impl RadixNode {
+    fn insert(&mut self, key: &[u8], mode: &MatchMode, index: usize) {
+        if key.is_empty() {
+            match mode {
+                MatchMode::Prefix => self.prefix.push(index),
+                _ => self.full.push(index),
+            }
+            return;
+        }
+
+        let existing = self
+            .children
+            .iter()
+            .position(|(label, _)| label[0] == key[0]);
+        let Some(pos) = existing else {
+            let mut child = RadixNode::default();
+            child.insert(&[], mode, index);
+            self.children.push((key.to_vec(), child));
+            return;
+        };
+
+        let (label, child) = &mut self.children[pos];
+        let common = label.iter().zip(key).take_while(|(a, b)| a == b).count();
+        if common < label.len() {
+            // 拆分边：公共部分成为新的中间节点
+            let suffix = label.split_off(common);
+            let old = std::mem::take(child);
+            child.children.push((suffix, old));
+        }
+        child.insert(&key[common..], mode, index);
+    }
+
+    /// 收集 key 为 `path` 前缀的 Prefix 路由与 key 等于 `path` 的 Full 路由
+    fn collect(&self, path: &[u8], out: &mut Vec<usize>) {
+        let mut node = self;
+        let mut rest = path;
+        loop {
+            out.extend_from_slice(&node.prefix);
+            if rest.is_empty() {
+                out.extend_from_slice(&node.full);
+                return;
+            }
+            let next = node
+                .children
+                .iter()
+                .find(|(label, _)| rest.starts_with(label));
+            match next {
+                Some((label, child)) => {
+                    rest = &rest[label.len()..];
+                    node = child;
+                }
+                None => return,
+            }
+        }
+    }
+}
+
+/// Regex/PrefixRegex 路由的按段索引：按 `/` 切分模式，含 `{param}` 的段视为通配段
+#[derive(Debug, Default)]
+struct SegmentNode {
+    literal: HashMap<String, SegmentNode>,
+    wildcard: Option<Box<SegmentNode>>,
+    /// 全部段结束于此的 Regex 路由
+    exact: Vec<usize>,
+    /// 末段之前的段结束于此的 PrefixRegex 路由（末段可能只匹配请求段的前缀）
+    prefix: Vec<usize>,
+}
+
+impl SegmentNode {
+    fn insert(&mut self, pattern: &str, mode: &MatchMode, index: usize) {
+        let segments: Vec<&str> = pattern.split('/').collect();
+        let (segments, is_prefix) = match mode {
+            MatchMode::PrefixRegex => (&segments[..segments.len() - 1], true),
+            _ => (&segments[..], false),
+        };
+        let mut node = self;
+        for segment in segments {
+            node = if segment.contains('{') {
+                node.wildcard.get_or_insert_with(Default::default)
+            } else {
+                node.literal.entry(segment.to_string()).or_default()
+            };
+        }
+        if is_prefix {
+            node.prefix.push(index);
+        } else {
+            node.exact.push(index);
+        }
+    }
+
+    /// 收集可能匹配 `segments` 的路由，最终由各自的正则校验
+    fn collect(&self, segments: &[&str], out: &mut Vec<usize>) {
+        out.extend_from_slice(&self.prefix);
+        let Some((segment, rest)) = segments.split_first() else {
+            out.extend_from_slice(&self.exact);
+            return;
+        };
+        if let Some(child) = self.literal.get(*segment) {
+            child.collect(rest, out);
+        }
+        if let Some(child) = &self.wildcard {
+            child.collect(rest, out);
+        }
+    }
 }
 
 /// 路由器
+///
+/// Full/Prefix 路由索引在 radix tree 中，Regex/PrefixRegex 路由按路径段索引；
+/// 索引只用于筛选候选，候选仍按配置顺序逐个校验并提取参数。
 #[derive(Debug)]
 pub struct Router {
     routes: Vec<Route>,
+    /// Full/Prefix 路由索引
+    tree: RadixNode,
+    /// Regex/PrefixRegex 路由索引
+    segments: SegmentNode,
 }
 
 impl Router {
     /// 创建新的路由器
     pub fn new() -> Self {
-        Router { routes: Vec::new() }
+        Router {
+            routes: Vec::new(),
+            tree: RadixNode::default(),
+            segments: SegmentNode::default(),
+        }
     }
 
     /// 添加路由规则
     pub fn add_route(&mut self, route: Route) {
+        let index = self.routes.len();
+        match route.mode {
+            MatchMode::Full | MatchMode::Prefix => {
+                self.tree
+                    .insert(route.pattern.as_bytes(), &route.mode, index)
+            }
+            MatchMode::Regex | MatchMode::PrefixRegex => {
+                self.segments.insert(&route.pattern, &route.mode, index)
+            }
+        }
         self.routes.push(route);
     }
 
+    /// 可能命中 `uri` 的路由下标（配置顺序）
+    fn candidate_indices(&self, uri: &str) -> Vec<usize> {
+        let mut out = Vec::new();
+        self.tree.collect(uri.as_bytes(), &mut out);
+        let segments: Vec<&str> = uri.split('/').collect();
+        self.segments.collect(&segments, &mut out);
+        out.sort_unstable();
+        out.dedup();
+        out
+    }
+
     /// 匹配 URI（返回第一个命中；保持既有 API）
     pub fn match_uri(&self, uri: &str) -> Option<(&Route, MatchResult)> {
         self.match_uri_candidates(uri).into_iter().next()
@@ -113,9 +473,30 @@ impl Router {
     /// 匹配 URI，返回全部候选（配置顺序）
     ///
     /// 供 mock 条件回退使用：第一个 mock 条件不命中时可尝试后续同路径 location。
+    /// 只看路径，不检查 hosts/methods 等条件。
     pub fn match_uri_candidates(&self, uri: &str) -> Vec<(&Route, MatchResult)> {
         let mut out = Vec::new();
-        for route in &self.routes {
+        for index in self.candidate_indices(uri) {
+            let route = &self.routes[index];
+            if let Some(result) = self.match_route(route, uri) {
+                out.push((route, result));
+            }
+        }
+        out
+    }
+
+    /// 按路径与请求信息匹配，返回全部候选（配置顺序）
+    pub fn match_request_candidates(
+        &self,
+        uri: &str,
+        info: &RequestInfo<'_>,
+    ) -> Vec<(&Route, MatchResult)> {
+        let mut out = Vec::new();
+        for index in self.candidate_indices(uri) {
+            let route = &self.routes[index];
+            if !route.matches_request(info) {
+                continue;
+            }
             if let Some(result) = self.match_route(route, uri) {
                 out.push((route, result));
             }
@@ -306,6 +687,17 @@ mod tests {
             request: None,
             index_files: None,
             enable_directory_listing: None,
+            grpc: None,
+            redirect: None,
+            retry: None,
+            rate_limit: None,
+            cache: None,
+            compression: None,
+            hosts: None,
+            methods: None,
+            match_headers: None,
+            match_query: None,
+            target: None,
         }
     }
 
@@ -478,6 +870,31 @@ mod tests {
         assert_eq!(match_result.params.get("post_id"), Some(&"456".to_string()));
     }
 
+    #[test]
+    fn test_captures_and_expand_params() {
+        let mut router = Router::new();
+        let route = Route::new(
+            "/users/{id}/".to_string(),
+            MatchMode::PrefixRegex,
+            create_test_location_config(),
+        )
+        .unwrap();
+        router.add_route(route);
+
+        let (_, match_result) = router.match_uri("/users/7/posts/1").unwrap();
+        let captures = match_result.captures();
+        assert_eq!(captures["id"], "7");
+        assert_eq!(captures["remaining"], "posts/1");
+
+        assert_eq!(
+            expand_params("/v2/users/{id}/{remaining}", &captures),
+            "/v2/users/7/posts/1"
+        );
+        // 未知名称与未闭合的花括号保留原文
+        assert_eq!(expand_params("{ghost}/{id", &captures), "{ghost}/{id");
+        assert_eq!(MatchResult::full().captures(), HashMap::new());
+    }
+
     #[test]
     fn test_router_priority() {
         let mut router = Router::new();
@@ -529,4 +946,211 @@ mod tests {
         let (_, match_result) = result.unwrap();
         assert_eq!(match_result.remaining, Some("".to_string()));
     }
+
+    #[test]
+    fn test_strip_port() {
+        assert_eq!(strip_port("example.com:8080"), "example.com");
+        assert_eq!(strip_port("example.com"), "example.com");
+        assert_eq!(strip_port("[::1]:8080"), "[::1]");
+        assert_eq!(strip_port("::1"), "::1");
+    }
+
+    #[test]
+    fn test_match_request_host_and_method() {
+        let mut router = Router::new();
+        let mut api = create_test_location_config();
+        api.hosts = Some(vec!["api.example.com".to_string()]);
+        api.methods = Some(vec!["post".to_string()]);
+        router.add_route(Route::new("/x".to_string(), MatchMode::Full, api).unwrap());
+        let mut tenants = create_test_location_config();
+        tenants.hosts = Some(vec!["*.example.com".to_string()]);
+        router.add_route(Route::new("/x".to_string(), MatchMode::Full, tenants).unwrap());
+        router.add_route(
+            Route::new(
+                "/x".to_string(),
+                MatchMode::Full,
+                create_test_location_config(),
+            )
+            .unwrap(),
+        );
+
+        // 返回命中的候选数：依次为 api 专用、通配子域名、无条件 location
+        let matched = |host: &str, method: &str| {
+            let req = Request::builder()
+                .method(method)
+                .uri("/x")
+                .header("host", host)
+                .body(())
+                .unwrap();
+            let info = RequestInfo::from_request(&req);
+            router.match_request_candidates("/x", &info).len()
+        };
+
+        assert_eq!(matched("API.example.com:8080", "POST"), 3);
+        assert_eq!(matched("api.example.com", "GET"), 2);
+        assert_eq!(matched("a.b.example.com", "GET"), 2);
+        // 通配符不匹配裸域名
+        assert_eq!(matched("example.com", "POST"), 1);
+    }
+
+    #[test]
+    fn test_match_request_headers_and_query() {
+        let mut router = Router::new();
+        let mut location = create_test_location_config();
+        location.match_headers = Some(HashMap::from([
+            ("X-Version".to_string(), "2".to_string()),
+            ("authorization".to_string(), "*".to_string()),
+        ]));
+        location.match_query = Some(HashMap::from([("beta".to_string(), "*".to_string())]));
+        router.add_route(Route::new("/x".to_string(), MatchMode::Full, location).unwrap());
+
+        let matches = |uri: &str, headers: &[(&str, &str)]| {
+            let mut builder = Request::builder().uri(uri);
+            for (name, value) in headers {
+                builder = builder.header(*name, *value);
+            }
+            let req = builder.body(()).unwrap();
+            !router
+                .match_request_candidates("/x", &RequestInfo::from_request(&req))
+                .is_empty()
+        };
+
+        let headers = [("x-version", "2"), ("authorization", "Bearer t")];
+        assert!(matches("/x?beta=1", &headers));
+        assert!(matches("/x?a=1&beta", &headers));
+        assert!(!matches("/x", &headers));
+        assert!(!matches("/x?beta=1", &[("x-version", "2")]));
+        assert!(!matches(
+            "/x?beta=1",
+            &[("x-version", "1"), ("authorization", "t")]
+        ));
+    }
+
+    #[test]
+    fn test_invalid_route_method_rejected() {
+        let mut location = create_test_location_config();
+        location.methods = Some(vec!["GET POST".to_string()]);
+        assert!(Route::new("/x".to_string(), MatchMode::Full, location).is_err());
+    }
+
+    #[test]
+    fn test_candidates_keep_configuration_order() {
+        let mut router = Router::new();
+        let routes = [
+            ("/api/{id}", MatchMode::Regex),
+            ("/api/", MatchMode::Prefix),
+            ("/api/users", MatchMode::Full),
+            ("/api/{id}/", MatchMode::PrefixRegex),
+            ("/", MatchMode::Prefix),
+            ("/api/users", MatchMode::Full),
+        ];
+        for (pattern, mode) in routes.iter().cloned() {
+            let mut location = create_test_location_config();
+            location.location = pattern.to_string();
+            router.add_route(Route::new(pattern.to_string(), mode, location).unwrap());
+        }
+
+        let modes = |uri: &str| {
+            router
+                .match_uri_candidates(uri)
+                .into_iter()
+                .map(|(route, _)| (route.pattern.as_str(), route.mode.clone()))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            modes("/api/users"),
+            vec![
+                ("/api/{id}", MatchMode::Regex),
+                ("/api/", MatchMode::Prefix),
+                ("/api/users", MatchMode::Full),
+                ("/", MatchMode::Prefix),
+                ("/api/users", MatchMode::Full),
+            ]
+        );
+        assert_eq!(
+            modes("/api/users/1"),
+            vec![
+                ("/api/", MatchMode::Prefix),
+                ("/api/{id}/", MatchMode::PrefixRegex),
+                ("/", MatchMode::Prefix),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_radix_tree_shared_prefixes() {
+        let mut router = Router::new();
+        for pattern in ["/abc", "/abd", "/ab", "/a", "/abcdef"] {
+            let mut location = create_test_location_config();
+            location.location = pattern.to_string();
+            router.add_route(Route::new(pattern.to_string(), MatchMode::Full, location).unwrap());
+        }
+
+        for pattern in ["/abc", "/abd", "/ab", "/a", "/abcdef"] {
+            let (route, _) = router.match_uri(pattern).unwrap();
+            assert_eq!(route.pattern, pattern);
+        }
+        assert!(router.match_uri("/abcd").is_none());
+        assert!(router.match_uri("/b").is_none());
+    }
+
+    #[test]
+    fn test_indexed_candidates_match_linear_scan() {
+        let patterns = [
+            ("/a", MatchMode::Prefix),
+            ("/a/", MatchMode::Prefix),
+            ("/a/b", MatchMode::Full),
+            ("/", MatchMode::Prefix),
+            ("/a/{id}", MatchMode::Regex),
+            ("/a/{id}/", MatchMode::Regex),
+            ("/a/{id}", MatchMode::PrefixRegex),
+            ("/a/b", MatchMode::PrefixRegex),
+            ("/a/{id}.json", MatchMode::Regex),
+            ("/x/{p}/y/{q}/", MatchMode::PrefixRegex),
+            ("/x/{p}/y/{q}", MatchMode::Regex),
+        ];
+        let mut router = Router::new();
+        for (pattern, mode) in patterns.iter().cloned() {
+            let mut location = create_test_location_config();
+            location.location = pattern.to_string();
+            router.add_route(Route::new(pattern.to_string(), mode, location).unwrap());
+        }
+
+        let uris = [
+            "/",
+            "/a",
+            "/a/",
+            "/ab",
+            "/a/b",
+            "/a/bc",
+            "/a/b/",
+            "/a/b/c",
+            "/a/5.json",
+            "/a/5/",
+            "/a//",
+            "/x/1/y/2",
+            "/x/1/y/2/",
+            "/x/1/y/2/z",
+            "/x/1/z/2",
+            "/other",
+        ];
+        for uri in uris {
+            let indexed: Vec<_> = router
+                .match_uri_candidates(uri)
+                .into_iter()
+                .map(|(route, result)| (route as *const Route, result.params, result.remaining))
+                .collect();
+            let linear: Vec<_> = router
+                .routes
+                .iter()
+                .filter_map(|route| {
+                    router
+                        .match_route(route, uri)
+                        .map(|result| (route as *const Route, result.params, result.remaining))
+                })
+                .collect();
+            assert_eq!(indexed, linear, "uri {uri}");
+        }
+    }
 }
@@ -132,8 +132,10 @@
 //! }
 //! ```
 
-use rustls::pki_types::{CertificateDer, PrivateKeyDer};
-use rustls::{ClientConfig, RootCertStore, ServerConfig};
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::crypto::CryptoProvider;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName, UnixTime};
+use rustls::{ClientConfig, DigitallySignedStruct, RootCertStore, ServerConfig, SignatureScheme};
 use rustls_pemfile::{certs, private_key};
 use std::path::Path;
 use std::sync::Arc;
@@ -565,17 +567,7 @@ impl TlsServer {
 pub fn create_tls_connector(ca_cert: Option<&Path>) -> crate::Result<TlsConnector> {
     let config = if let Some(ca_path) = ca_cert {
         // 使用自定义 CA 证书
-        let ca_content = std::fs::read(ca_path)?;
-        let ca_certs = certs(&mut ca_content.as_slice())
-            .collect::<std::result::Result<Vec<_>, _>>()
-            .map_err(|e| crate::MystiProxyError::Tls(format!("CA 证书解析失败: {e}")))?;
-
-        let mut root_cert_store = RootCertStore::empty();
-        for cert in ca_certs {
-            root_cert_store
-                .add(cert)
-                .map_err(|e| crate::MystiProxyError::Tls(format!("添加 CA 证书失败: {e}")))?;
-        }
+        let root_cert_store = load_root_cert_store(ca_path)?;
 
         ClientConfig::builder()
             .with_root_certificates(root_cert_store)
@@ -628,8 +620,54 @@ pub fn create_tls_connector_with_client_cert(
     client_cert: &Path,
     client_key: &Path,
 ) -> crate::Result<TlsConnector> {
-    // 加载 CA 证书
-    let ca_content = std::fs::read(ca_cert)?;
+    let config =
+        create_upstream_client_config(Some(ca_cert), Some((client_cert, client_key)), false)?;
+    Ok(TlsConnector::from(Arc::new(config)))
+}
+
+/// 创建连接上游使用的 TLS 客户端配置
+///
+/// # 参数
+/// - `ca_cert`: CA 证书路径，用于验证服务器证书；None 时使用内置根证书
+/// - `client_identity`: 客户端证书与私钥路径（用于双向认证）
+/// - `insecure_skip_verify`: 跳过服务器证书校验，仅用于测试环境
+///
+/// # 返回
+/// 成功返回 ClientConfig（未设置 ALPN），失败返回错误
+pub fn create_upstream_client_config(
+    ca_cert: Option<&Path>,
+    client_identity: Option<(&Path, &Path)>,
+    insecure_skip_verify: bool,
+) -> crate::Result<ClientConfig> {
+    let builder = ClientConfig::builder();
+    let builder = if insecure_skip_verify {
+        builder
+            .dangerous()
+            .with_custom_certificate_verifier(Arc::new(NoServerCertVerification::new()))
+    } else {
+        let root_cert_store = match ca_cert {
+            Some(ca_path) => load_root_cert_store(ca_path)?,
+            None => RootCertStore {
+                roots: webpki_roots::TLS_SERVER_ROOTS.to_vec(),
+            },
+        };
+        builder.with_root_certificates(root_cert_store)
+    };
+
+    match client_identity {
+        Some((client_cert, client_key)) => {
+            let (cert_chain, key) = load_client_identity(client_cert, client_key)?;
+            builder
+                .with_client_auth_cert(cert_chain, key)
+                .map_err(|e| crate::MystiProxyError::Tls(format!("客户端 TLS 配置创建失败: {e}")))
+        }
+        None => Ok(builder.with_no_client_auth()),
+    }
+}
+
+/// 从 PEM 文件加载 CA 证书
+fn load_root_cert_store(ca_path: &Path) -> crate::Result<RootCertStore> {
+    let ca_content = std::fs::read(ca_path)?;
     let ca_certs = certs(&mut ca_content.as_slice())
         .collect::<std::result::Result<Vec<_>, _>>()
         .map_err(|e| crate::MystiProxyError::Tls(format!("CA 证书解析失败: {e}")))?;
@@ -640,8 +678,14 @@ pub fn create_tls_connector_with_client_cert(
             .add(cert)
             .map_err(|e| crate::MystiProxyError::Tls(format!("添加 CA 证书失败: {e}")))?;
     }
+    Ok(root_cert_store)
+}
 
-    // 加载客户端证书和私钥
+/// 从 PEM 文件加载客户端证书链和私钥
+fn load_client_identity(
+    client_cert: &Path,
+    client_key: &Path,
+) -> crate::Result<(Vec<CertificateDer<'static>>, PrivateKeyDer<'static>)> {
     let cert_content = std::fs::read(client_cert)?;
     let cert_chain = certs(&mut cert_content.as_slice())
         .collect::<std::result::Result<Vec<_>, _>>()
@@ -650,13 +694,64 @@ pub fn create_tls_connector_with_client_cert(
     let key_content = std::fs::read(client_key)?;
     let key = private_key(&mut key_content.as_slice())?
         .ok_or_else(|| crate::MystiProxyError::Tls("未找到客户端私钥".to_string()))?;
+    Ok((cert_chain, key))
+}
 
-    let config = ClientConfig::builder()
-        .with_root_certificates(root_cert_store)
-        .with_client_auth_cert(cert_chain, key)
-        .map_err(|e| crate::MystiProxyError::Tls(format!("客户端 TLS 配置创建失败: {e}")))?;
+/// 不校验服务器证书链与域名的验证器（仍校验握手签名）
+///
+/// 仅用于 `insecure_skip_verify`，生产环境不应使用。
+#[derive(Debug)]
+struct NoServerCertVerification(Arc<CryptoProvider>);
 
-    Ok(TlsConnector::from(Arc::new(config)))
+impl NoServerCertVerification {
+    fn new() -> Self {
+        Self(Arc::new(rustls::crypto::ring::default_provider()))
+    }
+}
+
+impl ServerCertVerifier for NoServerCertVerification {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> std::result::Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> std::result::Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls12_signature(
+            message,
+            cert,
+            dss,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> std::result::Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls13_signature(
+            message,
+            cert,
+            dss,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.0.signature_verification_algorithms.supported_schemes()
+    }
 }
 
 #[cfg(test)]
@@ -735,6 +830,21 @@ MIIBkTCB+wIJAKHBfpLxAAAAADANBgkqhkiG9w0BAQsFADANMQswCQYDVQQDDAJj
         }
     }
 
+    #[test]
+    fn test_upstream_client_config_missing_files() {
+        let missing = Path::new("/nonexistent/ca.pem");
+        assert!(create_upstream_client_config(Some(missing), None, false).is_err());
+        assert!(create_upstream_client_config(None, Some((missing, missing)), false).is_err());
+    }
+
+    #[test]
+    fn test_upstream_client_config_defaults_and_insecure() {
+        assert!(create_upstream_client_config(None, None, false).is_ok());
+        // 跳过校验时忽略 CA 配置
+        let missing = Path::new("/nonexistent/ca.pem");
+        assert!(create_upstream_client_config(Some(missing), None, true).is_ok());
+    }
+
     #[test]
     fn test_tls_version_default() {
         let version = TlsVersion::default();
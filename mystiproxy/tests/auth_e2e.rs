@@ -57,7 +57,7 @@ async fn start_proxy_with_auth(auth_config: AuthConfig) -> (String, u16) {
 
     let config = EngineConfig {
         listen: listen.clone(),
-        target: format!("tcp://127.0.0.1:{upstream}"),
+        target: format!("tcp://127.0.0.1:{upstream}").into(),
         proxy_type: ProxyType::Http,
         request_timeout: Some(Duration::from_secs(5)),
         connection_timeout: None,
@@ -69,6 +69,20 @@ async fn start_proxy_with_auth(auth_config: AuthConfig) -> (String, u16) {
         deny: None,
         management: None,
         tls: None,
+        max_buffered_body_size: None,
+        load_balance: None,
+        health_check: None,
+        http2: None,
+        connection_pool: None,
+        upstream_tls: None,
+        retry: None,
+        circuit_breaker: None,
+        rate_limit: None,
+        connection_limit: None,
+        cache: None,
+        compression: None,
+        gateway: None,
+        mock_root: None,
     };
 
     let handler = create_handler(Arc::new(config)).expect("failed to create handler");
@@ -253,7 +267,7 @@ async fn test_e2e_auth_no_config_allows_all() {
 
     let config = EngineConfig {
         listen: listen.clone(),
-        target: format!("tcp://127.0.0.1:{upstream}"),
+        target: format!("tcp://127.0.0.1:{upstream}").into(),
         proxy_type: ProxyType::Http,
         request_timeout: Some(Duration::from_secs(5)),
         connection_timeout: None,
@@ -265,6 +279,20 @@ async fn test_e2e_auth_no_config_allows_all() {
         deny: None,
         management: None,
         tls: None,
+        max_buffered_body_size: None,
+        load_balance: None,
+        health_check: None,
+        http2: None,
+        connection_pool: None,
+        upstream_tls: None,
+        retry: None,
+        circuit_breaker: None,
+        rate_limit: None,
+        connection_limit: None,
+        cache: None,
+        compression: None,
+        gateway: None,
+        mock_root: None,
     };
 
     let handler = create_handler(Arc::new(config)).expect("handler failed");
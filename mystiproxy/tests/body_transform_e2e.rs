@@ -65,7 +65,7 @@ async fn start_proxy(upstream_port: u16, locations: Vec<LocationConfig>) -> Stri
 
     let config = EngineConfig {
         listen: listen.clone(),
-        target: format!("tcp://127.0.0.1:{upstream_port}"),
+        target: format!("tcp://127.0.0.1:{upstream_port}").into(),
         proxy_type: ProxyType::Http,
         request_timeout: Some(Duration::from_secs(5)),
         connection_timeout: None,
@@ -77,6 +77,20 @@ async fn start_proxy(upstream_port: u16, locations: Vec<LocationConfig>) -> Stri
         deny: None,
         management: None,
         tls: None,
+        max_buffered_body_size: None,
+        load_balance: None,
+        health_check: None,
+        http2: None,
+        connection_pool: None,
+        upstream_tls: None,
+        retry: None,
+        circuit_breaker: None,
+        rate_limit: None,
+        connection_limit: None,
+        cache: None,
+        compression: None,
+        gateway: None,
+        mock_root: None,
     };
 
     let handler = create_handler(Arc::new(config)).expect("handler failed");
@@ -155,10 +169,23 @@ async fn test_e2e_body_transform_overwrite_field() {
                 body_type: None,
                 template: None,
                 content: None,
+                file: None,
+                templated: None,
             }),
         }),
         index_files: None,
         enable_directory_listing: None,
+        grpc: None,
+        redirect: None,
+        retry: None,
+        rate_limit: None,
+        cache: None,
+        compression: None,
+        hosts: None,
+        methods: None,
+        match_headers: None,
+        match_query: None,
+        target: None,
     };
 
     let proxy = start_proxy(upstream, vec![loc]).await;
@@ -206,10 +233,23 @@ async fn test_e2e_body_transform_nested_field() {
                 body_type: None,
                 template: None,
                 content: None,
+                file: None,
+                templated: None,
             }),
         }),
         index_files: None,
         enable_directory_listing: None,
+        grpc: None,
+        redirect: None,
+        retry: None,
+        rate_limit: None,
+        cache: None,
+        compression: None,
+        hosts: None,
+        methods: None,
+        match_headers: None,
+        match_query: None,
+        target: None,
     };
 
     let proxy = start_proxy(upstream, vec![loc]).await;
@@ -256,10 +296,23 @@ async fn test_e2e_body_transform_delete_field() {
                 body_type: None,
                 template: None,
                 content: None,
+                file: None,
+                templated: None,
             }),
         }),
         index_files: None,
         enable_directory_listing: None,
+        grpc: None,
+        redirect: None,
+        retry: None,
+        rate_limit: None,
+        cache: None,
+        compression: None,
+        hosts: None,
+        methods: None,
+        match_headers: None,
+        match_query: None,
+        target: None,
     };
 
     let proxy = start_proxy(upstream, vec![loc]).await;
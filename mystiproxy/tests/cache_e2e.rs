@@ -0,0 +1,319 @@
+//! E2E tests for the proxy response cache.
+//!
+//! These tests verify that proxied GET responses are served from the cache
+//! while fresh, revalidated with `If-None-Match` once stale, split by `Vary`,
+//! controlled by per-location rules and purged through the admin endpoint.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use bytes::Bytes;
+use http_body_util::Full;
+use hyper::{Request, Response, StatusCode};
+use hyper_util::rt::TokioIo;
+use mystiproxy::config::{
+    CacheConfig, EngineConfig, LocationCacheConfig, LocationConfig, MatchMode, ProviderType,
+    ProxyType,
+};
+use mystiproxy::http::{create_handler, HttpServer, HttpServerConfig};
+use mystiproxy::metrics::MetricsManager;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+async fn get_available_port() -> u16 {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("bind failed");
+    listener.local_addr().expect("no addr").port()
+}
+
+/// Requests seen by the upstream: (path, if-none-match).
+type Seen = Arc<Mutex<Vec<(String, Option<String>)>>>;
+
+/// Start an upstream whose responses depend on the request path:
+///
+/// - `/fresh`: `Cache-Control: max-age=60`
+/// - `/etag`: `Cache-Control: no-cache` with `ETag: "v1"`, 304 on a match
+/// - `/vary`: `max-age=60`, `Vary: Accept-Language`, echoes the language
+/// - anything else: no caching headers
+///
+/// Every body carries the request number so cached answers are recognizable.
+async fn start_upstream() -> (u16, Seen) {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("upstream bind failed");
+    let port = listener.local_addr().expect("no addr").port();
+    let seen: Seen = Arc::new(Mutex::new(Vec::new()));
+    let counter = Arc::new(AtomicUsize::new(0));
+    let recorder = seen.clone();
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            let recorder = recorder.clone();
+            let counter = counter.clone();
+            tokio::spawn(async move {
+                let service = hyper::service::service_fn(move |req: Request<_>| {
+                    let n = counter.fetch_add(1, Ordering::SeqCst);
+                    let path = req.uri().path().to_string();
+                    let if_none_match = req
+                        .headers()
+                        .get("if-none-match")
+                        .map(|v| v.to_str().unwrap().to_string());
+                    recorder
+                        .lock()
+                        .unwrap()
+                        .push((path.clone(), if_none_match.clone()));
+                    let language = req
+                        .headers()
+                        .get("accept-language")
+                        .map(|v| v.to_str().unwrap().to_string())
+                        .unwrap_or_default();
+                    async move {
+                        let builder = Response::builder();
+                        let response = match path.as_str() {
+                            "/fresh" | "/docs/a" | "/docs/b" => builder
+                                .header("cache-control", "max-age=60")
+                                .body(Full::new(Bytes::from(format!("response-{n}")))),
+                            "/etag" if if_none_match.as_deref() == Some("\"v1\"") => builder
+                                .status(StatusCode::NOT_MODIFIED)
+                                .header("etag", "\"v1\"")
+                                .body(Full::new(Bytes::new())),
+                            "/etag" => builder
+                                .header("cache-control", "no-cache")
+                                .header("etag", "\"v1\"")
+                                .body(Full::new(Bytes::from(format!("response-{n}")))),
+                            "/vary" => builder
+                                .header("cache-control", "max-age=60")
+                                .header("vary", "Accept-Language")
+                                .body(Full::new(Bytes::from(format!("{language}-{n}")))),
+                            "/bypass/fresh" => builder
+                                .header("cache-control", "max-age=60")
+                                .body(Full::new(Bytes::from(format!("response-{n}")))),
+                            _ => builder.body(Full::new(Bytes::from(format!("response-{n}")))),
+                        };
+                        Ok::<_, std::convert::Infallible>(response.unwrap())
+                    }
+                });
+                let _ = hyper::server::conn::http1::Builder::new()
+                    .serve_connection(TokioIo::new(stream), service)
+                    .await;
+            });
+        }
+    });
+    (port, seen)
+}
+
+fn proxy_location(path: &str, cache: LocationCacheConfig) -> LocationConfig {
+    LocationConfig {
+        location: path.to_string(),
+        mode: MatchMode::Prefix,
+        provider: Some(ProviderType::Proxy),
+        root: None,
+        response: None,
+        request: None,
+        index_files: None,
+        enable_directory_listing: None,
+        grpc: None,
+        redirect: None,
+        retry: None,
+        rate_limit: None,
+        cache: Some(cache),
+        compression: None,
+        hosts: None,
+        methods: None,
+        match_headers: None,
+        match_query: None,
+        target: None,
+    }
+}
+
+fn engine_config(listen: String, upstream: u16) -> EngineConfig {
+    EngineConfig {
+        listen,
+        target: format!("tcp://127.0.0.1:{upstream}").into(),
+        proxy_type: ProxyType::Http,
+        request_timeout: Some(Duration::from_secs(5)),
+        connection_timeout: None,
+        header: None,
+        locations: Some(vec![
+            proxy_location(
+                "/forced",
+                LocationCacheConfig {
+                    ttl: Some(Duration::from_secs(60)),
+                    bypass: false,
+                },
+            ),
+            proxy_location(
+                "/bypass",
+                LocationCacheConfig {
+                    ttl: None,
+                    bypass: true,
+                },
+            ),
+        ]),
+        tls: None,
+        auth: None,
+        upstream: None,
+        allow: None,
+        deny: None,
+        management: None,
+        max_buffered_body_size: None,
+        load_balance: None,
+        health_check: None,
+        http2: None,
+        connection_pool: None,
+        upstream_tls: None,
+        retry: None,
+        circuit_breaker: None,
+        rate_limit: None,
+        connection_limit: None,
+        cache: Some(CacheConfig::default()),
+        compression: None,
+        gateway: None,
+        mock_root: None,
+    }
+}
+
+/// Start a caching engine in front of a fresh upstream. The cache is also
+/// registered with a private metrics server so purge requests can reach it.
+async fn start_engine() -> (u16, u16, Seen) {
+    let (upstream, seen) = start_upstream().await;
+    let port = get_available_port().await;
+    let listen = format!("tcp://127.0.0.1:{port}");
+    let handler =
+        create_handler(Arc::new(engine_config(listen.clone(), upstream))).expect("handler failed");
+
+    let metrics = Arc::new(MetricsManager::new());
+    metrics.register_cache("cache-e2e", handler.response_cache().expect("cache"));
+    let admin_port = get_available_port().await;
+    tokio::spawn(
+        metrics
+            .clone()
+            .start_server(format!("127.0.0.1:{admin_port}").parse().unwrap()),
+    );
+
+    let mut server = HttpServer::new(HttpServerConfig::new(listen, None), handler, None);
+    server.start().await.expect("start failed");
+    tokio::spawn(async move {
+        let _ = server.run().await;
+    });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    (port, admin_port, seen)
+}
+
+async fn send(port: u16, method: &str, path: &str, extra_headers: &str) -> String {
+    let mut stream = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+    let request = format!(
+        "{method} {path} HTTP/1.1\r\nHost: localhost\r\n{extra_headers}Content-Length: 0\r\nConnection: close\r\n\r\n"
+    );
+    stream.write_all(request.as_bytes()).await.unwrap();
+    let mut response = Vec::new();
+    tokio::time::timeout(Duration::from_secs(5), stream.read_to_end(&mut response))
+        .await
+        .expect("timeout")
+        .unwrap();
+    String::from_utf8_lossy(&response).to_lowercase()
+}
+
+async fn get(port: u16, path: &str) -> String {
+    send(port, "GET", path, "").await
+}
+
+/// Responses are written to the cache once their body has been forwarded.
+async fn settle() {
+    tokio::time::sleep(Duration::from_millis(50)).await;
+}
+
+#[tokio::test]
+async fn test_e2e_fresh_response_served_from_cache() {
+    let (port, _, seen) = start_engine().await;
+
+    let first = get(port, "/fresh").await;
+    assert!(first.contains("x-cache: miss"), "got: {first}");
+    assert!(first.ends_with("response-0"), "got: {first}");
+    settle().await;
+
+    let second = get(port, "/fresh").await;
+    assert!(second.starts_with("http/1.1 200"), "got: {second}");
+    assert!(second.contains("x-cache: hit"), "got: {second}");
+    assert!(second.contains("age: "), "got: {second}");
+    assert!(second.ends_with("response-0"), "got: {second}");
+    assert_eq!(seen.lock().unwrap().len(), 1);
+
+    // POST is never cached.
+    let post = send(port, "POST", "/fresh", "").await;
+    assert!(post.ends_with("response-1"), "got: {post}");
+}
+
+#[tokio::test]
+async fn test_e2e_stale_response_revalidated_with_etag() {
+    let (port, _, seen) = start_engine().await;
+
+    assert!(get(port, "/etag").await.ends_with("response-0"));
+    settle().await;
+
+    let second = get(port, "/etag").await;
+    assert!(second.starts_with("http/1.1 200"), "got: {second}");
+    assert!(second.contains("x-cache: revalidated"), "got: {second}");
+    assert!(second.ends_with("response-0"), "got: {second}");
+
+    let seen = seen.lock().unwrap();
+    assert_eq!(seen.len(), 2);
+    assert_eq!(seen[0].1, None);
+    assert_eq!(seen[1].1.as_deref(), Some("\"v1\""));
+}
+
+#[tokio::test]
+async fn test_e2e_vary_keeps_separate_variants() {
+    let (port, _, seen) = start_engine().await;
+
+    let english = "Accept-Language: en\r\n";
+    let german = "Accept-Language: de\r\n";
+    assert!(send(port, "GET", "/vary", english).await.ends_with("en-0"));
+    settle().await;
+    assert!(send(port, "GET", "/vary", german).await.ends_with("de-1"));
+    settle().await;
+    assert!(send(port, "GET", "/vary", english).await.ends_with("en-0"));
+    assert!(send(port, "GET", "/vary", german).await.ends_with("de-1"));
+    assert_eq!(seen.lock().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn test_e2e_location_rules_force_ttl_and_bypass() {
+    let (port, _, seen) = start_engine().await;
+
+    // The upstream sends no caching headers, but the location forces a TTL.
+    assert!(get(port, "/forced/a").await.ends_with("response-0"));
+    settle().await;
+    assert!(get(port, "/forced/a").await.ends_with("response-0"));
+
+    // Bypassed locations never touch the cache, even for cacheable responses.
+    assert!(get(port, "/bypass/fresh").await.ends_with("response-1"));
+    settle().await;
+    let bypassed = get(port, "/bypass/fresh").await;
+    assert!(bypassed.ends_with("response-2"), "got: {bypassed}");
+    assert!(!bypassed.contains("x-cache"), "got: {bypassed}");
+
+    // Responses without freshness information are not cached elsewhere.
+    assert!(get(port, "/plain").await.ends_with("response-3"));
+    settle().await;
+    assert!(get(port, "/plain").await.ends_with("response-4"));
+    assert_eq!(seen.lock().unwrap().len(), 5);
+}
+
+#[tokio::test]
+async fn test_e2e_admin_endpoint_purges_by_prefix() {
+    let (port, admin_port, _) = start_engine().await;
+
+    for path in ["/docs/a", "/docs/b", "/fresh"] {
+        get(port, path).await;
+    }
+    settle().await;
+
+    let purge = send(admin_port, "POST", "/cache/purge?prefix=/docs", "").await;
+    assert!(purge.starts_with("http/1.1 200"), "got: {purge}");
+    assert!(purge.ends_with(r#"{"purged":2}"#), "got: {purge}");
+
+    assert!(get(port, "/docs/a").await.contains("x-cache: miss"));
+    assert!(get(port, "/fresh").await.contains("x-cache: hit"));
+}
@@ -0,0 +1,260 @@
+//! E2E tests for upstream circuit breakers.
+//!
+//! These tests verify that HTTP and TCP engines stop contacting a failing
+//! target once its breaker opens, answer with 503 or the configured fallback
+//! while open, and recover through a half-open probe.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use bytes::Bytes;
+use http_body_util::Full;
+use hyper::{Response, StatusCode};
+use hyper_util::rt::TokioIo;
+use mystiproxy::config::{
+    BodyConfig, BodyType, CircuitBreakerConfig, EngineConfig, ProxyType, ResponseConfig,
+};
+use mystiproxy::http::{create_handler, HttpServer, HttpServerConfig};
+use mystiproxy::proxy::ProxyServer;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+async fn get_available_port() -> u16 {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("bind failed");
+    listener.local_addr().expect("no addr").port()
+}
+
+/// Start an upstream that answers 500 to its first `failures` requests and 200
+/// afterwards, counting the requests it receives.
+async fn start_flaky_upstream(failures: usize) -> (u16, Arc<AtomicUsize>) {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("upstream bind failed");
+    let port = listener.local_addr().expect("no addr").port();
+    let requests = Arc::new(AtomicUsize::new(0));
+    let counter = requests.clone();
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            let counter = counter.clone();
+            tokio::spawn(async move {
+                let service = hyper::service::service_fn(move |_req| {
+                    let n = counter.fetch_add(1, Ordering::SeqCst);
+                    async move {
+                        let mut response = Response::new(Full::new(Bytes::from("upstream")));
+                        if n < failures {
+                            *response.status_mut() = StatusCode::INTERNAL_SERVER_ERROR;
+                        }
+                        Ok::<_, std::convert::Infallible>(response)
+                    }
+                });
+                let _ = hyper::server::conn::http1::Builder::new()
+                    .serve_connection(TokioIo::new(stream), service)
+                    .await;
+            });
+        }
+    });
+    (port, requests)
+}
+
+fn engine_config(
+    listen: String,
+    proxy_type: ProxyType,
+    upstream: u16,
+    breaker: CircuitBreakerConfig,
+) -> EngineConfig {
+    EngineConfig {
+        listen,
+        target: format!("tcp://127.0.0.1:{upstream}").into(),
+        proxy_type,
+        request_timeout: Some(Duration::from_secs(5)),
+        connection_timeout: None,
+        header: None,
+        locations: None,
+        tls: None,
+        auth: None,
+        upstream: None,
+        allow: None,
+        deny: None,
+        management: None,
+        max_buffered_body_size: None,
+        load_balance: None,
+        health_check: None,
+        http2: None,
+        connection_pool: None,
+        upstream_tls: None,
+        retry: None,
+        circuit_breaker: Some(breaker),
+        rate_limit: None,
+        connection_limit: None,
+        cache: None,
+        compression: None,
+        gateway: None,
+        mock_root: None,
+    }
+}
+
+async fn start_http_engine(upstream: u16, breaker: CircuitBreakerConfig) -> u16 {
+    let port = get_available_port().await;
+    let listen = format!("tcp://127.0.0.1:{port}");
+    let config = engine_config(listen.clone(), ProxyType::Http, upstream, breaker);
+    let handler = create_handler(Arc::new(config)).expect("handler failed");
+    let mut server = HttpServer::new(HttpServerConfig::new(listen, None), handler, None);
+    server.start().await.expect("start failed");
+    tokio::spawn(async move {
+        let _ = server.run().await;
+    });
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    port
+}
+
+async fn http_get(port: u16) -> String {
+    let mut stream = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+    stream
+        .write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+        .await
+        .unwrap();
+    let mut response = Vec::new();
+    tokio::time::timeout(Duration::from_secs(5), stream.read_to_end(&mut response))
+        .await
+        .expect("timeout")
+        .unwrap();
+    String::from_utf8_lossy(&response).to_string()
+}
+
+#[tokio::test]
+async fn test_e2e_http_breaker_opens_and_fails_fast() {
+    let (upstream, requests) = start_flaky_upstream(usize::MAX).await;
+    let breaker = CircuitBreakerConfig {
+        consecutive_failures: Some(2),
+        open_duration: Some(Duration::from_secs(60)),
+        ..Default::default()
+    };
+    let port = start_http_engine(upstream, breaker).await;
+
+    for _ in 0..2 {
+        let response = http_get(port).await;
+        assert!(response.starts_with("HTTP/1.1 500"), "got: {response}");
+    }
+    for _ in 0..3 {
+        let response = http_get(port).await;
+        assert!(response.starts_with("HTTP/1.1 503"), "got: {response}");
+    }
+    assert_eq!(requests.load(Ordering::SeqCst), 2);
+
+    let metrics = mystiproxy::metrics::global_metrics().gather();
+    let gauge = format!(
+        r#"circuit_breaker_state{{engine="default",target="tcp://127.0.0.1:{upstream}"}} 1"#
+    );
+    assert!(metrics.contains(&gauge), "{metrics}");
+}
+
+#[tokio::test]
+async fn test_e2e_http_breaker_serves_fallback_while_open() {
+    let (upstream, _requests) = start_flaky_upstream(usize::MAX).await;
+    let breaker = CircuitBreakerConfig {
+        consecutive_failures: Some(1),
+        open_duration: Some(Duration::from_secs(60)),
+        fallback: Some(ResponseConfig {
+            status: Some(200),
+            headers: None,
+            body: Some(BodyConfig {
+                json: None,
+                body_type: Some(BodyType::Static),
+                content: Some("cached fallback".to_string()),
+                template: None,
+                file: None,
+                templated: None,
+            }),
+            status_map: None,
+            conditions: None,
+            sequence: None,
+        }),
+        ..Default::default()
+    };
+    let port = start_http_engine(upstream, breaker).await;
+
+    let response = http_get(port).await;
+    assert!(response.starts_with("HTTP/1.1 500"), "got: {response}");
+    let response = http_get(port).await;
+    assert!(response.starts_with("HTTP/1.1 200"), "got: {response}");
+    assert!(response.ends_with("cached fallback"), "got: {response}");
+}
+
+#[tokio::test]
+async fn test_e2e_http_breaker_recovers_after_half_open_probe() {
+    let (upstream, requests) = start_flaky_upstream(1).await;
+    let breaker = CircuitBreakerConfig {
+        consecutive_failures: Some(1),
+        open_duration: Some(Duration::from_millis(200)),
+        ..Default::default()
+    };
+    let port = start_http_engine(upstream, breaker).await;
+
+    assert!(http_get(port).await.starts_with("HTTP/1.1 500"));
+    assert!(http_get(port).await.starts_with("HTTP/1.1 503"));
+
+    tokio::time::sleep(Duration::from_millis(250)).await;
+    for _ in 0..2 {
+        let response = http_get(port).await;
+        assert!(response.starts_with("HTTP/1.1 200"), "got: {response}");
+    }
+    assert_eq!(requests.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn test_e2e_tcp_breaker_skips_unreachable_target() {
+    // Nothing listens on the target until the breaker has opened.
+    let upstream = get_available_port().await;
+    let listen = format!("tcp://127.0.0.1:{}", get_available_port().await);
+    let breaker = CircuitBreakerConfig {
+        consecutive_failures: Some(2),
+        open_duration: Some(Duration::from_millis(300)),
+        ..Default::default()
+    };
+    let config = engine_config(listen.clone(), ProxyType::Tcp, upstream, breaker);
+    let mut server = ProxyServer::from_engine_config(&config).expect("create failed");
+    server.start().await.expect("start failed");
+    tokio::spawn(async move {
+        let _ = server.run().await;
+    });
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    let proxy_port: u16 = listen.rsplit(':').next().unwrap().parse().unwrap();
+
+    let connect_and_read = || async move {
+        let mut stream = TcpStream::connect(("127.0.0.1", proxy_port)).await.unwrap();
+        // Half-close so the forwarder finishes once the target is done.
+        let _ = stream.shutdown().await;
+        let mut buf = Vec::new();
+        tokio::time::timeout(Duration::from_secs(2), stream.read_to_end(&mut buf))
+            .await
+            .expect("timeout")
+            .ok();
+        String::from_utf8_lossy(&buf).to_string()
+    };
+    for _ in 0..2 {
+        assert_eq!(connect_and_read().await, "");
+    }
+
+    let target = tokio::net::TcpListener::bind(("127.0.0.1", upstream))
+        .await
+        .expect("target bind failed");
+    let accepted = Arc::new(AtomicUsize::new(0));
+    let counter = accepted.clone();
+    tokio::spawn(async move {
+        while let Ok((mut stream, _)) = target.accept().await {
+            counter.fetch_add(1, Ordering::SeqCst);
+            let _ = stream.write_all(b"hello").await;
+        }
+    });
+
+    // Still open: the proxy drops the connection without dialing the target.
+    assert_eq!(connect_and_read().await, "");
+    assert_eq!(accepted.load(Ordering::SeqCst), 0);
+
+    tokio::time::sleep(Duration::from_millis(350)).await;
+    assert_eq!(connect_and_read().await, "hello");
+    assert_eq!(accepted.load(Ordering::SeqCst), 1);
+}
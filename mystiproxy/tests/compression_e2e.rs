@@ -0,0 +1,266 @@
+//! E2E tests for response compression.
+//!
+//! These tests verify that proxied and static responses are compressed
+//! according to `Accept-Encoding`, honour the size and content-type rules and
+//! per-location overrides, that precompressed `.br`/`.gz` siblings are served
+//! as-is, and that gzip request bodies are decoded for JSON body transforms.
+
+use std::io::Write;
+use std::sync::Arc;
+use std::time::Duration;
+
+use bytes::Bytes;
+use http_body_util::{BodyExt, Full};
+use hyper::body::Incoming;
+use hyper::header::HeaderMap;
+use hyper::{Request, Response};
+use hyper_util::client::legacy::Client;
+use hyper_util::rt::{TokioExecutor, TokioIo};
+use mystiproxy::config::EngineConfig;
+use mystiproxy::http::{create_handler, decode_body, HttpServer, HttpServerConfig};
+
+async fn get_available_port() -> u16 {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("bind failed");
+    listener.local_addr().expect("no addr").port()
+}
+
+/// A JSON document comfortably above the default 1 KiB minimum size.
+fn large_json() -> String {
+    let items: Vec<String> = (0..300).map(|i| format!("{{\"id\":{i}}}")).collect();
+    format!("[{}]", items.join(","))
+}
+
+/// Start an upstream whose responses depend on the request path:
+///
+/// - `/api/small`: a tiny JSON body
+/// - `/api/image`: a large `image/png` body
+/// - `/api/echo`: echoes the request body, reporting its `Content-Encoding`
+///   in `x-received-encoding`
+/// - anything else: [`large_json`]
+async fn start_upstream() -> u16 {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("upstream bind failed");
+    let port = listener.local_addr().expect("no addr").port();
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            tokio::spawn(async move {
+                let service = hyper::service::service_fn(|req: Request<Incoming>| async move {
+                    let path = req.uri().path().to_string();
+                    let encoding = req
+                        .headers()
+                        .get("content-encoding")
+                        .map(|v| v.to_str().unwrap().to_string())
+                        .unwrap_or_else(|| "none".to_string());
+                    let body = req.into_body().collect().await.unwrap().to_bytes();
+                    let builder = Response::builder();
+                    let response = match path.as_str() {
+                        "/api/small" => builder
+                            .header("content-type", "application/json")
+                            .body(Full::new(Bytes::from_static(b"{\"ok\":true}"))),
+                        "/api/image" => builder
+                            .header("content-type", "image/png")
+                            .body(Full::new(Bytes::from(vec![0u8; 4096]))),
+                        "/api/echo" => builder
+                            .header("content-type", "application/json")
+                            .header("x-received-encoding", encoding)
+                            .body(Full::new(body)),
+                        _ => builder
+                            .header("content-type", "application/json")
+                            .body(Full::new(Bytes::from(large_json()))),
+                    };
+                    Ok::<_, std::convert::Infallible>(response.unwrap())
+                });
+                let _ = hyper::server::conn::http1::Builder::new()
+                    .serve_connection(TokioIo::new(stream), service)
+                    .await;
+            });
+        }
+    });
+    port
+}
+
+/// Start a compressing engine with proxy locations and a static location
+/// rooted at `static_root`.
+async fn start_engine(static_root: &std::path::Path) -> u16 {
+    let upstream = start_upstream().await;
+    let port = get_available_port().await;
+    let yaml = format!(
+        r#"
+listen: tcp://127.0.0.1:{port}
+target: tcp://127.0.0.1:{upstream}
+proxy_type: http
+request_timeout: 5s
+compression:
+  algorithms: [br, gzip]
+locations:
+  - location: /download
+    mode: Prefix
+    compression:
+      enabled: false
+  - location: /api/echo
+    mode: Full
+    request:
+      body:
+        json:
+          path: $.count
+          value: "2"
+          action: overwrite
+    compression:
+      decompress_for_transform: true
+  - location: /static
+    mode: Prefix
+    provider: static
+    root: {root}
+"#,
+        root = static_root.display()
+    );
+    let config: EngineConfig = serde_yaml::from_str(&yaml).expect("valid yaml");
+    let handler = create_handler(Arc::new(config)).expect("handler failed");
+    let mut server = HttpServer::new(
+        HttpServerConfig::new(format!("tcp://127.0.0.1:{port}"), None),
+        handler,
+        None,
+    );
+    server.start().await.expect("start failed");
+    tokio::spawn(async move {
+        let _ = server.run().await;
+    });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    port
+}
+
+async fn send(
+    port: u16,
+    method: &str,
+    path: &str,
+    headers: &[(&str, &str)],
+    body: Bytes,
+) -> (HeaderMap, Bytes) {
+    let client: Client<_, Full<Bytes>> = Client::builder(TokioExecutor::new()).build_http();
+    let mut builder = Request::builder()
+        .method(method)
+        .uri(format!("http://127.0.0.1:{port}{path}"));
+    for (name, value) in headers {
+        builder = builder.header(*name, *value);
+    }
+    let response = client
+        .request(builder.body(Full::new(body)).unwrap())
+        .await
+        .expect("request failed");
+    let headers = response.headers().clone();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    (headers, body)
+}
+
+async fn get(port: u16, path: &str, accept_encoding: Option<&str>) -> (HeaderMap, Bytes) {
+    let headers: Vec<(&str, &str)> = accept_encoding
+        .map(|v| vec![("accept-encoding", v)])
+        .unwrap_or_default();
+    send(port, "GET", path, &headers, Bytes::new()).await
+}
+
+fn content_encoding(headers: &HeaderMap) -> Option<&str> {
+    headers.get("content-encoding").map(|v| v.to_str().unwrap())
+}
+
+#[tokio::test]
+async fn test_e2e_proxied_response_compressed_per_accept_encoding() {
+    let root = tempfile::tempdir().unwrap();
+    let port = start_engine(root.path()).await;
+
+    let (headers, body) = get(port, "/api/items", Some("gzip")).await;
+    assert_eq!(content_encoding(&headers), Some("gzip"));
+    assert_eq!(headers["vary"], "accept-encoding");
+    assert!(body.len() < large_json().len());
+    assert_eq!(
+        decode_body("gzip", &body, 1 << 20).unwrap(),
+        Bytes::from(large_json())
+    );
+
+    // Equal quality: the configured order (br first) wins.
+    let (headers, body) = get(port, "/api/items", Some("gzip, br, zstd")).await;
+    assert_eq!(content_encoding(&headers), Some("br"));
+    assert_eq!(
+        decode_body("br", &body, 1 << 20).unwrap(),
+        Bytes::from(large_json())
+    );
+
+    // zstd is not enabled on this engine.
+    let (headers, body) = get(port, "/api/items", Some("zstd")).await;
+    assert_eq!(content_encoding(&headers), None);
+    assert_eq!(headers["vary"], "accept-encoding");
+    assert_eq!(body, Bytes::from(large_json()));
+}
+
+#[tokio::test]
+async fn test_e2e_small_unlisted_and_disabled_responses_not_compressed() {
+    let root = tempfile::tempdir().unwrap();
+    let port = start_engine(root.path()).await;
+
+    let (headers, body) = get(port, "/api/small", Some("gzip")).await;
+    assert_eq!(content_encoding(&headers), None);
+    assert_eq!(&body[..], b"{\"ok\":true}");
+
+    let (headers, body) = get(port, "/api/image", Some("gzip")).await;
+    assert_eq!(content_encoding(&headers), None);
+    assert_eq!(body.len(), 4096);
+
+    let (headers, body) = get(port, "/download/file.json", Some("gzip")).await;
+    assert_eq!(content_encoding(&headers), None);
+    assert_eq!(body, Bytes::from(large_json()));
+}
+
+#[tokio::test]
+async fn test_e2e_static_precompressed_and_on_the_fly() {
+    let root = tempfile::tempdir().unwrap();
+    std::fs::write(root.path().join("app.js"), "console.log('original')").unwrap();
+    std::fs::write(root.path().join("app.js.br"), "precompressed-br").unwrap();
+    std::fs::write(root.path().join("data.json"), large_json()).unwrap();
+    let port = start_engine(root.path()).await;
+
+    let (headers, body) = get(port, "/static/app.js", Some("br, gzip")).await;
+    assert_eq!(content_encoding(&headers), Some("br"));
+    assert_eq!(headers["content-type"], "application/javascript");
+    assert_eq!(&body[..], b"precompressed-br");
+
+    // No gzip sibling and the original is below the minimum size.
+    let (headers, body) = get(port, "/static/app.js", Some("gzip")).await;
+    assert_eq!(content_encoding(&headers), None);
+    assert_eq!(&body[..], b"console.log('original')");
+
+    let (headers, body) = get(port, "/static/data.json", Some("gzip")).await;
+    assert_eq!(content_encoding(&headers), Some("gzip"));
+    assert_eq!(
+        decode_body("gzip", &body, 1 << 20).unwrap(),
+        Bytes::from(large_json())
+    );
+}
+
+#[tokio::test]
+async fn test_e2e_gzip_request_body_decoded_for_json_transform() {
+    let root = tempfile::tempdir().unwrap();
+    let port = start_engine(root.path()).await;
+
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(br#"{"count":1,"name":"x"}"#).unwrap();
+    let gzipped = Bytes::from(encoder.finish().unwrap());
+
+    let (headers, body) = send(
+        port,
+        "POST",
+        "/api/echo",
+        &[
+            ("content-type", "application/json"),
+            ("content-encoding", "gzip"),
+        ],
+        gzipped,
+    )
+    .await;
+    assert_eq!(headers["x-received-encoding"], "none");
+    let echoed: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(echoed["count"], 2);
+    assert_eq!(echoed["name"], "x");
+}
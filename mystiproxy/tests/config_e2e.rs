@@ -234,7 +234,7 @@ fn test_e2e_config_serialization_round_trip() {
 
     let original = EngineConfig {
         listen: "tcp://0.0.0.0:8080".to_string(),
-        target: "tcp://127.0.0.1:9000".to_string(),
+        target: "tcp://127.0.0.1:9000".to_string().into(),
         proxy_type: ProxyType::Http,
         request_timeout: Some(Duration::from_secs(10)),
         connection_timeout: Some(Duration::from_secs(3)),
@@ -248,11 +248,24 @@ fn test_e2e_config_serialization_round_trip() {
                 status: Some(204),
                 headers: None,
                 body: None,
+                status_map: None,
                 conditions: None,
+                sequence: None,
             }),
             request: None,
             index_files: None,
             enable_directory_listing: None,
+            grpc: None,
+            redirect: None,
+            retry: None,
+            rate_limit: None,
+            cache: None,
+            compression: None,
+            hosts: None,
+            methods: None,
+            match_headers: None,
+            match_query: None,
+            target: None,
         }]),
         auth: None,
         upstream: None,
@@ -260,6 +273,20 @@ fn test_e2e_config_serialization_round_trip() {
         deny: None,
         management: None,
         tls: None,
+        max_buffered_body_size: None,
+        load_balance: None,
+        health_check: None,
+        http2: None,
+        connection_pool: None,
+        upstream_tls: None,
+        retry: None,
+        circuit_breaker: None,
+        rate_limit: None,
+        connection_limit: None,
+        cache: None,
+        compression: None,
+        gateway: None,
+        mock_root: None,
     };
 
     // Duration is now serialized as a human-readable string, so full round-trip works.
@@ -362,7 +389,7 @@ fn test_e2e_build_config_from_structs() {
                     "default".to_string(),
                     EngineConfig {
                         listen: "tcp://0.0.0.0:8080".to_string(),
-                        target: "tcp://127.0.0.1:3000".to_string(),
+                        target: "tcp://127.0.0.1:3000".to_string().into(),
                         proxy_type: ProxyType::Http,
                         request_timeout: None,
                         connection_timeout: None,
@@ -380,20 +407,36 @@ fn test_e2e_build_config_from_structs() {
                                     content: None,
                                     template: None,
                                     body_type: Some(BodyType::Static),
+                                    file: None,
+                                    templated: None,
                                 }),
+                                status_map: None,
                                 conditions: None,
+                                sequence: None,
                             }),
                             request: Some(RequestConfig {
                                 method: None,
                                 uri: Some(UriConfig {
                                     path: Some("/internal/health".to_string()),
                                     query: None,
+                                    rewrite: None,
                                 }),
                                 headers: None,
                                 body: None,
                             }),
                             index_files: None,
                             enable_directory_listing: None,
+                            grpc: None,
+                            redirect: None,
+                            retry: None,
+                            rate_limit: None,
+                            cache: None,
+                            compression: None,
+                            hosts: None,
+                            methods: None,
+                            match_headers: None,
+                            match_query: None,
+                            target: None,
                         }]),
                         auth: Some(AuthConfig {
                             auth_type: "header".to_string(),
@@ -407,6 +450,20 @@ fn test_e2e_build_config_from_structs() {
                         allow: None,
                         deny: None,
                         management: None,
+                        max_buffered_body_size: None,
+                        load_balance: None,
+                        health_check: None,
+                        http2: None,
+                        connection_pool: None,
+                        upstream_tls: None,
+                        retry: None,
+                        circuit_breaker: None,
+                        rate_limit: None,
+                        connection_limit: None,
+                        cache: None,
+                        compression: None,
+                        gateway: None,
+                        mock_root: None,
                     },
                 );
                 m
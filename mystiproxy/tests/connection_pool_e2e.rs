@@ -0,0 +1,144 @@
+//! E2E tests for upstream connection pooling.
+//!
+//! These tests verify that the HTTP engine reuses keep-alive connections to
+//! its upstream across client requests, and that pool sizes are exported as
+//! metrics.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use bytes::Bytes;
+use http_body_util::Full;
+use hyper::Response;
+use hyper_util::rt::TokioIo;
+use mystiproxy::config::{ConnectionPoolConfig, EngineConfig, ProxyType};
+use mystiproxy::http::{create_handler, HttpServer, HttpServerConfig};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+async fn get_available_port() -> u16 {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("bind failed");
+    listener.local_addr().expect("no addr").port()
+}
+
+/// Start a keep-alive HTTP/1.1 upstream and count the connections it accepts.
+async fn start_counting_upstream() -> (u16, Arc<AtomicUsize>) {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("upstream bind failed");
+    let port = listener.local_addr().expect("no addr").port();
+    let accepted = Arc::new(AtomicUsize::new(0));
+    let counter = accepted.clone();
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            counter.fetch_add(1, Ordering::SeqCst);
+            tokio::spawn(async move {
+                let service = hyper::service::service_fn(|_req| async {
+                    Ok::<_, std::convert::Infallible>(Response::new(Full::new(Bytes::from(
+                        "pooled",
+                    ))))
+                });
+                let _ = hyper::server::conn::http1::Builder::new()
+                    .serve_connection(TokioIo::new(stream), service)
+                    .await;
+            });
+        }
+    });
+    (port, accepted)
+}
+
+async fn start_engine(upstream_port: u16, connection_pool: Option<ConnectionPoolConfig>) -> u16 {
+    let port = get_available_port().await;
+    let listen = format!("tcp://127.0.0.1:{port}");
+    let config = EngineConfig {
+        listen: listen.clone(),
+        target: format!("tcp://127.0.0.1:{upstream_port}").into(),
+        proxy_type: ProxyType::Http,
+        request_timeout: Some(Duration::from_secs(5)),
+        connection_timeout: None,
+        header: None,
+        locations: None,
+        tls: None,
+        auth: None,
+        upstream: None,
+        allow: None,
+        deny: None,
+        management: None,
+        max_buffered_body_size: None,
+        load_balance: None,
+        health_check: None,
+        http2: None,
+        connection_pool,
+        upstream_tls: None,
+        retry: None,
+        circuit_breaker: None,
+        rate_limit: None,
+        connection_limit: None,
+        cache: None,
+        compression: None,
+        gateway: None,
+        mock_root: None,
+    };
+    let handler = create_handler(Arc::new(config)).expect("handler failed");
+    let mut server = HttpServer::new(HttpServerConfig::new(listen, None), handler, None);
+    server.start().await.expect("start failed");
+    tokio::spawn(async move {
+        let _ = server.run().await;
+    });
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    port
+}
+
+/// Send one request on a fresh client connection and return the raw response.
+async fn http_get(port: u16) -> String {
+    let mut stream = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+    stream
+        .write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+        .await
+        .unwrap();
+    let mut response = Vec::new();
+    tokio::time::timeout(Duration::from_secs(5), stream.read_to_end(&mut response))
+        .await
+        .expect("timeout")
+        .unwrap();
+    // Give the proxy a moment to return the upstream connection to the pool.
+    tokio::time::sleep(Duration::from_millis(30)).await;
+    String::from_utf8_lossy(&response).to_string()
+}
+
+#[tokio::test]
+async fn test_e2e_upstream_connection_reused_across_requests() {
+    let (upstream, accepted) = start_counting_upstream().await;
+    let port = start_engine(upstream, None).await;
+
+    for _ in 0..3 {
+        let response = http_get(port).await;
+        assert!(response.starts_with("HTTP/1.1 200"), "got: {response}");
+        assert!(response.ends_with("pooled"), "got: {response}");
+    }
+    assert_eq!(accepted.load(Ordering::SeqCst), 1);
+
+    let metrics = mystiproxy::metrics::global_metrics().gather();
+    let gauge =
+        format!(r#"upstream_pool_idle_connections{{target="tcp://127.0.0.1:{upstream}"}} 1"#);
+    assert!(metrics.contains(&gauge), "{metrics}");
+}
+
+#[tokio::test]
+async fn test_e2e_pooling_disabled_opens_new_connections() {
+    let (upstream, accepted) = start_counting_upstream().await;
+    let pool = ConnectionPoolConfig {
+        max_idle: Some(0),
+        ..Default::default()
+    };
+    let port = start_engine(upstream, Some(pool)).await;
+
+    for _ in 0..3 {
+        let response = http_get(port).await;
+        assert!(response.ends_with("pooled"), "got: {response}");
+    }
+    assert_eq!(accepted.load(Ordering::SeqCst), 3);
+}
@@ -102,7 +102,7 @@ async fn start_test_server(locations: Vec<LocationConfig>) -> String {
 
     let config = EngineConfig {
         listen: listen.clone(),
-        target: "tcp://127.0.0.1:1".to_string(),
+        target: "tcp://127.0.0.1:1".to_string().into(),
         proxy_type: ProxyType::Http,
         request_timeout: Some(Duration::from_secs(5)),
         connection_timeout: None,
@@ -114,6 +114,20 @@ async fn start_test_server(locations: Vec<LocationConfig>) -> String {
         deny: None,
         management: None,
         tls: None,
+        max_buffered_body_size: None,
+        load_balance: None,
+        health_check: None,
+        http2: None,
+        connection_pool: None,
+        upstream_tls: None,
+        retry: None,
+        circuit_breaker: None,
+        rate_limit: None,
+        connection_limit: None,
+        cache: None,
+        compression: None,
+        gateway: None,
+        mock_root: None,
     };
 
     let handler = create_handler(Arc::new(config)).expect("handler failed");
@@ -275,6 +289,17 @@ async fn test_e2e_static_file_range_request() {
         request: None,
         index_files: None,
         enable_directory_listing: None,
+        grpc: None,
+        redirect: None,
+        retry: None,
+        rate_limit: None,
+        cache: None,
+        compression: None,
+        hosts: None,
+        methods: None,
+        match_headers: None,
+        match_query: None,
+        target: None,
     };
 
     let addr = start_test_server(vec![loc]).await;
@@ -321,6 +346,17 @@ async fn test_e2e_static_file_full_request_no_range() {
         request: None,
         index_files: None,
         enable_directory_listing: None,
+        grpc: None,
+        redirect: None,
+        retry: None,
+        rate_limit: None,
+        cache: None,
+        compression: None,
+        hosts: None,
+        methods: None,
+        match_headers: None,
+        match_query: None,
+        target: None,
     };
 
     let addr = start_test_server(vec![loc]).await;
@@ -372,12 +408,27 @@ async fn test_e2e_mock_with_custom_status_and_body() {
                 content: None,
                 template: None,
                 body_type: Some(BodyType::Static),
+                file: None,
+                templated: None,
             }),
+            status_map: None,
             conditions: None,
+            sequence: None,
         }),
         request: None,
         index_files: None,
         enable_directory_listing: None,
+        grpc: None,
+        redirect: None,
+        retry: None,
+        rate_limit: None,
+        cache: None,
+        compression: None,
+        hosts: None,
+        methods: None,
+        match_headers: None,
+        match_query: None,
+        target: None,
     };
 
     let addr = start_test_server(vec![loc]).await;
@@ -403,11 +454,24 @@ async fn test_e2e_mock_prefix_matches_subpaths() {
             status: Some(201),
             headers: None,
             body: None,
+            status_map: None,
             conditions: None,
+            sequence: None,
         }),
         request: None,
         index_files: None,
         enable_directory_listing: None,
+        grpc: None,
+        redirect: None,
+        retry: None,
+        rate_limit: None,
+        cache: None,
+        compression: None,
+        hosts: None,
+        methods: None,
+        match_headers: None,
+        match_query: None,
+        target: None,
     };
 
     let addr = start_test_server(vec![loc]).await;
@@ -446,11 +510,24 @@ async fn test_e2e_multiple_mock_locations_priority() {
                 status: Some(200),
                 headers: None,
                 body: None,
+                status_map: None,
                 conditions: None,
+                sequence: None,
             }),
             request: None,
             index_files: None,
             enable_directory_listing: None,
+            grpc: None,
+            redirect: None,
+            retry: None,
+            rate_limit: None,
+            cache: None,
+            compression: None,
+            hosts: None,
+            methods: None,
+            match_headers: None,
+            match_query: None,
+            target: None,
         },
         LocationConfig {
             location: "/api/special".to_string(),
@@ -461,11 +538,24 @@ async fn test_e2e_multiple_mock_locations_priority() {
                 status: Some(301),
                 headers: None,
                 body: None,
+                status_map: None,
                 conditions: None,
+                sequence: None,
             }),
             request: None,
             index_files: None,
             enable_directory_listing: None,
+            grpc: None,
+            redirect: None,
+            retry: None,
+            rate_limit: None,
+            cache: None,
+            compression: None,
+            hosts: None,
+            methods: None,
+            match_headers: None,
+            match_query: None,
+            target: None,
         },
     ];
 
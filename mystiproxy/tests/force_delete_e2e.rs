@@ -59,7 +59,7 @@ async fn start_proxy(upstream_port: u16, locations: Vec<LocationConfig>) -> Stri
 
     let config = EngineConfig {
         listen: listen.clone(),
-        target: format!("tcp://127.0.0.1:{upstream_port}"),
+        target: format!("tcp://127.0.0.1:{upstream_port}").into(),
         proxy_type: ProxyType::Http,
         request_timeout: Some(Duration::from_secs(5)),
         connection_timeout: None,
@@ -71,6 +71,20 @@ async fn start_proxy(upstream_port: u16, locations: Vec<LocationConfig>) -> Stri
         deny: None,
         management: None,
         tls: None,
+        max_buffered_body_size: None,
+        load_balance: None,
+        health_check: None,
+        http2: None,
+        connection_pool: None,
+        upstream_tls: None,
+        retry: None,
+        circuit_breaker: None,
+        rate_limit: None,
+        connection_limit: None,
+        cache: None,
+        compression: None,
+        gateway: None,
+        mock_root: None,
     };
 
     let handler = create_handler(Arc::new(config)).expect("handler failed");
@@ -164,6 +178,17 @@ async fn test_e2e_force_delete_removes_header() {
         }),
         index_files: None,
         enable_directory_listing: None,
+        grpc: None,
+        redirect: None,
+        retry: None,
+        rate_limit: None,
+        cache: None,
+        compression: None,
+        hosts: None,
+        methods: None,
+        match_headers: None,
+        match_query: None,
+        target: None,
     };
 
     let proxy = start_proxy(upstream, vec![loc]).await;
@@ -217,6 +242,17 @@ async fn test_e2e_force_delete_does_not_affect_other_headers() {
         }),
         index_files: None,
         enable_directory_listing: None,
+        grpc: None,
+        redirect: None,
+        retry: None,
+        rate_limit: None,
+        cache: None,
+        compression: None,
+        hosts: None,
+        methods: None,
+        match_headers: None,
+        match_query: None,
+        target: None,
     };
 
     let proxy = start_proxy(upstream, vec![loc]).await;
@@ -264,7 +300,7 @@ async fn test_e2e_engine_level_force_delete() {
 
     let config = EngineConfig {
         listen: listen.clone(),
-        target: format!("tcp://127.0.0.1:{upstream}"),
+        target: format!("tcp://127.0.0.1:{upstream}").into(),
         proxy_type: ProxyType::Http,
         request_timeout: Some(Duration::from_secs(5)),
         connection_timeout: None,
@@ -276,6 +312,20 @@ async fn test_e2e_engine_level_force_delete() {
         deny: None,
         management: None,
         tls: None,
+        max_buffered_body_size: None,
+        load_balance: None,
+        health_check: None,
+        http2: None,
+        connection_pool: None,
+        upstream_tls: None,
+        retry: None,
+        circuit_breaker: None,
+        rate_limit: None,
+        connection_limit: None,
+        cache: None,
+        compression: None,
+        gateway: None,
+        mock_root: None,
     };
 
     let handler = create_handler(Arc::new(config)).expect("handler failed");
@@ -1,9 +1,21 @@
 //! E2E tests for the Gateway URI mapping module.
 //!
 //! These tests exercise the URI matching and target URI building logic
-//! for all match types: exact, prefix, variable, and variable-prefix.
-
+//! for all match types: exact, prefix, variable, and variable-prefix, and
+//! an HTTP engine routing requests through its `gateway` configuration.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use bytes::Bytes;
+use http_body_util::{BodyExt, Full};
+use hyper::body::Incoming;
+use hyper::{Request, Response};
+use hyper_util::client::legacy::Client;
+use hyper_util::rt::{TokioExecutor, TokioIo};
+use mystiproxy::config::EngineConfig;
 use mystiproxy::gateway::UriMapping;
+use mystiproxy::http::{create_handler, HttpServer, HttpServerConfig};
 
 // ---------------------------------------------------------------------------
 // Tests: exact / prefix matching
@@ -206,3 +218,136 @@ fn test_e2e_gateway_supports_method_wildcard() {
     assert!(mapping.supports_method("PUT"));
     assert!(mapping.supports_method("DELETE"));
 }
+
+#[test]
+fn test_e2e_gateway_supports_any_method_when_unset() {
+    let mapping = UriMapping::default();
+    assert!(mapping.supports_method("PATCH"));
+}
+
+// ---------------------------------------------------------------------------
+// Tests: gateway mode on an HTTP engine
+// ---------------------------------------------------------------------------
+
+async fn get_available_port() -> u16 {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("bind failed");
+    listener.local_addr().expect("no addr").port()
+}
+
+/// Start an upstream that answers `<name> <path and query>`.
+async fn start_upstream(name: &'static str) -> u16 {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("upstream bind failed");
+    let port = listener.local_addr().expect("no addr").port();
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            tokio::spawn(async move {
+                let service =
+                    hyper::service::service_fn(move |req: Request<Incoming>| async move {
+                        let body = format!("{name} {}", req.uri());
+                        Ok::<_, std::convert::Infallible>(Response::new(Full::new(Bytes::from(
+                            body,
+                        ))))
+                    });
+                let _ = hyper::server::conn::http1::Builder::new()
+                    .serve_connection(TokioIo::new(stream), service)
+                    .await;
+            });
+        }
+    });
+    port
+}
+
+async fn start_gateway_engine() -> u16 {
+    let default = start_upstream("default").await;
+    let users = start_upstream("users").await;
+    let orders = start_upstream("orders").await;
+    let port = get_available_port().await;
+    let yaml = format!(
+        r#"
+listen: tcp://127.0.0.1:{port}
+target: tcp://127.0.0.1:{default}
+proxy_type: http
+request_timeout: 5s
+locations:
+  - location: /api/health
+    mode: Full
+    provider: mock
+    response:
+      body:
+        type: static
+        content: ok
+gateway:
+  services:
+    users: tcp://127.0.0.1:{users}
+  routes:
+    - uri: /api
+      target_uri: /legacy
+    - method: GET
+      uri: /api/users/{{id:[0-9]+}}
+      target_uri: /v1/users/{{id}}
+      target_service: users
+    - uri: /api/users/{{id:[0-9]+}}/orders/{{oid}}
+      target_uri: /orders/{{oid}}/user/{{id}}
+      target_service: tcp://127.0.0.1:{orders}
+"#
+    );
+    let config: EngineConfig = serde_yaml::from_str(&yaml).expect("valid yaml");
+    let handler = create_handler(Arc::new(config)).expect("handler failed");
+    let mut server = HttpServer::new(
+        HttpServerConfig::new(format!("tcp://127.0.0.1:{port}"), None),
+        handler,
+        None,
+    );
+    server.start().await.expect("start failed");
+    tokio::spawn(async move {
+        let _ = server.run().await;
+    });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    port
+}
+
+async fn send(port: u16, method: &str, path: &str) -> String {
+    let client: Client<_, Full<Bytes>> = Client::builder(TokioExecutor::new()).build_http();
+    let request = Request::builder()
+        .method(method)
+        .uri(format!("http://127.0.0.1:{port}{path}"))
+        .body(Full::new(Bytes::new()))
+        .unwrap();
+    let response = client.request(request).await.expect("request failed");
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    String::from_utf8(body.to_vec()).unwrap()
+}
+
+#[tokio::test]
+async fn test_e2e_gateway_routes_to_target_service_with_rewritten_path() {
+    let port = start_gateway_engine().await;
+
+    assert_eq!(
+        send(port, "GET", "/api/users/42?fields=name").await,
+        "users /v1/users/42?fields=name"
+    );
+    // The longer mapping wins even though it is configured last.
+    assert_eq!(
+        send(port, "GET", "/api/users/42/orders/7/items").await,
+        "orders /orders/7/user/42/items"
+    );
+}
+
+#[tokio::test]
+async fn test_e2e_gateway_method_filter_and_fallbacks() {
+    let port = start_gateway_engine().await;
+
+    // The users mapping only allows GET; DELETE falls back to the `/api` prefix.
+    assert_eq!(
+        send(port, "DELETE", "/api/users/42").await,
+        "default /legacy/users/42"
+    );
+    // Locations take precedence over gateway routes.
+    assert_eq!(send(port, "GET", "/api/health").await, "ok");
+    // Requests no mapping covers go to the engine target unchanged.
+    assert_eq!(send(port, "GET", "/other?x=1").await, "default /other?x=1");
+}
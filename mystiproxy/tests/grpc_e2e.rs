@@ -0,0 +1,243 @@
+//! E2E tests for the gRPC provider.
+//!
+//! These tests verify that gRPC locations return unary mock responses encoded
+//! from JSON via a descriptor set, and that proxied gRPC calls keep their
+//! `grpc-status` / `grpc-message` trailers.
+
+use std::io::Write;
+use std::sync::Arc;
+use std::time::Duration;
+
+use bytes::Bytes;
+use http_body_util::{BodyExt, Full, StreamBody};
+use hyper::body::{Frame, Incoming};
+use hyper::header::HeaderMap;
+use hyper::{Request, Response};
+use hyper_util::rt::{TokioExecutor, TokioIo};
+use mystiproxy::config::{
+    EngineConfig, GrpcConfig, GrpcMockConfig, Http2Config, LocationConfig, MatchMode, ProviderType,
+    ProxyType,
+};
+use mystiproxy::http::{create_handler, encode_message_frame, HttpServer, HttpServerConfig};
+use prost_reflect::prost::Message;
+use prost_reflect::prost_types::{
+    field_descriptor_proto::{Label, Type},
+    DescriptorProto, FieldDescriptorProto, FileDescriptorProto, FileDescriptorSet,
+    MethodDescriptorProto, ServiceDescriptorProto,
+};
+use tempfile::NamedTempFile;
+use tokio::net::TcpStream;
+
+async fn get_available_port() -> u16 {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("bind failed");
+    listener.local_addr().expect("no addr").port()
+}
+
+/// Write a descriptor set for
+/// `test.Greeter/SayHello(HelloRequest) -> HelloReply { string message = 1; }`.
+fn greeter_descriptor_file() -> NamedTempFile {
+    let field = |name: &str| FieldDescriptorProto {
+        name: Some(name.to_string()),
+        number: Some(1),
+        label: Some(Label::Optional as i32),
+        r#type: Some(Type::String as i32),
+        json_name: Some(name.to_string()),
+        ..Default::default()
+    };
+    let file = FileDescriptorProto {
+        name: Some("greeter.proto".to_string()),
+        package: Some("test".to_string()),
+        syntax: Some("proto3".to_string()),
+        message_type: vec![
+            DescriptorProto {
+                name: Some("HelloRequest".to_string()),
+                field: vec![field("name")],
+                ..Default::default()
+            },
+            DescriptorProto {
+                name: Some("HelloReply".to_string()),
+                field: vec![field("message")],
+                ..Default::default()
+            },
+        ],
+        service: vec![ServiceDescriptorProto {
+            name: Some("Greeter".to_string()),
+            method: vec![MethodDescriptorProto {
+                name: Some("SayHello".to_string()),
+                input_type: Some(".test.HelloRequest".to_string()),
+                output_type: Some(".test.HelloReply".to_string()),
+                ..Default::default()
+            }],
+            ..Default::default()
+        }],
+        ..Default::default()
+    };
+    let mut out = NamedTempFile::new().unwrap();
+    out.write_all(&FileDescriptorSet { file: vec![file] }.encode_to_vec())
+        .unwrap();
+    out
+}
+
+/// Start an HTTP/2 upstream that answers every call with a message and
+/// `grpc-status: 7` trailers.
+async fn start_grpc_upstream() -> u16 {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("upstream bind failed");
+    let port = listener.local_addr().expect("no addr").port();
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            tokio::spawn(async move {
+                let service = hyper::service::service_fn(|_req: Request<Incoming>| async move {
+                    let mut trailers = HeaderMap::new();
+                    trailers.insert("grpc-status", "7".parse().unwrap());
+                    trailers.insert("grpc-message", "denied".parse().unwrap());
+                    let frames = vec![
+                        Ok::<_, std::convert::Infallible>(Frame::data(encode_message_frame(
+                            b"upstream",
+                        ))),
+                        Ok(Frame::trailers(trailers)),
+                    ];
+                    let response = Response::builder()
+                        .header("content-type", "application/grpc")
+                        .body(StreamBody::new(futures::stream::iter(frames)))
+                        .unwrap();
+                    Ok::<_, std::convert::Infallible>(response)
+                });
+                let _ = hyper::server::conn::http2::Builder::new(TokioExecutor::new())
+                    .serve_connection(TokioIo::new(stream), service)
+                    .await;
+            });
+        }
+    });
+    port
+}
+
+fn grpc_location(location: &str, mode: MatchMode, grpc: GrpcConfig) -> LocationConfig {
+    LocationConfig {
+        location: location.to_string(),
+        mode,
+        provider: Some(ProviderType::Grpc),
+        root: None,
+        response: None,
+        request: None,
+        index_files: None,
+        enable_directory_listing: None,
+        grpc: Some(grpc),
+        redirect: None,
+        retry: None,
+        rate_limit: None,
+        cache: None,
+        compression: None,
+        hosts: None,
+        methods: None,
+        match_headers: None,
+        match_query: None,
+        target: None,
+    }
+}
+
+async fn start_engine(upstream_port: u16, locations: Vec<LocationConfig>) -> u16 {
+    let port = get_available_port().await;
+    let listen = format!("tcp://127.0.0.1:{port}");
+    let config = EngineConfig {
+        listen: listen.clone(),
+        target: format!("tcp://127.0.0.1:{upstream_port}").into(),
+        proxy_type: ProxyType::Http,
+        request_timeout: Some(Duration::from_secs(5)),
+        connection_timeout: None,
+        header: None,
+        locations: Some(locations),
+        tls: None,
+        auth: None,
+        upstream: None,
+        allow: None,
+        deny: None,
+        management: None,
+        max_buffered_body_size: None,
+        load_balance: None,
+        health_check: None,
+        http2: Some(Http2Config::default()),
+        connection_pool: None,
+        upstream_tls: None,
+        retry: None,
+        circuit_breaker: None,
+        rate_limit: None,
+        connection_limit: None,
+        cache: None,
+        compression: None,
+        gateway: None,
+        mock_root: None,
+    };
+    let handler = create_handler(Arc::new(config)).expect("handler failed");
+    let server_config = HttpServerConfig::new(listen, None).with_http2(true);
+    let mut server = HttpServer::new(server_config, handler, None);
+    server.start().await.expect("start failed");
+    tokio::spawn(async move {
+        let _ = server.run().await;
+    });
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    port
+}
+
+/// Make a unary gRPC call over h2c and return (message bytes, trailers).
+async fn grpc_call(port: u16, path: &str) -> (Bytes, HeaderMap) {
+    let stream = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+    let (mut sender, conn) = hyper::client::conn::http2::Builder::new(TokioExecutor::new())
+        .handshake(TokioIo::new(stream))
+        .await
+        .expect("h2 handshake failed");
+    tokio::spawn(conn);
+
+    let request = Request::builder()
+        .method("POST")
+        .uri(format!("http://127.0.0.1:{port}{path}"))
+        .header("content-type", "application/grpc")
+        .header("te", "trailers")
+        .body(Full::new(encode_message_frame(b"\x0a\x03bob")))
+        .unwrap();
+    let response = tokio::time::timeout(Duration::from_secs(5), sender.send_request(request))
+        .await
+        .expect("timeout")
+        .expect("request failed");
+    assert_eq!(response.status(), 200);
+    let collected = response.into_body().collect().await.unwrap();
+    let trailers = collected.trailers().cloned().unwrap_or_default();
+    (collected.to_bytes(), trailers)
+}
+
+#[tokio::test]
+async fn test_e2e_grpc_unary_mock_from_json() {
+    let descriptor = greeter_descriptor_file();
+    let location = grpc_location(
+        "/test.Greeter/SayHello",
+        MatchMode::Full,
+        GrpcConfig {
+            descriptor_set: Some(descriptor.path().to_string_lossy().to_string()),
+            mock: Some(GrpcMockConfig {
+                body: Some(serde_json::json!({"message": "hi"})),
+                status: 0,
+                message: None,
+            }),
+        },
+    );
+    let port = start_engine(get_available_port().await, vec![location]).await;
+
+    let (body, trailers) = grpc_call(port, "/test.Greeter/SayHello").await;
+    assert_eq!(&body[..], &encode_message_frame(b"\x0a\x02hi")[..]);
+    assert_eq!(trailers["grpc-status"], "0");
+}
+
+#[tokio::test]
+async fn test_e2e_grpc_proxy_preserves_trailers() {
+    let upstream = start_grpc_upstream().await;
+    let location = grpc_location("/test.Greeter/", MatchMode::Prefix, GrpcConfig::default());
+    let port = start_engine(upstream, vec![location]).await;
+
+    let (body, trailers) = grpc_call(port, "/test.Greeter/SayHello").await;
+    assert_eq!(&body[..], &encode_message_frame(b"upstream")[..]);
+    assert_eq!(trailers["grpc-status"], "7");
+    assert_eq!(trailers["grpc-message"], "denied");
+}
@@ -0,0 +1,172 @@
+//! E2E tests for host-, method-, header- and query-based routing.
+//!
+//! These tests verify that locations sharing a path are selected by
+//! `hosts` (exact and `*.` wildcard), `methods`, `match_headers` and
+//! `match_query`, and that a location `target` overrides the engine target.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use bytes::Bytes;
+use http_body_util::{BodyExt, Full};
+use hyper::body::Incoming;
+use hyper::{Request, Response};
+use hyper_util::client::legacy::Client;
+use hyper_util::rt::{TokioExecutor, TokioIo};
+use mystiproxy::config::EngineConfig;
+use mystiproxy::http::{create_handler, HttpServer, HttpServerConfig};
+
+async fn get_available_port() -> u16 {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("bind failed");
+    listener.local_addr().expect("no addr").port()
+}
+
+/// Start an upstream that answers every request with `name`.
+async fn start_upstream(name: &'static str) -> u16 {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("upstream bind failed");
+    let port = listener.local_addr().expect("no addr").port();
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            tokio::spawn(async move {
+                let service =
+                    hyper::service::service_fn(move |_req: Request<Incoming>| async move {
+                        Ok::<_, std::convert::Infallible>(Response::new(Full::new(
+                            Bytes::from_static(name.as_bytes()),
+                        )))
+                    });
+                let _ = hyper::server::conn::http1::Builder::new()
+                    .serve_connection(TokioIo::new(stream), service)
+                    .await;
+            });
+        }
+    });
+    port
+}
+
+async fn start_engine() -> u16 {
+    let default = start_upstream("default").await;
+    let tenants = start_upstream("tenants").await;
+    let port = get_available_port().await;
+    let yaml = format!(
+        r#"
+listen: tcp://127.0.0.1:{port}
+target: tcp://127.0.0.1:{default}
+proxy_type: http
+request_timeout: 5s
+locations:
+  - location: /items
+    mode: Full
+    hosts: [api.example.com]
+    methods: [POST]
+    provider: mock
+    response:
+      body:
+        type: static
+        content: api-create
+  - location: /items
+    mode: Full
+    hosts: [api.example.com]
+    provider: mock
+    response:
+      body:
+        type: static
+        content: api-read
+  - location: /items
+    mode: Full
+    hosts: ["*.tenants.example.com"]
+    target: tcp://127.0.0.1:{tenants}
+  - location: /items
+    mode: Full
+    match_headers:
+      X-Version: "2"
+    provider: mock
+    response:
+      body:
+        type: static
+        content: v2
+  - location: /items
+    mode: Full
+    match_query:
+      preview: "*"
+    provider: mock
+    response:
+      body:
+        type: static
+        content: preview
+"#
+    );
+    let config: EngineConfig = serde_yaml::from_str(&yaml).expect("valid yaml");
+    let handler = create_handler(Arc::new(config)).expect("handler failed");
+    let mut server = HttpServer::new(
+        HttpServerConfig::new(format!("tcp://127.0.0.1:{port}"), None),
+        handler,
+        None,
+    );
+    server.start().await.expect("start failed");
+    tokio::spawn(async move {
+        let _ = server.run().await;
+    });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    port
+}
+
+async fn send(port: u16, method: &str, path: &str, headers: &[(&str, &str)]) -> String {
+    let client: Client<_, Full<Bytes>> = Client::builder(TokioExecutor::new()).build_http();
+    let mut builder = Request::builder()
+        .method(method)
+        .uri(format!("http://127.0.0.1:{port}{path}"));
+    for (name, value) in headers {
+        builder = builder.header(*name, *value);
+    }
+    let response = client
+        .request(builder.body(Full::new(Bytes::new())).unwrap())
+        .await
+        .expect("request failed");
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    String::from_utf8(body.to_vec()).unwrap()
+}
+
+#[tokio::test]
+async fn test_e2e_route_by_host_and_method() {
+    let port = start_engine().await;
+
+    let api = [("host", "api.example.com")];
+    assert_eq!(send(port, "POST", "/items", &api).await, "api-create");
+    assert_eq!(send(port, "GET", "/items", &api).await, "api-read");
+    assert_eq!(
+        send(port, "GET", "/items", &[("host", "API.example.com:8080")]).await,
+        "api-read"
+    );
+    assert_eq!(send(port, "GET", "/items", &[]).await, "default");
+}
+
+#[tokio::test]
+async fn test_e2e_wildcard_host_uses_location_target() {
+    let port = start_engine().await;
+
+    let tenant = [("host", "acme.tenants.example.com")];
+    assert_eq!(send(port, "GET", "/items", &tenant).await, "tenants");
+    // The wildcard does not match the bare domain.
+    let bare = [("host", "tenants.example.com")];
+    assert_eq!(send(port, "GET", "/items", &bare).await, "default");
+}
+
+#[tokio::test]
+async fn test_e2e_route_by_header_and_query() {
+    let port = start_engine().await;
+
+    assert_eq!(
+        send(port, "GET", "/items", &[("x-version", "2")]).await,
+        "v2"
+    );
+    assert_eq!(
+        send(port, "GET", "/items", &[("x-version", "1")]).await,
+        "default"
+    );
+    assert_eq!(send(port, "GET", "/items?preview", &[]).await, "preview");
+    assert_eq!(send(port, "GET", "/items?other=1", &[]).await, "default");
+}
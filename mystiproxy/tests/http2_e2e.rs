@@ -0,0 +1,282 @@
+//! E2E tests for HTTP/2 support.
+//!
+//! These tests verify that the HTTP engine accepts HTTP/2 via TLS ALPN and
+//! prior-knowledge h2c once `http2` is configured, that it only serves
+//! HTTP/1.1 otherwise, and that it can talk HTTP/2 to an upstream.
+
+use std::io::Write;
+use std::sync::Arc;
+use std::time::Duration;
+
+use bytes::Bytes;
+use http_body_util::{BodyExt, Empty, Full};
+use hyper::body::Incoming;
+use hyper::{Request, Response, Version};
+use hyper_util::rt::{TokioExecutor, TokioIo};
+use mystiproxy::config::{EngineConfig, Http2Config, ProxyType, TlsConfig};
+use mystiproxy::http::{create_handler, HttpServer, HttpServerConfig};
+use tempfile::NamedTempFile;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+async fn get_available_port() -> u16 {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("bind failed");
+    listener.local_addr().expect("no addr").port()
+}
+
+/// Start an HTTP/1.1 upstream that answers every request with "h1-upstream".
+async fn start_http1_upstream() -> u16 {
+    let port = get_available_port().await;
+    tokio::spawn(async move {
+        let listener = tokio::net::TcpListener::bind(format!("127.0.0.1:{port}"))
+            .await
+            .expect("upstream bind failed");
+        loop {
+            if let Ok((mut stream, _)) = listener.accept().await {
+                tokio::spawn(async move {
+                    let mut buf = vec![0u8; 4096];
+                    if matches!(stream.read(&mut buf).await, Ok(0) | Err(_)) {
+                        return;
+                    }
+                    let body = "h1-upstream";
+                    let resp = format!(
+                        "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                        body.len()
+                    );
+                    let _ = stream.write_all(resp.as_bytes()).await;
+                });
+            }
+        }
+    });
+    tokio::time::sleep(Duration::from_millis(30)).await;
+    port
+}
+
+/// Start an HTTP/2-only upstream that echoes the request version and path.
+async fn start_http2_upstream() -> u16 {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("upstream bind failed");
+    let port = listener.local_addr().expect("no addr").port();
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            tokio::spawn(async move {
+                let service = hyper::service::service_fn(|req: Request<Incoming>| async move {
+                    let body = format!("{:?} {}", req.version(), req.uri().path());
+                    Ok::<_, std::convert::Infallible>(Response::new(Full::new(Bytes::from(body))))
+                });
+                let _ = hyper::server::conn::http2::Builder::new(TokioExecutor::new())
+                    .serve_connection(TokioIo::new(stream), service)
+                    .await;
+            });
+        }
+    });
+    port
+}
+
+fn engine_config(listen: String, upstream_port: u16, http2: Option<Http2Config>) -> EngineConfig {
+    EngineConfig {
+        listen,
+        target: format!("tcp://127.0.0.1:{upstream_port}").into(),
+        proxy_type: ProxyType::Http,
+        request_timeout: Some(Duration::from_secs(5)),
+        connection_timeout: None,
+        header: None,
+        locations: None,
+        tls: None,
+        auth: None,
+        upstream: None,
+        allow: None,
+        deny: None,
+        management: None,
+        max_buffered_body_size: None,
+        load_balance: None,
+        health_check: None,
+        http2,
+        connection_pool: None,
+        upstream_tls: None,
+        retry: None,
+        circuit_breaker: None,
+        rate_limit: None,
+        connection_limit: None,
+        cache: None,
+        compression: None,
+        gateway: None,
+        mock_root: None,
+    }
+}
+
+async fn start_engine(config: EngineConfig) -> u16 {
+    let listen = config.listen.clone();
+    let port = listen.rsplit(':').next().unwrap().parse().unwrap();
+    let tls = config.tls.clone();
+    let server_config = HttpServerConfig::new(listen, None)
+        .with_http2(config.http2.as_ref().is_some_and(|h| h.server));
+    let handler = create_handler(Arc::new(config)).expect("handler failed");
+    let mut server = match tls {
+        Some(tls) => HttpServer::new_with_tls(server_config, handler, &tls).expect("tls failed"),
+        None => HttpServer::new(server_config, handler, None),
+    };
+    server.start().await.expect("start failed");
+    tokio::spawn(async move {
+        let _ = server.run().await;
+    });
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    port
+}
+
+/// Send a GET over an HTTP/2 connection established on `io`.
+async fn h2_get<T>(io: T, uri: &str) -> (Version, String)
+where
+    T: hyper::rt::Read + hyper::rt::Write + Unpin + Send + 'static,
+{
+    let (mut sender, conn) = hyper::client::conn::http2::Builder::new(TokioExecutor::new())
+        .handshake(io)
+        .await
+        .expect("h2 handshake failed");
+    tokio::spawn(conn);
+
+    let request = Request::builder()
+        .uri(uri)
+        .body(Empty::<Bytes>::new())
+        .unwrap();
+    let response = tokio::time::timeout(Duration::from_secs(5), sender.send_request(request))
+        .await
+        .expect("timeout")
+        .expect("request failed");
+    let version = response.version();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    (version, String::from_utf8_lossy(&body).to_string())
+}
+
+#[tokio::test]
+async fn test_e2e_h2c_prior_knowledge() {
+    let upstream = start_http1_upstream().await;
+    let listen = format!("tcp://127.0.0.1:{}", get_available_port().await);
+    let http2 = Some(Http2Config::default());
+    let port = start_engine(engine_config(listen, upstream, http2)).await;
+
+    let stream = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+    let (version, body) = h2_get(TokioIo::new(stream), &format!("http://127.0.0.1:{port}/")).await;
+    assert_eq!(version, Version::HTTP_2);
+    assert_eq!(body, "h1-upstream");
+}
+
+#[tokio::test]
+async fn test_e2e_http1_still_served_with_http2_enabled() {
+    let upstream = start_http1_upstream().await;
+    let listen = format!("tcp://127.0.0.1:{}", get_available_port().await);
+    let http2 = Some(Http2Config::default());
+    let port = start_engine(engine_config(listen, upstream, http2)).await;
+
+    let mut stream = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+    stream
+        .write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+        .await
+        .unwrap();
+    let mut response = Vec::new();
+    tokio::time::timeout(Duration::from_secs(5), stream.read_to_end(&mut response))
+        .await
+        .expect("timeout")
+        .unwrap();
+    let response = String::from_utf8_lossy(&response);
+    assert!(response.starts_with("HTTP/1.1 200"), "got: {response}");
+    assert!(response.ends_with("h1-upstream"), "got: {response}");
+}
+
+#[tokio::test]
+async fn test_e2e_h2c_rejected_without_http2_config() {
+    let upstream = start_http1_upstream().await;
+    let listen = format!("tcp://127.0.0.1:{}", get_available_port().await);
+    let port = start_engine(engine_config(listen, upstream, None)).await;
+
+    let stream = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+    let (mut sender, conn) = hyper::client::conn::http2::Builder::new(TokioExecutor::new())
+        .handshake::<_, Empty<Bytes>>(TokioIo::new(stream))
+        .await
+        .expect("h2 handshake failed");
+    tokio::spawn(conn);
+
+    let request = Request::builder()
+        .uri(format!("http://127.0.0.1:{port}/"))
+        .body(Empty::<Bytes>::new())
+        .unwrap();
+    let result = tokio::time::timeout(Duration::from_secs(5), sender.send_request(request))
+        .await
+        .expect("timeout");
+    assert!(result.is_err(), "h2c must be opt-in, got: {result:?}");
+}
+
+#[tokio::test]
+async fn test_e2e_tls_alpn_negotiates_h2() {
+    let _ = rustls::crypto::ring::default_provider().install_default();
+
+    let key_pair = rcgen::KeyPair::generate().unwrap();
+    let cert = rcgen::CertificateParams::new(vec!["localhost".to_string()])
+        .unwrap()
+        .self_signed(&key_pair)
+        .unwrap();
+    let mut cert_file = NamedTempFile::new().unwrap();
+    let mut key_file = NamedTempFile::new().unwrap();
+    cert_file.write_all(cert.pem().as_bytes()).unwrap();
+    key_file
+        .write_all(key_pair.serialize_pem().as_bytes())
+        .unwrap();
+
+    let upstream = start_http1_upstream().await;
+    let listen = format!("tcp://127.0.0.1:{}", get_available_port().await);
+    let mut config = engine_config(listen, upstream, Some(Http2Config::default()));
+    config.tls = Some(TlsConfig {
+        cert_path: cert_file.path().to_string_lossy().to_string(),
+        key_path: key_file.path().to_string_lossy().to_string(),
+        client_ca_path: None,
+        mutual_auth: false,
+    });
+    let port = start_engine(config).await;
+
+    let mut roots = rustls::RootCertStore::empty();
+    roots.add(cert.der().clone()).unwrap();
+    let mut client_config = rustls::ClientConfig::builder()
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    client_config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+    let connector = tokio_rustls::TlsConnector::from(Arc::new(client_config));
+
+    let stream = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+    let tls = connector
+        .connect("localhost".try_into().unwrap(), stream)
+        .await
+        .expect("tls handshake failed");
+    assert_eq!(tls.get_ref().1.alpn_protocol(), Some(&b"h2"[..]));
+
+    let (version, body) = h2_get(TokioIo::new(tls), "https://localhost/").await;
+    assert_eq!(version, Version::HTTP_2);
+    assert_eq!(body, "h1-upstream");
+}
+
+#[tokio::test]
+async fn test_e2e_http2_upstream() {
+    let upstream = start_http2_upstream().await;
+    let listen = format!("tcp://127.0.0.1:{}", get_available_port().await);
+    let http2 = Http2Config {
+        server: true,
+        upstream: true,
+    };
+    let port = start_engine(engine_config(listen, upstream, Some(http2))).await;
+
+    let mut stream = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+    stream
+        .write_all(b"GET /items HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+        .await
+        .unwrap();
+    let mut response = Vec::new();
+    tokio::time::timeout(Duration::from_secs(5), stream.read_to_end(&mut response))
+        .await
+        .expect("timeout")
+        .unwrap();
+    let response = String::from_utf8_lossy(&response);
+    assert!(response.starts_with("HTTP/1.1 200"), "got: {response}");
+    assert!(response.contains("HTTP/2.0 /items"), "got: {response}");
+}
@@ -32,7 +32,7 @@ async fn get_available_port() -> u16 {
 fn make_engine_config(port: u16, locations: Vec<LocationConfig>) -> EngineConfig {
     EngineConfig {
         listen: format!("tcp://127.0.0.1:{port}"),
-        target: "tcp://127.0.0.1:1".to_string(),
+        target: "tcp://127.0.0.1:1".to_string().into(),
         proxy_type: ProxyType::Http,
         request_timeout: Some(Duration::from_secs(5)),
         connection_timeout: None,
@@ -48,6 +48,20 @@ fn make_engine_config(port: u16, locations: Vec<LocationConfig>) -> EngineConfig
         allow: None,
         deny: None,
         management: None,
+        max_buffered_body_size: None,
+        load_balance: None,
+        health_check: None,
+        http2: None,
+        connection_pool: None,
+        upstream_tls: None,
+        retry: None,
+        circuit_breaker: None,
+        rate_limit: None,
+        connection_limit: None,
+        cache: None,
+        compression: None,
+        gateway: None,
+        mock_root: None,
     }
 }
 
@@ -127,11 +141,24 @@ fn mock_location(path: &str, mode: MatchMode, status: u16) -> LocationConfig {
             status: Some(status),
             headers: None,
             body: None,
+            status_map: None,
             conditions: None,
+            sequence: None,
         }),
         request: None,
         index_files: None,
         enable_directory_listing: None,
+        grpc: None,
+        redirect: None,
+        retry: None,
+        rate_limit: None,
+        cache: None,
+        compression: None,
+        hosts: None,
+        methods: None,
+        match_headers: None,
+        match_query: None,
+        target: None,
     }
 }
 
@@ -150,11 +177,24 @@ fn mock_location_with_headers(
             status: Some(status),
             headers: Some(headers),
             body: None,
+            status_map: None,
             conditions: None,
+            sequence: None,
         }),
         request: None,
         index_files: None,
         enable_directory_listing: None,
+        grpc: None,
+        redirect: None,
+        retry: None,
+        rate_limit: None,
+        cache: None,
+        compression: None,
+        hosts: None,
+        methods: None,
+        match_headers: None,
+        match_query: None,
+        target: None,
     }
 }
 
@@ -168,6 +208,17 @@ fn static_location(path: &str, mode: MatchMode, root: &str) -> LocationConfig {
         request: None,
         index_files: None,
         enable_directory_listing: None,
+        grpc: None,
+        redirect: None,
+        retry: None,
+        rate_limit: None,
+        cache: None,
+        compression: None,
+        hosts: None,
+        methods: None,
+        match_headers: None,
+        match_query: None,
+        target: None,
     }
 }
 
@@ -185,12 +236,27 @@ fn mock_location_with_static_body(path: &str, mode: MatchMode, status: u16) -> L
                 content: None,
                 template: None,
                 body_type: Some(BodyType::Static),
+                file: None,
+                templated: None,
             }),
+            status_map: None,
             conditions: None,
+            sequence: None,
         }),
         request: None,
         index_files: None,
         enable_directory_listing: None,
+        grpc: None,
+        redirect: None,
+        retry: None,
+        rate_limit: None,
+        cache: None,
+        compression: None,
+        hosts: None,
+        methods: None,
+        match_headers: None,
+        match_query: None,
+        target: None,
     }
 }
 
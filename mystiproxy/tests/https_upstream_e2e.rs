@@ -0,0 +1,279 @@
+//! E2E tests for `https://` upstream targets.
+//!
+//! These tests verify that the HTTP engine connects to TLS backends using the
+//! engine's `upstream_tls` settings: custom CA, SNI override, client
+//! certificates for mTLS, and `insecure_skip_verify`.
+
+use std::io::Write;
+use std::sync::Arc;
+use std::time::Duration;
+
+use bytes::Bytes;
+use http_body_util::Full;
+use hyper::Response;
+use hyper_util::rt::TokioIo;
+use mystiproxy::config::{EngineConfig, ProxyType, UpstreamTlsConfig};
+use mystiproxy::http::{create_handler, HttpServer, HttpServerConfig};
+use rcgen::{BasicConstraints, CertificateParams, IsCa, KeyPair};
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, PrivatePkcs8KeyDer};
+use rustls::server::WebPkiClientVerifier;
+use tempfile::NamedTempFile;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+async fn get_available_port() -> u16 {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("bind failed");
+    listener.local_addr().expect("no addr").port()
+}
+
+fn pem_file(pem: &str) -> NamedTempFile {
+    let mut file = NamedTempFile::new().unwrap();
+    file.write_all(pem.as_bytes()).unwrap();
+    file
+}
+
+/// A test CA plus a server certificate for `backend.internal` and a client
+/// certificate, all signed by the CA.
+struct TestPki {
+    ca_pem: String,
+    ca_der: CertificateDer<'static>,
+    server_cert: CertificateDer<'static>,
+    server_key: Vec<u8>,
+    client_cert_pem: String,
+    client_key_pem: String,
+}
+
+fn test_pki() -> TestPki {
+    let ca_key = KeyPair::generate().unwrap();
+    let mut ca_params = CertificateParams::new(Vec::<String>::new()).unwrap();
+    ca_params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+    let ca = ca_params.self_signed(&ca_key).unwrap();
+
+    let server_key = KeyPair::generate().unwrap();
+    let server = CertificateParams::new(vec!["backend.internal".to_string()])
+        .unwrap()
+        .signed_by(&server_key, &ca, &ca_key)
+        .unwrap();
+
+    let client_key = KeyPair::generate().unwrap();
+    let client = CertificateParams::new(vec!["client".to_string()])
+        .unwrap()
+        .signed_by(&client_key, &ca, &ca_key)
+        .unwrap();
+
+    TestPki {
+        ca_pem: ca.pem(),
+        ca_der: ca.der().clone(),
+        server_cert: server.der().clone(),
+        server_key: server_key.serialize_der(),
+        client_cert_pem: client.pem(),
+        client_key_pem: client_key.serialize_pem(),
+    }
+}
+
+/// Start a TLS upstream that answers with the SNI it received. When
+/// `require_client_cert` is set, clients must present a certificate signed by
+/// the test CA.
+async fn start_tls_upstream(pki: &TestPki, require_client_cert: bool) -> u16 {
+    let _ = rustls::crypto::ring::default_provider().install_default();
+
+    let builder = rustls::ServerConfig::builder();
+    let builder = if require_client_cert {
+        let mut roots = rustls::RootCertStore::empty();
+        roots.add(pki.ca_der.clone()).unwrap();
+        builder.with_client_cert_verifier(
+            WebPkiClientVerifier::builder(Arc::new(roots))
+                .build()
+                .unwrap(),
+        )
+    } else {
+        builder.with_no_client_auth()
+    };
+    let key = PrivateKeyDer::Pkcs8(PrivatePkcs8KeyDer::from(pki.server_key.clone()));
+    let config = builder
+        .with_single_cert(vec![pki.server_cert.clone()], key)
+        .unwrap();
+    let acceptor = tokio_rustls::TlsAcceptor::from(Arc::new(config));
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("upstream bind failed");
+    let port = listener.local_addr().expect("no addr").port();
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            let acceptor = acceptor.clone();
+            tokio::spawn(async move {
+                let Ok(tls) = acceptor.accept(stream).await else {
+                    return;
+                };
+                let sni = tls.get_ref().1.server_name().unwrap_or("none").to_string();
+                let service = hyper::service::service_fn(move |_req| {
+                    let sni = sni.clone();
+                    async move {
+                        Ok::<_, std::convert::Infallible>(Response::new(Full::new(Bytes::from(
+                            format!("tls:{sni}"),
+                        ))))
+                    }
+                });
+                let _ = hyper::server::conn::http1::Builder::new()
+                    .serve_connection(TokioIo::new(tls), service)
+                    .await;
+            });
+        }
+    });
+    port
+}
+
+async fn start_engine(upstream_port: u16, upstream_tls: UpstreamTlsConfig) -> u16 {
+    let port = get_available_port().await;
+    let listen = format!("tcp://127.0.0.1:{port}");
+    let config = EngineConfig {
+        listen: listen.clone(),
+        target: format!("https://127.0.0.1:{upstream_port}").into(),
+        proxy_type: ProxyType::Http,
+        request_timeout: Some(Duration::from_secs(5)),
+        connection_timeout: None,
+        header: None,
+        locations: None,
+        tls: None,
+        auth: None,
+        upstream: None,
+        allow: None,
+        deny: None,
+        management: None,
+        max_buffered_body_size: None,
+        load_balance: None,
+        health_check: None,
+        http2: None,
+        connection_pool: None,
+        upstream_tls: Some(upstream_tls),
+        retry: None,
+        circuit_breaker: None,
+        rate_limit: None,
+        connection_limit: None,
+        cache: None,
+        compression: None,
+        gateway: None,
+        mock_root: None,
+    };
+    let handler = create_handler(Arc::new(config)).expect("handler failed");
+    let mut server = HttpServer::new(HttpServerConfig::new(listen, None), handler, None);
+    server.start().await.expect("start failed");
+    tokio::spawn(async move {
+        let _ = server.run().await;
+    });
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    port
+}
+
+async fn http_get(port: u16) -> String {
+    let mut stream = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+    stream
+        .write_all(b"GET / HTTP/1.1\r\nHost: backend.internal\r\nConnection: close\r\n\r\n")
+        .await
+        .unwrap();
+    let mut response = Vec::new();
+    tokio::time::timeout(Duration::from_secs(5), stream.read_to_end(&mut response))
+        .await
+        .expect("timeout")
+        .unwrap();
+    String::from_utf8_lossy(&response).to_string()
+}
+
+#[tokio::test]
+async fn test_e2e_https_upstream_with_custom_ca_and_sni() {
+    let pki = test_pki();
+    let ca = pem_file(&pki.ca_pem);
+    let upstream = start_tls_upstream(&pki, false).await;
+    let port = start_engine(
+        upstream,
+        UpstreamTlsConfig {
+            ca_path: Some(ca.path().to_string_lossy().to_string()),
+            sni: Some("backend.internal".to_string()),
+            ..Default::default()
+        },
+    )
+    .await;
+
+    let response = http_get(port).await;
+    assert!(response.starts_with("HTTP/1.1 200"), "got: {response}");
+    assert!(
+        response.ends_with("tls:backend.internal"),
+        "got: {response}"
+    );
+}
+
+#[tokio::test]
+async fn test_e2e_https_upstream_rejects_name_mismatch() {
+    let pki = test_pki();
+    let ca = pem_file(&pki.ca_pem);
+    let upstream = start_tls_upstream(&pki, false).await;
+    // Without an SNI override the certificate is checked against 127.0.0.1.
+    let port = start_engine(
+        upstream,
+        UpstreamTlsConfig {
+            ca_path: Some(ca.path().to_string_lossy().to_string()),
+            ..Default::default()
+        },
+    )
+    .await;
+
+    let response = http_get(port).await;
+    assert!(!response.starts_with("HTTP/1.1 200"), "got: {response}");
+}
+
+#[tokio::test]
+async fn test_e2e_https_upstream_mutual_tls() {
+    let pki = test_pki();
+    let ca = pem_file(&pki.ca_pem);
+    let cert = pem_file(&pki.client_cert_pem);
+    let key = pem_file(&pki.client_key_pem);
+    let upstream = start_tls_upstream(&pki, true).await;
+
+    let with_cert = start_engine(
+        upstream,
+        UpstreamTlsConfig {
+            ca_path: Some(ca.path().to_string_lossy().to_string()),
+            cert_path: Some(cert.path().to_string_lossy().to_string()),
+            key_path: Some(key.path().to_string_lossy().to_string()),
+            sni: Some("backend.internal".to_string()),
+            ..Default::default()
+        },
+    )
+    .await;
+    let response = http_get(with_cert).await;
+    assert!(response.starts_with("HTTP/1.1 200"), "got: {response}");
+
+    let without_cert = start_engine(
+        upstream,
+        UpstreamTlsConfig {
+            ca_path: Some(ca.path().to_string_lossy().to_string()),
+            sni: Some("backend.internal".to_string()),
+            ..Default::default()
+        },
+    )
+    .await;
+    let response = http_get(without_cert).await;
+    assert!(!response.starts_with("HTTP/1.1 200"), "got: {response}");
+}
+
+#[tokio::test]
+async fn test_e2e_https_upstream_insecure_skip_verify() {
+    let pki = test_pki();
+    let upstream = start_tls_upstream(&pki, false).await;
+    let port = start_engine(
+        upstream,
+        UpstreamTlsConfig {
+            insecure_skip_verify: true,
+            ..Default::default()
+        },
+    )
+    .await;
+
+    let response = http_get(port).await;
+    assert!(response.starts_with("HTTP/1.1 200"), "got: {response}");
+    // IP addresses are never sent as SNI.
+    assert!(response.ends_with("tls:none"), "got: {response}");
+}
@@ -16,7 +16,7 @@ use mystiproxy::proxy::ProxyServer;
 fn http_engine(listen: &str) -> EngineConfig {
     EngineConfig {
         listen: listen.to_string(),
-        target: "tcp://127.0.0.1:1".to_string(),
+        target: "tcp://127.0.0.1:1".to_string().into(),
         proxy_type: ProxyType::Http,
         request_timeout: Some(Duration::from_secs(2)),
         connection_timeout: None,
@@ -30,6 +30,17 @@ fn http_engine(listen: &str) -> EngineConfig {
             request: None,
             index_files: None,
             enable_directory_listing: None,
+            grpc: None,
+            redirect: None,
+            retry: None,
+            rate_limit: None,
+            cache: None,
+            compression: None,
+            hosts: None,
+            methods: None,
+            match_headers: None,
+            match_query: None,
+            target: None,
         }]),
         auth: None,
         tls: None,
@@ -37,6 +48,20 @@ fn http_engine(listen: &str) -> EngineConfig {
         allow: None,
         deny: None,
         management: None,
+        max_buffered_body_size: None,
+        load_balance: None,
+        health_check: None,
+        http2: None,
+        connection_pool: None,
+        upstream_tls: None,
+        retry: None,
+        circuit_breaker: None,
+        rate_limit: None,
+        connection_limit: None,
+        cache: None,
+        compression: None,
+        gateway: None,
+        mock_root: None,
     }
 }
 
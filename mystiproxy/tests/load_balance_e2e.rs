@@ -0,0 +1,242 @@
+//! E2E tests for multi-target engines.
+//!
+//! These tests verify that HTTP and TCP engines spread requests across a
+//! weighted target list according to the configured strategy.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use mystiproxy::config::{
+    EngineConfig, HealthCheckConfig, LoadBalanceConfig, LoadBalanceStrategy,
+    PassiveHealthCheckConfig, ProxyType, TargetConfig, WeightedTarget,
+};
+use mystiproxy::http::{create_handler, HttpServer, HttpServerConfig};
+use mystiproxy::io::SocketStream;
+use mystiproxy::proxy::ProxyServer;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+async fn get_available_port() -> u16 {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("bind failed");
+    listener.local_addr().expect("no addr").port()
+}
+
+/// Start an upstream that answers every connection with its own name.
+async fn start_named_upstream(name: &'static str) -> u16 {
+    let port = get_available_port().await;
+    tokio::spawn(async move {
+        let listener = tokio::net::TcpListener::bind(format!("127.0.0.1:{port}"))
+            .await
+            .expect("upstream bind failed");
+        loop {
+            if let Ok((mut stream, _)) = listener.accept().await {
+                tokio::spawn(async move {
+                    let mut buf = vec![0u8; 4096];
+                    if matches!(stream.read(&mut buf).await, Ok(0) | Err(_)) {
+                        return;
+                    }
+                    let resp = format!(
+                        "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{name}",
+                        name.len()
+                    );
+                    let _ = stream.write_all(resp.as_bytes()).await;
+                });
+            }
+        }
+    });
+    tokio::time::sleep(Duration::from_millis(30)).await;
+    port
+}
+
+fn engine_config(
+    listen: String,
+    proxy_type: ProxyType,
+    ports: &[u16],
+    strategy: LoadBalanceStrategy,
+    hash_header: Option<&str>,
+) -> EngineConfig {
+    EngineConfig {
+        listen,
+        target: TargetConfig::Weighted(
+            ports
+                .iter()
+                .map(|port| WeightedTarget {
+                    address: format!("tcp://127.0.0.1:{port}"),
+                    weight: 1,
+                })
+                .collect(),
+        ),
+        proxy_type,
+        request_timeout: Some(Duration::from_secs(5)),
+        connection_timeout: None,
+        header: None,
+        locations: None,
+        tls: None,
+        auth: None,
+        upstream: None,
+        allow: None,
+        deny: None,
+        management: None,
+        max_buffered_body_size: None,
+        load_balance: Some(LoadBalanceConfig {
+            strategy,
+            hash_header: hash_header.map(str::to_string),
+        }),
+        health_check: None,
+        http2: None,
+        connection_pool: None,
+        upstream_tls: None,
+        retry: None,
+        circuit_breaker: None,
+        rate_limit: None,
+        connection_limit: None,
+        cache: None,
+        compression: None,
+        gateway: None,
+        mock_root: None,
+    }
+}
+
+async fn start_http_engine(config: EngineConfig) -> String {
+    let listen = config.listen.clone();
+    let handler = create_handler(Arc::new(config)).expect("handler failed");
+    let mut server = HttpServer::new(HttpServerConfig::new(listen.clone(), None), handler, None);
+    server.start().await.expect("start failed");
+    tokio::spawn(async move {
+        let _ = server.run().await;
+    });
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    listen
+}
+
+async fn send_get(addr: &str, headers: &[(&str, &str)]) -> String {
+    send_get_with(addr, headers, false).await
+}
+
+/// `half_close` shuts down the write side after the request, which the TCP
+/// forwarder needs to see before it tears the connection down.
+async fn send_get_with(addr: &str, headers: &[(&str, &str)], half_close: bool) -> String {
+    let mut stream = SocketStream::connect(addr.to_string())
+        .await
+        .expect("connect failed");
+    let extra: String = headers
+        .iter()
+        .map(|(k, v)| format!("{k}: {v}\r\n"))
+        .collect();
+    let request = format!("GET / HTTP/1.1\r\nHost: localhost\r\n{extra}Connection: close\r\n\r\n");
+    stream.write_all(request.as_bytes()).await.expect("write");
+    if half_close {
+        stream.shutdown().await.expect("shutdown");
+    }
+
+    let mut response = Vec::new();
+    tokio::time::timeout(Duration::from_secs(5), stream.read_to_end(&mut response))
+        .await
+        .expect("timeout")
+        .expect("read");
+    let response = String::from_utf8_lossy(&response).to_string();
+    response
+        .split_once("\r\n\r\n")
+        .map(|(_, body)| body.to_string())
+        .unwrap_or_default()
+}
+
+#[tokio::test]
+async fn test_e2e_http_round_robin_across_targets() {
+    let a = start_named_upstream("A").await;
+    let b = start_named_upstream("B").await;
+    let listen = format!("tcp://127.0.0.1:{}", get_available_port().await);
+    let proxy = start_http_engine(engine_config(
+        listen,
+        ProxyType::Http,
+        &[a, b],
+        LoadBalanceStrategy::RoundRobin,
+        None,
+    ))
+    .await;
+
+    let mut bodies = Vec::new();
+    for _ in 0..4 {
+        bodies.push(send_get(&proxy, &[]).await);
+    }
+    assert_eq!(bodies, ["A", "B", "A", "B"]);
+}
+
+#[tokio::test]
+async fn test_e2e_http_consistent_hash_on_header() {
+    let a = start_named_upstream("A").await;
+    let b = start_named_upstream("B").await;
+    let listen = format!("tcp://127.0.0.1:{}", get_available_port().await);
+    let proxy = start_http_engine(engine_config(
+        listen,
+        ProxyType::Http,
+        &[a, b],
+        LoadBalanceStrategy::ConsistentHash,
+        Some("X-User"),
+    ))
+    .await;
+
+    let first = send_get(&proxy, &[("X-User", "alice")]).await;
+    for _ in 0..5 {
+        assert_eq!(send_get(&proxy, &[("X-User", "alice")]).await, first);
+    }
+}
+
+#[tokio::test]
+async fn test_e2e_tcp_round_robin_across_targets() {
+    let a = start_named_upstream("A").await;
+    let b = start_named_upstream("B").await;
+    let listen = format!("tcp://127.0.0.1:{}", get_available_port().await);
+    let config = engine_config(
+        listen.clone(),
+        ProxyType::Tcp,
+        &[a, b],
+        LoadBalanceStrategy::RoundRobin,
+        None,
+    );
+
+    let mut server = ProxyServer::from_engine_config(&config).expect("create failed");
+    server.start().await.expect("start failed");
+    tokio::spawn(async move {
+        let _ = server.run().await;
+    });
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    let mut bodies = Vec::new();
+    for _ in 0..4 {
+        bodies.push(send_get_with(&listen, &[], true).await);
+    }
+    assert_eq!(bodies, ["A", "B", "A", "B"]);
+}
+
+#[tokio::test]
+async fn test_e2e_passive_health_check_ejects_failing_target() {
+    let a = start_named_upstream("A").await;
+    // Nothing listens on this port, so every connect fails
+    let dead = get_available_port().await;
+    let listen = format!("tcp://127.0.0.1:{}", get_available_port().await);
+    let mut config = engine_config(
+        listen,
+        ProxyType::Http,
+        &[a, dead],
+        LoadBalanceStrategy::RoundRobin,
+        None,
+    );
+    config.health_check = Some(HealthCheckConfig {
+        active: None,
+        passive: Some(PassiveHealthCheckConfig {
+            consecutive_failures: Some(1),
+            cooldown: Some(Duration::from_secs(60)),
+        }),
+    });
+    let proxy = start_http_engine(config).await;
+
+    // The first round reaches the dead target once and ejects it
+    send_get(&proxy, &[]).await;
+    send_get(&proxy, &[]).await;
+
+    for _ in 0..4 {
+        assert_eq!(send_get(&proxy, &[]).await, "A");
+    }
+}
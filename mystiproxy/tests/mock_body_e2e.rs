@@ -127,7 +127,7 @@ async fn test_e2e_mock_static_body_struct_config() {
 
     let engine = EngineConfig {
         listen: "tcp://127.0.0.1:19203".to_string(),
-        target: "tcp://127.0.0.1:1".to_string(),
+        target: "tcp://127.0.0.1:1".to_string().into(),
         proxy_type: ProxyType::Http,
         request_timeout: Some(Duration::from_secs(2)),
         connection_timeout: None,
@@ -138,7 +138,9 @@ async fn test_e2e_mock_static_body_struct_config() {
             provider: Some(ProviderType::Mock),
             root: None,
             response: Some(ResponseConfig {
+                status_map: None,
                 conditions: None,
+                sequence: None,
                 status: Some(200),
                 headers: Some(HashMap::from([(
                     "X-From".to_string(),
@@ -153,11 +155,24 @@ async fn test_e2e_mock_static_body_struct_config() {
                     json: None,
                     body_type: Some(BodyType::Static),
                     content: Some("hello from struct".to_string()),
+                    file: None,
+                    templated: None,
                 }),
             }),
             request: None,
             index_files: None,
             enable_directory_listing: None,
+            grpc: None,
+            redirect: None,
+            retry: None,
+            rate_limit: None,
+            cache: None,
+            compression: None,
+            hosts: None,
+            methods: None,
+            match_headers: None,
+            match_query: None,
+            target: None,
         }]),
         auth: None,
         tls: None,
@@ -165,6 +180,20 @@ async fn test_e2e_mock_static_body_struct_config() {
         allow: None,
         deny: None,
         management: None,
+        max_buffered_body_size: None,
+        load_balance: None,
+        health_check: None,
+        http2: None,
+        connection_pool: None,
+        upstream_tls: None,
+        retry: None,
+        circuit_breaker: None,
+        rate_limit: None,
+        connection_limit: None,
+        cache: None,
+        compression: None,
+        gateway: None,
+        mock_root: None,
     };
 
     let handler = create_handler(Arc::new(engine)).expect("handler");
@@ -194,7 +223,7 @@ async fn test_e2e_mock_body_json_type_without_content() {
 
     let engine = EngineConfig {
         listen: "tcp://127.0.0.1:19204".to_string(),
-        target: "tcp://127.0.0.1:1".to_string(),
+        target: "tcp://127.0.0.1:1".to_string().into(),
         proxy_type: ProxyType::Http,
         request_timeout: Some(Duration::from_secs(2)),
         connection_timeout: None,
@@ -205,7 +234,9 @@ async fn test_e2e_mock_body_json_type_without_content() {
             provider: Some(ProviderType::Mock),
             root: None,
             response: Some(ResponseConfig {
+                status_map: None,
                 conditions: None,
+                sequence: None,
                 status: Some(200),
                 headers: None,
                 body: Some(BodyConfig {
@@ -213,11 +244,24 @@ async fn test_e2e_mock_body_json_type_without_content() {
                     json: None,
                     body_type: Some(BodyType::Json),
                     content: None,
+                    file: None,
+                    templated: None,
                 }),
             }),
             request: None,
             index_files: None,
             enable_directory_listing: None,
+            grpc: None,
+            redirect: None,
+            retry: None,
+            rate_limit: None,
+            cache: None,
+            compression: None,
+            hosts: None,
+            methods: None,
+            match_headers: None,
+            match_query: None,
+            target: None,
         }]),
         auth: None,
         tls: None,
@@ -225,6 +269,20 @@ async fn test_e2e_mock_body_json_type_without_content() {
         allow: None,
         deny: None,
         management: None,
+        max_buffered_body_size: None,
+        load_balance: None,
+        health_check: None,
+        http2: None,
+        connection_pool: None,
+        upstream_tls: None,
+        retry: None,
+        circuit_breaker: None,
+        rate_limit: None,
+        connection_limit: None,
+        cache: None,
+        compression: None,
+        gateway: None,
+        mock_root: None,
     };
 
     let handler = create_handler(Arc::new(engine)).expect("handler");
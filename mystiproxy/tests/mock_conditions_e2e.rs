@@ -0,0 +1,165 @@
+//! E2E tests for mock conditions evaluated against the request body.
+//!
+//! These tests verify that `body`/`json` conditions and `{{body.*}}` template
+//! placeholders see the buffered JSON request body, that requests falling
+//! through to the upstream still carry the original body, and that bodies over
+//! `max_buffered_body_size` are rejected with 413.
+
+use std::io::Write;
+use std::sync::Arc;
+use std::time::Duration;
+
+use bytes::Bytes;
+use http_body_util::{BodyExt, Full};
+use hyper::body::Incoming;
+use hyper::{Request, Response, StatusCode};
+use hyper_util::client::legacy::Client;
+use hyper_util::rt::{TokioExecutor, TokioIo};
+use mystiproxy::config::EngineConfig;
+use mystiproxy::http::{create_handler, HttpServer, HttpServerConfig};
+
+async fn get_available_port() -> u16 {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("bind failed");
+    listener.local_addr().expect("no addr").port()
+}
+
+/// Start an upstream that echoes the request body prefixed with `upstream:`.
+async fn start_echo_upstream() -> u16 {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("upstream bind failed");
+    let port = listener.local_addr().expect("no addr").port();
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            tokio::spawn(async move {
+                let service = hyper::service::service_fn(|req: Request<Incoming>| async move {
+                    let body = req.into_body().collect().await?.to_bytes();
+                    let mut echoed = b"upstream:".to_vec();
+                    echoed.extend_from_slice(&body);
+                    Ok::<_, hyper::Error>(Response::new(Full::new(Bytes::from(echoed))))
+                });
+                let _ = hyper::server::conn::http1::Builder::new()
+                    .serve_connection(TokioIo::new(stream), service)
+                    .await;
+            });
+        }
+    });
+    port
+}
+
+async fn start_engine() -> u16 {
+    let upstream = start_echo_upstream().await;
+    let port = get_available_port().await;
+    let yaml = format!(
+        r#"
+listen: tcp://127.0.0.1:{port}
+target: tcp://127.0.0.1:{upstream}
+proxy_type: http
+request_timeout: 5s
+max_buffered_body_size: 64
+locations:
+  - location: /orders
+    mode: Full
+    provider: mock
+    response:
+      conditions:
+        - condition_type: json
+          value: $.tier=premium
+      body:
+        type: template
+        template: "priority order for {{{{body.$.customer}}}}"
+  - location: /greet
+    mode: Full
+    provider: mock
+    response:
+      body:
+        type: template
+        template: "hello {{{{body.$.name}}}}"
+"#
+    );
+    let config: EngineConfig = serde_yaml::from_str(&yaml).expect("valid yaml");
+    let handler = create_handler(Arc::new(config)).expect("handler failed");
+    let mut server = HttpServer::new(
+        HttpServerConfig::new(format!("tcp://127.0.0.1:{port}"), None),
+        handler,
+        None,
+    );
+    server.start().await.expect("start failed");
+    tokio::spawn(async move {
+        let _ = server.run().await;
+    });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    port
+}
+
+async fn post(
+    port: u16,
+    path: &str,
+    headers: &[(&str, &str)],
+    body: impl Into<Bytes>,
+) -> (StatusCode, Bytes) {
+    let client: Client<_, Full<Bytes>> = Client::builder(TokioExecutor::new()).build_http();
+    let mut builder = Request::builder()
+        .method("POST")
+        .uri(format!("http://127.0.0.1:{port}{path}"))
+        .header("content-type", "application/json");
+    for (name, value) in headers {
+        builder = builder.header(*name, *value);
+    }
+    let response = client
+        .request(builder.body(Full::new(body.into())).unwrap())
+        .await
+        .expect("request failed");
+    let status = response.status();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    (status, body)
+}
+
+#[tokio::test]
+async fn test_e2e_json_condition_matches_request_body() {
+    let port = start_engine().await;
+
+    let (status, body) = post(
+        port,
+        "/orders",
+        &[],
+        r#"{"tier":"premium","customer":"ada"}"#,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(&body[..], b"priority order for ada");
+}
+
+#[tokio::test]
+async fn test_e2e_unmatched_condition_replays_body_upstream() {
+    let port = start_engine().await;
+
+    let payload = r#"{"tier":"basic","customer":"bob"}"#;
+    let (status, body) = post(port, "/orders", &[], payload).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, format!("upstream:{payload}"));
+}
+
+#[tokio::test]
+async fn test_e2e_template_reads_compressed_request_body() {
+    let port = start_engine().await;
+
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(br#"{"name":"grace"}"#).unwrap();
+    let gzipped = encoder.finish().unwrap();
+
+    let (status, body) = post(port, "/greet", &[("content-encoding", "gzip")], gzipped).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(&body[..], b"hello grace");
+}
+
+#[tokio::test]
+async fn test_e2e_oversized_body_rejected() {
+    let port = start_engine().await;
+
+    let payload = format!(r#"{{"tier":"premium","customer":"{}"}}"#, "x".repeat(100));
+    let (status, _) = post(port, "/orders", &[], payload).await;
+    assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+}
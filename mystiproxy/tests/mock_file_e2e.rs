@@ -0,0 +1,141 @@
+//! E2E tests for file-backed mock bodies.
+//!
+//! These tests verify that `type: file` mock bodies are read relative to the
+//! engine's `mock_root`, streamed with a detected `Content-Type`, optionally
+//! rendered as templates, picked up again after the file changes and answered
+//! with 500 when the file is missing.
+
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use bytes::Bytes;
+use http_body_util::{BodyExt, Full};
+use hyper::{Request, StatusCode};
+use hyper_util::client::legacy::Client;
+use hyper_util::rt::TokioExecutor;
+use mystiproxy::config::EngineConfig;
+use mystiproxy::http::{create_handler, HttpServer, HttpServerConfig};
+
+async fn get_available_port() -> u16 {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("bind failed");
+    listener.local_addr().expect("no addr").port()
+}
+
+async fn start_engine(root: &Path) -> u16 {
+    let port = get_available_port().await;
+    let yaml = format!(
+        r#"
+listen: tcp://127.0.0.1:{port}
+target: tcp://127.0.0.1:1
+proxy_type: http
+request_timeout: 5s
+mock_root: {root}
+locations:
+  - location: /report
+    mode: Full
+    provider: mock
+    response:
+      body:
+        type: file
+        file: fixtures/report.pdf
+  - location: /greeting
+    mode: Full
+    provider: mock
+    response:
+      headers:
+        Cache-Control:
+          action: overwrite
+          value: no-store
+      body:
+        type: file
+        file: greeting.html
+        templated: true
+  - location: /missing
+    mode: Full
+    provider: mock
+    response:
+      body:
+        type: file
+        file: nope.json
+"#,
+        root = root.display()
+    );
+    let config: EngineConfig = serde_yaml::from_str(&yaml).expect("valid yaml");
+    let handler = create_handler(Arc::new(config)).expect("handler failed");
+    let mut server = HttpServer::new(
+        HttpServerConfig::new(format!("tcp://127.0.0.1:{port}"), None),
+        handler,
+        None,
+    );
+    server.start().await.expect("start failed");
+    tokio::spawn(async move {
+        let _ = server.run().await;
+    });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    port
+}
+
+async fn get(port: u16, path: &str) -> (StatusCode, Option<String>, Bytes) {
+    let client: Client<_, Full<Bytes>> = Client::builder(TokioExecutor::new()).build_http();
+    let request = Request::builder()
+        .uri(format!("http://127.0.0.1:{port}{path}"))
+        .body(Full::new(Bytes::new()))
+        .unwrap();
+    let response = client.request(request).await.expect("request failed");
+    let status = response.status();
+    let content_type = response
+        .headers()
+        .get("content-type")
+        .map(|v| v.to_str().unwrap().to_string());
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    (status, content_type, body)
+}
+
+#[tokio::test]
+async fn test_e2e_binary_file_streamed_with_mime() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir(dir.path().join("fixtures")).unwrap();
+    // 大于单个读取块，验证分块流式返回
+    let data: Vec<u8> = (0..200_000u32).map(|i| (i % 251) as u8).collect();
+    std::fs::write(dir.path().join("fixtures/report.pdf"), &data).unwrap();
+    let port = start_engine(dir.path()).await;
+
+    let (status, content_type, body) = get(port, "/report").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(content_type.as_deref(), Some("application/pdf"));
+    assert_eq!(&body[..], &data[..]);
+}
+
+#[tokio::test]
+async fn test_e2e_templated_file_picks_up_edits() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("greeting.html");
+    std::fs::write(&path, "<p>hello {{query.name}}</p>").unwrap();
+    let port = start_engine(dir.path()).await;
+
+    let (status, content_type, body) = get(port, "/greeting?name=ada").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(content_type.as_deref(), Some("text/html; charset=utf-8"));
+    assert_eq!(&body[..], b"<p>hello ada</p>");
+
+    // 修改文件后无需重启
+    std::fs::write(&path, "<p>bye {{query.name}}</p>").unwrap();
+    assert_eq!(
+        &get(port, "/greeting?name=ada").await.2[..],
+        b"<p>bye ada</p>"
+    );
+}
+
+#[tokio::test]
+async fn test_e2e_missing_file_returns_500() {
+    let dir = tempfile::tempdir().unwrap();
+    let port = start_engine(dir.path()).await;
+
+    assert_eq!(
+        get(port, "/missing").await.0,
+        StatusCode::INTERNAL_SERVER_ERROR
+    );
+}
@@ -0,0 +1,106 @@
+//! E2E tests for the mock template engine.
+//!
+//! These tests verify that mock bodies and header values rendered through the
+//! template engine see the request method, path params, headers, cookies,
+//! client IP and JSON body, and that conditionals, loops and helpers work
+//! end to end.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use bytes::Bytes;
+use http_body_util::{BodyExt, Full};
+use hyper::{Request, StatusCode};
+use hyper_util::client::legacy::Client;
+use hyper_util::rt::TokioExecutor;
+use mystiproxy::config::EngineConfig;
+use mystiproxy::http::{create_handler, HttpServer, HttpServerConfig};
+
+async fn get_available_port() -> u16 {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("bind failed");
+    listener.local_addr().expect("no addr").port()
+}
+
+async fn start_engine() -> u16 {
+    let port = get_available_port().await;
+    let yaml = format!(
+        r#"
+listen: tcp://127.0.0.1:{port}
+target: tcp://127.0.0.1:1
+proxy_type: http
+request_timeout: 5s
+locations:
+  - location: /orders/{{id}}
+    mode: Regex
+    provider: mock
+    response:
+      headers:
+        X-Order:
+          action: overwrite
+          value: "order-{{{{path.id}}}}"
+        X-Request-Id:
+          action: overwrite
+          value: "{{{{uuid}}}}"
+      body:
+        type: template
+        template: >-
+          {{"id":"{{{{path.id}}}}","method":"{{{{method}}}}","user":"{{{{header.x-user}}}}",
+          "session":"{{{{cookie.sid}}}}","ip":"{{{{client_ip}}}}",
+          "vip":{{{{#if body.$.vip}}}}true{{{{else}}}}false{{{{/if}}}},
+          "note":"{{{{jsonEscape body.$.note}}}}",
+          "skus":[{{{{#each body.$.items}}}}"{{{{sku}}}}"{{{{#unless @last}}}},{{{{/unless}}}}{{{{/each}}}}]}}
+"#
+    );
+    let config: EngineConfig = serde_yaml::from_str(&yaml).expect("valid yaml");
+    let handler = create_handler(Arc::new(config)).expect("handler failed");
+    let mut server = HttpServer::new(
+        HttpServerConfig::new(format!("tcp://127.0.0.1:{port}"), None),
+        handler,
+        None,
+    );
+    server.start().await.expect("start failed");
+    tokio::spawn(async move {
+        let _ = server.run().await;
+    });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    port
+}
+
+#[tokio::test]
+async fn test_e2e_template_renders_request_data() {
+    let port = start_engine().await;
+
+    let client: Client<_, Full<Bytes>> = Client::builder(TokioExecutor::new()).build_http();
+    let request = Request::builder()
+        .method("POST")
+        .uri(format!("http://127.0.0.1:{port}/orders/42"))
+        .header("X-User", "ada")
+        .header("Cookie", "theme=dark; sid=s-1")
+        .header("Content-Type", "application/json")
+        .body(Full::new(Bytes::from(
+            r#"{"vip": true, "note": "say \"hi\"", "items": [{"sku": "A1"}, {"sku": "B2"}]}"#,
+        )))
+        .unwrap();
+    let response = client.request(request).await.expect("request failed");
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["x-order"], "order-42");
+    assert_eq!(response.headers()["x-request-id"].len(), 36);
+
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let json: serde_json::Value = serde_json::from_slice(&body).expect("rendered JSON");
+    assert_eq!(
+        json,
+        serde_json::json!({
+            "id": "42",
+            "method": "POST",
+            "user": "ada",
+            "session": "s-1",
+            "ip": "127.0.0.1",
+            "vip": true,
+            "note": "say \"hi\"",
+            "skus": ["A1", "B2"]
+        })
+    );
+}
@@ -120,7 +120,7 @@ async fn start_proxy(upstream_port: u16, locations: Vec<LocationConfig>) -> Stri
 
     let config = EngineConfig {
         listen: listen.clone(),
-        target: format!("tcp://127.0.0.1:{upstream_port}"),
+        target: format!("tcp://127.0.0.1:{upstream_port}").into(),
         proxy_type: ProxyType::Http,
         request_timeout: Some(Duration::from_secs(5)),
         connection_timeout: None,
@@ -136,6 +136,20 @@ async fn start_proxy(upstream_port: u16, locations: Vec<LocationConfig>) -> Stri
         deny: None,
         management: None,
         tls: None,
+        max_buffered_body_size: None,
+        load_balance: None,
+        health_check: None,
+        http2: None,
+        connection_pool: None,
+        upstream_tls: None,
+        retry: None,
+        circuit_breaker: None,
+        rate_limit: None,
+        connection_limit: None,
+        cache: None,
+        compression: None,
+        gateway: None,
+        mock_root: None,
     };
 
     let handler = create_handler(Arc::new(config)).expect("failed to create handler");
@@ -412,11 +426,24 @@ async fn test_e2e_proxy_and_mock_coexist() {
             status: Some(201),
             headers: None,
             body: None,
+            status_map: None,
             conditions: None,
+            sequence: None,
         }),
         request: None,
         index_files: None,
         enable_directory_listing: None,
+        grpc: None,
+        redirect: None,
+        retry: None,
+        rate_limit: None,
+        cache: None,
+        compression: None,
+        hosts: None,
+        methods: None,
+        match_headers: None,
+        match_query: None,
+        target: None,
     };
 
     let proxy = start_proxy(upstream, vec![mock_loc]).await;
@@ -456,7 +483,7 @@ async fn test_e2e_proxy_adds_engine_level_headers() {
 
     let config = EngineConfig {
         listen: listen.clone(),
-        target: format!("tcp://127.0.0.1:{upstream}"),
+        target: format!("tcp://127.0.0.1:{upstream}").into(),
         proxy_type: ProxyType::Http,
         request_timeout: Some(Duration::from_secs(5)),
         connection_timeout: None,
@@ -468,6 +495,20 @@ async fn test_e2e_proxy_adds_engine_level_headers() {
         deny: None,
         management: None,
         tls: None,
+        max_buffered_body_size: None,
+        load_balance: None,
+        health_check: None,
+        http2: None,
+        connection_pool: None,
+        upstream_tls: None,
+        retry: None,
+        circuit_breaker: None,
+        rate_limit: None,
+        connection_limit: None,
+        cache: None,
+        compression: None,
+        gateway: None,
+        mock_root: None,
     };
 
     let handler = create_handler(Arc::new(config)).expect("failed to create handler");
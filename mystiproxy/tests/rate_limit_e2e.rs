@@ -0,0 +1,268 @@
+//! E2E tests for rate limiting.
+//!
+//! These tests verify that HTTP engines answer 429 with `Retry-After` and
+//! `RateLimit-*` headers once a client's token bucket is empty, that keys and
+//! locations get separate buckets, and that TCP engines cap concurrent
+//! connections.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use mystiproxy::config::{
+    BodyConfig, BodyType, ConnectionLimitConfig, EngineConfig, LocationConfig, MatchMode,
+    ProviderType, ProxyType, RateLimitConfig, RateLimitKey, ResponseConfig,
+};
+use mystiproxy::http::{create_handler, HttpServer, HttpServerConfig};
+use mystiproxy::proxy::ProxyServer;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+async fn get_available_port() -> u16 {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("bind failed");
+    listener.local_addr().expect("no addr").port()
+}
+
+fn mock_location(path: &str, rate_limit: Option<RateLimitConfig>) -> LocationConfig {
+    LocationConfig {
+        location: path.to_string(),
+        mode: MatchMode::Prefix,
+        provider: Some(ProviderType::Mock),
+        root: None,
+        response: Some(ResponseConfig {
+            status: Some(200),
+            headers: None,
+            body: Some(BodyConfig {
+                json: None,
+                body_type: Some(BodyType::Static),
+                content: Some("ok".to_string()),
+                template: None,
+                file: None,
+                templated: None,
+            }),
+            status_map: None,
+            conditions: None,
+            sequence: None,
+        }),
+        request: None,
+        index_files: None,
+        enable_directory_listing: None,
+        grpc: None,
+        redirect: None,
+        retry: None,
+        rate_limit,
+        cache: None,
+        compression: None,
+        hosts: None,
+        methods: None,
+        match_headers: None,
+        match_query: None,
+        target: None,
+    }
+}
+
+fn engine_config(listen: String, proxy_type: ProxyType) -> EngineConfig {
+    EngineConfig {
+        listen,
+        target: "tcp://127.0.0.1:1".into(),
+        proxy_type,
+        request_timeout: Some(Duration::from_secs(5)),
+        connection_timeout: None,
+        header: None,
+        locations: None,
+        tls: None,
+        auth: None,
+        upstream: None,
+        allow: None,
+        deny: None,
+        management: None,
+        max_buffered_body_size: None,
+        load_balance: None,
+        health_check: None,
+        http2: None,
+        connection_pool: None,
+        upstream_tls: None,
+        retry: None,
+        circuit_breaker: None,
+        rate_limit: None,
+        connection_limit: None,
+        cache: None,
+        compression: None,
+        gateway: None,
+        mock_root: None,
+    }
+}
+
+async fn start_http_engine(configure: impl FnOnce(&mut EngineConfig)) -> u16 {
+    let port = get_available_port().await;
+    let listen = format!("tcp://127.0.0.1:{port}");
+    let mut config = engine_config(listen.clone(), ProxyType::Http);
+    configure(&mut config);
+    let handler = create_handler(Arc::new(config)).expect("handler failed");
+    let mut server = HttpServer::new(HttpServerConfig::new(listen, None), handler, None);
+    server.start().await.expect("start failed");
+    tokio::spawn(async move {
+        let _ = server.run().await;
+    });
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    port
+}
+
+async fn http_get(port: u16, path: &str, extra_headers: &str) -> String {
+    let mut stream = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+    let request = format!(
+        "GET {path} HTTP/1.1\r\nHost: localhost\r\n{extra_headers}Connection: close\r\n\r\n"
+    );
+    stream.write_all(request.as_bytes()).await.unwrap();
+    let mut response = Vec::new();
+    tokio::time::timeout(Duration::from_secs(5), stream.read_to_end(&mut response))
+        .await
+        .expect("timeout")
+        .unwrap();
+    String::from_utf8_lossy(&response).to_lowercase()
+}
+
+#[tokio::test]
+async fn test_e2e_engine_rate_limit_returns_429_with_headers() {
+    let port = start_http_engine(|config| {
+        config.locations = Some(vec![mock_location("/", None)]);
+        config.rate_limit = Some(RateLimitConfig {
+            requests_per_second: 0.5,
+            burst: Some(2),
+            key: RateLimitKey::ClientIp,
+            header_name: None,
+        });
+    })
+    .await;
+
+    for _ in 0..2 {
+        let response = http_get(port, "/", "").await;
+        assert!(response.starts_with("http/1.1 200"), "got: {response}");
+    }
+    let response = http_get(port, "/", "").await;
+    assert!(response.starts_with("http/1.1 429"), "got: {response}");
+    assert!(response.contains("retry-after: 2\r\n"), "got: {response}");
+    assert!(
+        response.contains("ratelimit-limit: 2\r\n"),
+        "got: {response}"
+    );
+    assert!(
+        response.contains("ratelimit-remaining: 0\r\n"),
+        "got: {response}"
+    );
+    assert!(
+        response.contains("ratelimit-reset: 4\r\n"),
+        "got: {response}"
+    );
+}
+
+#[tokio::test]
+async fn test_e2e_header_key_limits_clients_separately() {
+    let port = start_http_engine(|config| {
+        config.locations = Some(vec![mock_location("/", None)]);
+        config.rate_limit = Some(RateLimitConfig {
+            requests_per_second: 0.1,
+            burst: Some(1),
+            key: RateLimitKey::Header,
+            header_name: Some("X-Api-Key".to_string()),
+        });
+    })
+    .await;
+
+    let alice = "X-Api-Key: alice\r\n";
+    let bob = "X-Api-Key: bob\r\n";
+    assert!(http_get(port, "/", alice).await.starts_with("http/1.1 200"));
+    assert!(http_get(port, "/", alice).await.starts_with("http/1.1 429"));
+    assert!(http_get(port, "/", bob).await.starts_with("http/1.1 200"));
+}
+
+#[tokio::test]
+async fn test_e2e_location_rate_limit_only_applies_to_its_location() {
+    let port = start_http_engine(|config| {
+        config.locations = Some(vec![
+            mock_location(
+                "/limited",
+                Some(RateLimitConfig {
+                    requests_per_second: 0.1,
+                    burst: Some(1),
+                    key: RateLimitKey::Global,
+                    header_name: None,
+                }),
+            ),
+            mock_location("/", None),
+        ]);
+    })
+    .await;
+
+    assert!(http_get(port, "/limited", "")
+        .await
+        .starts_with("http/1.1 200"));
+    assert!(http_get(port, "/limited", "")
+        .await
+        .starts_with("http/1.1 429"));
+    for _ in 0..3 {
+        assert!(http_get(port, "/open", "")
+            .await
+            .starts_with("http/1.1 200"));
+    }
+}
+
+/// Send a ping through `stream` and wait for the echo; None when the proxy
+/// dropped the connection.
+async fn echo(stream: &mut TcpStream) -> Option<[u8; 4]> {
+    stream.write_all(b"ping").await.ok()?;
+    let mut buf = [0u8; 4];
+    tokio::time::timeout(Duration::from_secs(2), stream.read_exact(&mut buf))
+        .await
+        .ok()?
+        .ok()?;
+    Some(buf)
+}
+
+#[tokio::test]
+async fn test_e2e_tcp_connection_limit_caps_concurrency() {
+    let target = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("target bind failed");
+    let target_port = target.local_addr().unwrap().port();
+    tokio::spawn(async move {
+        while let Ok((mut stream, _)) = target.accept().await {
+            // Echo one message, then close so the forwarder can finish.
+            tokio::spawn(async move {
+                let mut buf = [0u8; 4];
+                if stream.read_exact(&mut buf).await.is_ok() {
+                    let _ = stream.write_all(&buf).await;
+                }
+            });
+        }
+    });
+
+    let listen = format!("tcp://127.0.0.1:{}", get_available_port().await);
+    let mut config = engine_config(listen.clone(), ProxyType::Tcp);
+    config.target = format!("tcp://127.0.0.1:{target_port}").into();
+    config.connection_limit = Some(ConnectionLimitConfig {
+        max_connections: Some(1),
+        ..Default::default()
+    });
+    let mut server = ProxyServer::from_engine_config(&config).expect("create failed");
+    server.start().await.expect("start failed");
+    tokio::spawn(async move {
+        let _ = server.run().await;
+    });
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    let proxy_port: u16 = listen.rsplit(':').next().unwrap().parse().unwrap();
+
+    let mut first = TcpStream::connect(("127.0.0.1", proxy_port)).await.unwrap();
+    assert_eq!(echo(&mut first).await, Some(*b"ping"));
+
+    // The second concurrent connection is dropped by the proxy.
+    let mut second = TcpStream::connect(("127.0.0.1", proxy_port)).await.unwrap();
+    assert_eq!(echo(&mut second).await, None);
+
+    // Once the first connection closes its slot is available again.
+    drop(first);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    let mut third = TcpStream::connect(("127.0.0.1", proxy_port)).await.unwrap();
+    assert_eq!(echo(&mut third).await, Some(*b"ping"));
+}
@@ -0,0 +1,168 @@
+//! E2E tests for the redirect provider.
+//!
+//! These tests verify that `provider: redirect` locations answer with the
+//! configured status and a `Location` built from the template, captured route
+//! parameters and the original query string, and that rule-only redirects
+//! (force HTTPS, trailing slash, canonical host) fall through to the next
+//! location once the request already satisfies them.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use bytes::Bytes;
+use http_body_util::{BodyExt, Full};
+use hyper::body::Incoming;
+use hyper::header::HeaderMap;
+use hyper::{Request, Response, StatusCode};
+use hyper_util::client::legacy::Client;
+use hyper_util::rt::{TokioExecutor, TokioIo};
+use mystiproxy::config::EngineConfig;
+use mystiproxy::http::{create_handler, HttpServer, HttpServerConfig};
+
+async fn get_available_port() -> u16 {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("bind failed");
+    listener.local_addr().expect("no addr").port()
+}
+
+/// Start an upstream that answers every request with `upstream`.
+async fn start_upstream() -> u16 {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("upstream bind failed");
+    let port = listener.local_addr().expect("no addr").port();
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            tokio::spawn(async move {
+                let service = hyper::service::service_fn(|_req: Request<Incoming>| async move {
+                    Ok::<_, std::convert::Infallible>(Response::new(Full::new(Bytes::from_static(
+                        b"upstream",
+                    ))))
+                });
+                let _ = hyper::server::conn::http1::Builder::new()
+                    .serve_connection(TokioIo::new(stream), service)
+                    .await;
+            });
+        }
+    });
+    port
+}
+
+async fn start_engine() -> u16 {
+    let upstream = start_upstream().await;
+    let port = get_available_port().await;
+    let yaml = format!(
+        r#"
+listen: tcp://127.0.0.1:{port}
+target: tcp://127.0.0.1:{upstream}
+proxy_type: http
+request_timeout: 5s
+locations:
+  - location: /users/{{id}}/
+    mode: PrefixRegex
+    provider: redirect
+    redirect:
+      status: 301
+      location: /v2/users/{{id}}/{{remaining}}
+  - location: /secure
+    mode: Prefix
+    provider: redirect
+    redirect:
+      status: 308
+      force_https: true
+  - location: /docs
+    mode: Prefix
+    provider: redirect
+    redirect:
+      trailing_slash: add
+  - location: /
+    mode: Prefix
+    hosts: [example.com]
+    provider: redirect
+    redirect:
+      status: 301
+      host: www.example.com
+"#
+    );
+    let config: EngineConfig = serde_yaml::from_str(&yaml).expect("valid yaml");
+    let handler = create_handler(Arc::new(config)).expect("handler failed");
+    let mut server = HttpServer::new(
+        HttpServerConfig::new(format!("tcp://127.0.0.1:{port}"), None),
+        handler,
+        None,
+    );
+    server.start().await.expect("start failed");
+    tokio::spawn(async move {
+        let _ = server.run().await;
+    });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    port
+}
+
+async fn get(port: u16, path: &str, headers: &[(&str, &str)]) -> (StatusCode, HeaderMap, Bytes) {
+    let client: Client<_, Full<Bytes>> = Client::builder(TokioExecutor::new()).build_http();
+    let mut builder = Request::builder().uri(format!("http://127.0.0.1:{port}{path}"));
+    for (name, value) in headers {
+        builder = builder.header(*name, *value);
+    }
+    let response = client
+        .request(builder.body(Full::new(Bytes::new())).unwrap())
+        .await
+        .expect("request failed");
+    let status = response.status();
+    let headers = response.headers().clone();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    (status, headers, body)
+}
+
+#[tokio::test]
+async fn test_e2e_redirect_template_with_params_and_query() {
+    let port = start_engine().await;
+
+    let (status, headers, _) = get(port, "/users/42/posts?page=2", &[]).await;
+    assert_eq!(status, StatusCode::MOVED_PERMANENTLY);
+    assert_eq!(headers["location"], "/v2/users/42/posts?page=2");
+}
+
+#[tokio::test]
+async fn test_e2e_redirect_force_https() {
+    let port = start_engine().await;
+
+    let host = [("host", "example.org:8080")];
+    let (status, headers, _) = get(port, "/secure/area?x=1", &host).await;
+    assert_eq!(status, StatusCode::PERMANENT_REDIRECT);
+    assert_eq!(headers["location"], "https://example.org/secure/area?x=1");
+
+    // Already HTTPS behind a TLS-terminating proxy: forwarded upstream.
+    let forwarded = [("host", "example.org"), ("x-forwarded-proto", "https")];
+    let (status, _, body) = get(port, "/secure/area", &forwarded).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(&body[..], b"upstream");
+}
+
+#[tokio::test]
+async fn test_e2e_redirect_trailing_slash() {
+    let port = start_engine().await;
+
+    let (status, headers, _) = get(port, "/docs?v=1", &[]).await;
+    assert_eq!(status, StatusCode::FOUND);
+    assert_eq!(headers["location"], "/docs/?v=1");
+
+    let (status, _, body) = get(port, "/docs/", &[]).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(&body[..], b"upstream");
+}
+
+#[tokio::test]
+async fn test_e2e_redirect_canonical_host() {
+    let port = start_engine().await;
+
+    let (status, headers, _) = get(port, "/about", &[("host", "example.com")]).await;
+    assert_eq!(status, StatusCode::MOVED_PERMANENTLY);
+    assert_eq!(headers["location"], "http://www.example.com/about");
+
+    let (status, _, body) = get(port, "/about", &[("host", "www.example.com")]).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(&body[..], b"upstream");
+}
@@ -0,0 +1,173 @@
+//! E2E tests for mock response sequences.
+//!
+//! These tests verify that a mock location with `response.sequence` returns
+//! its responses in order (sticking on the last one or cycling), that step
+//! fields fall back to the outer response, that counters are kept per location,
+//! that rate-limited requests do not advance a sequence and that resetting the
+//! shared counters restarts the sequence.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use bytes::Bytes;
+use http_body_util::{BodyExt, Full};
+use hyper::{Request, StatusCode};
+use hyper_util::client::legacy::Client;
+use hyper_util::rt::TokioExecutor;
+use mystiproxy::config::EngineConfig;
+use mystiproxy::http::{create_handler, HttpServer, HttpServerConfig};
+use mystiproxy::mock::sequence::SequenceCounters;
+
+async fn get_available_port() -> u16 {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("bind failed");
+    listener.local_addr().expect("no addr").port()
+}
+
+async fn start_engine() -> (u16, Arc<SequenceCounters>) {
+    let port = get_available_port().await;
+    let yaml = format!(
+        r#"
+listen: tcp://127.0.0.1:{port}
+target: tcp://127.0.0.1:1
+proxy_type: http
+request_timeout: 5s
+locations:
+  - location: /flaky
+    mode: Full
+    provider: mock
+    response:
+      headers:
+        X-Mock:
+          action: overwrite
+          value: flaky
+      body:
+        type: static
+        content: ok
+      sequence:
+        responses:
+          - status: 503
+            repeat: 2
+            body:
+              type: static
+              content: unavailable
+          - status: 200
+  - location: /rotate
+    mode: Full
+    provider: mock
+    response:
+      sequence:
+        mode: cycle
+        responses:
+          - body: {{ type: static, content: a }}
+          - body: {{ type: static, content: b }}
+  - location: /limited
+    mode: Full
+    provider: mock
+    rate_limit:
+      requests_per_second: 0.01
+      burst: 1
+    response:
+      sequence:
+        responses:
+          - body: {{ type: static, content: first }}
+          - body: {{ type: static, content: second }}
+"#
+    );
+    let config: EngineConfig = serde_yaml::from_str(&yaml).expect("valid yaml");
+    let counters = Arc::new(SequenceCounters::new());
+    let handler = create_handler(Arc::new(config))
+        .expect("handler failed")
+        .with_response_sequences(counters.clone());
+    let mut server = HttpServer::new(
+        HttpServerConfig::new(format!("tcp://127.0.0.1:{port}"), None),
+        handler,
+        None,
+    );
+    server.start().await.expect("start failed");
+    tokio::spawn(async move {
+        let _ = server.run().await;
+    });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    (port, counters)
+}
+
+async fn get(port: u16, path: &str) -> (StatusCode, Option<String>, Bytes) {
+    let client: Client<_, Full<Bytes>> = Client::builder(TokioExecutor::new()).build_http();
+    let request = Request::builder()
+        .uri(format!("http://127.0.0.1:{port}{path}"))
+        .body(Full::new(Bytes::new()))
+        .unwrap();
+    let response = client.request(request).await.expect("request failed");
+    let status = response.status();
+    let header = response
+        .headers()
+        .get("x-mock")
+        .map(|v| v.to_str().unwrap().to_string());
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    (status, header, body)
+}
+
+#[tokio::test]
+async fn test_e2e_sequential_fails_then_succeeds() {
+    let (port, _) = start_engine().await;
+
+    for _ in 0..2 {
+        let (status, header, body) = get(port, "/flaky").await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(header.as_deref(), Some("flaky"));
+        assert_eq!(&body[..], b"unavailable");
+    }
+    // 之后停留在最后一项；未设置的 body 沿用外层 response
+    for _ in 0..2 {
+        let (status, _, body) = get(port, "/flaky").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(&body[..], b"ok");
+    }
+}
+
+#[tokio::test]
+async fn test_e2e_cycle_rotates_per_location() {
+    let (port, _) = start_engine().await;
+
+    let mut bodies = Vec::new();
+    for _ in 0..3 {
+        bodies.push(get(port, "/rotate").await.2);
+    }
+    assert_eq!(bodies, ["a", "b", "a"]);
+
+    // /flaky 的计数独立
+    assert_eq!(get(port, "/flaky").await.0, StatusCode::SERVICE_UNAVAILABLE);
+}
+
+#[tokio::test]
+async fn test_e2e_reset_restarts_sequence() {
+    let (port, counters) = start_engine().await;
+
+    for _ in 0..3 {
+        get(port, "/flaky").await;
+    }
+    assert_eq!(get(port, "/flaky").await.0, StatusCode::OK);
+    assert_eq!(counters.counters()[0].count, 4);
+
+    counters.reset(Some("/flaky"));
+    assert_eq!(get(port, "/flaky").await.0, StatusCode::SERVICE_UNAVAILABLE);
+}
+
+#[tokio::test]
+async fn test_e2e_rate_limited_requests_do_not_advance_sequence() {
+    let (port, counters) = start_engine().await;
+
+    assert_eq!(&get(port, "/limited").await.2[..], b"first");
+    for _ in 0..3 {
+        assert_eq!(get(port, "/limited").await.0, StatusCode::TOO_MANY_REQUESTS);
+    }
+    // 被限流的请求不计入响应序列
+    let limited = counters
+        .counters()
+        .into_iter()
+        .find(|c| c.location == "/limited")
+        .unwrap();
+    assert_eq!(limited.count, 1);
+}
@@ -0,0 +1,236 @@
+//! E2E tests for upstream response transformation.
+//!
+//! These tests verify that the `response` block on proxy locations remaps
+//! upstream status codes, applies header actions, edits JSON bodies by path,
+//! replaces bodies from templates or static content, and decodes compressed
+//! upstream bodies when `decompress_for_transform` is enabled.
+
+use std::io::Write;
+use std::sync::Arc;
+use std::time::Duration;
+
+use bytes::Bytes;
+use http_body_util::{BodyExt, Full};
+use hyper::body::Incoming;
+use hyper::header::HeaderMap;
+use hyper::{Request, Response, StatusCode};
+use hyper_util::client::legacy::Client;
+use hyper_util::rt::{TokioExecutor, TokioIo};
+use mystiproxy::config::EngineConfig;
+use mystiproxy::http::{create_handler, HttpServer, HttpServerConfig};
+
+async fn get_available_port() -> u16 {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("bind failed");
+    listener.local_addr().expect("no addr").port()
+}
+
+fn gzip(data: &[u8]) -> Bytes {
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(data).unwrap();
+    Bytes::from(encoder.finish().unwrap())
+}
+
+/// Start an upstream whose responses depend on the request path:
+///
+/// - `/broken`: 502 with a JSON error body
+/// - `/text`: a plain-text body
+/// - `/gzip/...`: a gzip-encoded JSON body
+/// - anything else: a JSON user document with `Server` and `ETag` headers
+async fn start_upstream() -> u16 {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("upstream bind failed");
+    let port = listener.local_addr().expect("no addr").port();
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            tokio::spawn(async move {
+                let service = hyper::service::service_fn(|req: Request<Incoming>| async move {
+                    let builder = Response::builder();
+                    let response = match req.uri().path() {
+                        "/broken" => builder
+                            .header("content-type", "application/json")
+                            .status(StatusCode::BAD_GATEWAY)
+                            .body(Full::new(Bytes::from_static(b"{\"error\":\"down\"}"))),
+                        "/text" => builder
+                            .header("content-type", "text/plain")
+                            .body(Full::new(Bytes::from_static(b"not json"))),
+                        path if path.starts_with("/gzip/") => builder
+                            .header("content-type", "application/json")
+                            .header("content-encoding", "gzip")
+                            .body(Full::new(gzip(br#"{"id":7,"secret":"s"}"#))),
+                        _ => builder
+                            .header("content-type", "application/json")
+                            .header("server", "backend/1.0")
+                            .header("etag", "\"v1\"")
+                            .body(Full::new(Bytes::from_static(
+                                br#"{"id":1,"name":"alice","internal":{"token":"t"}}"#,
+                            ))),
+                    };
+                    Ok::<_, std::convert::Infallible>(response.unwrap())
+                });
+                let _ = hyper::server::conn::http1::Builder::new()
+                    .serve_connection(TokioIo::new(stream), service)
+                    .await;
+            });
+        }
+    });
+    port
+}
+
+async fn start_engine() -> u16 {
+    let upstream = start_upstream().await;
+    let port = get_available_port().await;
+    let yaml = format!(
+        r#"
+listen: tcp://127.0.0.1:{port}
+target: tcp://127.0.0.1:{upstream}
+proxy_type: http
+request_timeout: 5s
+locations:
+  - location: /broken
+    mode: Full
+    response:
+      status_map:
+        502: 503
+  - location: /users
+    mode: Prefix
+    response:
+      headers:
+        Server:
+          value: ""
+          action: forceDelete
+        X-Patched:
+          value: "true"
+          action: overwrite
+      body:
+        json:
+          path: $.internal
+          value: ""
+          action: delete
+  - location: /summary
+    mode: Prefix
+    response:
+      status: 200
+      headers:
+        Content-Type:
+          value: text/plain
+          action: overwrite
+      body:
+        type: template
+        template: "user {{{{body.$.name}}}} ({{{{query.lang}}}}) {{{{method}}}} {{{{client_ip}}}} {{{{header.host}}}}"
+  - location: /text
+    mode: Full
+    response:
+      body:
+        json:
+          path: $.added
+          value: "1"
+          action: add
+  - location: /gzip/plain
+    mode: Full
+    response:
+      body:
+        json:
+          path: $.secret
+          value: ""
+          action: delete
+  - location: /gzip/decoded
+    mode: Full
+    compression:
+      enabled: false
+      decompress_for_transform: true
+    response:
+      body:
+        json:
+          path: $.secret
+          value: ""
+          action: delete
+"#
+    );
+    let config: EngineConfig = serde_yaml::from_str(&yaml).expect("valid yaml");
+    let handler = create_handler(Arc::new(config)).expect("handler failed");
+    let mut server = HttpServer::new(
+        HttpServerConfig::new(format!("tcp://127.0.0.1:{port}"), None),
+        handler,
+        None,
+    );
+    server.start().await.expect("start failed");
+    tokio::spawn(async move {
+        let _ = server.run().await;
+    });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    port
+}
+
+async fn get(port: u16, path: &str) -> (StatusCode, HeaderMap, Bytes) {
+    let client: Client<_, Full<Bytes>> = Client::builder(TokioExecutor::new()).build_http();
+    let uri = format!("http://127.0.0.1:{port}{path}").parse().unwrap();
+    let response = client.get(uri).await.expect("request failed");
+    let status = response.status();
+    let headers = response.headers().clone();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    (status, headers, body)
+}
+
+#[tokio::test]
+async fn test_e2e_response_status_remapped() {
+    let port = start_engine().await;
+
+    let (status, _, body) = get(port, "/broken").await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(&body[..], b"{\"error\":\"down\"}");
+}
+
+#[tokio::test]
+async fn test_e2e_response_headers_and_json_body_edited() {
+    let port = start_engine().await;
+
+    let (status, headers, body) = get(port, "/users/1").await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(!headers.contains_key("server"));
+    assert_eq!(headers["x-patched"], "true");
+    // The body changed, so the upstream validator no longer applies.
+    assert!(!headers.contains_key("etag"));
+    assert_eq!(headers["content-length"], body.len().to_string().as_str());
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json, serde_json::json!({"id": 1, "name": "alice"}));
+}
+
+#[tokio::test]
+async fn test_e2e_response_body_replaced_by_template() {
+    let port = start_engine().await;
+
+    let (status, headers, body) = get(port, "/summary/1?lang=en").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(headers["content-type"], "text/plain");
+    assert_eq!(
+        String::from_utf8_lossy(&body),
+        format!("user alice (en) GET 127.0.0.1 127.0.0.1:{port}")
+    );
+}
+
+#[tokio::test]
+async fn test_e2e_non_json_response_forwarded_unchanged() {
+    let port = start_engine().await;
+
+    let (_, headers, body) = get(port, "/text").await;
+    assert_eq!(headers["content-type"], "text/plain");
+    assert_eq!(&body[..], b"not json");
+}
+
+#[tokio::test]
+async fn test_e2e_compressed_response_decoded_only_when_enabled() {
+    let port = start_engine().await;
+
+    // Without decompression the encoded body is passed through untouched.
+    let (_, headers, body) = get(port, "/gzip/plain").await;
+    assert_eq!(headers["content-encoding"], "gzip");
+    assert_eq!(body, gzip(br#"{"id":7,"secret":"s"}"#));
+
+    let (_, headers, body) = get(port, "/gzip/decoded").await;
+    assert!(!headers.contains_key("content-encoding"));
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json, serde_json::json!({"id": 7}));
+}
@@ -0,0 +1,253 @@
+//! E2E tests for proxy retries.
+//!
+//! These tests verify that the HTTP engine retries failed upstream attempts
+//! according to its `retry` policy: retryable statuses, connection errors on
+//! multi-target engines, method filtering, body replay and per-try timeouts.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use bytes::Bytes;
+use http_body_util::{BodyExt, Full};
+use hyper::{Response, StatusCode};
+use hyper_util::rt::TokioIo;
+use mystiproxy::config::{
+    EngineConfig, LoadBalanceConfig, LoadBalanceStrategy, ProxyType, RetryConfig, TargetConfig,
+    WeightedTarget,
+};
+use mystiproxy::http::{create_handler, HttpServer, HttpServerConfig};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+async fn get_available_port() -> u16 {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("bind failed");
+    listener.local_addr().expect("no addr").port()
+}
+
+/// How the upstream answers its n-th request (counting from 0).
+#[derive(Clone, Copy)]
+enum Script {
+    /// 503 for the first `n` requests, then 200.
+    FailFirst(usize),
+    /// Stall the first request for two seconds, answer the rest at once.
+    SlowFirst,
+}
+
+/// Start an upstream that follows `script`, echoes the request body and counts
+/// the requests it receives.
+async fn start_scripted_upstream(script: Script) -> (u16, Arc<AtomicUsize>) {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("upstream bind failed");
+    let port = listener.local_addr().expect("no addr").port();
+    let requests = Arc::new(AtomicUsize::new(0));
+    let counter = requests.clone();
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            let counter = counter.clone();
+            tokio::spawn(async move {
+                let service = hyper::service::service_fn(
+                    move |req: hyper::Request<hyper::body::Incoming>| {
+                        let n = counter.fetch_add(1, Ordering::SeqCst);
+                        async move {
+                            let body = req.into_body().collect().await?.to_bytes();
+                            let status = match script {
+                                Script::FailFirst(failures) if n < failures => {
+                                    StatusCode::SERVICE_UNAVAILABLE
+                                }
+                                Script::SlowFirst if n == 0 => {
+                                    tokio::time::sleep(Duration::from_secs(2)).await;
+                                    StatusCode::OK
+                                }
+                                _ => StatusCode::OK,
+                            };
+                            let mut response = Response::new(Full::new(if body.is_empty() {
+                                Bytes::from(format!("attempt-{n}"))
+                            } else {
+                                body
+                            }));
+                            *response.status_mut() = status;
+                            Ok::<_, hyper::Error>(response)
+                        }
+                    },
+                );
+                let _ = hyper::server::conn::http1::Builder::new()
+                    .serve_connection(TokioIo::new(stream), service)
+                    .await;
+            });
+        }
+    });
+    (port, requests)
+}
+
+fn engine_config(listen: String, target: TargetConfig, retry: RetryConfig) -> EngineConfig {
+    EngineConfig {
+        listen,
+        target,
+        proxy_type: ProxyType::Http,
+        request_timeout: Some(Duration::from_secs(5)),
+        connection_timeout: None,
+        header: None,
+        locations: None,
+        tls: None,
+        auth: None,
+        upstream: None,
+        allow: None,
+        deny: None,
+        management: None,
+        max_buffered_body_size: None,
+        load_balance: None,
+        health_check: None,
+        http2: None,
+        connection_pool: None,
+        upstream_tls: None,
+        retry: Some(retry),
+        circuit_breaker: None,
+        rate_limit: None,
+        connection_limit: None,
+        cache: None,
+        compression: None,
+        gateway: None,
+        mock_root: None,
+    }
+}
+
+async fn start_engine(config: EngineConfig) -> u16 {
+    let listen = config.listen.clone();
+    let port: u16 = listen.rsplit(':').next().unwrap().parse().unwrap();
+    let handler = create_handler(Arc::new(config)).expect("handler failed");
+    let mut server = HttpServer::new(HttpServerConfig::new(listen, None), handler, None);
+    server.start().await.expect("start failed");
+    tokio::spawn(async move {
+        let _ = server.run().await;
+    });
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    port
+}
+
+async fn start_single_target_engine(upstream: u16, retry: RetryConfig) -> u16 {
+    let port = get_available_port().await;
+    let config = engine_config(
+        format!("tcp://127.0.0.1:{port}"),
+        format!("tcp://127.0.0.1:{upstream}").into(),
+        retry,
+    );
+    start_engine(config).await
+}
+
+async fn send(port: u16, method: &str, body: &str) -> String {
+    let mut stream = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+    let request = format!(
+        "{method} / HTTP/1.1\r\nHost: localhost\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    );
+    stream.write_all(request.as_bytes()).await.unwrap();
+    let mut response = Vec::new();
+    tokio::time::timeout(Duration::from_secs(5), stream.read_to_end(&mut response))
+        .await
+        .expect("timeout")
+        .unwrap();
+    String::from_utf8_lossy(&response).to_string()
+}
+
+#[tokio::test]
+async fn test_e2e_retry_on_503_until_success() {
+    let (upstream, requests) = start_scripted_upstream(Script::FailFirst(2)).await;
+    let port = start_single_target_engine(upstream, RetryConfig::default()).await;
+
+    let response = send(port, "GET", "").await;
+    assert!(response.starts_with("HTTP/1.1 200"), "got: {response}");
+    assert!(response.ends_with("attempt-2"), "got: {response}");
+    assert_eq!(requests.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn test_e2e_retry_gives_up_after_attempts() {
+    let (upstream, requests) = start_scripted_upstream(Script::FailFirst(usize::MAX)).await;
+    let retry = RetryConfig {
+        attempts: Some(2),
+        ..Default::default()
+    };
+    let port = start_single_target_engine(upstream, retry).await;
+
+    let response = send(port, "GET", "").await;
+    assert!(response.starts_with("HTTP/1.1 503"), "got: {response}");
+    assert_eq!(requests.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_e2e_post_not_retried_by_default() {
+    let (upstream, requests) = start_scripted_upstream(Script::FailFirst(1)).await;
+    let port = start_single_target_engine(upstream, RetryConfig::default()).await;
+
+    let response = send(port, "POST", "payload").await;
+    assert!(response.starts_with("HTTP/1.1 503"), "got: {response}");
+    assert_eq!(requests.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_e2e_retry_replays_request_body() {
+    let (upstream, requests) = start_scripted_upstream(Script::FailFirst(1)).await;
+    let retry = RetryConfig {
+        methods: Some(vec!["POST".to_string()]),
+        ..Default::default()
+    };
+    let port = start_single_target_engine(upstream, retry).await;
+
+    let response = send(port, "POST", "payload").await;
+    assert!(response.starts_with("HTTP/1.1 200"), "got: {response}");
+    assert!(response.ends_with("payload"), "got: {response}");
+    assert_eq!(requests.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_e2e_per_try_timeout_triggers_retry() {
+    let (upstream, requests) = start_scripted_upstream(Script::SlowFirst).await;
+    let retry = RetryConfig {
+        per_try_timeout: Some(Duration::from_millis(300)),
+        ..Default::default()
+    };
+    let port = start_single_target_engine(upstream, retry).await;
+
+    let started = Instant::now();
+    let response = send(port, "GET", "").await;
+    assert!(response.starts_with("HTTP/1.1 200"), "got: {response}");
+    assert!(response.ends_with("attempt-1"), "got: {response}");
+    assert!(started.elapsed() < Duration::from_secs(2));
+    assert_eq!(requests.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_e2e_retry_moves_to_another_target_on_connect_error() {
+    let (healthy, requests) = start_scripted_upstream(Script::FailFirst(0)).await;
+    // Nothing listens on this port, so connecting to it fails.
+    let dead = get_available_port().await;
+    let port = get_available_port().await;
+    let mut config = engine_config(
+        format!("tcp://127.0.0.1:{port}"),
+        TargetConfig::Weighted(
+            [dead, healthy]
+                .iter()
+                .map(|port| WeightedTarget {
+                    address: format!("tcp://127.0.0.1:{port}"),
+                    weight: 1,
+                })
+                .collect(),
+        ),
+        RetryConfig::default(),
+    );
+    config.load_balance = Some(LoadBalanceConfig {
+        strategy: LoadBalanceStrategy::RoundRobin,
+        hash_header: None,
+    });
+    let port = start_engine(config).await;
+
+    for _ in 0..4 {
+        let response = send(port, "GET", "").await;
+        assert!(response.starts_with("HTTP/1.1 200"), "got: {response}");
+    }
+    assert_eq!(requests.load(Ordering::SeqCst), 4);
+}
@@ -7,8 +7,8 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use mystiproxy::config::{
-    EngineConfig, HeaderAction, HeaderActionType, LocationConfig, MatchMode, ProviderType,
-    ProxyType, RequestConfig, UriConfig,
+    EngineConfig, HeaderAction, HeaderActionType, LocationConfig, MatchMode, PathRewriteConfig,
+    ProviderType, ProxyType, RequestConfig, UriConfig,
 };
 use mystiproxy::http::{create_handler, HttpServer, HttpServerConfig};
 use mystiproxy::io::SocketStream;
@@ -61,7 +61,7 @@ async fn start_proxy(upstream_port: u16, locations: Vec<LocationConfig>) -> Stri
 
     let config = EngineConfig {
         listen: listen.clone(),
-        target: format!("tcp://127.0.0.1:{upstream_port}"),
+        target: format!("tcp://127.0.0.1:{upstream_port}").into(),
         proxy_type: ProxyType::Http,
         request_timeout: Some(Duration::from_secs(5)),
         connection_timeout: None,
@@ -73,6 +73,20 @@ async fn start_proxy(upstream_port: u16, locations: Vec<LocationConfig>) -> Stri
         deny: None,
         management: None,
         tls: None,
+        max_buffered_body_size: None,
+        load_balance: None,
+        health_check: None,
+        http2: None,
+        connection_pool: None,
+        upstream_tls: None,
+        retry: None,
+        circuit_breaker: None,
+        rate_limit: None,
+        connection_limit: None,
+        cache: None,
+        compression: None,
+        gateway: None,
+        mock_root: None,
     };
 
     let handler = create_handler(Arc::new(config)).expect("handler failed");
@@ -142,12 +156,24 @@ async fn test_e2e_uri_path_rewrite() {
             uri: Some(UriConfig {
                 path: Some("/new".to_string()),
                 query: None,
+                rewrite: None,
             }),
             headers: None,
             body: None,
         }),
         index_files: None,
         enable_directory_listing: None,
+        grpc: None,
+        redirect: None,
+        retry: None,
+        rate_limit: None,
+        cache: None,
+        compression: None,
+        hosts: None,
+        methods: None,
+        match_headers: None,
+        match_query: None,
+        target: None,
     };
 
     let proxy = start_proxy(upstream, vec![loc]).await;
@@ -162,6 +188,68 @@ async fn test_e2e_uri_path_rewrite() {
     );
 }
 
+fn regex_rewrite_location(pattern: &str) -> LocationConfig {
+    LocationConfig {
+        location: "/old".to_string(),
+        mode: MatchMode::Prefix,
+        provider: Some(ProviderType::Proxy),
+        root: None,
+        response: None,
+        request: Some(RequestConfig {
+            method: None,
+            uri: Some(UriConfig {
+                path: None,
+                query: None,
+                rewrite: Some(PathRewriteConfig {
+                    pattern: pattern.to_string(),
+                    replacement: "/v2/$1".to_string(),
+                }),
+            }),
+            headers: None,
+            body: None,
+        }),
+        index_files: None,
+        enable_directory_listing: None,
+        grpc: None,
+        redirect: None,
+        retry: None,
+        rate_limit: None,
+        cache: None,
+        compression: None,
+        hosts: None,
+        methods: None,
+        match_headers: None,
+        match_query: None,
+        target: None,
+    }
+}
+
+#[tokio::test]
+async fn test_e2e_uri_regex_rewrite() {
+    let upstream = start_echo_upstream().await;
+    let proxy = start_proxy(upstream, vec![regex_rewrite_location(r"^/old/(\w+)$")]).await;
+
+    for name in ["first", "second"] {
+        let response = send_request(&proxy, "GET", &format!("/old/{name}")).await;
+        let body = extract_body(&response);
+        assert!(
+            body.contains(&format!("GET /v2/{name}")),
+            "URI path should be rewritten by regex, got: {}",
+            body
+        );
+    }
+}
+
+#[test]
+fn test_invalid_rewrite_regex_rejected_at_startup() {
+    let mut config: EngineConfig = serde_yaml::from_str(
+        "listen: tcp://127.0.0.1:0\ntarget: tcp://127.0.0.1:1\nproxy_type: http\n",
+    )
+    .unwrap();
+    config.locations = Some(vec![regex_rewrite_location("(unclosed")]);
+    assert!(create_handler(Arc::new(config)).is_err());
+}
+
 #[tokio::test]
 async fn test_e2e_uri_query_rewrite() {
     let upstream = start_echo_upstream().await;
@@ -177,12 +265,24 @@ async fn test_e2e_uri_query_rewrite() {
             uri: Some(UriConfig {
                 path: Some("/api".to_string()),
                 query: Some("rewritten=true".to_string()),
+                rewrite: None,
             }),
             headers: None,
             body: None,
         }),
         index_files: None,
         enable_directory_listing: None,
+        grpc: None,
+        redirect: None,
+        retry: None,
+        rate_limit: None,
+        cache: None,
+        compression: None,
+        hosts: None,
+        methods: None,
+        match_headers: None,
+        match_query: None,
+        target: None,
     };
 
     let proxy = start_proxy(upstream, vec![loc]).await;
@@ -219,6 +319,17 @@ async fn test_e2e_method_rewrite_get_to_post() {
         }),
         index_files: None,
         enable_directory_listing: None,
+        grpc: None,
+        redirect: None,
+        retry: None,
+        rate_limit: None,
+        cache: None,
+        compression: None,
+        hosts: None,
+        methods: None,
+        match_headers: None,
+        match_query: None,
+        target: None,
     };
 
     let proxy = start_proxy(upstream, vec![loc]).await;
@@ -265,6 +376,17 @@ async fn test_e2e_header_overwrite() {
         }),
         index_files: None,
         enable_directory_listing: None,
+        grpc: None,
+        redirect: None,
+        retry: None,
+        rate_limit: None,
+        cache: None,
+        compression: None,
+        hosts: None,
+        methods: None,
+        match_headers: None,
+        match_query: None,
+        target: None,
     };
 
     let proxy = start_proxy(upstream, vec![loc]).await;
@@ -307,6 +429,17 @@ async fn test_e2e_header_missed_only_adds_if_absent() {
         }),
         index_files: None,
         enable_directory_listing: None,
+        grpc: None,
+        redirect: None,
+        retry: None,
+        rate_limit: None,
+        cache: None,
+        compression: None,
+        hosts: None,
+        methods: None,
+        match_headers: None,
+        match_query: None,
+        target: None,
     };
 
     let proxy = start_proxy(upstream, vec![loc]).await;
@@ -350,12 +483,24 @@ async fn test_e2e_combined_rewrite() {
             uri: Some(UriConfig {
                 path: Some("/v2".to_string()),
                 query: Some("version=2".to_string()),
+                rewrite: None,
             }),
             headers: Some(headers),
             body: None,
         }),
         index_files: None,
         enable_directory_listing: None,
+        grpc: None,
+        redirect: None,
+        retry: None,
+        rate_limit: None,
+        cache: None,
+        compression: None,
+        hosts: None,
+        methods: None,
+        match_headers: None,
+        match_query: None,
+        target: None,
     };
 
     let proxy = start_proxy(upstream, vec![loc]).await;
@@ -374,3 +519,96 @@ async fn test_e2e_combined_rewrite() {
         body
     );
 }
+
+// ---------------------------------------------------------------------------
+// Tests: rewrite with captured route parameters
+// ---------------------------------------------------------------------------
+
+fn yaml_location(yaml: &str) -> LocationConfig {
+    serde_yaml::from_str(yaml).expect("valid location yaml")
+}
+
+#[tokio::test]
+async fn test_e2e_path_template_uses_captured_params() {
+    let upstream = start_echo_upstream().await;
+
+    let loc = yaml_location(
+        r#"
+location: /users/{id}/
+mode: PrefixRegex
+request:
+  uri:
+    path: /v2/users/{id}/{remaining}
+  headers:
+    X-User-Id:
+      value: "{id}"
+      action: overwrite
+"#,
+    );
+
+    let proxy = start_proxy(upstream, vec![loc]).await;
+
+    let response = send_request(&proxy, "GET", "/users/42/posts/7").await;
+    let body = extract_body(&response).to_lowercase();
+
+    assert!(
+        body.contains("get /v2/users/42/posts/7 "),
+        "path should be built from captured params, got: {}",
+        body
+    );
+    assert!(
+        body.contains("x-user-id: 42"),
+        "header value should use captured params, got: {}",
+        body
+    );
+}
+
+#[tokio::test]
+async fn test_e2e_path_regex_replace() {
+    let upstream = start_echo_upstream().await;
+
+    let loc = yaml_location(
+        r#"
+location: /legacy
+mode: Prefix
+request:
+  uri:
+    rewrite:
+      pattern: ^/legacy/(?P<kind>\w+)/(\d+)$
+      replacement: /api/${kind}?id=$2
+"#,
+    );
+
+    let proxy = start_proxy(upstream, vec![loc]).await;
+
+    let response = send_request(&proxy, "GET", "/legacy/orders/9").await;
+    let body = extract_body(&response);
+
+    assert!(
+        body.contains("GET /api/orders?id=9 "),
+        "path should be rewritten by the regex rule, got: {}",
+        body
+    );
+}
+
+#[tokio::test]
+async fn test_e2e_mock_template_uses_path_params() {
+    let upstream = start_echo_upstream().await;
+
+    let loc = yaml_location(
+        r#"
+location: /items/{sku}
+mode: Regex
+provider: mock
+response:
+  body:
+    type: template
+    template: "item {{path.sku}}"
+"#,
+    );
+
+    let proxy = start_proxy(upstream, vec![loc]).await;
+
+    let response = send_request(&proxy, "GET", "/items/ab-12").await;
+    assert_eq!(extract_body(&response), "item ab-12");
+}
@@ -0,0 +1,310 @@
+//! E2E tests for streamed proxy bodies.
+//!
+//! These tests verify that upstream responses reach the client before the
+//! upstream has finished sending, and that buffering for JSON body transforms
+//! honours `max_buffered_body_size`.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use mystiproxy::config::{
+    BodyConfig, EngineConfig, JsonBodyAction, JsonBodyConfig, LocationConfig, MatchMode,
+    ProviderType, ProxyType, RequestConfig,
+};
+use mystiproxy::http::{create_handler, HttpServer, HttpServerConfig};
+use mystiproxy::io::SocketStream;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::sync::Notify;
+
+async fn get_available_port() -> u16 {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("bind failed");
+    listener.local_addr().expect("no addr").port()
+}
+
+/// Start an upstream that sends one SSE event, then waits for `release`
+/// before sending the second event and closing the stream.
+async fn start_sse_upstream(release: Arc<Notify>) -> u16 {
+    let port = get_available_port().await;
+    tokio::spawn(async move {
+        let listener = tokio::net::TcpListener::bind(format!("127.0.0.1:{port}"))
+            .await
+            .expect("upstream bind failed");
+        loop {
+            if let Ok((mut stream, _)) = listener.accept().await {
+                let release = release.clone();
+                tokio::spawn(async move {
+                    let mut buf = vec![0u8; 4096];
+                    if matches!(stream.read(&mut buf).await, Ok(0) | Err(_)) {
+                        return;
+                    }
+                    let head = "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nTransfer-Encoding: chunked\r\n\r\n";
+                    let _ = stream.write_all(head.as_bytes()).await;
+                    let _ = stream.write_all(chunk("data: one\n\n").as_bytes()).await;
+                    let _ = stream.flush().await;
+
+                    release.notified().await;
+                    let _ = stream.write_all(chunk("data: two\n\n").as_bytes()).await;
+                    let _ = stream.write_all(b"0\r\n\r\n").await;
+                });
+            }
+        }
+    });
+    tokio::time::sleep(Duration::from_millis(30)).await;
+    port
+}
+
+fn chunk(data: &str) -> String {
+    format!("{:x}\r\n{data}\r\n", data.len())
+}
+
+/// Start an upstream that replies with the number of body bytes it received.
+async fn start_counting_upstream() -> u16 {
+    let port = get_available_port().await;
+    tokio::spawn(async move {
+        let listener = tokio::net::TcpListener::bind(format!("127.0.0.1:{port}"))
+            .await
+            .expect("upstream bind failed");
+        loop {
+            if let Ok((mut stream, _)) = listener.accept().await {
+                tokio::spawn(async move {
+                    let mut data = Vec::new();
+                    let mut buf = vec![0u8; 65536];
+                    // Read headers, then Content-Length bytes of body
+                    let (header_end, content_length) = loop {
+                        let n = match stream.read(&mut buf).await {
+                            Ok(0) | Err(_) => return,
+                            Ok(n) => n,
+                        };
+                        data.extend_from_slice(&buf[..n]);
+                        let text = String::from_utf8_lossy(&data).to_string();
+                        if let Some(pos) = text.find("\r\n\r\n") {
+                            let len = text[..pos]
+                                .lines()
+                                .find_map(|l| {
+                                    let (k, v) = l.split_once(':')?;
+                                    k.eq_ignore_ascii_case("content-length")
+                                        .then(|| v.trim().parse::<usize>().ok())?
+                                })
+                                .unwrap_or(0);
+                            break (pos + 4, len);
+                        }
+                    };
+                    while data.len() - header_end < content_length {
+                        match stream.read(&mut buf).await {
+                            Ok(0) | Err(_) => break,
+                            Ok(n) => data.extend_from_slice(&buf[..n]),
+                        }
+                    }
+                    let resp_body = format!("RECEIVED:{}", data.len() - header_end);
+                    let resp = format!(
+                        "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{resp_body}",
+                        resp_body.len()
+                    );
+                    let _ = stream.write_all(resp.as_bytes()).await;
+                });
+            }
+        }
+    });
+    tokio::time::sleep(Duration::from_millis(30)).await;
+    port
+}
+
+async fn start_proxy(
+    upstream_port: u16,
+    locations: Option<Vec<LocationConfig>>,
+    max_buffered_body_size: Option<usize>,
+) -> String {
+    let proxy_port = get_available_port().await;
+    let listen = format!("tcp://127.0.0.1:{proxy_port}");
+
+    let config = EngineConfig {
+        listen: listen.clone(),
+        target: format!("tcp://127.0.0.1:{upstream_port}").into(),
+        proxy_type: ProxyType::Http,
+        request_timeout: Some(Duration::from_secs(5)),
+        connection_timeout: None,
+        header: None,
+        locations,
+        auth: None,
+        upstream: None,
+        allow: None,
+        deny: None,
+        management: None,
+        tls: None,
+        max_buffered_body_size,
+        load_balance: None,
+        health_check: None,
+        http2: None,
+        connection_pool: None,
+        upstream_tls: None,
+        retry: None,
+        circuit_breaker: None,
+        rate_limit: None,
+        connection_limit: None,
+        cache: None,
+        compression: None,
+        gateway: None,
+        mock_root: None,
+    };
+
+    let handler = create_handler(Arc::new(config)).expect("handler failed");
+    let mut server = HttpServer::new(HttpServerConfig::new(listen.clone(), None), handler, None);
+    server.start().await.expect("start failed");
+    tokio::spawn(async move {
+        let _ = server.run().await;
+    });
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    listen
+}
+
+async fn read_to_end(stream: &mut SocketStream) -> String {
+    let mut response = Vec::new();
+    tokio::time::timeout(Duration::from_secs(5), async {
+        let mut buf = [0u8; 16384];
+        loop {
+            let n = stream.read(&mut buf).await.expect("read");
+            if n == 0 {
+                break;
+            }
+            response.extend_from_slice(&buf[..n]);
+        }
+    })
+    .await
+    .expect("timeout");
+    String::from_utf8_lossy(&response).to_string()
+}
+
+fn transform_location() -> LocationConfig {
+    LocationConfig {
+        location: "/transform".to_string(),
+        mode: MatchMode::Prefix,
+        provider: Some(ProviderType::Proxy),
+        root: None,
+        response: None,
+        request: Some(RequestConfig {
+            method: None,
+            uri: None,
+            headers: None,
+            body: Some(BodyConfig {
+                json: Some(JsonBodyConfig {
+                    path: "$.name".to_string(),
+                    value: "changed".to_string(),
+                    action: JsonBodyAction::Overwrite,
+                }),
+                body_type: None,
+                content: None,
+                template: None,
+                file: None,
+                templated: None,
+            }),
+        }),
+        index_files: None,
+        enable_directory_listing: None,
+        grpc: None,
+        redirect: None,
+        retry: None,
+        rate_limit: None,
+        cache: None,
+        compression: None,
+        hosts: None,
+        methods: None,
+        match_headers: None,
+        match_query: None,
+        target: None,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[tokio::test]
+async fn test_e2e_sse_response_streams_before_upstream_finishes() {
+    let release = Arc::new(Notify::new());
+    let upstream = start_sse_upstream(release.clone()).await;
+    let proxy = start_proxy(upstream, None, None).await;
+
+    let mut stream = SocketStream::connect(proxy).await.expect("connect failed");
+    stream
+        .write_all(b"GET /events HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+        .await
+        .expect("write");
+
+    // The first event must arrive while the upstream is still holding the stream open
+    let mut received = Vec::new();
+    tokio::time::timeout(Duration::from_secs(2), async {
+        let mut buf = [0u8; 4096];
+        while !String::from_utf8_lossy(&received).contains("data: one") {
+            let n = stream.read(&mut buf).await.expect("read");
+            assert!(n > 0, "connection closed before first event");
+            received.extend_from_slice(&buf[..n]);
+        }
+    })
+    .await
+    .expect("first event was not streamed");
+
+    let head = String::from_utf8_lossy(&received).to_string();
+    assert!(head.contains("200 OK"), "got: {head}");
+    assert!(!head.contains("data: two"), "got: {head}");
+
+    release.notify_one();
+    let rest = read_to_end(&mut stream).await;
+    assert!(rest.contains("data: two"), "got: {rest}");
+}
+
+#[tokio::test]
+async fn test_e2e_large_request_body_streamed_upstream() {
+    let upstream = start_counting_upstream().await;
+    // Limit only applies to buffered transforms, so a plain proxy route is unaffected
+    let proxy = start_proxy(upstream, None, Some(16)).await;
+
+    let body = vec![b'x'; 1024 * 1024];
+    let mut stream = SocketStream::connect(proxy).await.expect("connect failed");
+    let head = format!(
+        "POST /upload HTTP/1.1\r\nHost: localhost\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        body.len()
+    );
+    stream.write_all(head.as_bytes()).await.expect("write");
+    stream.write_all(&body).await.expect("write body");
+
+    let response = read_to_end(&mut stream).await;
+    assert!(response.contains("200 OK"), "got: {response}");
+    assert!(response.contains("RECEIVED:1048576"), "got: {response}");
+}
+
+#[tokio::test]
+async fn test_e2e_transform_body_over_limit_rejected() {
+    let upstream = start_counting_upstream().await;
+    let proxy = start_proxy(upstream, Some(vec![transform_location()]), Some(16)).await;
+
+    let json = r#"{"name":"a-name-that-is-long-enough"}"#;
+    let mut stream = SocketStream::connect(proxy).await.expect("connect failed");
+    let request = format!(
+        "POST /transform HTTP/1.1\r\nHost: localhost\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{json}",
+        json.len()
+    );
+    stream.write_all(request.as_bytes()).await.expect("write");
+
+    let response = read_to_end(&mut stream).await;
+    assert!(response.contains("413"), "got: {response}");
+}
+
+#[tokio::test]
+async fn test_e2e_transform_body_within_limit_forwarded() {
+    let upstream = start_counting_upstream().await;
+    let proxy = start_proxy(upstream, Some(vec![transform_location()]), None).await;
+
+    let json = r#"{"name":"a"}"#;
+    let mut stream = SocketStream::connect(proxy).await.expect("connect failed");
+    let request = format!(
+        "POST /transform HTTP/1.1\r\nHost: localhost\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{json}",
+        json.len()
+    );
+    stream.write_all(request.as_bytes()).await.expect("write");
+
+    let response = read_to_end(&mut stream).await;
+    let expected = format!("RECEIVED:{}", r#"{"name":"changed"}"#.len());
+    assert!(response.contains(&expected), "got: {response}");
+}
@@ -55,7 +55,7 @@ async fn test_tcp_proxy_forwarding() {
 
     let config = EngineConfig {
         listen: format!("tcp://127.0.0.1:{}", proxy_port),
-        target: format!("tcp://127.0.0.1:{}", echo_port),
+        target: format!("tcp://127.0.0.1:{}", echo_port).into(),
         proxy_type: ProxyType::Tcp,
         request_timeout: None,
         connection_timeout: None,
@@ -67,6 +67,20 @@ async fn test_tcp_proxy_forwarding() {
         allow: None,
         deny: None,
         management: None,
+        max_buffered_body_size: None,
+        load_balance: None,
+        health_check: None,
+        http2: None,
+        connection_pool: None,
+        upstream_tls: None,
+        retry: None,
+        circuit_breaker: None,
+        rate_limit: None,
+        connection_limit: None,
+        cache: None,
+        compression: None,
+        gateway: None,
+        mock_root: None,
     };
 
     let mut server =
@@ -99,7 +113,7 @@ async fn test_tcp_proxy_with_timeout() {
 
     let config = EngineConfig {
         listen: format!("tcp://127.0.0.1:{}", proxy_port),
-        target: format!("tcp://127.0.0.1:{}", echo_port),
+        target: format!("tcp://127.0.0.1:{}", echo_port).into(),
         proxy_type: ProxyType::Tcp,
         request_timeout: Some(Duration::from_secs(5)),
         connection_timeout: None,
@@ -111,6 +125,20 @@ async fn test_tcp_proxy_with_timeout() {
         allow: None,
         deny: None,
         management: None,
+        max_buffered_body_size: None,
+        load_balance: None,
+        health_check: None,
+        http2: None,
+        connection_pool: None,
+        upstream_tls: None,
+        retry: None,
+        circuit_breaker: None,
+        rate_limit: None,
+        connection_limit: None,
+        cache: None,
+        compression: None,
+        gateway: None,
+        mock_root: None,
     };
 
     let mut server =
@@ -283,7 +311,7 @@ async fn test_proxy_server_lifecycle() {
 
     let config = EngineConfig {
         listen: format!("tcp://127.0.0.1:{}", proxy_port),
-        target: format!("tcp://127.0.0.1:{}", echo_port),
+        target: format!("tcp://127.0.0.1:{}", echo_port).into(),
         proxy_type: ProxyType::Tcp,
         request_timeout: None,
         connection_timeout: None,
@@ -295,6 +323,20 @@ async fn test_proxy_server_lifecycle() {
         allow: None,
         deny: None,
         management: None,
+        max_buffered_body_size: None,
+        load_balance: None,
+        health_check: None,
+        http2: None,
+        connection_pool: None,
+        upstream_tls: None,
+        retry: None,
+        circuit_breaker: None,
+        rate_limit: None,
+        connection_limit: None,
+        cache: None,
+        compression: None,
+        gateway: None,
+        mock_root: None,
     };
 
     let mut server =
@@ -334,7 +376,7 @@ async fn test_proxy_server_lifecycle() {
 async fn test_proxy_config_from_engine_config() {
     let config = EngineConfig {
         listen: "tcp://127.0.0.1:19000".to_string(),
-        target: "tcp://127.0.0.1:19001".to_string(),
+        target: "tcp://127.0.0.1:19001".to_string().into(),
         proxy_type: ProxyType::Tcp,
         request_timeout: Some(Duration::from_secs(10)),
         connection_timeout: None,
@@ -346,6 +388,20 @@ async fn test_proxy_config_from_engine_config() {
         allow: None,
         deny: None,
         management: None,
+        max_buffered_body_size: None,
+        load_balance: None,
+        health_check: None,
+        http2: None,
+        connection_pool: None,
+        upstream_tls: None,
+        retry: None,
+        circuit_breaker: None,
+        rate_limit: None,
+        connection_limit: None,
+        cache: None,
+        compression: None,
+        gateway: None,
+        mock_root: None,
     };
 
     let server = ProxyServer::from_engine_config(&config).expect("creation failed");
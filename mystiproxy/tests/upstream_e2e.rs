@@ -97,7 +97,7 @@ async fn test_e2e_http_engine_via_upstream_connect_tunnel() {
 
     let engine = EngineConfig {
         listen: "tcp://127.0.0.1:19250".to_string(),
-        target: "tcp://127.0.0.1:19252".to_string(),
+        target: "tcp://127.0.0.1:19252".to_string().into(),
         proxy_type: ProxyType::Http,
         request_timeout: Some(Duration::from_secs(5)),
         connection_timeout: None,
@@ -111,6 +111,17 @@ async fn test_e2e_http_engine_via_upstream_connect_tunnel() {
             request: None,
             index_files: None,
             enable_directory_listing: None,
+            grpc: None,
+            redirect: None,
+            retry: None,
+            rate_limit: None,
+            cache: None,
+            compression: None,
+            hosts: None,
+            methods: None,
+            match_headers: None,
+            match_query: None,
+            target: None,
         }]),
         auth: None,
         tls: None,
@@ -118,6 +129,20 @@ async fn test_e2e_http_engine_via_upstream_connect_tunnel() {
         allow: None,
         deny: None,
         management: None,
+        max_buffered_body_size: None,
+        load_balance: None,
+        health_check: None,
+        http2: None,
+        connection_pool: None,
+        upstream_tls: None,
+        retry: None,
+        circuit_breaker: None,
+        rate_limit: None,
+        connection_limit: None,
+        cache: None,
+        compression: None,
+        gateway: None,
+        mock_root: None,
     };
 
     let handler = mystiproxy::http::create_handler(Arc::new(engine)).expect("handler");